@@ -0,0 +1,179 @@
+use anyhow::Result;
+use serialport::{DataBits, Parity, StopBits};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::config::SerialConfig;
+
+/// First bytes of every capture file
+pub const CAPTURE_MAGIC: &[u8; 5] = b"VRCAP";
+
+/// Format version written after the magic, bumped on incompatible changes
+pub const CAPTURE_VERSION: u16 = 1;
+
+/// Largest entry payload accepted, far above any chunk read from a port
+pub const MAX_ENTRY_SIZE: usize = 16 * 1024 * 1024;
+
+pub(crate) const KIND_START: u8 = 1;
+pub(crate) const KIND_RECEIVED: u8 = 2;
+pub(crate) const KIND_SENT: u8 = 3;
+pub(crate) const KIND_STOP: u8 = 4;
+
+/// Port and serial settings of a recorded session
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaptureMetadata {
+    pub port: String,
+    pub baud: u32,
+    pub data_bits: u8,
+    /// "none", "odd", "even", "mark" or "space"
+    pub parity: String,
+    /// 2 stands for 1.5 with 5 data bits, as in `SerialConfig`
+    pub stop_bits: u8,
+    /// All settings as a `SerialConfig` config string, with flow control,
+    /// DTR/RTS and buffers; `None` in files written before it was added
+    pub config: Option<String>,
+    /// Local wall clock time the session started
+    pub started: String,
+    /// Version of vital-reader that wrote the file
+    pub reader_version: String,
+}
+
+impl CaptureMetadata {
+    pub fn new(port: &str, config: &SerialConfig) -> Self {
+        Self {
+            port: port.to_string(),
+            baud: config.baud,
+            data_bits: match config.data_bits {
+                DataBits::Five => 5,
+                DataBits::Six => 6,
+                DataBits::Seven => 7,
+                DataBits::Eight => 8,
+            },
+            parity: match (config.parity, config.stick_parity) {
+                (Parity::None, _) => "none",
+                (Parity::Odd, false) => "odd",
+                (Parity::Even, false) => "even",
+                (Parity::Odd, true) => "mark",
+                (Parity::Even, true) => "space",
+            }
+            .to_string(),
+            stop_bits: match config.stop_bits {
+                StopBits::One => 1,
+                StopBits::Two => 2,
+            },
+            config: Some(config.to_config_string()),
+            started: chrono::Local::now()
+                .format("%Y-%m-%d %H:%M:%S%.3f")
+                .to_string(),
+            reader_version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+
+    /// The serial settings the session was recorded with
+    pub fn serial_config(&self) -> Result<SerialConfig> {
+        if let Some(config) = &self.config {
+            return SerialConfig::from_string(config);
+        }
+        SerialConfig::new(self.baud, self.data_bits, &self.parity, self.stop_bits)
+    }
+
+    /// Encode as `key=value` lines
+    pub fn encode(&self) -> Vec<u8> {
+        [
+            ("port", Some(self.port.clone())),
+            ("baud", Some(self.baud.to_string())),
+            ("data_bits", Some(self.data_bits.to_string())),
+            ("parity", Some(self.parity.clone())),
+            ("stop_bits", Some(self.stop_bits.to_string())),
+            ("config", self.config.clone()),
+            ("started", Some(self.started.clone())),
+            ("reader_version", Some(self.reader_version.clone())),
+        ]
+        .iter()
+        .filter_map(|(key, value)| value.as_ref().map(|value| (key, value)))
+        .map(|(key, value)| format!("{}={}\n", key, value))
+        .collect::<String>()
+        .into_bytes()
+    }
+
+    /// Decode `key=value` lines, unknown keys are ignored
+    pub fn decode(payload: &[u8]) -> Result<Self> {
+        let text = std::str::from_utf8(payload)
+            .map_err(|_| anyhow::anyhow!("Capture metadata is not valid UTF-8"))?;
+        let mut metadata = Self {
+            port: String::new(),
+            baud: 0,
+            data_bits: 8,
+            parity: "none".to_string(),
+            stop_bits: 1,
+            config: None,
+            started: String::new(),
+            reader_version: String::new(),
+        };
+
+        for line in text.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            match key {
+                "port" => metadata.port = value.to_string(),
+                "baud" => metadata.baud = parse_number(line, value)?,
+                "data_bits" => metadata.data_bits = parse_number(line, value)?,
+                "parity" => metadata.parity = value.to_string(),
+                "stop_bits" => metadata.stop_bits = parse_number(line, value)?,
+                "config" => metadata.config = Some(value.to_string()),
+                "started" => metadata.started = value.to_string(),
+                "reader_version" => metadata.reader_version = value.to_string(),
+                _ => {}
+            }
+        }
+
+        Ok(metadata)
+    }
+}
+
+/// Parse a metadata value into its field type, out of range values included
+fn parse_number<T: FromStr>(line: &str, value: &str) -> Result<T> {
+    value
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid capture metadata: {}", line))
+}
+
+/// What happened at a point of a recorded session
+#[derive(Debug, Clone, PartialEq)]
+pub enum CaptureEntry {
+    /// Session start, with the port settings
+    Start(CaptureMetadata),
+    /// A chunk read from the port
+    Received(Vec<u8>),
+    /// Bytes written to the port (commands, ACKs, driver requests)
+    Sent(Vec<u8>),
+    /// Session end
+    Stop,
+}
+
+impl CaptureEntry {
+    pub(crate) fn kind(&self) -> u8 {
+        match self {
+            Self::Start(_) => KIND_START,
+            Self::Received(_) => KIND_RECEIVED,
+            Self::Sent(_) => KIND_SENT,
+            Self::Stop => KIND_STOP,
+        }
+    }
+
+    pub(crate) fn payload(&self) -> Vec<u8> {
+        match self {
+            Self::Start(metadata) => metadata.encode(),
+            Self::Received(data) | Self::Sent(data) => data.clone(),
+            Self::Stop => Vec::new(),
+        }
+    }
+}
+
+/// A capture entry with the time elapsed since the recording started
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaptureRecord {
+    pub elapsed: Duration,
+    pub entry: CaptureEntry,
+}
@@ -0,0 +1,9 @@
+mod format;
+mod reader;
+mod writer;
+
+pub use format::{
+    CaptureEntry, CaptureMetadata, CaptureRecord, CAPTURE_MAGIC, CAPTURE_VERSION, MAX_ENTRY_SIZE,
+};
+pub use reader::CaptureReader;
+pub use writer::CaptureWriter;
@@ -0,0 +1,130 @@
+use anyhow::Result;
+use std::fs::File;
+use std::io::{BufReader, ErrorKind, Read};
+use std::time::Duration;
+
+use super::format::{KIND_RECEIVED, KIND_SENT, KIND_START, KIND_STOP};
+use super::{
+    CaptureEntry, CaptureMetadata, CaptureRecord, CAPTURE_MAGIC, CAPTURE_VERSION, MAX_ENTRY_SIZE,
+};
+
+/// Reads the entries of a capture file in order
+///
+/// Entries of an unknown kind are skipped, so files written by newer
+/// releases stay readable as long as the version is unchanged. A final
+/// entry cut short (the recorder was killed mid-write) ends the capture.
+pub struct CaptureReader {
+    input: Box<dyn Read + Send>,
+    version: u16,
+    done: bool,
+    truncated: bool,
+}
+
+impl CaptureReader {
+    pub fn open(path: &str) -> Result<Self> {
+        let file = File::open(path)
+            .map_err(|e| anyhow::anyhow!("Failed to open capture file {}: {}", path, e))?;
+        Self::with_reader(Box::new(BufReader::new(file)))
+    }
+
+    /// Read and check the file header
+    pub fn with_reader(mut input: Box<dyn Read + Send>) -> Result<Self> {
+        let mut header = [0u8; 7];
+        input
+            .read_exact(&mut header)
+            .map_err(|_| anyhow::anyhow!("Not a capture file: header too short"))?;
+        if &header[..5] != CAPTURE_MAGIC {
+            return Err(anyhow::anyhow!("Not a capture file: bad magic"));
+        }
+
+        let version = u16::from_le_bytes([header[5], header[6]]);
+        if version == 0 || version > CAPTURE_VERSION {
+            return Err(anyhow::anyhow!(
+                "Unsupported capture version: {} (expected {})",
+                version,
+                CAPTURE_VERSION
+            ));
+        }
+
+        Ok(Self {
+            input,
+            version,
+            done: false,
+            truncated: false,
+        })
+    }
+
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// Whether the capture ended in the middle of an entry
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Read the next entry, `None` at the end of the file
+    pub fn next_record(&mut self) -> Result<Option<CaptureRecord>> {
+        loop {
+            let mut kind = [0u8; 1];
+            match self.input.read_exact(&mut kind) {
+                Ok(()) => {}
+                Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+                Err(e) => return Err(e.into()),
+            }
+
+            let mut header = [0u8; 12];
+            if !self.read_rest(&mut header)? {
+                return Ok(None);
+            }
+            let length =
+                u32::from_le_bytes([header[8], header[9], header[10], header[11]]) as usize;
+            if length > MAX_ENTRY_SIZE {
+                return Err(anyhow::anyhow!("Capture entry too large: {} bytes", length));
+            }
+            let mut payload = vec![0u8; length];
+            if !self.read_rest(&mut payload)? {
+                return Ok(None);
+            }
+            let mut micros = [0u8; 8];
+            micros.copy_from_slice(&header[..8]);
+            let elapsed = Duration::from_micros(u64::from_le_bytes(micros));
+
+            let entry = match kind[0] {
+                KIND_START => CaptureEntry::Start(CaptureMetadata::decode(&payload)?),
+                KIND_RECEIVED => CaptureEntry::Received(payload),
+                KIND_SENT => CaptureEntry::Sent(payload),
+                KIND_STOP => CaptureEntry::Stop,
+                _ => continue,
+            };
+            return Ok(Some(CaptureRecord { elapsed, entry }));
+        }
+    }
+
+    /// Fill `buf` with the rest of an entry, `false` if the file ends first
+    fn read_rest(&mut self, buf: &mut [u8]) -> Result<bool> {
+        match self.input.read_exact(buf) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => {
+                self.truncated = true;
+                Ok(false)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl Iterator for CaptureReader {
+    type Item = Result<CaptureRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.next_record().transpose();
+        if !matches!(result, Some(Ok(_))) {
+            self.done = true;
+        }
+        result
+    }
+}
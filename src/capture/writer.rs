@@ -0,0 +1,62 @@
+use anyhow::Result;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::time::{Duration, Instant};
+
+use super::{CaptureEntry, CAPTURE_MAGIC, CAPTURE_VERSION, MAX_ENTRY_SIZE};
+
+/// Records a session to a capture file, see the README for the layout
+pub struct CaptureWriter {
+    out: Box<dyn Write + Send>,
+    started: Instant,
+}
+
+impl CaptureWriter {
+    /// Create (or truncate) the capture file at `path`
+    pub fn create(path: &str) -> Result<Self> {
+        let file = File::create(path)
+            .map_err(|e| anyhow::anyhow!("Failed to create capture file {}: {}", path, e))?;
+        Self::with_writer(Box::new(BufWriter::new(file)))
+    }
+
+    /// Write the file header; entry times are measured from here
+    pub fn with_writer(mut out: Box<dyn Write + Send>) -> Result<Self> {
+        out.write_all(CAPTURE_MAGIC)?;
+        out.write_all(&CAPTURE_VERSION.to_le_bytes())?;
+        Ok(Self {
+            out,
+            started: Instant::now(),
+        })
+    }
+
+    /// Append an entry stamped with the monotonic time since the header
+    pub fn write(&mut self, entry: &CaptureEntry) -> Result<()> {
+        self.write_at(self.started.elapsed(), entry)
+    }
+
+    /// Append an entry with an explicit elapsed time
+    pub fn write_at(&mut self, elapsed: Duration, entry: &CaptureEntry) -> Result<()> {
+        let payload = entry.payload();
+        if payload.len() > MAX_ENTRY_SIZE {
+            return Err(anyhow::anyhow!(
+                "Capture entry too large: {} bytes",
+                payload.len()
+            ));
+        }
+        let length = payload.len() as u32;
+        let micros = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+
+        self.out.write_all(&[entry.kind()])?;
+        self.out.write_all(&micros.to_le_bytes())?;
+        self.out.write_all(&length.to_le_bytes())?;
+        self.out.write_all(&payload)?;
+        // Flush per entry so a crash or power loss keeps what was captured
+        self.out.flush()?;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        self.out.flush()?;
+        Ok(())
+    }
+}
@@ -1,11 +1,13 @@
 use anyhow::Result;
-use serialport::{DataBits, Parity, StopBits};
 use std::time::Duration;
 
 use super::UI;
-use crate::data::DataParser;
+use crate::config::{Profile, Profiles, SerialConfig};
+use crate::data::{Decoder, DecoderRegistry};
 use crate::fake::{CustomGenerator, Hl7Generator, VitalSignsGenerator, WaveformGenerator};
-use crate::port::{PortDetector, PortSelector};
+use crate::port::{Autodetect, PortConnection, PortDetector, PortSelector};
+use crate::protocol::{DevicePreset, DEVICE_PRESETS};
+use crate::reader::Pipeline;
 
 pub struct Commands;
 
@@ -102,36 +104,39 @@ impl Commands {
 
         let port_name = PortSelector::select_interactive()?;
 
-        println!("\nConfiguration options:");
-        println!("  [1] Use config string (e.g., 57600,0,8,1)");
-        println!("  [2] Configure individually");
-        let choice = UI::prompt("Select [1/2]: ");
-
-        let (baud, data_bits, parity, stop_bits) = if choice == "1" {
-            Self::get_config_from_string()?
-        } else {
-            Self::get_config_individually()?
+        let (config, decoder, driver) = match Self::select_device() {
+            Some(preset) => (
+                preset.serial_config(),
+                preset.decoder()?,
+                preset.create_driver(None)?,
+            ),
+            None => (
+                Self::prompt_serial_config()?,
+                Self::select_protocol()?,
+                None,
+            ),
         };
 
         println!("\n════════════════════════════════════════════════════");
         println!("Configuration:");
         println!("  Port:      {}", port_name);
-        println!("  Baud:      {}", baud);
-        println!("  Data bits: {:?}", data_bits);
-        println!("  Parity:    {:?}", parity);
-        println!("  Stop bits: {:?}", stop_bits);
+        println!("  Serial:    {}", config);
+        println!("  Flow:      {:?}", config.flow_control);
+        println!("  Protocol:  {}", decoder.name());
+        if let Some(ref driver) = driver {
+            println!("  Driver:    {}", driver.name());
+        }
         println!("════════════════════════════════════════════════════");
         println!("\nConnecting... (Press Ctrl+C to stop)");
 
-        let mut port = serialport::new(&port_name, baud)
-            .timeout(Duration::from_millis(100))
-            .data_bits(data_bits)
-            .parity(parity)
-            .stop_bits(stop_bits)
-            .open()?;
+        let mut port = PortConnection::open(&port_name, &config, 100)?;
 
-        let mut parser = DataParser::new();
-        let mut buffer = vec![0u8; 1024];
+        let mut pipeline = Pipeline::new();
+        pipeline.set_decoder(decoder);
+        if let Some(driver) = driver {
+            pipeline.set_driver(driver);
+        }
+        let mut buffer = vec![0u8; config.read_buffer];
         let mut total_bytes = 0u64;
         let start_time = std::time::Instant::now();
 
@@ -143,12 +148,17 @@ impl Commands {
         let no_data_timeout = Duration::from_secs(5);
 
         loop {
+            let request = pipeline.poll_driver(std::time::Instant::now());
+            if !request.is_empty() {
+                port.write(&request)?;
+            }
+
             match port.read(&mut buffer) {
                 Ok(n) if n > 0 => {
                     total_bytes += n as u64;
                     last_data_time = std::time::Instant::now();
                     let timestamp = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
-                    parser.process_data(&buffer[..n], &timestamp);
+                    pipeline.receive(&buffer[..n], &timestamp)?;
                 }
                 Ok(_) => {
                     if last_data_time.elapsed() > no_data_timeout && total_bytes == 0 {
@@ -160,11 +170,8 @@ impl Commands {
                         break;
                     }
                 }
-                Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => {
-                    // Timeout is normal
-                }
                 Err(e) => {
-                    println!("\nRead error: {}", e);
+                    println!("\n{}", e);
                     break;
                 }
             }
@@ -186,7 +193,7 @@ impl Commands {
                 "  Average rate:     {:.2} bytes/sec",
                 total_bytes as f64 / start_time.elapsed().as_secs_f64()
             );
-            parser.print_stats();
+            pipeline.parser().print_stats();
         }
         println!("════════════════════════════════════════════════════");
 
@@ -209,8 +216,9 @@ impl Commands {
         println!("  [4] Custom text message");
         println!("  [5] Custom hex data");
         println!("  [6] Continuous random data stream");
+        println!("  [7] HL7 Complete over MLLP - Framed message");
 
-        let preset = UI::prompt("\nSelect preset [1-6]: ");
+        let preset = UI::prompt("\nSelect preset [1-7]: ");
 
         let mut port = serialport::new(&port_name, baud)
             .timeout(Duration::from_millis(1000))
@@ -227,6 +235,7 @@ impl Commands {
             "4" => CustomGenerator::send_text(&mut port)?,
             "5" => CustomGenerator::send_hex(&mut port)?,
             "6" => CustomGenerator::send_continuous(&mut port)?,
+            "7" => Hl7Generator::send_mllp(&mut port)?,
             _ => println!("Invalid preset selected."),
         }
 
@@ -237,68 +246,118 @@ impl Commands {
         Ok(())
     }
 
-    fn get_config_from_string() -> Result<(u32, DataBits, Parity, StopBits)> {
-        println!("\nExamples:");
-        println!("  57600,0,8,1  (57600 baud, no parity, 8 data bits, 1 stop bit)");
-        println!("  9600,2,7,1   (9600 baud, even parity, 7 data bits, 1 stop bit)");
-        println!("  115200,1,8,2 (115200 baud, odd parity, 8 data bits, 2 stop bits)");
+    pub fn autodetect() -> Result<()> {
+        UI::print_section_header("Detect Baud Rate and Framing");
 
-        let config_str = UI::prompt("\nConfig string: ");
-        let parts: Vec<&str> = config_str.split(',').collect();
+        let port_name = PortSelector::select_interactive()?;
+        let window: u64 = UI::prompt_with_default("Seconds to listen per setting [2]: ", "2")
+            .parse()
+            .unwrap_or(2);
 
-        if parts.len() != 4 {
-            println!("Error: Invalid config format!");
-            return Err(anyhow::anyhow!("Invalid config format"));
+        let autodetect = Autodetect::new().with_window(Duration::from_secs(window));
+        println!(
+            "\nTrying {} settings on {} (about {} seconds)...",
+            autodetect.candidates().len(),
+            port_name,
+            autodetect.duration().as_secs()
+        );
+        let results = autodetect.run(&port_name)?;
+
+        println!("────────────────────────────────────────────────────");
+        println!("  Setting        Bytes   ASCII  Errors  Score  Markers");
+        for result in &results {
+            println!(
+                "  {:<13} {:>6}  {:>5.0}%  {:>6}  {:>5.2}  {}",
+                result.config.to_string(),
+                result.bytes,
+                result.ascii_ratio * 100.0,
+                result.framing_errors,
+                result.score,
+                result.markers.join(", ")
+            );
         }
+        println!("────────────────────────────────────────────────────");
 
-        let baud: u32 = parts[0].parse().unwrap_or(115200);
-        let parity_num: u8 = parts[1].parse().unwrap_or(0);
-        let parity = match parity_num {
-            1 => Parity::Odd,
-            2 => Parity::Even,
-            _ => Parity::None,
-        };
-        let data_bits = match parts[2].parse().unwrap_or(8) {
-            5 => DataBits::Five,
-            6 => DataBits::Six,
-            7 => DataBits::Seven,
-            _ => DataBits::Eight,
-        };
-        let stop_bits = match parts[3].parse().unwrap_or(1) {
-            2 => StopBits::Two,
-            _ => StopBits::One,
-        };
+        match Autodetect::best(&results) {
+            Some(best) => println!("✓ Best match: {}", best.config),
+            None => println!("No data received at any setting. Is the device sending?"),
+        }
 
-        Ok((baud, data_bits, parity, stop_bits))
+        UI::wait_for_enter();
+        Ok(())
     }
 
-    fn get_config_individually() -> Result<(u32, DataBits, Parity, StopBits)> {
-        let baud: u32 = UI::prompt_with_default("\nBaud rate [115200]: ", "115200")
-            .parse()
-            .unwrap_or(115200);
+    /// Pick a device preset, `None` to enter serial settings by hand
+    fn select_device() -> Option<&'static DevicePreset> {
+        println!("\nChoose your device:");
+        for (idx, preset) in DEVICE_PRESETS.iter().enumerate() {
+            println!("  [{}] {}", idx + 1, preset.description);
+        }
+        println!("  [0] Other (enter serial settings)");
 
-        let data_bits_input = UI::prompt_with_default("Data bits (5-8) [8]: ", "8");
-        let data_bits = match data_bits_input.parse().unwrap_or(8) {
-            5 => DataBits::Five,
-            6 => DataBits::Six,
-            7 => DataBits::Seven,
-            _ => DataBits::Eight,
-        };
+        let choice = UI::prompt(&format!("Select [0-{}]: ", DEVICE_PRESETS.len()));
+        match choice.parse::<usize>() {
+            Ok(n) if (1..=DEVICE_PRESETS.len()).contains(&n) => Some(&DEVICE_PRESETS[n - 1]),
+            _ => None,
+        }
+    }
 
-        let parity_input = UI::prompt_with_default("Parity (none/odd/even) [none]: ", "none");
-        let parity = match parity_input.to_lowercase().as_str() {
-            "odd" | "o" => Parity::Odd,
-            "even" | "e" => Parity::Even,
-            _ => Parity::None,
-        };
+    fn select_protocol() -> Result<Box<dyn Decoder>> {
+        let registry = DecoderRegistry::new();
+        let protocol = UI::prompt_with_default(
+            &format!("Protocol ({}) [hl7]: ", registry.names().join("/")),
+            "hl7",
+        );
+        registry.create(&protocol)
+    }
 
-        let stop_bits_input = UI::prompt_with_default("Stop bits (1/2) [1]: ", "1");
-        let stop_bits = match stop_bits_input.parse().unwrap_or(1) {
-            2 => StopBits::Two,
-            _ => StopBits::One,
-        };
+    /// Serial settings entered as a config string or one by one
+    fn prompt_serial_config() -> Result<SerialConfig> {
+        println!("\nConfiguration options:");
+        println!("  [1] Use config string (e.g., 57600,0,8,1)");
+        println!("  [2] Configure individually");
+        let choice = UI::prompt("Select [1/2]: ");
 
-        Ok((baud, data_bits, parity, stop_bits))
+        if choice == "1" {
+            Self::get_config_from_string()
+        } else {
+            Self::get_config_individually()
+        }
+    }
+
+    fn get_config_from_string() -> Result<SerialConfig> {
+        println!("\nExamples:");
+        println!("  57600,0,8,1  (57600 baud, no parity, 8 data bits, 1 stop bit)");
+        println!("  9600,2,7,1   (9600 baud, even parity, 7 data bits, 1 stop bit)");
+        println!("  115200,1,8,2 (115200 baud, odd parity, 8 data bits, 2 stop bits)");
+        println!("  9600,E,7,1,rtscts (even parity, RTS/CTS flow control)");
+        println!("Options: rtscts, xonxoff, dtr=on|off, rts=on|off, rbuf=BYTES, wbuf=BYTES");
+
+        SerialConfig::from_string(&UI::prompt("\nConfig string: "))
+    }
+
+    fn get_config_individually() -> Result<SerialConfig> {
+        let mut config = SerialConfig::default();
+        let prompts = [
+            ("baud", "\nBaud rate [115200]: ", "115200"),
+            ("data_bits", "Data bits (5-8) [8]: ", "8"),
+            (
+                "parity",
+                "Parity (none/odd/even/mark/space) [none]: ",
+                "none",
+            ),
+            ("stop_bits", "Stop bits (1/1.5/2) [1]: ", "1"),
+            (
+                "flow",
+                "Flow control (none/rtscts/xonxoff) [none]: ",
+                "none",
+            ),
+        ];
+        for (key, prompt, default) in prompts {
+            config.set_option(key, &UI::prompt_with_default(prompt, default))?;
+        }
+        config.validate()?;
+        Ok(config)
     }
 
     pub fn generate_command() -> Result<()> {
@@ -329,36 +388,35 @@ impl Commands {
 
         // Get configuration
         let port_name = PortSelector::select_interactive()?;
+        let preset = Self::select_device();
 
-        println!("\nConfiguration method:");
-        println!("  [1] Use config string (e.g., 57600,0,8,1)");
-        println!("  [2] Configure individually");
-        let choice = UI::prompt("Select [1/2]: ");
+        let config_str = match preset {
+            Some(_) => None,
+            None => Some(Self::prompt_serial_config()?.to_config_string()),
+        };
+        let stats = UI::prompt("Show statistics? (y/n) [n]: ");
+        let show_stats = stats.to_lowercase() == "y";
 
-        let (config_str, show_stats) = if choice == "1" {
-            println!("\nExamples:");
-            println!("  57600,0,8,1  (57600 baud, no parity, 8 data bits, 1 stop bit)");
-            println!("  9600,2,7,1   (9600 baud, even parity, 7 data bits, 1 stop bit)");
-            let cfg = UI::prompt("\nConfig string: ");
-            let stats = UI::prompt("Show statistics? (y/n) [n]: ");
-            (Some(cfg), stats.to_lowercase() == "y")
-        } else {
-            let baud = UI::prompt_with_default("\nBaud rate [115200]: ", "115200");
-            let data_bits = UI::prompt_with_default("Data bits (5-8) [8]: ", "8");
-            let parity = UI::prompt_with_default("Parity (none/odd/even) [none]: ", "none");
-            let stop_bits = UI::prompt_with_default("Stop bits (1/2) [1]: ", "1");
-            let stats = UI::prompt("Show statistics? (y/n) [n]: ");
-
-            // Convert to config string format
-            let parity_num = match parity.to_lowercase().as_str() {
-                "odd" | "o" => "1",
-                "even" | "e" => "2",
-                _ => "0",
-            };
-            let cfg = format!("{},{},{},{}", baud, parity_num, data_bits, stop_bits);
-            (Some(cfg), stats.to_lowercase() == "y")
+        // A preset brings its own protocol
+        let protocol = match preset {
+            Some(_) => None,
+            None => Some(Self::select_protocol()?.name()),
         };
 
+        let save = UI::prompt("Save as a profile instead of a command line? (y/n) [n]: ");
+        if save.to_lowercase() == "y" {
+            let mut profile = Profile::new(&UI::prompt_with_default(
+                "Profile name [default]: ",
+                "default",
+            ));
+            profile.port = Some(port_name);
+            profile.device = preset.map(|preset| preset.name.to_string());
+            profile.config = config_str;
+            profile.protocol = protocol.map(str::to_string);
+            profile.stats = show_stats.then_some(true);
+            return Self::save_profile(&profile);
+        }
+
         // Build the command
         let exe_name = if in_path {
             "vital-reader".to_string()
@@ -375,10 +433,18 @@ impl Commands {
 
         let mut command = format!("{} --port {}", exe_name, port_name);
 
+        if let Some(preset) = preset {
+            command.push_str(&format!(" --device {}", preset.name));
+        }
+
         if let Some(cfg) = config_str {
             command.push_str(&format!(" --config \"{}\"", cfg));
         }
 
+        if let Some(protocol) = protocol.filter(|protocol| *protocol != "hl7") {
+            command.push_str(&format!(" --protocol {}", protocol));
+        }
+
         if show_stats {
             command.push_str(" --stats");
         }
@@ -423,6 +489,29 @@ impl Commands {
         Ok(())
     }
 
+    fn save_profile(profile: &Profile) -> Result<()> {
+        let default_path = Profiles::default_path()
+            .map(|path| path.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "vital-reader.toml".to_string());
+        let path =
+            UI::prompt_with_default(&format!("Profile file [{}]: ", default_path), &default_path);
+
+        match Profiles::save(&path, profile) {
+            Ok(()) => {
+                println!("\n✓ Saved profile {} to {}", profile.name, path);
+                let mut command = format!("vital-reader --profile {}", profile.name);
+                if path != default_path {
+                    command.push_str(&format!(" --profile-file \"{}\"", path));
+                }
+                println!("\nRun it with:\n  {}", command);
+            }
+            Err(e) => println!("\n✗ Could not save profile: {:#}", e),
+        }
+
+        UI::wait_for_enter();
+        Ok(())
+    }
+
     fn check_if_in_path() -> bool {
         #[cfg(target_os = "windows")]
         let exe_name = "vital-reader.exe";
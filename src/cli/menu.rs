@@ -15,6 +15,7 @@ pub fn run_cli_mode() -> Result<()> {
             "3" => Commands::connect_and_read()?,
             "4" => Commands::send_fake_data()?,
             "5" => Commands::generate_command()?,
+            "6" => Commands::autodetect()?,
             "q" | "Q" => {
                 println!("\nGoodbye!");
                 break;
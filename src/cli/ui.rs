@@ -18,6 +18,7 @@ impl UI {
         println!("║  [3] Connect and read from port                    ║");
         println!("║  [4] Send fake data to port (testing)              ║");
         println!("║  [5] Generate command for listener                 ║");
+        println!("║  [6] Detect baud rate and framing                  ║");
         println!("║  [q] Quit                                          ║");
         println!("╚════════════════════════════════════════════════════╝");
     }
@@ -1,3 +1,5 @@
+mod profile;
 mod serial_config;
 
+pub use profile::{Profile, Profiles};
 pub use serial_config::SerialConfig;
@@ -0,0 +1,334 @@
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use toml::{Table, Value};
+
+use super::SerialConfig;
+use crate::port::PortRule;
+
+/// Saved command line settings for one device
+///
+/// Keys are named after the long command line options. A `rule` table
+/// picks the adapter by its USB descriptors, see `PortRule`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    pub port: Option<String>,
+    pub rule: Option<PortRule>,
+    /// Device preset filling in what the profile leaves out
+    pub device: Option<String>,
+    /// Config string, e.g. "19200,2,8,1"
+    pub config: Option<String>,
+    /// Baud rate or "auto"
+    pub baud: Option<String>,
+    pub data_bits: Option<u8>,
+    pub parity: Option<String>,
+    pub stop_bits: Option<u8>,
+    pub protocol: Option<String>,
+    pub driver: Option<String>,
+    pub interval: Option<u64>,
+    pub timeout: Option<u64>,
+    pub watchdog: Option<u64>,
+    pub output: Option<String>,
+    pub stats: Option<bool>,
+    pub csv: Option<String>,
+    pub csv_columns: Option<String>,
+    pub csv_missing: Option<String>,
+    pub record: Option<String>,
+    pub reconnect: Option<bool>,
+    pub reconnect_attempts: Option<u32>,
+    pub reconnect_match: Option<String>,
+    pub ack: Option<bool>,
+    pub ack_app: Option<String>,
+    pub ack_facility: Option<String>,
+}
+
+impl Profile {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Self::default()
+        }
+    }
+
+    fn from_table(name: &str, table: &Table) -> Result<Self> {
+        let mut profile = Self::new(name);
+        for (key, value) in table {
+            let invalid =
+                |kind: &str| anyhow::anyhow!("Profile {}: {} must be {}", name, key, kind);
+            let text = || match value {
+                Value::String(text) => Ok(text.clone()),
+                _ => Err(invalid("a string")),
+            };
+            let number = || match value {
+                Value::Integer(number) if *number >= 0 => Ok(*number as u64),
+                _ => Err(invalid("a positive integer")),
+            };
+            let small = || {
+                number()
+                    .and_then(|number| u8::try_from(number).map_err(|_| invalid("a small number")))
+            };
+            let flag = || match value {
+                Value::Boolean(flag) => Ok(*flag),
+                _ => Err(invalid("true or false")),
+            };
+
+            match key.as_str() {
+                "port" => profile.port = Some(text()?),
+                "rule" => profile.rule = Some(Self::rule(name, value)?),
+                "device" => profile.device = Some(text()?),
+                "config" => {
+                    let config = text()?;
+                    SerialConfig::from_string(&config)
+                        .context(format!("Profile {}: invalid config", name))?;
+                    profile.config = Some(config);
+                }
+                "baud" => {
+                    profile.baud = Some(match value {
+                        Value::String(auto) if auto == "auto" => auto.clone(),
+                        _ => number()?.to_string(),
+                    })
+                }
+                "data_bits" => {
+                    let bits = small()?;
+                    SerialConfig::parse_data_bits(bits)?;
+                    profile.data_bits = Some(bits);
+                }
+                "parity" => {
+                    let parity = text()?;
+                    SerialConfig::parse_parity(&parity)?;
+                    profile.parity = Some(parity);
+                }
+                "stop_bits" => {
+                    let bits = small()?;
+                    SerialConfig::parse_stop_bits(bits)?;
+                    profile.stop_bits = Some(bits);
+                }
+                "protocol" => profile.protocol = Some(text()?),
+                "driver" => profile.driver = Some(text()?),
+                "interval" => profile.interval = Some(number()?),
+                "timeout" => profile.timeout = Some(number()?),
+                "watchdog" => profile.watchdog = Some(number()?),
+                "output" => profile.output = Some(text()?),
+                "stats" => profile.stats = Some(flag()?),
+                "csv" => profile.csv = Some(text()?),
+                "csv_columns" => profile.csv_columns = Some(text()?),
+                "csv_missing" => profile.csv_missing = Some(text()?),
+                "record" => profile.record = Some(text()?),
+                "reconnect" => profile.reconnect = Some(flag()?),
+                "reconnect_attempts" => {
+                    profile.reconnect_attempts =
+                        Some(u32::try_from(number()?).map_err(|_| invalid("a smaller number"))?)
+                }
+                "reconnect_match" => profile.reconnect_match = Some(text()?),
+                "ack" => profile.ack = Some(flag()?),
+                "ack_app" => profile.ack_app = Some(text()?),
+                "ack_facility" => profile.ack_facility = Some(text()?),
+                _ => return Err(anyhow::anyhow!("Profile {}: unknown key {}", name, key)),
+            }
+        }
+        Ok(profile)
+    }
+
+    /// Fill the settings left out with those of `defaults`
+    ///
+    /// Serial settings are taken as a whole, so a profile giving only a
+    /// baud rate does not inherit the parity of its device.
+    pub fn with_defaults(mut self, defaults: &Profile) -> Self {
+        macro_rules! fill {
+            ($($field:ident),*) => {$(
+                if self.$field.is_none() {
+                    self.$field = defaults.$field.clone();
+                }
+            )*};
+        }
+
+        let serial = self.config.is_some()
+            || self.baud.is_some()
+            || self.data_bits.is_some()
+            || self.parity.is_some()
+            || self.stop_bits.is_some();
+        if !serial {
+            fill!(config, baud, data_bits, parity, stop_bits);
+        }
+        fill!(port, rule, device, protocol, driver, interval, timeout, watchdog, output);
+        fill!(stats, csv, csv_columns, csv_missing, record);
+        fill!(
+            reconnect,
+            reconnect_attempts,
+            reconnect_match,
+            ack,
+            ack_app,
+            ack_facility
+        );
+        self
+    }
+
+    /// The `rule` table, named after the profile unless it has a name
+    fn rule(name: &str, value: &Value) -> Result<PortRule> {
+        let Value::Table(table) = value else {
+            return Err(anyhow::anyhow!("Profile {}: rule must be a table", name));
+        };
+        let mut table = table.clone();
+        table
+            .entry("name")
+            .or_insert_with(|| Value::String(name.to_string()));
+        PortRule::from_table(&table)
+    }
+
+    /// Table as written to a profile file
+    pub fn to_table(&self) -> Table {
+        let mut table = Table::new();
+        let mut text = |key: &str, value: &Option<String>| {
+            if let Some(value) = value {
+                table.insert(key.to_string(), Value::String(value.clone()));
+            }
+        };
+        text("port", &self.port);
+        text("device", &self.device);
+        text("config", &self.config);
+        text("parity", &self.parity);
+        text("protocol", &self.protocol);
+        text("driver", &self.driver);
+        text("output", &self.output);
+        text("csv", &self.csv);
+        text("csv_columns", &self.csv_columns);
+        text("csv_missing", &self.csv_missing);
+        text("record", &self.record);
+        text("reconnect_match", &self.reconnect_match);
+        text("ack_app", &self.ack_app);
+        text("ack_facility", &self.ack_facility);
+
+        if let Some(ref baud) = self.baud {
+            let value = match baud.parse() {
+                Ok(baud) => Value::Integer(baud),
+                Err(_) => Value::String(baud.clone()),
+            };
+            table.insert("baud".to_string(), value);
+        }
+        let numbers = [
+            ("data_bits", self.data_bits.map(u64::from)),
+            ("stop_bits", self.stop_bits.map(u64::from)),
+            ("interval", self.interval),
+            ("timeout", self.timeout),
+            ("watchdog", self.watchdog),
+            ("reconnect_attempts", self.reconnect_attempts.map(u64::from)),
+        ];
+        for (key, value) in numbers {
+            if let Some(value) = value {
+                table.insert(key.to_string(), Value::Integer(value as i64));
+            }
+        }
+        let flags = [
+            ("stats", self.stats),
+            ("reconnect", self.reconnect),
+            ("ack", self.ack),
+        ];
+        for (key, value) in flags {
+            if let Some(value) = value {
+                table.insert(key.to_string(), Value::Boolean(value));
+            }
+        }
+
+        if let Some(ref rule) = self.rule {
+            table.insert("rule".to_string(), Value::Table(rule.to_table()));
+        }
+        table
+    }
+}
+
+/// Named profiles from a TOML file, one `[profile.NAME]` table each
+///
+/// ```toml
+/// [profile.drager-evita]
+/// config = "19200,2,8,1"
+/// driver = "medibus"
+/// output = "observations"
+///
+/// [profile.drager-evita.rule]
+/// vid = "0403"
+/// serial_number = "A12BC3"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Profiles {
+    profiles: Vec<Profile>,
+}
+
+impl Profiles {
+    /// `vital-reader/config.toml` in the user's configuration directory
+    pub fn default_path() -> Option<PathBuf> {
+        #[cfg(target_os = "windows")]
+        let base = std::env::var_os("APPDATA").map(PathBuf::from);
+        #[cfg(not(target_os = "windows"))]
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+
+        base.map(|dir| dir.join("vital-reader").join("config.toml"))
+    }
+
+    pub fn load(path: &str) -> Result<Self> {
+        let text = std::fs::read_to_string(path).context(format!("Failed to read {}", path))?;
+        Self::parse(&text).context(format!("Invalid profiles in {}", path))
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let table: Table = text.parse()?;
+        let profiles = match table.get("profile") {
+            Some(Value::Table(profiles)) => profiles
+                .iter()
+                .map(|(name, profile)| match profile {
+                    Value::Table(profile) => Profile::from_table(name, profile),
+                    _ => Err(anyhow::anyhow!("Profile {} must be a table", name)),
+                })
+                .collect::<Result<Vec<_>>>()?,
+            Some(_) => return Err(anyhow::anyhow!("Profiles must be [profile.NAME] tables")),
+            None => Vec::new(),
+        };
+        Ok(Self { profiles })
+    }
+
+    pub fn profiles(&self) -> &[Profile] {
+        &self.profiles
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|profile| profile.name == name)
+    }
+
+    /// The profile named `name`, listing the known ones if there is none
+    pub fn select(&self, name: &str) -> Result<&Profile> {
+        self.get(name).ok_or_else(|| {
+            let names: Vec<_> = self.profiles.iter().map(|p| p.name.as_str()).collect();
+            anyhow::anyhow!("No profile named {} (have: {})", name, names.join(", "))
+        })
+    }
+
+    /// Add or replace a profile in the file at `path`, creating it if needed
+    ///
+    /// Other tables in the file are kept, comments are not.
+    pub fn save(path: &str, profile: &Profile) -> Result<()> {
+        let mut table: Table = match std::fs::read_to_string(path) {
+            Ok(text) => text.parse().context(format!("Invalid TOML in {}", path))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Table::new(),
+            Err(e) => return Err(anyhow::anyhow!("Failed to read {}: {}", path, e)),
+        };
+
+        let profiles = table
+            .entry("profile")
+            .or_insert_with(|| Value::Table(Table::new()));
+        let Value::Table(profiles) = profiles else {
+            return Err(anyhow::anyhow!("{}: profile is not a table", path));
+        };
+        // Refuse what would not load back
+        let entry = profile.to_table();
+        Profile::from_table(&profile.name, &entry)?;
+        profiles.insert(profile.name.clone(), Value::Table(entry));
+
+        if let Some(dir) = std::path::Path::new(path).parent() {
+            std::fs::create_dir_all(dir).context(format!("Failed to create {}", dir.display()))?;
+        }
+        let text = toml::to_string(&table)?;
+        std::fs::write(path, text).context(format!("Failed to write {}", path))
+    }
+}
@@ -1,5 +1,6 @@
 use anyhow::{Context, Result};
-use serialport::{DataBits, Parity, StopBits};
+use serialport::{DataBits, FlowControl, Parity, StopBits};
+use std::fmt;
 
 /// Serial port configuration
 #[derive(Debug, Clone)]
@@ -7,63 +8,200 @@ pub struct SerialConfig {
     pub baud: u32,
     pub data_bits: DataBits,
     pub parity: Parity,
+    /// `Two` with 5 data bits is sent as 1.5 stop bits by the UART
     pub stop_bits: StopBits,
+    /// Parity bit held at a fixed level: mark with `Parity::Odd`, space
+    /// with `Parity::Even`
+    pub stick_parity: bool,
+    pub flow_control: FlowControl,
+    /// DTR level set on open, `None` to keep the driver's
+    pub dtr: Option<bool>,
+    /// RTS level set on open, `None` to keep the driver's
+    pub rts: Option<bool>,
+    /// Bytes read from the port at once
+    pub read_buffer: usize,
+    /// Largest write handed to the port at once, longer ones are split
+    pub write_buffer: usize,
 }
 
 impl SerialConfig {
     /// Create a new serial configuration
     pub fn new(baud: u32, data_bits: u8, parity: &str, stop_bits: u8) -> Result<Self> {
+        let (parity, stick_parity) = Self::parse_parity(parity)?;
         Ok(Self {
             baud,
             data_bits: Self::parse_data_bits(data_bits)?,
-            parity: Self::parse_parity(parity)?,
+            parity,
             stop_bits: Self::parse_stop_bits(stop_bits)?,
+            stick_parity,
+            ..Self::default()
         })
     }
 
-    /// Parse config from string format: "baud,parity,data_bits,stop_bits"
-    /// Example: "115200,0,8,1"
-    /// Parity: 0=none, 1=odd, 2=even
+    /// Parse config from string format: "baud,parity,data_bits,stop_bits[,option...]"
+    /// Example: "115200,0,8,1" or "9600,E,7,1,rtscts"
+    /// Parity: 0=none, 1=odd, 2=even, or a letter N, O, E, M (mark), S (space)
+    /// Stop bits: 1, 2, or 1.5 with 5 data bits
+    /// Options: rtscts, xonxoff, dtr=on|off, rts=on|off, rbuf=BYTES, wbuf=BYTES
     pub fn from_string(config: &str) -> Result<Self> {
         let parts: Vec<&str> = config.split(',').collect();
 
-        if parts.len() != 4 {
+        if parts.len() < 4 {
             return Err(anyhow::anyhow!(
-                "Invalid config format. Expected: baud,parity,data_bits,stop_bits (e.g., 57600,0,8,1)"
+                "Invalid config format. Expected: baud,parity,data_bits,stop_bits[,option...] (e.g., 57600,0,8,1)"
             ));
         }
 
-        let baud: u32 = parts[0].parse().context("Invalid baud rate")?;
+        let mut result = Self {
+            baud: parts[0].parse().context("Invalid baud rate")?,
+            ..Self::default()
+        };
 
-        let parity_num: u8 = parts[1]
-            .parse()
-            .context("Invalid parity (0=none, 1=odd, 2=even)")?;
-        let parity = match parity_num {
-            0 => Parity::None,
-            1 => Parity::Odd,
-            2 => Parity::Even,
+        (result.parity, result.stick_parity) = match parts[1] {
+            "0" => (Parity::None, false),
+            "1" => (Parity::Odd, false),
+            "2" => (Parity::Even, false),
+            letter if letter.len() == 1 => Self::parse_parity(letter).map_err(|_| {
+                anyhow::anyhow!(
+                    "Parity must be 0 (none), 1 (odd), or 2 (even), or one of N, O, E, M, S"
+                )
+            })?,
             _ => {
                 return Err(anyhow::anyhow!(
-                    "Parity must be 0 (none), 1 (odd), or 2 (even)"
+                    "Parity must be 0 (none), 1 (odd), or 2 (even), or one of N, O, E, M, S"
                 ))
             }
         };
 
-        let data_bits_num: u8 = parts[2].parse().context("Invalid data bits")?;
-        let data_bits = Self::parse_data_bits(data_bits_num)?;
+        result.set_option("data_bits", parts[2])?;
+        result.set_option("stop_bits", parts[3])?;
 
-        let stop_bits_num: u8 = parts[3].parse().context("Invalid stop bits")?;
-        let stop_bits = Self::parse_stop_bits(stop_bits_num)?;
+        for option in &parts[4..] {
+            match option.split_once('=') {
+                Some((key, value)) => result.set_option(key, value)?,
+                None if matches!(*option, "rtscts" | "xonxoff") => {
+                    result.set_option("flow", option)?
+                }
+                None => result.set_option(option, "")?,
+            }
+        }
 
-        Ok(Self {
-            baud,
-            data_bits,
+        result.validate()?;
+        Ok(result)
+    }
+
+    /// Set one setting by name, as in a config string or `serial://` URI
+    ///
+    /// Keys: baud, data_bits, parity, stop_bits, flow (none, rtscts,
+    /// xonxoff), dtr and rts (on, off), rbuf and wbuf (bytes).
+    pub fn set_option(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "baud" => self.baud = value.parse().context("Invalid baud rate")?,
+            "data_bits" => {
+                self.data_bits = Self::parse_data_bits(value.parse().context("Invalid data bits")?)?
+            }
+            "parity" => (self.parity, self.stick_parity) = Self::parse_parity(value)?,
+            "stop_bits" if value == "1.5" => {
+                if self.data_bits != DataBits::Five {
+                    return Err(anyhow::anyhow!("1.5 stop bits need 5 data bits"));
+                }
+                self.stop_bits = StopBits::Two;
+            }
+            "stop_bits" => {
+                self.stop_bits = Self::parse_stop_bits(value.parse().context("Invalid stop bits")?)?
+            }
+            "flow" => self.flow_control = Self::parse_flow_control(value)?,
+            "dtr" => self.dtr = Some(Self::parse_level(key, value)?),
+            "rts" => self.rts = Some(Self::parse_level(key, value)?),
+            "rbuf" => self.read_buffer = Self::parse_buffer_size(key, value)?,
+            "wbuf" => self.write_buffer = Self::parse_buffer_size(key, value)?,
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "Unknown serial option: {} (expected {})",
+                    key,
+                    "rtscts, xonxoff, dtr, rts, rbuf or wbuf"
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    /// Check settings that conflict in combination, and buffer sizes set
+    /// directly on the fields
+    pub fn validate(&self) -> Result<()> {
+        if self.rts.is_some() && self.flow_control == FlowControl::Hardware {
+            return Err(anyhow::anyhow!(
+                "RTS is driven by the port with rtscts flow control"
+            ));
+        }
+        if self.stick_parity && self.parity == Parity::None {
+            return Err(anyhow::anyhow!("Mark and space parity need a parity bit"));
+        }
+        if self.read_buffer == 0 || self.write_buffer == 0 {
+            return Err(anyhow::anyhow!(
+                "Read and write buffers need at least one byte"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Parity as a letter: N, O, E, M or S
+    pub fn parity_letter(&self) -> char {
+        match (self.parity, self.stick_parity) {
+            (Parity::None, _) => 'N',
+            (Parity::Odd, false) => 'O',
+            (Parity::Even, false) => 'E',
+            (Parity::Odd, true) => 'M',
+            (Parity::Even, true) => 'S',
+        }
+    }
+
+    /// Stop bits as sent on the wire: "1", "1.5" or "2"
+    pub fn stop_bits_name(&self) -> &'static str {
+        match (self.stop_bits, self.data_bits) {
+            (StopBits::One, _) => "1",
+            (StopBits::Two, DataBits::Five) => "1.5",
+            (StopBits::Two, _) => "2",
+        }
+    }
+
+    /// The settings as a config string, read back by `from_string`
+    pub fn to_config_string(&self) -> String {
+        let parity = match self.parity_letter() {
+            'N' => "0".to_string(),
+            'O' => "1".to_string(),
+            'E' => "2".to_string(),
+            letter => letter.to_string(),
+        };
+        let mut parts = vec![
+            self.baud.to_string(),
             parity,
-            stop_bits,
-        })
+            u8::from(self.data_bits).to_string(),
+            self.stop_bits_name().to_string(),
+        ];
+        match self.flow_control {
+            FlowControl::None => {}
+            FlowControl::Software => parts.push("xonxoff".to_string()),
+            FlowControl::Hardware => parts.push("rtscts".to_string()),
+        }
+        let level = |on: bool| if on { "on" } else { "off" };
+        if let Some(dtr) = self.dtr {
+            parts.push(format!("dtr={}", level(dtr)));
+        }
+        if let Some(rts) = self.rts {
+            parts.push(format!("rts={}", level(rts)));
+        }
+        let defaults = Self::default();
+        if self.read_buffer != defaults.read_buffer {
+            parts.push(format!("rbuf={}", self.read_buffer));
+        }
+        if self.write_buffer != defaults.write_buffer {
+            parts.push(format!("wbuf={}", self.write_buffer));
+        }
+        parts.join(",")
     }
 
-    fn parse_data_bits(bits: u8) -> Result<DataBits> {
+    pub(crate) fn parse_data_bits(bits: u8) -> Result<DataBits> {
         match bits {
             5 => Ok(DataBits::Five),
             6 => Ok(DataBits::Six),
@@ -73,22 +211,56 @@ impl SerialConfig {
         }
     }
 
-    fn parse_parity(parity: &str) -> Result<Parity> {
+    /// Parity and whether it is a stick (mark or space) parity
+    pub(crate) fn parse_parity(parity: &str) -> Result<(Parity, bool)> {
         match parity.to_lowercase().as_str() {
-            "none" | "n" => Ok(Parity::None),
-            "odd" | "o" => Ok(Parity::Odd),
-            "even" | "e" => Ok(Parity::Even),
+            "none" | "n" => Ok((Parity::None, false)),
+            "odd" | "o" => Ok((Parity::Odd, false)),
+            "even" | "e" => Ok((Parity::Even, false)),
+            "mark" | "m" => Ok((Parity::Odd, true)),
+            "space" | "s" => Ok((Parity::Even, true)),
             _ => Err(anyhow::anyhow!("Invalid parity: {}", parity)),
         }
     }
 
-    fn parse_stop_bits(bits: u8) -> Result<StopBits> {
+    pub(crate) fn parse_stop_bits(bits: u8) -> Result<StopBits> {
         match bits {
             1 => Ok(StopBits::One),
             2 => Ok(StopBits::Two),
             _ => Err(anyhow::anyhow!("Invalid stop bits: {}", bits)),
         }
     }
+
+    fn parse_flow_control(flow: &str) -> Result<FlowControl> {
+        match flow.to_lowercase().as_str() {
+            "none" => Ok(FlowControl::None),
+            "rtscts" => Ok(FlowControl::Hardware),
+            "xonxoff" => Ok(FlowControl::Software),
+            _ => Err(anyhow::anyhow!(
+                "Invalid flow control: {} (expected none, rtscts or xonxoff)",
+                flow
+            )),
+        }
+    }
+
+    fn parse_level(line: &str, level: &str) -> Result<bool> {
+        match level.to_lowercase().as_str() {
+            "on" | "1" => Ok(true),
+            "off" | "0" => Ok(false),
+            _ => Err(anyhow::anyhow!(
+                "Invalid {} level: {} (expected on or off)",
+                line,
+                level
+            )),
+        }
+    }
+
+    fn parse_buffer_size(key: &str, size: &str) -> Result<usize> {
+        match size.parse() {
+            Ok(size) if size > 0 => Ok(size),
+            _ => Err(anyhow::anyhow!("Invalid {} size: {}", key, size)),
+        }
+    }
 }
 
 impl Default for SerialConfig {
@@ -98,6 +270,32 @@ impl Default for SerialConfig {
             data_bits: DataBits::Eight,
             parity: Parity::None,
             stop_bits: StopBits::One,
+            stick_parity: false,
+            flow_control: FlowControl::None,
+            dtr: None,
+            rts: None,
+            read_buffer: 1024,
+            write_buffer: 4096,
         }
     }
 }
+
+/// Compact form such as "9600 7E1"
+impl fmt::Display for SerialConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let data_bits = match self.data_bits {
+            DataBits::Five => 5,
+            DataBits::Six => 6,
+            DataBits::Seven => 7,
+            DataBits::Eight => 8,
+        };
+        write!(
+            f,
+            "{} {}{}{}",
+            self.baud,
+            data_bits,
+            self.parity_letter(),
+            self.stop_bits_name()
+        )
+    }
+}
@@ -0,0 +1,330 @@
+use anyhow::Result;
+
+use super::{Decoder, Frame};
+
+const MAX_PACKET_SIZE: usize = 4096;
+
+/// How the end of a packet body is found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PacketSize {
+    /// Body has a fixed number of bytes (sequence, samples and checksum)
+    Fixed(usize),
+    /// First body byte gives the number of sequence and sample bytes that
+    /// follow, the checksum comes after them
+    LengthPrefixed,
+    /// Body runs until the end byte
+    Delimited,
+}
+
+/// Checksum carried in the last body byte
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumKind {
+    None,
+    /// Sum of the covered bytes modulo 256
+    Sum8,
+    /// XOR of the covered bytes
+    Xor8,
+}
+
+impl ChecksumKind {
+    /// Parse a checksum name: "none", "sum8" or "xor8"
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "none" => Ok(Self::None),
+            "sum8" | "sum" => Ok(Self::Sum8),
+            "xor8" | "xor" => Ok(Self::Xor8),
+            _ => Err(anyhow::anyhow!(
+                "Invalid checksum: {} (expected none, sum8 or xor8)",
+                name
+            )),
+        }
+    }
+
+    pub fn compute(&self, bytes: &[u8]) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Sum8 => bytes.iter().fold(0u8, |acc, &b| acc.wrapping_add(b)),
+            Self::Xor8 => bytes.iter().fold(0u8, |acc, &b| acc ^ b),
+        }
+    }
+}
+
+/// Layout of a framed binary packet: `<start> body [end]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinaryFrameConfig {
+    pub start: u8,
+    pub end: Option<u8>,
+    pub size: PacketSize,
+    /// Covers the body bytes before it (length, sequence and samples)
+    pub checksum: ChecksumKind,
+    /// First body byte (after a length byte) is a wrapping sequence number
+    pub sequence: bool,
+    /// Bytes per sample: 1, or 2 for big endian 16 bit samples
+    pub sample_width: usize,
+}
+
+impl BinaryFrameConfig {
+    /// `[STX, SEQ, VALUE, CHECKSUM, ETX]` as sent by the waveform simulator
+    pub fn simulator() -> Self {
+        Self {
+            start: 0x02,
+            end: Some(0x03),
+            size: PacketSize::Fixed(3),
+            checksum: ChecksumKind::Sum8,
+            sequence: true,
+            sample_width: 1,
+        }
+    }
+}
+
+impl Default for BinaryFrameConfig {
+    fn default() -> Self {
+        Self::simulator()
+    }
+}
+
+/// A decoded packet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinaryPacket {
+    pub sequence: Option<u8>,
+    pub samples: Vec<u16>,
+}
+
+/// What the decoder found in the byte stream
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BinaryEvent {
+    Packet(BinaryPacket),
+    ChecksumError {
+        sequence: Option<u8>,
+        expected: u8,
+        received: u8,
+    },
+    /// Packets between `expected` and `received` were lost
+    SequenceGap {
+        expected: u8,
+        received: u8,
+    },
+}
+
+/// Decoder for STX/ETX style binary packets
+///
+/// Bytes outside a packet, or packets whose end byte is missing, are
+/// discarded and counted. Checksum failures and sequence gaps are reported
+/// as events and counted so stream integrity can be checked afterwards.
+pub struct BinaryDecoder {
+    config: BinaryFrameConfig,
+    buffer: Vec<u8>,
+    in_packet: bool,
+    next_sequence: Option<u8>,
+    packets: u64,
+    checksum_errors: u64,
+    sequence_gaps: u64,
+    lost_packets: u64,
+    discarded: u64,
+}
+
+impl BinaryDecoder {
+    pub fn new(config: BinaryFrameConfig) -> Self {
+        Self {
+            config,
+            buffer: Vec::new(),
+            in_packet: false,
+            next_sequence: None,
+            packets: 0,
+            checksum_errors: 0,
+            sequence_gaps: 0,
+            lost_packets: 0,
+            discarded: 0,
+        }
+    }
+
+    pub fn config(&self) -> &BinaryFrameConfig {
+        &self.config
+    }
+
+    /// Feed received bytes and return decoded packets and integrity errors
+    pub fn decode(&mut self, data: &[u8]) -> Vec<BinaryEvent> {
+        let mut events = Vec::new();
+
+        for &byte in data {
+            if !self.in_packet {
+                if byte == self.config.start {
+                    self.in_packet = true;
+                    self.buffer.clear();
+                } else {
+                    self.discarded += 1;
+                }
+                continue;
+            }
+
+            match self.body_length() {
+                // Body complete, only the end byte is missing
+                Some(length) if self.buffer.len() == length => {
+                    self.in_packet = false;
+                    if self.config.end == Some(byte) {
+                        self.finish_packet(&mut events);
+                    } else {
+                        self.resync(byte);
+                    }
+                }
+                _ if self.config.size == PacketSize::Delimited && self.config.end == Some(byte) => {
+                    self.in_packet = false;
+                    self.finish_packet(&mut events);
+                }
+                _ => {
+                    self.buffer.push(byte);
+                    if self.buffer.len() > MAX_PACKET_SIZE {
+                        self.discarded += self.buffer.len() as u64 + 1;
+                        self.buffer.clear();
+                        self.in_packet = false;
+                    } else if self.config.end.is_none()
+                        && self.body_length() == Some(self.buffer.len())
+                    {
+                        self.in_packet = false;
+                        self.finish_packet(&mut events);
+                    }
+                }
+            }
+        }
+
+        events
+    }
+
+    pub fn packets(&self) -> u64 {
+        self.packets
+    }
+
+    pub fn checksum_errors(&self) -> u64 {
+        self.checksum_errors
+    }
+
+    pub fn sequence_gaps(&self) -> u64 {
+        self.sequence_gaps
+    }
+
+    /// Packets missing according to the sequence numbers
+    pub fn lost_packets(&self) -> u64 {
+        self.lost_packets
+    }
+
+    /// Bytes received outside a valid frame
+    pub fn discarded_bytes(&self) -> u64 {
+        self.discarded
+    }
+
+    /// Total body length once it is known
+    fn body_length(&self) -> Option<usize> {
+        let checksum = usize::from(self.config.checksum != ChecksumKind::None);
+        match self.config.size {
+            PacketSize::Fixed(length) => Some(length),
+            PacketSize::LengthPrefixed => self
+                .buffer
+                .first()
+                .map(|&length| 1 + length as usize + checksum),
+            PacketSize::Delimited => None,
+        }
+    }
+
+    /// The end byte was wrong: drop the packet and restart on a start byte
+    fn resync(&mut self, byte: u8) {
+        self.discarded += self.buffer.len() as u64 + 1;
+        self.buffer.clear();
+        if byte == self.config.start {
+            self.discarded -= 1;
+            self.in_packet = true;
+        }
+    }
+
+    fn finish_packet(&mut self, events: &mut Vec<BinaryEvent>) {
+        let mut body = std::mem::take(&mut self.buffer);
+
+        let mut content_start = 0;
+        if self.config.size == PacketSize::LengthPrefixed {
+            content_start = 1;
+        }
+
+        if self.config.checksum != ChecksumKind::None {
+            let Some(received) = body.pop() else {
+                self.discarded += 1;
+                return;
+            };
+            let expected = self.config.checksum.compute(&body);
+            if received != expected {
+                self.checksum_errors += 1;
+                let sequence = body
+                    .get(content_start)
+                    .copied()
+                    .filter(|_| self.config.sequence);
+                // Already counted as a checksum error, not lost again
+                if let Some(number) = sequence {
+                    self.next_sequence = Some(number.wrapping_add(1));
+                }
+                events.push(BinaryEvent::ChecksumError {
+                    sequence,
+                    expected,
+                    received,
+                });
+                return;
+            }
+        }
+
+        let mut content = body.get(content_start..).unwrap_or(&[]);
+        let mut sequence = None;
+        if self.config.sequence {
+            let Some((&number, rest)) = content.split_first() else {
+                self.discarded += body.len() as u64;
+                return;
+            };
+            sequence = Some(number);
+            content = rest;
+            self.check_sequence(number, events);
+        }
+
+        let samples = match self.config.sample_width {
+            2 => content
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                .collect(),
+            _ => content.iter().map(|&b| b as u16).collect(),
+        };
+
+        self.packets += 1;
+        events.push(BinaryEvent::Packet(BinaryPacket { sequence, samples }));
+    }
+
+    fn check_sequence(&mut self, received: u8, events: &mut Vec<BinaryEvent>) {
+        if let Some(expected) = self.next_sequence {
+            if received != expected {
+                self.sequence_gaps += 1;
+                self.lost_packets += received.wrapping_sub(expected) as u64;
+                events.push(BinaryEvent::SequenceGap { expected, received });
+            }
+        }
+        self.next_sequence = Some(received.wrapping_add(1));
+    }
+}
+
+impl Default for BinaryDecoder {
+    fn default() -> Self {
+        Self::new(BinaryFrameConfig::simulator())
+    }
+}
+
+impl Decoder for BinaryDecoder {
+    fn name(&self) -> &'static str {
+        "binary"
+    }
+
+    fn feed(&mut self, data: &[u8]) -> Vec<Frame> {
+        self.decode(data).into_iter().map(Frame::Binary).collect()
+    }
+
+    fn stats(&self) -> Vec<(&'static str, u64)> {
+        vec![
+            ("Packets", self.packets),
+            ("Checksum errors", self.checksum_errors),
+            ("Sequence gaps", self.sequence_gaps),
+            ("Lost packets", self.lost_packets),
+        ]
+    }
+}
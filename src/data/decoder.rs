@@ -0,0 +1,254 @@
+use super::hl7::{Message, MessageAssembler};
+use super::{BinaryEvent, MllpCodec, VitalObservation};
+
+const MAX_LINE_LENGTH: usize = 65536;
+
+/// A unit decoded from the received byte stream
+#[derive(Debug, Clone, PartialEq)]
+pub enum Frame {
+    /// A line, including its CR or LF terminator when there was one
+    Line(Vec<u8>),
+    /// A read chunk passed through unchanged
+    Chunk(Vec<u8>),
+    /// A complete HL7 message
+    Message(Message),
+    /// A framed binary packet or integrity error
+    Binary(BinaryEvent),
+    /// A measurement decoded by a device specific decoder
+    Observation(VitalObservation),
+}
+
+/// Turns received bytes into frames
+///
+/// Implement this for in-house devices and register it in a
+/// `DecoderRegistry` to make it selectable with `--protocol`.
+pub trait Decoder: Send {
+    /// Protocol name, as given to `--protocol`
+    fn name(&self) -> &'static str;
+
+    /// Feed received bytes and return the frames completed by them
+    fn feed(&mut self, data: &[u8]) -> Vec<Frame>;
+
+    /// The link went idle, complete what the sender may consider finished
+    fn idle(&mut self) -> Vec<Frame> {
+        Vec::new()
+    }
+
+    /// End of stream, flush anything still buffered
+    fn finish(&mut self) -> Vec<Frame> {
+        Vec::new()
+    }
+
+    /// Wrap an outgoing reply (e.g. an HL7 ACK) in this protocol's framing
+    fn encode(&self, payload: &[u8]) -> Vec<u8> {
+        payload.to_vec()
+    }
+
+    /// Protocol specific counters shown with the session statistics
+    fn stats(&self) -> Vec<(&'static str, u64)> {
+        Vec::new()
+    }
+}
+
+/// CR and/or LF terminated lines
+///
+/// CR LF counts as a single terminator. Lines longer than 64 KiB are
+/// flushed without one.
+pub struct LineDecoder {
+    buffer: Vec<u8>,
+    last_was_cr: bool,
+}
+
+impl LineDecoder {
+    pub fn new() -> Self {
+        Self {
+            buffer: Vec::new(),
+            last_was_cr: false,
+        }
+    }
+
+    fn flush(&mut self, frames: &mut Vec<Frame>) {
+        if !self.buffer.is_empty() {
+            frames.push(Frame::Line(std::mem::take(&mut self.buffer)));
+        }
+    }
+}
+
+impl Decoder for LineDecoder {
+    fn name(&self) -> &'static str {
+        "line"
+    }
+
+    fn feed(&mut self, data: &[u8]) -> Vec<Frame> {
+        let mut frames = Vec::new();
+
+        for &byte in data {
+            if byte == b'\r' {
+                self.buffer.push(byte);
+                self.last_was_cr = true;
+                self.flush(&mut frames);
+            } else if byte == b'\n' {
+                if !self.last_was_cr {
+                    self.buffer.push(byte);
+                    self.flush(&mut frames);
+                }
+                self.last_was_cr = false;
+            } else {
+                self.last_was_cr = false;
+                self.buffer.push(byte);
+
+                if self.buffer.len() > MAX_LINE_LENGTH {
+                    self.flush(&mut frames);
+                }
+            }
+        }
+
+        frames
+    }
+
+    fn finish(&mut self) -> Vec<Frame> {
+        let mut frames = Vec::new();
+        self.flush(&mut frames);
+        frames
+    }
+}
+
+impl Default for LineDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// HL7 v2 over plain lines: every line, plus each message once complete
+pub struct Hl7Decoder {
+    lines: LineDecoder,
+    assembler: MessageAssembler,
+}
+
+impl Hl7Decoder {
+    pub fn new() -> Self {
+        Self {
+            lines: LineDecoder::new(),
+            assembler: MessageAssembler::new(),
+        }
+    }
+
+    fn assemble(&mut self, lines: Vec<Frame>) -> Vec<Frame> {
+        let mut frames = Vec::with_capacity(lines.len());
+        for frame in lines {
+            let message = match &frame {
+                Frame::Line(line) => std::str::from_utf8(line)
+                    .ok()
+                    .and_then(|line| self.assembler.push_line(line)),
+                _ => None,
+            };
+            frames.push(frame);
+            frames.extend(message.map(Frame::Message));
+        }
+        frames
+    }
+}
+
+impl Decoder for Hl7Decoder {
+    fn name(&self) -> &'static str {
+        "hl7"
+    }
+
+    fn feed(&mut self, data: &[u8]) -> Vec<Frame> {
+        let lines = self.lines.feed(data);
+        self.assemble(lines)
+    }
+
+    /// Completes the message in progress but keeps a partial line, the
+    /// sender may be waiting for an ACK
+    fn idle(&mut self) -> Vec<Frame> {
+        self.assembler
+            .finish()
+            .map(Frame::Message)
+            .into_iter()
+            .collect()
+    }
+
+    fn finish(&mut self) -> Vec<Frame> {
+        let lines = self.lines.finish();
+        let mut frames = self.assemble(lines);
+        frames.extend(self.idle());
+        frames
+    }
+}
+
+impl Default for Hl7Decoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// HL7 v2 in MLLP blocks: the segments of each block, then its message
+pub struct MllpDecoder {
+    codec: MllpCodec,
+}
+
+impl MllpDecoder {
+    pub fn new() -> Self {
+        Self {
+            codec: MllpCodec::new(),
+        }
+    }
+}
+
+impl Decoder for MllpDecoder {
+    fn name(&self) -> &'static str {
+        "mllp"
+    }
+
+    fn feed(&mut self, data: &[u8]) -> Vec<Frame> {
+        let mut frames = Vec::new();
+
+        for block in self.codec.decode(data) {
+            for segment in block.split(|&b| b == b'\r' || b == b'\n') {
+                if !segment.is_empty() {
+                    frames.push(Frame::Line(segment.to_vec()));
+                }
+            }
+
+            if let Ok(text) = std::str::from_utf8(&block) {
+                if let Ok(message) = Message::parse(text) {
+                    frames.push(Frame::Message(message));
+                }
+            }
+        }
+
+        frames
+    }
+
+    fn encode(&self, payload: &[u8]) -> Vec<u8> {
+        MllpCodec::encode(payload)
+    }
+
+    fn stats(&self) -> Vec<(&'static str, u64)> {
+        vec![("Discarded bytes", self.codec.discarded_bytes())]
+    }
+}
+
+impl Default for MllpDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Every read chunk as is, without looking for any framing
+pub struct RawDecoder;
+
+impl Decoder for RawDecoder {
+    fn name(&self) -> &'static str {
+        "raw"
+    }
+
+    fn feed(&mut self, data: &[u8]) -> Vec<Frame> {
+        if data.is_empty() {
+            Vec::new()
+        } else {
+            vec![Frame::Chunk(data.to_vec())]
+        }
+    }
+}
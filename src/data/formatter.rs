@@ -1,4 +1,4 @@
-use super::DataType;
+use super::{BinaryEvent, DataType, ParserStats, VitalObservation};
 
 /// Formats data for display
 pub struct DataFormatter;
@@ -79,4 +79,106 @@ impl DataFormatter {
             DataType::Mixed => Some(Self::format_mixed(data, timestamp)),
         }
     }
+
+    /// Format a decoded observation for display
+    pub fn format_observation(observation: &VitalObservation, timestamp: &str) -> String {
+        let mut output = format!(
+            "[{}] OBS: {} ({}) = {}",
+            timestamp, observation.display_name, observation.code, observation.value
+        );
+
+        if let Some(unit) = &observation.unit {
+            output.push_str(&format!(" {}", unit));
+        }
+        if let Some(range) = &observation.reference_range {
+            output.push_str(&format!(" [{}]", range));
+        }
+        if let Some(flag) = &observation.abnormal_flag {
+            output.push_str(&format!(" flag={}", flag));
+        }
+        if let Some(producer) = &observation.producer {
+            output.push_str(&format!(" from {}", producer));
+        }
+
+        output
+    }
+
+    /// Format a framed binary packet or integrity error for display
+    pub fn format_binary_event(event: &BinaryEvent, timestamp: &str) -> String {
+        match event {
+            BinaryEvent::Packet(packet) => {
+                let samples: Vec<String> = packet.samples.iter().map(|s| s.to_string()).collect();
+                match packet.sequence {
+                    Some(sequence) => format!(
+                        "[{}] PACKET #{}: {}",
+                        timestamp,
+                        sequence,
+                        samples.join(" ")
+                    ),
+                    None => format!("[{}] PACKET: {}", timestamp, samples.join(" ")),
+                }
+            }
+            BinaryEvent::ChecksumError {
+                sequence,
+                expected,
+                received,
+            } => {
+                let packet = sequence.map(|s| format!(" in packet #{}", s));
+                format!(
+                    "[{}] CHECKSUM ERROR{}: expected 0x{:02X}, got 0x{:02X}",
+                    timestamp,
+                    packet.unwrap_or_default(),
+                    expected,
+                    received
+                )
+            }
+            BinaryEvent::SequenceGap { expected, received } => format!(
+                "[{}] SEQUENCE GAP: expected #{}, got #{}",
+                timestamp, expected, received
+            ),
+        }
+    }
+
+    /// Format parser statistics as the multi-line "Data Analysis" report
+    pub fn format_stats(stats: &ParserStats) -> String {
+        let percent = |count: u64| count as f64 / stats.total_bytes as f64 * 100.0;
+
+        let mut lines = vec![
+            "\nData Analysis:".to_string(),
+            format!("  Total bytes:      {}", stats.total_bytes),
+            format!(
+                "  ASCII bytes:      {} ({:.1}%)",
+                stats.ascii_bytes,
+                percent(stats.ascii_bytes)
+            ),
+            format!(
+                "  Binary bytes:     {} ({:.1}%)",
+                stats.binary_bytes,
+                percent(stats.binary_bytes)
+            ),
+            format!("  Detected type:    {:?}", stats.detected_type),
+            format!("  Protocol:         {}", stats.protocol),
+        ];
+        for (name, value) in &stats.decoder {
+            lines.push(format!("  {:<18}{}", format!("{}:", name), value));
+        }
+
+        lines.push("\nMost common bytes:".to_string());
+        for &(byte, count) in &stats.most_common {
+            let char_repr = if Self::is_printable_ascii(byte) {
+                format!("'{}' ", byte as char)
+            } else {
+                String::new()
+            };
+            lines.push(format!(
+                "    0x{:02X} {}: {} times ({:.1}%)",
+                byte,
+                char_repr,
+                count,
+                percent(count)
+            ));
+        }
+
+        lines.join("\n")
+    }
 }
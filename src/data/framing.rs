@@ -0,0 +1,45 @@
+use anyhow::Result;
+
+/// How messages are delimited on the wire
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Framing {
+    /// CR and/or LF terminated lines
+    #[default]
+    Line,
+    /// MLLP blocks (VT ... FS CR), one HL7 message per block
+    Mllp,
+    /// STX/ETX binary packets, see `BinaryFrameConfig`
+    Binary,
+}
+
+impl Framing {
+    /// Parse a framing name: "line", "mllp" or "binary"
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "line" | "lines" => Ok(Self::Line),
+            "mllp" => Ok(Self::Mllp),
+            "binary" => Ok(Self::Binary),
+            _ => Err(anyhow::anyhow!(
+                "Invalid framing: {} (expected line, mllp or binary)",
+                name
+            )),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Line => "line",
+            Self::Mllp => "mllp",
+            Self::Binary => "binary",
+        }
+    }
+
+    /// Decoder protocol implementing this framing, line framing assembles HL7
+    pub fn protocol(&self) -> &'static str {
+        match self {
+            Self::Line => "hl7",
+            Self::Mllp => "mllp",
+            Self::Binary => "binary",
+        }
+    }
+}
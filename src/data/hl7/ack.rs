@@ -0,0 +1,157 @@
+use chrono::Local;
+
+use super::{Field, Message, Segment};
+
+/// MSA-1 acknowledgment code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AckCode {
+    /// AA - application accept
+    Accept,
+    /// AE - application error
+    Error,
+    /// AR - application reject
+    Reject,
+}
+
+impl AckCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Accept => "AA",
+            Self::Error => "AE",
+            Self::Reject => "AR",
+        }
+    }
+
+    /// Decide how a received message should be acknowledged
+    /// Returns the code and, for AE/AR, a short reason for MSA-3
+    pub fn evaluate(message: &Message) -> (Self, Option<String>) {
+        if message.message_type().is_none() {
+            return (Self::Reject, Some("Missing message type (MSH-9)".into()));
+        }
+
+        if let Some(version) = message.version() {
+            if !version.starts_with("2.") {
+                return (
+                    Self::Reject,
+                    Some(format!("Unsupported HL7 version {}", version)),
+                );
+            }
+        }
+
+        let processing_id = message.segment("MSH").map(|msh| msh.value(11));
+        if let Some(id) = processing_id.filter(|id| !id.is_empty()) {
+            if !matches!(id, "P" | "D" | "T") {
+                return (
+                    Self::Reject,
+                    Some(format!("Unsupported processing ID {}", id)),
+                );
+            }
+        }
+
+        if message.control_id().is_none() {
+            return (
+                Self::Error,
+                Some("Missing message control ID (MSH-10)".into()),
+            );
+        }
+
+        (Self::Accept, None)
+    }
+}
+
+/// Identity used in the MSH of generated acknowledgements
+#[derive(Debug, Clone, Default)]
+pub struct AckConfig {
+    /// Sending application (MSH-3); defaults to the receiving application of the message
+    pub application: Option<String>,
+    /// Sending facility (MSH-4); defaults to the receiving facility of the message
+    pub facility: Option<String>,
+}
+
+/// Builds ACK messages in reply to received HL7 messages
+pub struct AckGenerator {
+    config: AckConfig,
+    sequence: u64,
+}
+
+impl AckGenerator {
+    pub fn new(config: AckConfig) -> Self {
+        Self {
+            config,
+            sequence: 0,
+        }
+    }
+
+    /// Build the acknowledgement for a message, choosing AA/AE/AR automatically
+    pub fn acknowledge(&mut self, message: &Message) -> Message {
+        let (code, text) = AckCode::evaluate(message);
+        self.build(message, code, text.as_deref())
+    }
+
+    /// Build an acknowledgement with an explicit code
+    pub fn build(&mut self, message: &Message, code: AckCode, text: Option<&str>) -> Message {
+        self.sequence += 1;
+
+        let original = message
+            .segment("MSH")
+            .cloned()
+            .unwrap_or_else(|| Segment::new("MSH"));
+        let encoding = message.encoding().clone();
+        let trigger = original.component(9, 2).unwrap_or("");
+
+        let application = self
+            .config
+            .application
+            .clone()
+            .unwrap_or_else(|| original.value(5).to_string());
+        let facility = self
+            .config
+            .facility
+            .clone()
+            .unwrap_or_else(|| original.value(6).to_string());
+
+        let processing_id = match original.value(11) {
+            "" => "P",
+            id => id,
+        };
+        let version = match original.value(12) {
+            "" => "2.5",
+            version => version,
+        };
+
+        let mut msh = Segment::new("MSH");
+        msh.set_field(1, Field::new(&encoding.field.to_string()));
+        msh.set_field(2, Field::new(&encoding.msh2()));
+        msh.set_field(3, Field::new(&application));
+        msh.set_field(4, Field::new(&facility));
+        if let Some(field) = original.field(3) {
+            msh.set_field(5, field.clone());
+        }
+        if let Some(field) = original.field(4) {
+            msh.set_field(6, field.clone());
+        }
+        msh.set_field(
+            7,
+            Field::new(&Local::now().format("%Y%m%d%H%M%S").to_string()),
+        );
+        msh.set_field(9, Field::from_components(&["ACK", trigger, "ACK"]));
+        msh.set_field(10, Field::new(&format!("ACK{:06}", self.sequence)));
+        msh.set_field(11, Field::new(processing_id));
+        msh.set_field(12, Field::new(version));
+
+        let mut msa = Segment::new("MSA");
+        msa.set_field(1, Field::new(code.as_str()));
+        msa.set_field(2, Field::new(message.control_id().unwrap_or("")));
+        if let Some(text) = text {
+            msa.set_field(3, Field::new(text));
+        }
+
+        Message::new(encoding, vec![msh, msa])
+    }
+}
+
+impl Default for AckGenerator {
+    fn default() -> Self {
+        Self::new(AckConfig::default())
+    }
+}
@@ -0,0 +1,63 @@
+use super::{EncodingCharacters, Message, Segment};
+
+/// Groups CR-terminated segment lines into complete messages
+///
+/// A message starts at an MSH segment and ends when the next MSH arrives,
+/// when a line that is not a segment is received, or when `finish` is called.
+pub struct MessageAssembler {
+    encoding: Option<EncodingCharacters>,
+    segments: Vec<Segment>,
+}
+
+impl MessageAssembler {
+    pub fn new() -> Self {
+        Self {
+            encoding: None,
+            segments: Vec::new(),
+        }
+    }
+
+    /// Feed one segment line; returns a message when one is completed
+    pub fn push_line(&mut self, line: &str) -> Option<Message> {
+        let line = line.trim_matches(['\r', '\n']);
+        if line.is_empty() {
+            return None;
+        }
+
+        if let Ok(encoding) = EncodingCharacters::from_msh(line) {
+            let completed = self.finish();
+            if let Ok(segment) = Segment::parse(line, &encoding) {
+                self.segments.push(segment);
+                self.encoding = Some(encoding);
+            }
+            return completed;
+        }
+
+        let encoding = self.encoding.as_ref()?;
+        match Segment::parse(line, encoding) {
+            Ok(segment) => {
+                self.segments.push(segment);
+                None
+            }
+            Err(_) => self.finish(),
+        }
+    }
+
+    /// Complete the message in progress, if any
+    pub fn finish(&mut self) -> Option<Message> {
+        let encoding = self.encoding.take()?;
+        let segments = std::mem::take(&mut self.segments);
+        Some(Message::new(encoding, segments))
+    }
+
+    /// Whether a message is currently being assembled
+    pub fn in_progress(&self) -> bool {
+        self.encoding.is_some()
+    }
+}
+
+impl Default for MessageAssembler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
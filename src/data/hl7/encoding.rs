@@ -0,0 +1,177 @@
+use anyhow::Result;
+
+/// Delimiters declared in MSH-1 and MSH-2 of an HL7 v2 message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodingCharacters {
+    pub field: char,
+    pub component: char,
+    pub repetition: char,
+    pub escape: char,
+    pub subcomponent: char,
+    pub truncation: Option<char>,
+}
+
+impl EncodingCharacters {
+    /// Read the encoding characters from the start of an MSH segment
+    /// Example: "MSH|^~\&|..." gives field '|', component '^', repetition '~',
+    /// escape '\' and subcomponent '&'
+    pub fn from_msh(segment: &str) -> Result<Self> {
+        let mut chars = segment.chars();
+        let header: String = chars.by_ref().take(3).collect();
+        if header != "MSH" {
+            return Err(anyhow::anyhow!("Segment does not start with MSH"));
+        }
+
+        let field = chars
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("MSH segment is missing the field separator"))?;
+        let declared: Vec<char> = chars.take_while(|&c| c != field).collect();
+
+        if declared.len() < 4 || declared.len() > 5 {
+            return Err(anyhow::anyhow!(
+                "MSH-2 must declare 4 or 5 encoding characters, found {}",
+                declared.len()
+            ));
+        }
+
+        let encoding = Self {
+            field,
+            component: declared[0],
+            repetition: declared[1],
+            escape: declared[2],
+            subcomponent: declared[3],
+            truncation: declared.get(4).copied(),
+        };
+
+        let mut delimiters = vec![
+            encoding.field,
+            encoding.component,
+            encoding.repetition,
+            encoding.escape,
+            encoding.subcomponent,
+        ];
+        delimiters.sort_unstable();
+        delimiters.dedup();
+        if delimiters.len() != 5 {
+            return Err(anyhow::anyhow!("MSH encoding characters must be distinct"));
+        }
+
+        Ok(encoding)
+    }
+
+    /// The MSH-2 value as it appears on the wire
+    pub fn msh2(&self) -> String {
+        let mut value: String = [
+            self.component,
+            self.repetition,
+            self.escape,
+            self.subcomponent,
+        ]
+        .iter()
+        .collect();
+        if let Some(truncation) = self.truncation {
+            value.push(truncation);
+        }
+        value
+    }
+
+    /// Replace delimiter characters in a value with HL7 escape sequences
+    pub fn escape(&self, value: &str) -> String {
+        let mut output = String::with_capacity(value.len());
+        for c in value.chars() {
+            let code = if c == self.field {
+                Some('F')
+            } else if c == self.component {
+                Some('S')
+            } else if c == self.subcomponent {
+                Some('T')
+            } else if c == self.repetition {
+                Some('R')
+            } else if c == self.escape {
+                Some('E')
+            } else {
+                None
+            };
+
+            match code {
+                Some(code) => {
+                    output.push(self.escape);
+                    output.push(code);
+                    output.push(self.escape);
+                }
+                None => output.push(c),
+            }
+        }
+        output
+    }
+
+    /// Decode HL7 escape sequences (\F\, \S\, \T\, \R\, \E\ and \Xhh\)
+    /// Formatting sequences such as \.br\ are kept verbatim
+    pub fn unescape(&self, value: &str) -> String {
+        if !value.contains(self.escape) {
+            return value.to_string();
+        }
+
+        let mut output = String::with_capacity(value.len());
+        let mut rest = value;
+
+        while let Some(start) = rest.find(self.escape) {
+            output.push_str(&rest[..start]);
+            let after = &rest[start + self.escape.len_utf8()..];
+
+            let Some(end) = after.find(self.escape) else {
+                // Unterminated sequence, keep the remainder as-is
+                output.push_str(&rest[start..]);
+                return output;
+            };
+
+            let sequence = &after[..end];
+            match self.decode_sequence(sequence) {
+                Some(decoded) => output.push_str(&decoded),
+                None => {
+                    output.push(self.escape);
+                    output.push_str(sequence);
+                    output.push(self.escape);
+                }
+            }
+            rest = &after[end + self.escape.len_utf8()..];
+        }
+
+        output.push_str(rest);
+        output
+    }
+
+    fn decode_sequence(&self, sequence: &str) -> Option<String> {
+        match sequence {
+            "F" => Some(self.field.to_string()),
+            "S" => Some(self.component.to_string()),
+            "T" => Some(self.subcomponent.to_string()),
+            "R" => Some(self.repetition.to_string()),
+            "E" => Some(self.escape.to_string()),
+            _ => {
+                let hex = sequence.strip_prefix('X')?;
+                if hex.is_empty() || hex.len() % 2 != 0 {
+                    return None;
+                }
+                let bytes: Option<Vec<u8>> = (0..hex.len())
+                    .step_by(2)
+                    .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+                    .collect();
+                bytes.map(|b| String::from_utf8_lossy(&b).into_owned())
+            }
+        }
+    }
+}
+
+impl Default for EncodingCharacters {
+    fn default() -> Self {
+        Self {
+            field: '|',
+            component: '^',
+            repetition: '~',
+            escape: '\\',
+            subcomponent: '&',
+            truncation: None,
+        }
+    }
+}
@@ -0,0 +1,346 @@
+use anyhow::Result;
+
+use super::EncodingCharacters;
+
+/// A component, made of one or more subcomponents
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Component {
+    subcomponents: Vec<String>,
+}
+
+impl Component {
+    /// Create a component holding a single value
+    pub fn new(value: &str) -> Self {
+        Self {
+            subcomponents: vec![value.to_string()],
+        }
+    }
+
+    fn parse(raw: &str, encoding: &EncodingCharacters) -> Self {
+        Self {
+            subcomponents: raw
+                .split(encoding.subcomponent)
+                .map(|s| encoding.unescape(s))
+                .collect(),
+        }
+    }
+
+    /// First subcomponent, which is the whole value for simple components
+    pub fn value(&self) -> &str {
+        self.subcomponents.first().map(String::as_str).unwrap_or("")
+    }
+
+    /// Get a subcomponent by its 1-based position
+    pub fn subcomponent(&self, position: usize) -> Option<&str> {
+        position
+            .checked_sub(1)
+            .and_then(|i| self.subcomponents.get(i))
+            .map(String::as_str)
+    }
+
+    pub fn subcomponents(&self) -> &[String] {
+        &self.subcomponents
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.subcomponents.iter().all(String::is_empty)
+    }
+
+    fn encode(&self, encoding: &EncodingCharacters) -> String {
+        self.subcomponents
+            .iter()
+            .map(|s| encoding.escape(s))
+            .collect::<Vec<_>>()
+            .join(&encoding.subcomponent.to_string())
+    }
+}
+
+/// One occurrence of a repeating field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repetition {
+    components: Vec<Component>,
+}
+
+impl Repetition {
+    fn parse(raw: &str, encoding: &EncodingCharacters) -> Self {
+        Self {
+            components: raw
+                .split(encoding.component)
+                .map(|c| Component::parse(c, encoding))
+                .collect(),
+        }
+    }
+
+    /// Get a component by its 1-based position
+    pub fn component(&self, position: usize) -> Option<&Component> {
+        position.checked_sub(1).and_then(|i| self.components.get(i))
+    }
+
+    pub fn components(&self) -> &[Component] {
+        &self.components
+    }
+
+    fn encode(&self, encoding: &EncodingCharacters) -> String {
+        self.components
+            .iter()
+            .map(|c| c.encode(encoding))
+            .collect::<Vec<_>>()
+            .join(&encoding.component.to_string())
+    }
+}
+
+/// A segment field, possibly repeated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    repetitions: Vec<Repetition>,
+}
+
+impl Field {
+    /// Create a field holding a single value
+    pub fn new(value: &str) -> Self {
+        Self::from_components(&[value])
+    }
+
+    /// Create a field from component values, e.g. ["ACK", "R01"] for ACK^R01
+    pub fn from_components(components: &[&str]) -> Self {
+        Self {
+            repetitions: vec![Repetition {
+                components: components.iter().map(|c| Component::new(c)).collect(),
+            }],
+        }
+    }
+
+    fn parse(raw: &str, encoding: &EncodingCharacters) -> Self {
+        Self {
+            repetitions: raw
+                .split(encoding.repetition)
+                .map(|r| Repetition::parse(r, encoding))
+                .collect(),
+        }
+    }
+
+    /// First component of the first repetition
+    pub fn value(&self) -> &str {
+        self.component(1).map(Component::value).unwrap_or("")
+    }
+
+    /// Get a component of the first repetition by its 1-based position
+    pub fn component(&self, position: usize) -> Option<&Component> {
+        self.repetitions.first()?.component(position)
+    }
+
+    pub fn repetitions(&self) -> &[Repetition] {
+        &self.repetitions
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.repetitions
+            .iter()
+            .all(|r| r.components.iter().all(Component::is_empty))
+    }
+
+    /// Serialize the field with its repetitions, components and escapes
+    pub fn encode(&self, encoding: &EncodingCharacters) -> String {
+        self.repetitions
+            .iter()
+            .map(|r| r.encode(encoding))
+            .collect::<Vec<_>>()
+            .join(&encoding.repetition.to_string())
+    }
+}
+
+/// A named segment such as MSH, PID or OBX
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    name: String,
+    fields: Vec<Field>,
+}
+
+impl Segment {
+    /// Create an empty segment
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Check whether a line looks like an HL7 segment ("XXX" followed by the separator)
+    pub fn is_segment_line(line: &str, field_separator: char) -> bool {
+        Self::has_segment_name(line) && line[3..].starts_with(field_separator)
+    }
+
+    fn has_segment_name(line: &str) -> bool {
+        line.len() >= 3
+            && line.as_bytes()[..3]
+                .iter()
+                .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+    }
+
+    /// Parse one segment line using the given encoding characters
+    pub fn parse(line: &str, encoding: &EncodingCharacters) -> Result<Self> {
+        let line = line.trim_end_matches(['\r', '\n']);
+        let bare_name = line.len() == 3 && Self::has_segment_name(line);
+        if !bare_name && !Self::is_segment_line(line, encoding.field) {
+            return Err(anyhow::anyhow!("Invalid HL7 segment: {}", line));
+        }
+
+        let mut parts = line.split(encoding.field);
+        let name = parts.next().unwrap_or_default().to_string();
+        let mut fields = Vec::new();
+
+        if name == "MSH" {
+            // MSH-1 is the field separator itself and MSH-2 is taken literally
+            fields.push(Field::new(&encoding.field.to_string()));
+            fields.push(Field::new(parts.next().unwrap_or_default()));
+        }
+
+        fields.extend(parts.map(|f| Field::parse(f, encoding)));
+
+        Ok(Self { name, fields })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get a field by its HL7 position (OBX-5 is `field(5)`)
+    pub fn field(&self, position: usize) -> Option<&Field> {
+        position.checked_sub(1).and_then(|i| self.fields.get(i))
+    }
+
+    /// Value of a field, empty if the field is absent
+    pub fn value(&self, position: usize) -> &str {
+        self.field(position).map(Field::value).unwrap_or("")
+    }
+
+    /// Value of a component inside a field, e.g. `component(3, 2)` for OBX-3.2
+    pub fn component(&self, position: usize, component: usize) -> Option<&str> {
+        self.field(position)?
+            .component(component)
+            .map(Component::value)
+    }
+
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    /// Set a field by its HL7 position, padding with empty fields as needed
+    pub fn set_field(&mut self, position: usize, field: Field) {
+        if position == 0 {
+            return;
+        }
+        while self.fields.len() < position {
+            self.fields.push(Field::new(""));
+        }
+        self.fields[position - 1] = field;
+    }
+
+    /// Serialize the segment without its terminator
+    pub fn encode(&self, encoding: &EncodingCharacters) -> String {
+        let mut output = self.name.clone();
+        let skip = if self.name == "MSH" {
+            output.push(encoding.field);
+            output.push_str(&encoding.msh2());
+            2
+        } else {
+            0
+        };
+
+        for field in self.fields.iter().skip(skip) {
+            output.push(encoding.field);
+            output.push_str(&field.encode(encoding));
+        }
+        output
+    }
+}
+
+/// A complete HL7 v2 message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    encoding: EncodingCharacters,
+    segments: Vec<Segment>,
+}
+
+impl Message {
+    /// Build a message from already parsed segments
+    pub fn new(encoding: EncodingCharacters, segments: Vec<Segment>) -> Self {
+        Self { encoding, segments }
+    }
+
+    /// Parse a message; segments may be separated by CR, LF or CRLF
+    pub fn parse(text: &str) -> Result<Self> {
+        let text = text.trim_start_matches(['\r', '\n']);
+        let encoding = EncodingCharacters::from_msh(text)?;
+
+        let segments = text
+            .split(['\r', '\n'])
+            .filter(|line| !line.is_empty())
+            .map(|line| Segment::parse(line, &encoding))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { encoding, segments })
+    }
+
+    pub fn encoding(&self) -> &EncodingCharacters {
+        &self.encoding
+    }
+
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    /// First segment with the given name
+    pub fn segment(&self, name: &str) -> Option<&Segment> {
+        self.segments.iter().find(|s| s.name == name)
+    }
+
+    /// All segments with the given name, in message order
+    pub fn segments_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Segment> + 'a {
+        self.segments.iter().filter(move |s| s.name == name)
+    }
+
+    /// MSH-9 as written on the wire, e.g. "ORU^R01"
+    pub fn message_type(&self) -> Option<String> {
+        let field = self.segment("MSH")?.field(9)?;
+        if field.is_empty() {
+            return None;
+        }
+        Some(
+            field
+                .repetitions()
+                .first()?
+                .components()
+                .iter()
+                .map(Component::value)
+                .collect::<Vec<_>>()
+                .join(&self.encoding.component.to_string()),
+        )
+    }
+
+    /// MSH-10 message control ID
+    pub fn control_id(&self) -> Option<&str> {
+        Self::non_empty(self.segment("MSH")?.value(10))
+    }
+
+    /// MSH-12 version ID
+    pub fn version(&self) -> Option<&str> {
+        Self::non_empty(self.segment("MSH")?.value(12))
+    }
+
+    /// Serialize the message with CR segment terminators
+    pub fn encode(&self) -> String {
+        self.segments
+            .iter()
+            .map(|s| format!("{}\r", s.encode(&self.encoding)))
+            .collect()
+    }
+
+    fn non_empty(value: &str) -> Option<&str> {
+        if value.is_empty() {
+            None
+        } else {
+            Some(value)
+        }
+    }
+}
@@ -0,0 +1,9 @@
+mod ack;
+mod assembler;
+mod encoding;
+mod message;
+
+pub use ack::{AckCode, AckConfig, AckGenerator};
+pub use assembler::MessageAssembler;
+pub use encoding::EncodingCharacters;
+pub use message::{Component, Field, Message, Repetition, Segment};
@@ -0,0 +1,107 @@
+/// MLLP start block character (VT)
+pub const START_BLOCK: u8 = 0x0B;
+/// MLLP end block character (FS)
+pub const END_BLOCK: u8 = 0x1C;
+/// Carriage return that follows the end block
+pub const CARRIAGE_RETURN: u8 = 0x0D;
+
+const MAX_FRAME_SIZE: usize = 1024 * 1024;
+
+#[derive(Debug, PartialEq)]
+enum State {
+    Idle,
+    InFrame,
+    AwaitingCr,
+}
+
+/// Minimal Lower Layer Protocol frame decoder and encoder
+///
+/// Frames are `<VT> payload <FS><CR>`. Bytes outside a frame are discarded
+/// and counted, and a lone FS without the trailing CR is accepted.
+pub struct MllpCodec {
+    state: State,
+    buffer: Vec<u8>,
+    discarded: u64,
+}
+
+impl MllpCodec {
+    pub fn new() -> Self {
+        Self {
+            state: State::Idle,
+            buffer: Vec::new(),
+            discarded: 0,
+        }
+    }
+
+    /// Wrap a payload in an MLLP frame
+    pub fn encode(payload: &[u8]) -> Vec<u8> {
+        let mut frame = Vec::with_capacity(payload.len() + 3);
+        frame.push(START_BLOCK);
+        frame.extend_from_slice(payload);
+        frame.push(END_BLOCK);
+        frame.push(CARRIAGE_RETURN);
+        frame
+    }
+
+    /// Feed received bytes and return the payloads of completed frames
+    pub fn decode(&mut self, data: &[u8]) -> Vec<Vec<u8>> {
+        let mut frames = Vec::new();
+
+        for &byte in data {
+            if self.state == State::AwaitingCr {
+                self.state = State::Idle;
+                if byte == CARRIAGE_RETURN {
+                    continue;
+                }
+            }
+
+            match self.state {
+                State::Idle => {
+                    if byte == START_BLOCK {
+                        self.state = State::InFrame;
+                    } else {
+                        self.discarded += 1;
+                    }
+                }
+                State::InFrame => match byte {
+                    END_BLOCK => {
+                        frames.push(std::mem::take(&mut self.buffer));
+                        self.state = State::AwaitingCr;
+                    }
+                    START_BLOCK => {
+                        // A new frame started before the previous one ended
+                        self.discarded += self.buffer.len() as u64;
+                        self.buffer.clear();
+                    }
+                    _ => {
+                        self.buffer.push(byte);
+                        if self.buffer.len() > MAX_FRAME_SIZE {
+                            self.discarded += self.buffer.len() as u64;
+                            self.buffer.clear();
+                            self.state = State::Idle;
+                        }
+                    }
+                },
+                State::AwaitingCr => unreachable!(),
+            }
+        }
+
+        frames
+    }
+
+    /// Whether a frame has been started but not completed
+    pub fn in_frame(&self) -> bool {
+        self.state == State::InFrame
+    }
+
+    /// Number of bytes received outside of any frame
+    pub fn discarded_bytes(&self) -> u64 {
+        self.discarded
+    }
+}
+
+impl Default for MllpCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,5 +1,20 @@
+mod binary;
+mod decoder;
 mod formatter;
+mod framing;
+pub mod hl7;
+mod mllp;
+mod observation;
 mod parser;
+mod registry;
 
+pub use binary::{
+    BinaryDecoder, BinaryEvent, BinaryFrameConfig, BinaryPacket, ChecksumKind, PacketSize,
+};
+pub use decoder::{Decoder, Frame, Hl7Decoder, LineDecoder, MllpDecoder, RawDecoder};
 pub use formatter::DataFormatter;
-pub use parser::{DataParser, DataType};
+pub use framing::Framing;
+pub use mllp::MllpCodec;
+pub use observation::{ObservationValue, VitalObservation};
+pub use parser::{DataParser, DataType, ParserStats};
+pub use registry::DecoderRegistry;
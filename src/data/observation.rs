@@ -0,0 +1,246 @@
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use std::fmt;
+
+use super::hl7::{EncodingCharacters, Field, Message, Segment};
+
+/// Value carried by an observation
+#[derive(Debug, Clone, PartialEq)]
+pub enum ObservationValue {
+    Numeric(f64),
+    Text(String),
+}
+
+impl ObservationValue {
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Numeric(value) => Some(*value),
+            Self::Text(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for ObservationValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Numeric(value) => write!(f, "{}", value),
+            Self::Text(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+/// A single vital-sign measurement, independent of the wire protocol
+#[derive(Debug, Clone, PartialEq)]
+pub struct VitalObservation {
+    /// Observation identifier, e.g. LOINC "8867-4"
+    pub code: String,
+    /// Human readable name, e.g. "Heart Rate"
+    pub display_name: String,
+    /// Coding system of `code`, e.g. "LN" for LOINC
+    pub coding_system: String,
+    pub value: ObservationValue,
+    /// UCUM unit code, e.g. "mm[Hg]"
+    pub unit: Option<String>,
+    pub reference_range: Option<String>,
+    /// Abnormal flag (N, H, L, ...)
+    pub abnormal_flag: Option<String>,
+    /// Result status (F = final, P = preliminary, ...)
+    pub result_status: Option<String>,
+    pub observation_time: Option<NaiveDateTime>,
+    /// Producing device or module, e.g. "GE_MONITOR^SPO2_MODULE"
+    pub producer: Option<String>,
+}
+
+impl VitalObservation {
+    /// Create a numeric observation with only the mandatory fields set
+    pub fn numeric(code: &str, display_name: &str, coding_system: &str, value: f64) -> Self {
+        Self {
+            code: code.to_string(),
+            display_name: display_name.to_string(),
+            coding_system: coding_system.to_string(),
+            value: ObservationValue::Numeric(value),
+            unit: None,
+            reference_range: None,
+            abnormal_flag: None,
+            result_status: None,
+            observation_time: None,
+            producer: None,
+        }
+    }
+
+    /// Build an observation from an OBX segment
+    pub fn from_obx(segment: &Segment, encoding: &EncodingCharacters) -> Result<Self> {
+        if segment.name() != "OBX" {
+            return Err(anyhow::anyhow!(
+                "Expected OBX segment, got {}",
+                segment.name()
+            ));
+        }
+
+        let code = segment.component(3, 1).unwrap_or("");
+        if code.is_empty() {
+            return Err(anyhow::anyhow!("OBX-3 observation identifier is empty"));
+        }
+
+        let raw_value = segment.value(5);
+        if segment.field(5).is_none_or(|field| field.is_empty()) {
+            return Err(anyhow::anyhow!("OBX-5 value is empty for {}", code));
+        }
+
+        let value = match segment.value(2) {
+            "NM" => Self::numeric_value(raw_value),
+            // Structured numeric: comparator^number, e.g. "<^10" or "^72"
+            "SN" => match segment.component(5, 2) {
+                Some(number) if !number.is_empty() => Self::numeric_value(number),
+                _ => Self::numeric_value(raw_value),
+            },
+            _ => ObservationValue::Text(Self::join(segment.field(5), encoding)),
+        };
+
+        // Devices often report the module in OBX-16 when OBX-15 is empty
+        let producer = Self::non_empty(Self::join(segment.field(15), encoding))
+            .or_else(|| Self::non_empty(Self::join(segment.field(16), encoding)));
+
+        Ok(Self {
+            code: code.to_string(),
+            display_name: segment.component(3, 2).unwrap_or("").to_string(),
+            coding_system: segment.component(3, 3).unwrap_or("").to_string(),
+            value,
+            unit: segment
+                .component(6, 1)
+                .and_then(|u| Self::non_empty(u.to_string())),
+            reference_range: Self::non_empty(segment.value(7).to_string()),
+            abnormal_flag: Self::non_empty(segment.value(8).to_string()),
+            result_status: Self::non_empty(segment.value(11).to_string()),
+            observation_time: Self::parse_hl7_timestamp(segment.value(14)),
+            producer,
+        })
+    }
+
+    /// Extract every valid OBX observation of a message
+    /// Missing OBX-14 times fall back to OBR-7, then to MSH-7
+    pub fn from_message(message: &Message) -> Vec<Self> {
+        let message_time = message
+            .segment("MSH")
+            .and_then(|msh| Self::parse_hl7_timestamp(msh.value(7)));
+        let mut request_time = None;
+        let mut observations = Vec::new();
+
+        for segment in message.segments() {
+            match segment.name() {
+                "OBR" => request_time = Self::parse_hl7_timestamp(segment.value(7)),
+                "OBX" => {
+                    if let Ok(mut observation) = Self::from_obx(segment, message.encoding()) {
+                        if observation.observation_time.is_none() {
+                            observation.observation_time = request_time.or(message_time);
+                        }
+                        observations.push(observation);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        observations
+    }
+
+    /// Extract the vital signs of a `KEY=VALUE|...` line, as sent by the
+    /// vital signs simulator. `BP=sys/dia` gives two observations and
+    /// unknown keys or non-numeric values are skipped
+    pub fn from_key_values(line: &str) -> Vec<Self> {
+        let mut observations = Vec::new();
+
+        for pair in line.trim().split('|') {
+            let Some((key, value)) = pair.split_once('=') else {
+                continue;
+            };
+            let key = key.trim().to_ascii_uppercase();
+
+            if key == "BP" {
+                if let Some((systolic, diastolic)) = value.split_once('/') {
+                    observations.extend(Self::key_value("BP_SYS", systolic));
+                    observations.extend(Self::key_value("BP_DIA", diastolic));
+                }
+                continue;
+            }
+            observations.extend(Self::key_value(&key, value));
+        }
+
+        observations
+    }
+
+    fn key_value(key: &str, value: &str) -> Option<Self> {
+        let (code, display_name, unit) = match key {
+            "HR" => ("8867-4", "Heart Rate", "bpm"),
+            "SPO2" => ("2708-6", "Oxygen Saturation", "%"),
+            "BP_SYS" => ("8480-6", "Systolic BP", "mm[Hg]"),
+            "BP_DIA" => ("8462-4", "Diastolic BP", "mm[Hg]"),
+            "TEMP" => ("8310-5", "Body Temperature", "Cel"),
+            "RR" => ("9279-1", "Respiratory Rate", "/min"),
+            "ETCO2" => ("19889-5", "End Tidal CO2", "mm[Hg]"),
+            _ => return None,
+        };
+        let value = value.trim().parse().ok()?;
+
+        let mut observation = Self::numeric(code, display_name, "LN", value);
+        observation.unit = Some(unit.to_string());
+        Some(observation)
+    }
+
+    /// Parse an HL7 TS value (YYYY[MM[DD[HH[MM[SS[.S+]]]]]][+/-ZZZZ])
+    /// The timezone offset is ignored and the value is returned as local time
+    pub fn parse_hl7_timestamp(value: &str) -> Option<NaiveDateTime> {
+        let value = value.split(['+', '-']).next().unwrap_or("");
+        let (main, fraction) = match value.split_once('.') {
+            Some((main, fraction)) => (main, fraction),
+            None => (value, ""),
+        };
+
+        if main.len() < 4 || main.len() > 14 || main.len() % 2 != 0 {
+            return None;
+        }
+        if !main.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+
+        // Pad missing month/day with 01 and missing time with zeros
+        let padding = "0101000000";
+        let mut full = main.to_string();
+        full.push_str(&padding[main.len() - 4..]);
+
+        let mut parsed = NaiveDateTime::parse_from_str(&full, "%Y%m%d%H%M%S").ok()?;
+        if !fraction.is_empty() && fraction.bytes().all(|b| b.is_ascii_digit()) {
+            let digits = &fraction[..fraction.len().min(9)];
+            let nanos: i64 = format!("{:0<9}", digits).parse().ok()?;
+            parsed += chrono::Duration::nanoseconds(nanos);
+        }
+        Some(parsed)
+    }
+
+    fn numeric_value(raw: &str) -> ObservationValue {
+        match raw.trim().parse::<f64>() {
+            Ok(number) => ObservationValue::Numeric(number),
+            Err(_) => ObservationValue::Text(raw.to_string()),
+        }
+    }
+
+    fn join(field: Option<&Field>, encoding: &EncodingCharacters) -> String {
+        let Some(repetition) = field.and_then(|f| f.repetitions().first()) else {
+            return String::new();
+        };
+        let separator = encoding.component.to_string();
+        let parts: Vec<&str> = repetition.components().iter().map(|c| c.value()).collect();
+        parts
+            .join(&separator)
+            .trim_end_matches(encoding.component)
+            .to_string()
+    }
+
+    fn non_empty(value: String) -> Option<String> {
+        if value.is_empty() {
+            None
+        } else {
+            Some(value)
+        }
+    }
+}
@@ -1,33 +1,76 @@
-use super::DataFormatter;
+use super::hl7::Message;
+use super::{
+    BinaryDecoder, BinaryFrameConfig, DataFormatter, Decoder, Frame, Framing, Hl7Decoder,
+    MllpDecoder,
+};
+use crate::output::{Event, Record};
 use std::collections::HashMap;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DataType {
     Ascii,
     Binary,
     Mixed,
 }
 
+/// Byte counters and decoder statistics of a parser
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParserStats {
+    pub total_bytes: u64,
+    pub ascii_bytes: u64,
+    pub binary_bytes: u64,
+    pub detected_type: DataType,
+    pub protocol: &'static str,
+    /// Protocol specific counters, see `Decoder::stats`
+    pub decoder: Vec<(&'static str, u64)>,
+    /// Most frequent bytes with their count, most frequent first
+    pub most_common: Vec<(u8, u64)>,
+}
+
 pub struct DataParser {
     ascii_count: u64,
     binary_count: u64,
     total_count: u64,
-    line_buffer: Vec<u8>,
     detected_type: DataType,
     char_frequency: HashMap<u8, u64>,
-    last_was_cr: bool,
+    decoder: Box<dyn Decoder>,
+    messages: Vec<Message>,
+    records: Vec<Record>,
+    last_timestamp: String,
 }
 
 impl DataParser {
     pub fn new() -> Self {
+        Self::with_framing(Framing::Line)
+    }
+
+    /// Create a parser for the given wire framing
+    pub fn with_framing(framing: Framing) -> Self {
+        let decoder: Box<dyn Decoder> = match framing {
+            Framing::Line => Box::new(Hl7Decoder::new()),
+            Framing::Mllp => Box::new(MllpDecoder::new()),
+            Framing::Binary => Box::new(BinaryDecoder::default()),
+        };
+        Self::with_decoder(decoder)
+    }
+
+    /// Create a binary packet parser with a custom frame layout
+    pub fn with_binary_config(config: BinaryFrameConfig) -> Self {
+        Self::with_decoder(Box::new(BinaryDecoder::new(config)))
+    }
+
+    /// Create a parser around any decoder, see `DecoderRegistry`
+    pub fn with_decoder(decoder: Box<dyn Decoder>) -> Self {
         Self {
             ascii_count: 0,
             binary_count: 0,
             total_count: 0,
-            line_buffer: Vec::new(),
             detected_type: DataType::Mixed,
             char_frequency: HashMap::new(),
-            last_was_cr: false,
+            decoder,
+            messages: Vec::new(),
+            records: Vec::new(),
+            last_timestamp: String::new(),
         }
     }
 
@@ -57,78 +100,102 @@ impl DataParser {
             } else {
                 self.binary_count += 1;
             }
+        }
 
-            if byte == b'\r' {
-                self.line_buffer.push(byte);
-                self.last_was_cr = true;
-                self.flush_line(timestamp);
-            } else if byte == b'\n' {
-                if !self.last_was_cr {
-                    self.line_buffer.push(byte);
-                    self.flush_line(timestamp);
-                }
-                self.last_was_cr = false;
-            } else {
-                self.last_was_cr = false;
-                self.line_buffer.push(byte);
+        let frames = self.decoder.feed(data);
+        self.handle_frames(frames, timestamp);
+        self.last_timestamp = timestamp.to_string();
+
+        self.detect_data_type();
+    }
 
-                if self.line_buffer.len() > 65536 {
-                    self.flush_line(timestamp);
+    fn handle_frames(&mut self, frames: Vec<Frame>, timestamp: &str) {
+        for frame in frames {
+            let event = match frame {
+                Frame::Line(bytes) | Frame::Chunk(bytes) => Event::Data {
+                    bytes,
+                    data_type: self.detected_type,
+                },
+                Frame::Binary(event) => Event::Packet(event),
+                Frame::Observation(observation) => Event::Observation(observation),
+                Frame::Message(message) => {
+                    self.messages.push(message);
+                    continue;
                 }
-            }
+            };
+            self.records.push(Record::new(timestamp, event));
         }
+    }
 
-        self.detect_data_type();
+    /// Flush any partial line and complete the HL7 message in progress
+    pub fn finish(&mut self, timestamp: &str) {
+        let frames = self.decoder.finish();
+        self.handle_frames(frames, timestamp);
     }
 
-    fn flush_line(&mut self, timestamp: &str) {
-        if self.line_buffer.is_empty() {
-            return;
-        }
+    /// Complete the HL7 message in progress without touching the line buffer
+    /// Used when the link goes idle, as the sender may be waiting for an ACK
+    pub fn complete_message(&mut self) {
+        let frames = self.decoder.idle();
+        let timestamp = self.last_timestamp.clone();
+        self.handle_frames(frames, &timestamp);
+    }
 
-        if let Some(formatted) =
-            DataFormatter::format_data(&self.line_buffer, &self.detected_type, timestamp)
-        {
-            println!("{}", formatted);
+    /// Wire framing of the decoder in use, `Line` for non-HL7 protocols
+    pub fn framing(&self) -> Framing {
+        match self.decoder.name() {
+            "mllp" => Framing::Mllp,
+            "binary" => Framing::Binary,
+            _ => Framing::Line,
         }
+    }
 
-        self.line_buffer.clear();
+    pub fn decoder(&self) -> &dyn Decoder {
+        self.decoder.as_ref()
     }
 
-    pub fn print_stats(&self) {
-        println!("\nData Analysis:");
-        println!("  Total bytes:      {}", self.total_count);
-        println!(
-            "  ASCII bytes:      {} ({:.1}%)",
-            self.ascii_count,
-            self.ascii_count as f64 / self.total_count as f64 * 100.0
-        );
-        println!(
-            "  Binary bytes:     {} ({:.1}%)",
-            self.binary_count,
-            self.binary_count as f64 / self.total_count as f64 * 100.0
-        );
-        println!("  Detected type:    {:?}", self.detected_type);
-
-        let mut freq_vec: Vec<_> = self.char_frequency.iter().collect();
-        freq_vec.sort_by(|a, b| b.1.cmp(a.1));
-
-        println!("\nMost common bytes:");
-        for (byte, count) in freq_vec.iter().take(5) {
-            let char_repr = if DataFormatter::is_printable_ascii(**byte) {
-                format!("'{}' ", **byte as char)
-            } else {
-                String::new()
-            };
-            println!(
-                "    0x{:02X} {}: {} times ({:.1}%)",
-                byte,
-                char_repr,
-                count,
-                **count as f64 / self.total_count as f64 * 100.0
-            );
+    /// Wrap an outgoing reply in the framing of the decoder in use
+    pub fn encode_reply(&self, payload: &[u8]) -> Vec<u8> {
+        self.decoder.encode(payload)
+    }
+
+    /// Take the HL7 messages completed so far
+    pub fn take_messages(&mut self) -> Vec<Message> {
+        std::mem::take(&mut self.messages)
+    }
+
+    /// Take the lines, packets and observations decoded so far
+    pub fn take_records(&mut self) -> Vec<Record> {
+        std::mem::take(&mut self.records)
+    }
+
+    pub fn detected_type(&self) -> DataType {
+        self.detected_type
+    }
+
+    pub fn stats(&self) -> ParserStats {
+        let mut most_common: Vec<_> = self
+            .char_frequency
+            .iter()
+            .map(|(&byte, &count)| (byte, count))
+            .collect();
+        most_common.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        most_common.truncate(5);
+
+        ParserStats {
+            total_bytes: self.total_count,
+            ascii_bytes: self.ascii_count,
+            binary_bytes: self.binary_count,
+            detected_type: self.detected_type,
+            protocol: self.decoder.name(),
+            decoder: self.decoder.stats(),
+            most_common,
         }
     }
+
+    pub fn print_stats(&self) {
+        println!("{}", DataFormatter::format_stats(&self.stats()));
+    }
 }
 
 impl Default for DataParser {
@@ -0,0 +1,67 @@
+use anyhow::Result;
+
+use super::{BinaryDecoder, Decoder, Hl7Decoder, LineDecoder, MllpDecoder, RawDecoder};
+
+type Factory = Box<dyn Fn() -> Box<dyn Decoder> + Send + Sync>;
+
+/// Decoders selectable by protocol name
+///
+/// Comes with the built-in line, hl7, mllp, binary and raw decoders.
+/// Registering a name again replaces the previous decoder.
+pub struct DecoderRegistry {
+    factories: Vec<(String, Factory)>,
+}
+
+impl DecoderRegistry {
+    pub fn new() -> Self {
+        let mut registry = Self {
+            factories: Vec::new(),
+        };
+        registry.register("hl7", || Box::new(Hl7Decoder::new()));
+        registry.register("line", || Box::new(LineDecoder::new()));
+        registry.register("mllp", || Box::new(MllpDecoder::new()));
+        registry.register("binary", || Box::new(BinaryDecoder::default()));
+        registry.register("raw", || Box::new(RawDecoder));
+        registry
+    }
+
+    /// Add a decoder under the given protocol name
+    pub fn register<F>(&mut self, name: &str, factory: F)
+    where
+        F: Fn() -> Box<dyn Decoder> + Send + Sync + 'static,
+    {
+        let name = name.to_lowercase();
+        let factory: Factory = Box::new(factory);
+        match self.factories.iter_mut().find(|(n, _)| *n == name) {
+            Some(entry) => entry.1 = factory,
+            None => self.factories.push((name, factory)),
+        }
+    }
+
+    /// Create a new decoder for a protocol name
+    pub fn create(&self, name: &str) -> Result<Box<dyn Decoder>> {
+        let name_lower = name.to_lowercase();
+        self.factories
+            .iter()
+            .find(|(n, _)| *n == name_lower)
+            .map(|(_, factory)| factory())
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Invalid protocol: {} (expected {})",
+                    name,
+                    self.names().join(", ")
+                )
+            })
+    }
+
+    /// Registered protocol names, built-in ones first
+    pub fn names(&self) -> Vec<&str> {
+        self.factories.iter().map(|(n, _)| n.as_str()).collect()
+    }
+}
+
+impl Default for DecoderRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
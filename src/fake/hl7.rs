@@ -2,6 +2,8 @@ use anyhow::Result;
 use std::io::Write;
 use std::time::Duration;
 
+use crate::data::MllpCodec;
+
 pub struct Hl7Generator;
 
 impl Hl7Generator {
@@ -21,44 +23,59 @@ impl Hl7Generator {
         Ok(())
     }
 
-    fn send_header<W: Write>(port: &mut W, timestamp: &str) -> Result<()> {
-        let msh = format!(
-            "MSH|^~\\&|GE_MONITOR|ICU_01|VITAL_REC|HOSPITAL|{}||ORU^R01|MSG{:06}|P|2.5\r",
-            timestamp, 1
-        );
-        port.write_all(msh.as_bytes())?;
+    /// Send the same message as `send`, wrapped in a single MLLP frame
+    pub fn send_mllp<W: Write>(port: &mut W) -> Result<()> {
+        println!("Sending HL7 message in an MLLP frame...\n");
+
+        let timestamp = chrono::Local::now().format("%Y%m%d%H%M%S").to_string();
+        let frame = MllpCodec::encode(Self::build_message(&timestamp).concat().as_bytes());
+
+        port.write_all(&frame)?;
         port.flush()?;
-        println!("Sent: {}", msh.trim());
-        std::thread::sleep(Duration::from_millis(100));
+        println!("Sent MLLP frame: {} bytes", frame.len());
+
+        println!("\n✓ Complete HL7 message sent with all modules!");
         Ok(())
     }
 
-    fn send_patient_info<W: Write>(port: &mut W) -> Result<()> {
-        let pid = "PID|1||123456^^^HOSPITAL^MR||DOE^JOHN^A||19800515|M|||123 MAIN ST^^PARIS^^75001^FR||(33)123456789||FR|M|CAT|||123-45-6789\r";
-        port.write_all(pid.as_bytes())?;
-        port.flush()?;
-        println!("Sent: {}", pid.trim());
-        std::thread::sleep(Duration::from_millis(100));
+    /// All CR-terminated segments of the simulated ORU^R01 message
+    pub fn build_message(timestamp: &str) -> Vec<String> {
+        let mut segments = vec![Self::header(timestamp)];
+        segments.extend(Self::patient_info());
+        segments.extend(Self::ge_monitor_data(timestamp));
+        segments.extend(Self::drager_ventilator_data(timestamp));
+        segments.extend(Self::drager_humidifier_data(timestamp));
+        segments
+    }
 
-        let pv1 = "PV1|1|I|ICU^101^01^HOSPITAL||||123456^SMITH^ROBERT^A^^^DR|||SUR||||ADM|||123456^SMITH^ROBERT^A^^^DR||V123456|||||||||||||||||||||||||20250103080000\r";
-        port.write_all(pv1.as_bytes())?;
+    fn header(timestamp: &str) -> String {
+        format!(
+            "MSH|^~\\&|GE_MONITOR|ICU_01|VITAL_REC|HOSPITAL|{}||ORU^R01|MSG{:06}|P|2.5\r",
+            timestamp, 1
+        )
+    }
+
+    fn send_header<W: Write>(port: &mut W, timestamp: &str) -> Result<()> {
+        let msh = Self::header(timestamp);
+        port.write_all(msh.as_bytes())?;
         port.flush()?;
-        println!("Sent: {}", pv1.trim());
+        println!("Sent: {}", msh.trim());
         std::thread::sleep(Duration::from_millis(100));
-
         Ok(())
     }
 
-    fn send_ge_monitor_data<W: Write>(port: &mut W, timestamp: &str) -> Result<()> {
-        let obr = format!("OBR|1|ORD123456|RES123456|VS^VITAL SIGNS^LOCAL|||{}||||||||123456^SMITH^ROBERT^A^^^DR\r", timestamp);
-        port.write_all(obr.as_bytes())?;
-        port.flush()?;
-        println!("Sent: {}", obr.trim());
-        std::thread::sleep(Duration::from_millis(100));
+    fn patient_info() -> Vec<String> {
+        vec![
+            "PID|1||123456^^^HOSPITAL^MR||DOE^JOHN^A||19800515|M|||123 MAIN ST^^PARIS^^75001^FR||(33)123456789||FR|M|CAT|||123-45-6789\r".to_string(),
+            "PV1|1|I|ICU^101^01^HOSPITAL||||123456^SMITH^ROBERT^A^^^DR|||SUR||||ADM|||123456^SMITH^ROBERT^A^^^DR||V123456|||||||||||||||||||||||||20250103080000\r".to_string(),
+        ]
+    }
 
-        let observations = vec![
+    fn ge_monitor_data(timestamp: &str) -> Vec<String> {
+        vec![
+            format!("OBR|1|ORD123456|RES123456|VS^VITAL SIGNS^LOCAL|||{}||||||||123456^SMITH^ROBERT^A^^^DR\r", timestamp),
             format!("OBX|1|NM|8867-4^Heart Rate^LN||72|bpm^beats/min^UCUM|60-100|N|||F|||{}||GE_MONITOR^ECG_MODULE\r", timestamp),
-            format!("OBX|2|ST|8884-9^ECG Rhythm^LN||NSR|||||N|||F|||{}||GE_MONITOR^ECG_MODULE\r", timestamp),
+            format!("OBX|2|ST|8884-9^ECG Rhythm^LN||NSR|||N|||F|||{}||GE_MONITOR^ECG_MODULE\r", timestamp),
             format!("OBX|3|NM|9279-1^Respiratory Rate^LN||16|/min^per minute^UCUM|12-20|N|||F|||{}||GE_MONITOR^RESP_MODULE\r", timestamp),
             format!("OBX|4|NM|8480-6^Systolic BP^LN||125|mm[Hg]^millimeter of mercury^UCUM|90-140|N|||F|||{}||GE_MONITOR^PNI_MODULE\r", timestamp),
             format!("OBX|5|NM|8462-4^Diastolic BP^LN||78|mm[Hg]^millimeter of mercury^UCUM|60-90|N|||F|||{}||GE_MONITOR^PNI_MODULE\r", timestamp),
@@ -79,53 +96,72 @@ impl Hl7Generator {
             format!("OBX|20|NM|93503-4^EEG Alpha Power^LN||28|%^percent^UCUM|20-40|N|||F|||{}||GE_MONITOR^EEG_MODULE\r", timestamp),
             format!("OBX|21|NM|93504-2^EEG Beta Power^LN||35|%^percent^UCUM|20-50|N|||F|||{}||GE_MONITOR^EEG_MODULE\r", timestamp),
             format!("OBX|22|NM|2713-6^Mixed Venous O2 Sat^LN||72|%^percent^UCUM|60-80|N|||F|||{}||GE_MONITOR^SVO2_MODULE\r", timestamp),
-        ];
-
-        for obs in observations {
-            port.write_all(obs.as_bytes())?;
-            port.flush()?;
-            println!("Sent: {}", obs.trim());
-            std::thread::sleep(Duration::from_millis(50));
-        }
-
-        Ok(())
+        ]
     }
 
-    fn send_drager_ventilator_data<W: Write>(port: &mut W, timestamp: &str) -> Result<()> {
-        let obr_vent = format!("OBR|2|VENT123456|VRES123456|VENT^VENTILATION^LOCAL|||{}||||||||123456^SMITH^ROBERT^A^^^DR\r", timestamp);
-        port.write_all(obr_vent.as_bytes())?;
-        port.flush()?;
-        println!("\nSent: {}", obr_vent.trim());
-        std::thread::sleep(Duration::from_millis(100));
-
-        let vent_data = vec![
+    fn drager_ventilator_data(timestamp: &str) -> Vec<String> {
+        vec![
+            format!("OBR|2|VENT123456|VRES123456|VENT^VENTILATION^LOCAL|||{}||||||||123456^SMITH^ROBERT^A^^^DR\r", timestamp),
             format!("OBX|23|NM|20112-9^Tidal Volume^LN||450|mL^milliliter^UCUM|400-600|N|||F|||{}||DRAGER^VENTILATOR\r", timestamp),
             format!("OBX|24|NM|20139-2^Minute Volume^LN||7.2|L/min^liter/minute^UCUM|5.0-10.0|N|||F|||{}||DRAGER^VENTILATOR\r", timestamp),
             format!("OBX|25|NM|76531-8^Peak Pressure^LN||22|cm[H2O]^centimeter of water^UCUM|15-30|N|||F|||{}||DRAGER^VENTILATOR\r", timestamp),
             format!("OBX|26|NM|76530-0^Plateau Pressure^LN||18|cm[H2O]^centimeter of water^UCUM|10-25|N|||F|||{}||DRAGER^VENTILATOR\r", timestamp),
             format!("OBX|27|NM|76248-9^PEEP^LN||5|cm[H2O]^centimeter of water^UCUM|3-10|N|||F|||{}||DRAGER^VENTILATOR\r", timestamp),
             format!("OBX|28|NM|3150-0^FiO2^LN||40|%^percent^UCUM|21-100|N|||F|||{}||DRAGER^VENTILATOR\r", timestamp),
-            format!("OBX|29|ST|76334-7^I:E Ratio^LN||1:2.5|||||N|||F|||{}||DRAGER^VENTILATOR\r", timestamp),
-        ];
+            format!("OBX|29|ST|76334-7^I:E Ratio^LN||1:2.5|||N|||F|||{}||DRAGER^VENTILATOR\r", timestamp),
+        ]
+    }
 
-        for data in vent_data {
-            port.write_all(data.as_bytes())?;
+    fn drager_humidifier_data(timestamp: &str) -> Vec<String> {
+        vec![
+            format!("OBX|30|NM|8310-5^Humidifier Temperature^LN||37.0|Cel^degree Celsius^UCUM|36.0-38.0|N|||F|||{}||DRAGER^HUMIDIFIER\r", timestamp),
+            format!("OBX|31|NM|3143-5^Relative Humidity^LN||95|%^percent^UCUM|80-100|N|||F|||{}||DRAGER^HUMIDIFIER\r", timestamp),
+            format!("OBX|32|NM|90401-4^Water Level^LN||85|%^percent^UCUM|50-100|N|||F|||{}||DRAGER^HUMIDIFIER\r", timestamp),
+        ]
+    }
+
+    fn send_patient_info<W: Write>(port: &mut W) -> Result<()> {
+        for segment in Self::patient_info() {
+            port.write_all(segment.as_bytes())?;
             port.flush()?;
-            println!("Sent DRAGER Vent: {}", data.trim());
-            std::thread::sleep(Duration::from_millis(50));
+            println!("Sent: {}", segment.trim());
+            std::thread::sleep(Duration::from_millis(100));
         }
 
         Ok(())
     }
 
-    fn send_drager_humidifier_data<W: Write>(port: &mut W, timestamp: &str) -> Result<()> {
-        let hum_data = vec![
-            format!("OBX|30|NM|8310-5^Humidifier Temperature^LN||37.0|Cel^degree Celsius^UCUM|36.0-38.0|N|||F|||{}||DRAGER^HUMIDIFIER\r", timestamp),
-            format!("OBX|31|NM|3143-5^Relative Humidity^LN||95|%^percent^UCUM|80-100|N|||F|||{}||DRAGER^HUMIDIFIER\r", timestamp),
-            format!("OBX|32|NM|90401-4^Water Level^LN||85|%^percent^UCUM|50-100|N|||F|||{}||DRAGER^HUMIDIFIER\r", timestamp),
-        ];
+    fn send_ge_monitor_data<W: Write>(port: &mut W, timestamp: &str) -> Result<()> {
+        for (idx, segment) in Self::ge_monitor_data(timestamp).iter().enumerate() {
+            port.write_all(segment.as_bytes())?;
+            port.flush()?;
+            println!("Sent: {}", segment.trim());
+            // The OBR header gets a longer pause than the observations
+            let delay = if idx == 0 { 100 } else { 50 };
+            std::thread::sleep(Duration::from_millis(delay));
+        }
+
+        Ok(())
+    }
 
-        for data in hum_data {
+    fn send_drager_ventilator_data<W: Write>(port: &mut W, timestamp: &str) -> Result<()> {
+        for (idx, segment) in Self::drager_ventilator_data(timestamp).iter().enumerate() {
+            port.write_all(segment.as_bytes())?;
+            port.flush()?;
+            if idx == 0 {
+                println!("\nSent: {}", segment.trim());
+                std::thread::sleep(Duration::from_millis(100));
+            } else {
+                println!("Sent DRAGER Vent: {}", segment.trim());
+                std::thread::sleep(Duration::from_millis(50));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn send_drager_humidifier_data<W: Write>(port: &mut W, timestamp: &str) -> Result<()> {
+        for data in Self::drager_humidifier_data(timestamp) {
             port.write_all(data.as_bytes())?;
             port.flush()?;
             println!("Sent DRAGER Hum: {}", data.trim());
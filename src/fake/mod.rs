@@ -1,9 +1,11 @@
 mod generators;
 mod hl7;
+mod rfc2217;
 mod vital_signs;
 mod waveform;
 
 pub use generators::CustomGenerator;
 pub use hl7::Hl7Generator;
+pub use rfc2217::Rfc2217Server;
 pub use vital_signs::VitalSignsGenerator;
 pub use waveform::WaveformGenerator;
@@ -0,0 +1,207 @@
+use anyhow::Result;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::transport::rfc2217::{
+    DTR_OFF, DTR_ON, FLOW_HARDWARE, FLOW_NONE, NOTIFY_MODEMSTATE, RTS_OFF, RTS_ON, SERVER_OFFSET,
+    SET_BAUDRATE, SET_CONTROL, SET_DATASIZE, SET_MODEMSTATE_MASK, SET_PARITY, SET_STOPSIZE,
+};
+use crate::transport::telnet::{
+    self, TelnetEvent, TelnetParser, BINARY, COM_PORT_OPTION, DO, DONT, SUPPRESS_GO_AHEAD, WILL,
+    WONT,
+};
+use crate::transport::ComPortSettings;
+
+#[derive(Default)]
+struct ServerState {
+    settings: ComPortSettings,
+    /// SET-CONTROL flow value: 1 none, 2 XON/XOFF, 3 hardware
+    flow: Option<u8>,
+    dtr: Option<bool>,
+    rts: Option<bool>,
+    modem_state: u8,
+    modem_mask: u8,
+    received: Vec<u8>,
+    // Bytes queued for the client, already Telnet encoded
+    outgoing: Vec<u8>,
+}
+
+/// Stand-in RFC 2217 serial server on localhost, for tests without hardware
+///
+/// Agrees to COM-PORT-OPTION, confirms every setting as requested and
+/// loops written data back to the client, like a port with RX tied to TX.
+pub struct Rfc2217Server {
+    address: SocketAddr,
+    state: Arc<Mutex<ServerState>>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Rfc2217Server {
+    pub fn start() -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        listener.set_nonblocking(true)?;
+        let address = listener.local_addr()?;
+
+        let state = Arc::new(Mutex::new(ServerState::default()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let handle = {
+            let state = Arc::clone(&state);
+            let stop = Arc::clone(&stop);
+            std::thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    match listener.accept() {
+                        Ok((stream, _)) => {
+                            // A dropped client ends its connection, not the server
+                            let _ = serve(stream, &state, &stop);
+                        }
+                        Err(_) => std::thread::sleep(Duration::from_millis(10)),
+                    }
+                }
+            })
+        };
+
+        Ok(Self {
+            address,
+            state,
+            stop,
+            handle: Some(handle),
+        })
+    }
+
+    /// `host:port` to connect to, e.g. in an `rfc2217://` URI
+    pub fn address(&self) -> String {
+        self.address.to_string()
+    }
+
+    /// Line settings last requested by a client
+    pub fn settings(&self) -> ComPortSettings {
+        self.state.lock().unwrap().settings
+    }
+
+    /// Flow control last requested, as its SET-CONTROL value
+    pub fn flow_control(&self) -> Option<u8> {
+        self.state.lock().unwrap().flow
+    }
+
+    pub fn dtr(&self) -> Option<bool> {
+        self.state.lock().unwrap().dtr
+    }
+
+    pub fn rts(&self) -> Option<bool> {
+        self.state.lock().unwrap().rts
+    }
+
+    /// Data received from clients, unescaped
+    pub fn received(&self) -> Vec<u8> {
+        self.state.lock().unwrap().received.clone()
+    }
+
+    /// Send data to the client as if the device had transmitted it
+    pub fn send(&self, data: &[u8]) {
+        let mut state = self.state.lock().unwrap();
+        state.outgoing.extend_from_slice(&telnet::escape(data));
+    }
+
+    /// Change the modem lines, notifying the client of the watched ones
+    pub fn set_modem_state(&self, bits: u8) {
+        let mut state = self.state.lock().unwrap();
+        state.modem_state = bits;
+        let notification = modem_notification(&state);
+        state.outgoing.extend_from_slice(&notification);
+    }
+}
+
+impl Drop for Rfc2217Server {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn modem_notification(state: &ServerState) -> Vec<u8> {
+    telnet::com_port_command(
+        NOTIFY_MODEMSTATE + SERVER_OFFSET,
+        &[state.modem_state & state.modem_mask],
+    )
+}
+
+fn serve(mut stream: TcpStream, state: &Mutex<ServerState>, stop: &AtomicBool) -> Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_millis(10)))?;
+    let mut parser = TelnetParser::new();
+    let mut buffer = [0u8; 1024];
+
+    while !stop.load(Ordering::Relaxed) {
+        let outgoing = std::mem::take(&mut state.lock().unwrap().outgoing);
+        stream.write_all(&outgoing)?;
+
+        let n = match stream.read(&mut buffer) {
+            Ok(0) => return Ok(()),
+            Ok(n) => n,
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => continue,
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut data = Vec::new();
+        let mut reply = Vec::new();
+        let mut state = state.lock().unwrap();
+        for event in parser.feed(&buffer[..n], &mut data) {
+            match event {
+                TelnetEvent::Negotiation { command, option } => {
+                    let answer = match (command, option) {
+                        (WILL, COM_PORT_OPTION | BINARY | SUPPRESS_GO_AHEAD) => DO,
+                        (DO, BINARY | SUPPRESS_GO_AHEAD) => WILL,
+                        (WILL, _) => DONT,
+                        (DO, _) => WONT,
+                        _ => continue,
+                    };
+                    reply.extend_from_slice(&telnet::negotiation(answer, option));
+                }
+                TelnetEvent::Subnegotiation(payload) => {
+                    let [COM_PORT_OPTION, command, value @ ..] = payload.as_slice() else {
+                        continue;
+                    };
+                    match (*command, value) {
+                        (SET_BAUDRATE, [a, b, c, d]) => {
+                            state.settings.baud = u32::from_be_bytes([*a, *b, *c, *d])
+                        }
+                        (SET_DATASIZE, [size]) => state.settings.data_size = *size,
+                        (SET_PARITY, [parity]) => state.settings.parity = *parity,
+                        (SET_STOPSIZE, [size]) => state.settings.stop_size = *size,
+                        (SET_CONTROL, [flow @ FLOW_NONE..=FLOW_HARDWARE]) => {
+                            state.flow = Some(*flow)
+                        }
+                        (SET_CONTROL, [DTR_ON]) => state.dtr = Some(true),
+                        (SET_CONTROL, [DTR_OFF]) => state.dtr = Some(false),
+                        (SET_CONTROL, [RTS_ON]) => state.rts = Some(true),
+                        (SET_CONTROL, [RTS_OFF]) => state.rts = Some(false),
+                        (SET_MODEMSTATE_MASK, [mask]) => state.modem_mask = *mask,
+                        _ => {}
+                    }
+                    // Confirm the request with the value as applied
+                    reply.extend_from_slice(&telnet::com_port_command(
+                        command + SERVER_OFFSET,
+                        value,
+                    ));
+                    if *command == SET_MODEMSTATE_MASK {
+                        reply.extend_from_slice(&modem_notification(&state));
+                    }
+                }
+            }
+        }
+
+        // Loop the data back, as if RX was wired to TX
+        state.received.extend_from_slice(&data);
+        reply.extend_from_slice(&telnet::escape(&data));
+        drop(state);
+        stream.write_all(&reply)?;
+    }
+    Ok(())
+}
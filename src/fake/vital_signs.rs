@@ -9,21 +9,8 @@ impl VitalSignsGenerator {
         println!("Sending vital signs data ({} samples)...\n", num_samples);
 
         for i in 1..=num_samples {
-            let hr = 60 + (i % 20);
-            let spo2 = 95 + (i % 5);
-            let bp_sys = 110 + (i % 20);
-            let bp_dia = 70 + (i % 10);
-            let temp = 36.5 + (i as f32 * 0.1);
-
-            let data = format!(
-                "PATIENT_ID=12345|HR={}|SPO2={}|BP={}/{}|TEMP={:.1}|TIME={}\n",
-                hr,
-                spo2,
-                bp_sys,
-                bp_dia,
-                temp,
-                chrono::Local::now().format("%H:%M:%S")
-            );
+            let time = chrono::Local::now().format("%H:%M:%S").to_string();
+            let data = Self::build_line(i, &time);
 
             port.write_all(data.as_bytes())?;
             port.flush()?;
@@ -33,4 +20,18 @@ impl VitalSignsGenerator {
 
         Ok(())
     }
+
+    /// The `KEY=VALUE|...` line of sample `i`
+    pub fn build_line(i: usize, time: &str) -> String {
+        let hr = 60 + (i % 20);
+        let spo2 = 95 + (i % 5);
+        let bp_sys = 110 + (i % 20);
+        let bp_dia = 70 + (i % 10);
+        let temp = 36.5 + (i as f32 * 0.1);
+
+        format!(
+            "PATIENT_ID=12345|HR={}|SPO2={}|BP={}/{}|TEMP={:.1}|TIME={}\n",
+            hr, spo2, bp_sys, bp_dia, temp, time
+        )
+    }
 }
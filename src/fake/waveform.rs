@@ -9,14 +9,7 @@ impl WaveformGenerator {
         println!("Sending binary waveform data (simulated ECG)...\n");
 
         for i in 0..100 {
-            // Simple sine wave to simulate waveform
-            let angle = (i as f32) * 0.1;
-            let value = (angle.sin() * 127.0 + 128.0) as u8;
-
-            // Binary packet: [STX, SEQ, VALUE, CHECKSUM, ETX]
-            let seq = (i % 256) as u8;
-            let checksum = seq.wrapping_add(value);
-            let packet = vec![0x02, seq, value, checksum, 0x03];
+            let packet = Self::build_packet(i);
 
             port.write_all(&packet)?;
             port.flush()?;
@@ -30,4 +23,15 @@ impl WaveformGenerator {
 
         Ok(())
     }
+
+    /// Packet `i` of the simulated ECG: [STX, SEQ, VALUE, CHECKSUM, ETX]
+    pub fn build_packet(i: usize) -> Vec<u8> {
+        // Simple sine wave to simulate waveform
+        let angle = (i as f32) * 0.1;
+        let value = (angle.sin() * 127.0 + 128.0) as u8;
+
+        let seq = (i % 256) as u8;
+        let checksum = seq.wrapping_add(value);
+        vec![0x02, seq, value, checksum, 0x03]
+    }
 }
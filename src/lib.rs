@@ -1,11 +1,15 @@
 // Library entry point - exports all public modules
 
+pub mod capture;
 pub mod cli;
 pub mod config;
 pub mod data;
 pub mod fake;
+pub mod output;
 pub mod port;
+pub mod protocol;
 pub mod reader;
+pub mod transport;
 
 // Re-export commonly used types
 pub use config::SerialConfig;
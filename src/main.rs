@@ -1,26 +1,49 @@
-use anyhow::Result;
-use clap::Parser;
+use anyhow::{Context, Result};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
+use signal_hook::consts::{SIGINT, SIGTERM};
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
+use vital_reader::capture::CaptureWriter;
 use vital_reader::cli::run_cli_mode;
+use vital_reader::config::{Profile, Profiles};
+use vital_reader::data::hl7::AckConfig;
+use vital_reader::data::{Decoder, DecoderRegistry, Framing};
+use vital_reader::output::{CsvColumn, CsvSink, MissingValue};
+use vital_reader::port::{Autodetect, PortMatcher, PortRule, PortRules, PortWatcher};
+#[cfg(target_os = "linux")]
+use vital_reader::port::{LoopbackGenerator, LoopbackPreset, VirtualPortPair};
+use vital_reader::protocol::{driver_from_name, DevicePreset, ProtocolDriver};
+use vital_reader::reader::{
+    OutputMode, ReconnectPolicy, Reconnector, ReplaySession, ReplaySpeed, Watchdog,
+};
+use vital_reader::transport::Endpoint;
 use vital_reader::{PortDetector, ReaderSession, SerialConfig};
 
+/// How often the port list is checked while waiting for a device
+const HOTPLUG_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
 #[derive(Parser, Debug)]
 #[command(name = "vital-reader")]
 #[command(about = "Serial port data reader for medical devices (GE/Dräger scopes)", long_about = None)]
 struct Args {
-    /// Serial port path (e.g., COM3, /dev/ttyUSB0)
+    /// Serial port path (e.g., COM3, /dev/ttyUSB0) or URI (tcp://host:port,
+    /// tcp-listen://addr:port, serial:///dev/ttyUSB0?baud=9600, file://dump.bin, -)
     #[arg(short, long)]
     port: Option<String>,
 
-    /// Baud rate
+    /// Baud rate, or "auto" to detect the baud rate and framing
     #[arg(short, long, default_value = "115200")]
-    baud: u32,
+    baud: String,
 
     /// Data bits (5, 6, 7, 8)
     #[arg(long, default_value = "8")]
     data_bits: u8,
 
-    /// Parity (none, odd, even)
+    /// Parity (none, odd, even, mark, space)
     #[arg(long, default_value = "none")]
     parity: String,
 
@@ -28,49 +51,250 @@ struct Args {
     #[arg(long, default_value = "1")]
     stop_bits: u8,
 
-    /// Configuration string format: baud,parity,data_bits,stop_bits (e.g., "57600,0,8,1")
+    /// Configuration string format: baud,parity,data_bits,stop_bits[,option...]
+    /// (e.g., "57600,0,8,1" or "9600,E,7,1,rtscts,dtr=on")
+    /// Options: rtscts, xonxoff, dtr=on|off, rts=on|off, rbuf=BYTES, wbuf=BYTES
     /// Overrides individual settings if provided
     #[arg(short, long)]
     config: Option<String>,
 
+    /// Device model (e.g. drager-evita), setting its factory serial settings,
+    /// protocol, driver and CSV columns unless given otherwise
+    #[arg(long, global = true)]
+    device: Option<String>,
+
+    /// Load settings from a named profile; options given here take precedence
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// Profile file (default: vital-reader/config.toml in the user config directory)
+    #[arg(long, requires = "profile", global = true)]
+    profile_file: Option<String>,
+
     /// Enable interactive CLI mode for testing
     #[arg(long)]
     cli: bool,
 
+    /// Read without keyboard commands, stopping on SIGINT or SIGTERM
+    /// (default when stdin is not a terminal, e.g. under systemd or Docker)
+    #[arg(long, global = true)]
+    headless: bool,
+
     /// Show statistics (bytes received, connection time)
-    #[arg(long)]
+    #[arg(long, global = true)]
     stats: bool,
 
     /// Read timeout in milliseconds
-    #[arg(long, default_value = "100")]
+    #[arg(long, default_value = "100", global = true)]
     timeout: u64,
+
+    /// Protocol decoder (hl7, line, mllp, binary, raw)
+    #[arg(long, default_value = "hl7", global = true)]
+    protocol: String,
+
+    /// Message framing (line, mllp, binary), alias for the matching --protocol
+    #[arg(long, conflicts_with = "protocol", global = true)]
+    framing: Option<String>,
+
+    /// Output mode (raw, observations, jsonl)
+    #[arg(long, default_value = "raw", global = true)]
+    output: String,
+
+    /// Poll the device with a protocol driver (medibus, datex, intellivue)
+    #[arg(long, global = true)]
+    driver: Option<String>,
+
+    /// Driver poll/transmission interval in seconds
+    #[arg(long, requires = "driver", global = true)]
+    interval: Option<u64>,
+
+    /// Also write numeric vital signs to this CSV file, one row per timestamp
+    #[arg(long, global = true)]
+    csv: Option<String>,
+
+    /// CSV columns, known names or HEADER=CODE (e.g. "HR,SpO2,Temp=8310-5")
+    #[arg(long, requires = "csv", global = true)]
+    csv_columns: Option<String>,
+
+    /// CSV cells without a value (empty, na, last)
+    #[arg(long, requires = "csv", global = true)]
+    csv_missing: Option<String>,
+
+    /// Record the raw session (received chunks, sent bytes) to a capture file
+    #[arg(long, global = true)]
+    record: Option<String>,
+
+    /// Report the connection as stale after this many seconds without data
+    #[arg(long, default_value = "30", global = true)]
+    watchdog: u64,
+
+    /// Port rules file (TOML) picking adapters by USB descriptors
+    #[arg(long, global = true)]
+    rules: Option<String>,
+
+    /// Rule to use from the rules file (default: its only rule)
+    #[arg(long, requires = "rules", global = true)]
+    rule: Option<String>,
+
+    /// Reopen the port with exponential backoff when it disappears
+    #[arg(long, global = true)]
+    reconnect: bool,
+
+    /// Give up after this many failed reconnect attempts (default: never)
+    #[arg(long, requires = "reconnect", global = true)]
+    reconnect_attempts: Option<u32>,
+
+    /// Port to reconnect to: serial=NUMBER, usb=VID:PID or a port name
+    /// (default: the serial number or USB ID of the port at startup)
+    #[arg(long, requires = "reconnect", global = true)]
+    reconnect_match: Option<String>,
+
+    /// Acknowledge each received HL7 message (MSA AA/AE/AR)
+    #[arg(long, global = true)]
+    ack: bool,
+
+    /// Sending application used in ACK messages (MSH-3)
+    #[arg(long, requires = "ack", global = true)]
+    ack_app: Option<String>,
+
+    /// Sending facility used in ACK messages (MSH-4)
+    #[arg(long, requires = "ack", global = true)]
+    ack_facility: Option<String>,
+
+    /// Milliseconds without data before a line-framed HL7 message is
+    /// complete and acknowledged (keep above the sender's segment pauses)
+    #[arg(long, default_value = "1000", requires = "ack", global = true)]
+    ack_quiet: u64,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Replay a capture file recorded with --record
+    Replay {
+        /// Capture file to replay
+        file: String,
+
+        /// Replay speed: realtime, a multiplier (e.g. 2x, 0.5) or max
+        #[arg(long, default_value = "realtime")]
+        speed: String,
+    },
+    /// Read simulated device traffic through a virtual serial port pair (Linux)
+    Loopback {
+        /// Simulated traffic (hl7, mllp, vitals, waveform)
+        #[arg(long, default_value = "hl7")]
+        preset: String,
+
+        /// Milliseconds between two transmissions (default depends on the preset)
+        #[arg(long)]
+        period: Option<u64>,
+    },
 }
 
 #[cfg(not(tarpaulin_include))]
 fn main() -> Result<()> {
-    let args = Args::parse();
-
-    if args.cli {
-        run_cli_mode()?;
+    let matches = Args::command().get_matches();
+    let mut args = Args::from_arg_matches(&matches)?;
+    // Before anything that can block, so a signal while waiting for a port
+    // or detecting its settings also stops cleanly. The interactive menu
+    // keeps the default Ctrl+C.
+    let stop = if args.cli && args.command.is_none() {
+        Arc::new(AtomicBool::new(false))
     } else {
-        run_reader_mode(&args)?;
+        stop_on_signals()?
+    };
+    let (mut profile, profile_path) = match load_profile(&args)? {
+        Some((profile, path)) => (Some(profile), Some(path)),
+        None => (None, None),
+    };
+    let device = args
+        .device
+        .clone()
+        .or_else(|| profile.as_ref().and_then(|profile| profile.device.clone()));
+    let preset = device.as_deref().map(DevicePreset::from_name).transpose()?;
+    if let Some(preset) = preset {
+        profile = Some(profile.unwrap_or_default().with_defaults(&preset.profile()));
+    }
+    if let Some(ref profile) = profile {
+        apply_profile(&mut args, &matches, profile);
+    }
+    let profile_rule = profile.and_then(|profile| profile.rule);
+
+    // The output mode may come from the profile, status lines wait for it
+    let output_mode = OutputMode::from_name(&args.output)?;
+    if let (Some(name), Some(path)) = (&args.profile, profile_path) {
+        print_status(
+            output_mode,
+            &format!("Using profile {} from {}", name, path),
+        );
+    }
+    if let Some(preset) = preset {
+        print_status(
+            output_mode,
+            &format!(
+                "Using device preset {}: {}",
+                preset.name, preset.description
+            ),
+        );
+    }
+
+    match args.command {
+        Some(Command::Replay {
+            ref file,
+            ref speed,
+        }) => run_replay_mode(&args, file, speed, stop)?,
+        Some(Command::Loopback { ref preset, period }) => {
+            run_loopback_mode(&args, preset, period, stop)?
+        }
+        None if args.cli => run_cli_mode()?,
+        None => run_reader_mode(&args, profile_rule, output_mode, stop)?,
     }
 
     Ok(())
 }
 
 #[cfg(not(tarpaulin_include))]
-fn run_reader_mode(args: &Args) -> Result<()> {
+fn run_reader_mode(
+    args: &Args,
+    profile_rule: Option<PortRule>,
+    output_mode: OutputMode,
+    stop: Arc<AtomicBool>,
+) -> Result<()> {
+    let status = |text: &str| print_status(output_mode, text);
+    let rule = match args.rules {
+        Some(ref path) => Some(PortRules::load(path)?.select(args.rule.as_deref())?.clone()),
+        None => profile_rule,
+    };
+
     // Get port name
     let port_name = if let Some(ref p) = args.port {
         p.clone()
+    } else if let Some(ref rule) = rule {
+        status(&format!(
+            "Waiting for a device matching rule {}...",
+            rule.name
+        ));
+        let matcher = PortMatcher::Rule(rule.clone());
+        let Some(port) = PortWatcher::new()
+            .with_stop_flag(Arc::clone(&stop))
+            .wait_for(&matcher, HOTPLUG_POLL_INTERVAL)
+        else {
+            return Ok(());
+        };
+        status(&format!("Rule {} matched {}", rule.name, port));
+        port
     } else {
         let detector = PortDetector::new();
-        println!("No port specified. Available ports:");
-        detector.list_ports();
+        status("No port specified. Available ports:");
+        // The port list is printed on stdout
+        if output_mode != OutputMode::Jsonl {
+            detector.list_ports();
+        }
 
         if let Some(suggested) = detector.suggest_port() {
-            println!("\nUsing suggested port: {}", suggested);
+            status(&format!("\nUsing suggested port: {}", suggested));
             suggested
         } else {
             return Err(anyhow::anyhow!(
@@ -81,32 +305,397 @@ fn run_reader_mode(args: &Args) -> Result<()> {
 
     // Parse configuration
     let serial_config = if let Some(ref config_str) = args.config {
-        println!("Using config string: {}", config_str);
+        status(&format!("Using config string: {}", config_str));
         SerialConfig::from_string(config_str)?
+    } else if args.baud == "auto" {
+        match detect_serial_config(&port_name, output_mode, &stop)? {
+            Some(config) => config,
+            None => return Ok(()),
+        }
     } else {
-        SerialConfig::new(args.baud, args.data_bits, &args.parity, args.stop_bits)?
+        let baud = args.baud.parse().context("Invalid baud rate")?;
+        SerialConfig::new(baud, args.data_bits, &args.parity, args.stop_bits)?
+    };
+
+    run_session(
+        args,
+        &port_name,
+        &serial_config,
+        create_decoder(args)?,
+        rule.as_ref(),
+        stop,
+    )
+}
+
+/// The `--profile` named on the command line and the file it came from
+#[cfg(not(tarpaulin_include))]
+fn load_profile(args: &Args) -> Result<Option<(Profile, String)>> {
+    let Some(ref name) = args.profile else {
+        return Ok(None);
+    };
+    let path = match args.profile_file {
+        Some(ref path) => path.clone(),
+        None => Profiles::default_path()
+            .ok_or_else(|| anyhow::anyhow!("No config directory found, use --profile-file"))?
+            .to_string_lossy()
+            .into_owned(),
+    };
+    let profile = Profiles::load(&path)?.select(name)?.clone();
+    Ok(Some((profile, path)))
+}
+
+/// Fill in the settings of `profile` not given on the command line
+#[cfg(not(tarpaulin_include))]
+fn apply_profile(args: &mut Args, matches: &ArgMatches, profile: &Profile) {
+    // Global options may also follow a subcommand
+    let given = |id: &str| {
+        std::iter::once(matches)
+            .chain(matches.subcommand().map(|(_, sub)| sub))
+            .any(|matches| {
+                matches.ids().any(|known| known == id)
+                    && matches.value_source(id) == Some(ValueSource::CommandLine)
+            })
+    };
+    macro_rules! apply {
+        ($($field:ident),*) => {$(
+            if let (false, Some(value)) = (given(stringify!($field)), &profile.$field) {
+                args.$field = value.clone();
+            }
+        )*};
+    }
+    macro_rules! apply_option {
+        ($($field:ident),*) => {$(
+            if let (false, Some(value)) = (given(stringify!($field)), &profile.$field) {
+                args.$field = Some(value.clone());
+            }
+        )*};
+    }
+
+    apply!(baud, data_bits, parity, stop_bits, protocol, timeout, watchdog, output);
+    apply!(stats, reconnect, ack);
+    apply_option!(port, device, config, driver, interval, record);
+    apply_option!(csv, csv_columns, csv_missing);
+    apply_option!(reconnect_attempts, reconnect_match, ack_app, ack_facility);
+    // A serial setting on the command line replaces the profile's config string
+    if ["baud", "data_bits", "parity", "stop_bits"]
+        .iter()
+        .any(|id| given(id))
+        && !given("config")
+    {
+        args.config = None;
+    }
+}
+
+/// Listen at each candidate setting and pick the best scoring one, `None`
+/// when stopped before the end
+#[cfg(not(tarpaulin_include))]
+fn detect_serial_config(
+    port_name: &str,
+    output_mode: OutputMode,
+    stop: &Arc<AtomicBool>,
+) -> Result<Option<SerialConfig>> {
+    let Endpoint::Serial { port, .. } = Endpoint::parse(port_name, &SerialConfig::default())?
+    else {
+        return Err(anyhow::anyhow!(
+            "--baud auto needs a serial port, not {}",
+            port_name
+        ));
     };
 
+    let autodetect = Autodetect::new().with_stop_flag(Arc::clone(stop));
+    print_status(
+        output_mode,
+        &format!(
+            "Detecting baud rate and framing on {} (about {} seconds)...",
+            port,
+            autodetect.duration().as_secs()
+        ),
+    );
+    let results = autodetect.run(&port)?;
+    if stop.load(Ordering::Relaxed) {
+        return Ok(None);
+    }
+    let best = Autodetect::best(&results)
+        .ok_or_else(|| anyhow::anyhow!("No data received on {} at any candidate setting", port))?;
+
+    print_status(
+        output_mode,
+        &format!(
+            "Detected {} ({} bytes, {:.0}% ASCII, {} framing errors{})",
+            best.config,
+            best.bytes,
+            best.ascii_ratio * 100.0,
+            best.framing_errors,
+            if best.markers.is_empty() {
+                String::new()
+            } else {
+                format!(", {}", best.markers.join(", "))
+            }
+        ),
+    );
+    Ok(Some(best.config.clone()))
+}
+
+/// Read a port with the decoding and output options of the command line
+#[cfg(not(tarpaulin_include))]
+fn run_session(
+    args: &Args,
+    port_name: &str,
+    serial_config: &SerialConfig,
+    decoder: Box<dyn Decoder>,
+    rule: Option<&PortRule>,
+    stop: Arc<AtomicBool>,
+) -> Result<()> {
+    let output_mode = OutputMode::from_name(&args.output)?;
+    let driver = create_driver(args)?;
+    let csv = args
+        .csv
+        .as_deref()
+        .map(|path| create_csv_sink(path, args))
+        .transpose()?;
+    let recorder = args
+        .record
+        .as_deref()
+        .map(CaptureWriter::create)
+        .transpose()?;
+
     // Print configuration
-    print_configuration(&port_name, &serial_config);
+    let endpoint = Endpoint::parse(port_name, serial_config)?;
+    let reconnector = reconnect_config(args, endpoint.clone(), rule)?;
+    let headless = args.headless || !std::io::stdin().is_terminal();
+    print_configuration(
+        port_name,
+        endpoint.serial_config(),
+        decoder.name(),
+        output_mode,
+        headless,
+    );
 
     // Create and run session
-    let mut session = ReaderSession::new(&port_name, &serial_config, args.timeout, args.stats)?;
+    let serial_config = endpoint.serial_config().unwrap_or(serial_config);
+    let mut session =
+        ReaderSession::from_transport(endpoint.open(args.timeout)?, serial_config, args.stats)
+            .with_decoder(decoder)
+            .with_output_mode(output_mode)
+            .with_watchdog(Watchdog::new().with_stale_after(Duration::from_secs(args.watchdog)))
+            .with_headless(headless)
+            .with_stop_flag(stop);
+    if let Some(driver) = driver {
+        session = session.with_driver(driver);
+    }
+    if let Some(csv) = csv {
+        session = session.with_sink(Box::new(csv));
+    }
+    if let Some(recorder) = recorder {
+        session = session.with_recorder(recorder);
+    }
+    if let Some(config) = ack_config(args) {
+        session = session
+            .with_ack(config)
+            .with_ack_quiet_period(Duration::from_millis(args.ack_quiet));
+    }
+    if let Some(reconnector) = reconnector {
+        session = session.with_reconnect(reconnector);
+    }
     session.run()?;
 
     Ok(())
 }
 
+/// Flag set by SIGINT or SIGTERM, a second signal exits at once
+#[cfg(not(tarpaulin_include))]
+fn stop_on_signals() -> Result<Arc<AtomicBool>> {
+    let stop = Arc::new(AtomicBool::new(false));
+    for signal in [SIGINT, SIGTERM] {
+        signal_hook::flag::register_conditional_shutdown(signal, 1, Arc::clone(&stop))?;
+        signal_hook::flag::register(signal, Arc::clone(&stop))?;
+    }
+    Ok(stop)
+}
+
 #[cfg(not(tarpaulin_include))]
-fn print_configuration(port_name: &str, config: &SerialConfig) {
-    println!("\n╔════════════════════════════════════════╗");
-    println!("║      VITAL SERIAL READER v0.1.0       ║");
-    println!("╚════════════════════════════════════════╝");
-    println!("\nConfiguration:");
-    println!("  Port:         {}", port_name);
-    println!("  Baud rate:    {}", config.baud);
-    println!("  Data bits:    {:?}", config.data_bits);
-    println!("  Parity:       {:?}", config.parity);
-    println!("  Stop bits:    {:?}", config.stop_bits);
-    println!("\nPress [h] for help, [q] to quit\n");
+fn run_replay_mode(args: &Args, file: &str, speed: &str, stop: Arc<AtomicBool>) -> Result<()> {
+    let speed = ReplaySpeed::from_name(speed)?;
+    let output_mode = OutputMode::from_name(&args.output)?;
+    let mut session = ReplaySession::open(file, args.stats)?
+        .with_speed(speed)
+        .with_idle_timeout(Duration::from_millis(args.timeout))
+        .with_watchdog(Watchdog::new().with_stale_after(Duration::from_secs(args.watchdog)))
+        .with_decoder(create_decoder(args)?)
+        .with_output_mode(output_mode)
+        .with_stop_flag(stop);
+    if let Some(driver) = create_driver(args)? {
+        session = session.with_driver(driver);
+    }
+    if let Some(ref path) = args.csv {
+        session = session.with_sink(Box::new(create_csv_sink(path, args)?));
+    }
+    if let Some(config) = ack_config(args) {
+        session = session
+            .with_ack(config)
+            .with_ack_quiet_period(Duration::from_millis(args.ack_quiet));
+    }
+    session.run()
+}
+
+#[cfg(all(target_os = "linux", not(tarpaulin_include)))]
+fn run_loopback_mode(
+    args: &Args,
+    preset: &str,
+    period: Option<u64>,
+    stop: Arc<AtomicBool>,
+) -> Result<()> {
+    let preset = LoopbackPreset::from_name(preset)?;
+    let period = period.map_or(preset.period(), Duration::from_millis);
+    let pair = VirtualPortPair::open()?;
+    let generator = LoopbackGenerator::spawn(pair.device()?, preset, period);
+    let decoder = DecoderRegistry::new().create(preset.protocol())?;
+
+    let result = run_session(
+        args,
+        pair.path(),
+        &SerialConfig::default(),
+        decoder,
+        None,
+        stop,
+    );
+    result.and(generator.stop())
+}
+
+#[cfg(all(not(target_os = "linux"), not(tarpaulin_include)))]
+fn run_loopback_mode(
+    _args: &Args,
+    _preset: &str,
+    _period: Option<u64>,
+    _stop: Arc<AtomicBool>,
+) -> Result<()> {
+    Err(anyhow::anyhow!("Loopback mode is only available on Linux"))
+}
+
+#[cfg(not(tarpaulin_include))]
+fn create_decoder(args: &Args) -> Result<Box<dyn Decoder>> {
+    let protocol = match args.framing.as_deref() {
+        Some(framing) => Framing::from_name(framing)?.protocol(),
+        None => args.protocol.as_str(),
+    };
+    DecoderRegistry::new().create(protocol)
+}
+
+#[cfg(not(tarpaulin_include))]
+fn create_driver(args: &Args) -> Result<Option<Box<dyn ProtocolDriver>>> {
+    let interval = args.interval.map(Duration::from_secs);
+    // The preset polls for what its model reports
+    if let Some(ref device) = args.device {
+        let preset = DevicePreset::from_name(device)?;
+        if preset.driver.is_some() && preset.driver == args.driver.as_deref() {
+            return preset.create_driver(interval);
+        }
+    }
+    args.driver
+        .as_deref()
+        .map(|name| driver_from_name(name, interval))
+        .transpose()
+}
+
+#[cfg(not(tarpaulin_include))]
+fn ack_config(args: &Args) -> Option<AckConfig> {
+    args.ack.then(|| AckConfig {
+        application: args.ack_app.clone(),
+        facility: args.ack_facility.clone(),
+    })
+}
+
+#[cfg(not(tarpaulin_include))]
+fn create_csv_sink(path: &str, args: &Args) -> Result<CsvSink> {
+    let mut sink = CsvSink::create(path)?;
+    if let Some(ref columns) = args.csv_columns {
+        sink = sink.with_columns(CsvColumn::parse_list(columns)?);
+    }
+    if let Some(ref missing) = args.csv_missing {
+        sink = sink.with_missing(MissingValue::from_name(missing)?);
+    }
+    Ok(sink)
+}
+
+#[cfg(not(tarpaulin_include))]
+fn reconnect_config(
+    args: &Args,
+    endpoint: Endpoint,
+    rule: Option<&PortRule>,
+) -> Result<Option<Reconnector>> {
+    // A rule session follows its device through unplugs on its own
+    let hotplug = rule.is_some() && matches!(endpoint, Endpoint::Serial { .. });
+    if !args.reconnect && !hotplug {
+        return Ok(None);
+    }
+
+    let mut policy = ReconnectPolicy::new();
+    if let Some(attempts) = args.reconnect_attempts {
+        policy = policy.with_max_attempts(attempts);
+    }
+    if hotplug {
+        policy = policy.with_max_delay(HOTPLUG_POLL_INTERVAL * 4);
+    }
+
+    // Remember the adapter now, it may come back under another name
+    let matcher = match (&args.reconnect_match, rule, &endpoint) {
+        (Some(spec), _, _) => Some(PortMatcher::from_name(spec)?),
+        (None, Some(rule), _) => Some(PortMatcher::Rule(rule.clone())),
+        (None, None, Endpoint::Serial { port, .. }) => Some(PortMatcher::for_port(
+            port,
+            &PortDetector::get_available_ports(),
+        )),
+        (None, None, _) => None,
+    };
+
+    let mut reconnector = Reconnector::new(endpoint, args.timeout)?.with_policy(policy);
+    if let Some(matcher) = matcher {
+        reconnector = reconnector.with_matcher(matcher);
+    }
+    Ok(Some(reconnector))
+}
+
+#[cfg(not(tarpaulin_include))]
+fn print_configuration(
+    port_name: &str,
+    config: Option<&SerialConfig>,
+    protocol: &str,
+    output_mode: OutputMode,
+    headless: bool,
+) {
+    let mut lines = vec![
+        "\n╔════════════════════════════════════════╗".to_string(),
+        "║      VITAL SERIAL READER v0.1.0       ║".to_string(),
+        "╚════════════════════════════════════════╝".to_string(),
+        "\nConfiguration:".to_string(),
+        format!("  Port:         {}", port_name),
+    ];
+    // Network and file inputs have no line settings
+    if let Some(config) = config {
+        lines.extend([
+            format!("  Baud rate:    {}", config.baud),
+            format!("  Data bits:    {:?}", config.data_bits),
+            format!("  Parity:       {}", config.parity_letter()),
+            format!("  Stop bits:    {}", config.stop_bits_name()),
+            format!("  Flow control: {:?}", config.flow_control),
+        ]);
+    }
+    lines.push(format!("  Protocol:     {}", protocol));
+    lines.push(if headless {
+        "\nRunning headless, stop with Ctrl+C or SIGTERM\n".to_string()
+    } else {
+        "\nPress [h] for help, [q] to quit\n".to_string()
+    });
+    print_status(output_mode, &lines.join("\n"));
+}
+
+/// Print a message outside the session, on stderr when stdout carries JSON
+/// Lines
+#[cfg(not(tarpaulin_include))]
+fn print_status(output_mode: OutputMode, text: &str) {
+    if output_mode == OutputMode::Jsonl {
+        eprintln!("{}", text);
+    } else {
+        println!("{}", text);
+    }
 }
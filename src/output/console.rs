@@ -0,0 +1,98 @@
+use anyhow::Result;
+use std::io::{self, Write};
+
+use super::{Event, Record, Sink};
+use crate::data::DataFormatter;
+use crate::reader::OutputMode;
+
+/// Human readable output, one line per record
+///
+/// Received data and packets are shown in raw mode, observations, settings
+/// and waveforms in observations mode. Device, alarm, warning, sent and
+/// connection messages are always shown.
+pub struct ConsoleSink {
+    mode: OutputMode,
+    out: Box<dyn Write + Send>,
+}
+
+impl ConsoleSink {
+    /// Print to stdout
+    pub fn new(mode: OutputMode) -> Self {
+        Self::with_writer(mode, Box::new(io::stdout()))
+    }
+
+    pub fn with_writer(mode: OutputMode, out: Box<dyn Write + Send>) -> Self {
+        Self { mode, out }
+    }
+
+    pub fn mode(&self) -> OutputMode {
+        self.mode
+    }
+
+    /// The line printed for a record, if any in the current mode
+    pub fn format(&self, record: &Record) -> Option<String> {
+        let ts = record.timestamp.as_str();
+        let raw = self.mode == OutputMode::Raw;
+        let observations = self.mode == OutputMode::Observations;
+
+        match &record.event {
+            Event::Data { bytes, data_type } if raw => {
+                DataFormatter::format_data(bytes, data_type, ts)
+            }
+            Event::Packet(event) if raw => Some(DataFormatter::format_binary_event(event, ts)),
+            Event::Observation(observation) | Event::Setting(observation) if observations => {
+                Some(DataFormatter::format_observation(observation, ts))
+            }
+            Event::Waveform {
+                name,
+                unit,
+                sample_rate,
+                samples,
+            } if observations => Some(format!(
+                "[{}] WAVE: {} {} samples @ {} Hz ({})",
+                ts,
+                name,
+                samples.len(),
+                sample_rate,
+                unit
+            )),
+            Event::Device(device) => Some(format!("[{}] DEVICE: {}", ts, device)),
+            Event::Alarm {
+                priority,
+                code,
+                text,
+            } => Some(format!(
+                "[{}] ALARM: {} (code {}, priority {})",
+                ts, text, code, priority
+            )),
+            Event::Warning(message) => Some(format!("[{}] WARNING: {}", ts, message)),
+            Event::AckSent { code, control_id } => {
+                Some(format!("[{}] SENT ACK: {} for {}", ts, code, control_id))
+            }
+            Event::CommandSent(command) => Some(format!("[{}] SENT: {}", ts, command)),
+            Event::ConnectionState { state, detail } => {
+                Some(format!("[{}] STATE: {} ({})", ts, state, detail))
+            }
+            Event::Disconnected(reason) => Some(format!("[{}] DISCONNECTED: {}", ts, reason)),
+            Event::Reconnected { port, attempts } => Some(format!(
+                "[{}] RECONNECTED: {} after {} attempt(s)",
+                ts, port, attempts
+            )),
+            _ => None,
+        }
+    }
+}
+
+impl Sink for ConsoleSink {
+    fn write(&mut self, record: &Record) -> Result<()> {
+        if let Some(line) = self.format(record) {
+            writeln!(self.out, "{}", line)?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.out.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,322 @@
+use anyhow::Result;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use super::{Event, Record, Sink};
+use crate::data::VitalObservation;
+
+/// Row timestamps, the format of session record timestamps
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3f";
+
+/// Decimals written at most, enough for every known parameter
+const VALUE_DECIMALS: usize = 2;
+
+/// Columns known by name with the UCUM unit they are written in, the first
+/// ten are the default column set
+const KNOWN_COLUMNS: [(&str, &str, &str); 13] = [
+    ("HR", "8867-4", "/min"),
+    ("SpO2", "2708-6", "%"),
+    ("NIBP_SYS", "8480-6", "mm[Hg]"),
+    ("NIBP_DIA", "8462-4", "mm[Hg]"),
+    ("NIBP_MEAN", "8478-0", "mm[Hg]"),
+    ("EtCO2", "19889-5", "mm[Hg]"),
+    ("BIS", "90371-9", "{score}"),
+    ("TV", "20112-9", "mL"),
+    ("PEEP", "76248-9", "cm[H2O]"),
+    ("FiO2", "3150-0", "%"),
+    ("RR", "9279-1", "/min"),
+    ("TEMP", "8310-5", "Cel"),
+    ("PR", "8889-8", "/min"),
+];
+
+/// Factors between units a column value can be converted from and to,
+/// including the non-UCUM spellings devices send for the same unit
+const UNIT_CONVERSIONS: [(&str, &str, f64); 6] = [
+    ("kPa", "mm[Hg]", 7.500_617),
+    ("mm[Hg]", "kPa", 0.133_322_4),
+    ("mbar", "cm[H2O]", 1.019_716),
+    ("L", "mL", 1000.0),
+    ("bpm", "/min", 1.0),
+    ("1", "{score}", 1.0),
+];
+
+/// A CSV column holding the values of one observation code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvColumn {
+    pub header: String,
+    /// Observation identifier, e.g. LOINC "8867-4"
+    pub code: String,
+    /// UCUM unit of the values, `None` to write them as received
+    pub unit: Option<String>,
+}
+
+impl CsvColumn {
+    pub fn new(header: &str, code: &str) -> Self {
+        Self {
+            header: header.to_string(),
+            code: code.to_string(),
+            unit: None,
+        }
+    }
+
+    pub fn with_unit(mut self, unit: &str) -> Self {
+        self.unit = Some(unit.to_string());
+        self
+    }
+
+    /// `value` received in `unit` converted to the column unit
+    ///
+    /// `None` when the value has no unit or one without a conversion, so the
+    /// column never mixes units. Columns without a unit take every value.
+    pub fn convert(&self, value: f64, unit: Option<&str>) -> Option<f64> {
+        let Some(to) = self.unit.as_deref() else {
+            return Some(value);
+        };
+        let from = unit?;
+        if from == to {
+            return Some(value);
+        }
+        UNIT_CONVERSIONS
+            .iter()
+            .find(|(f, t, _)| *f == from && *t == to)
+            .map(|(_, _, factor)| value * factor)
+    }
+
+    /// HR, SpO2, NIBP sys/dia/mean, EtCO2, BIS, tidal volume, PEEP and FiO2
+    pub fn defaults() -> Vec<Self> {
+        KNOWN_COLUMNS[..10]
+            .iter()
+            .map(|(header, code, unit)| Self::new(header, code).with_unit(unit))
+            .collect()
+    }
+
+    /// Parse a column: a known name (e.g. "HR") or "HEADER=CODE"
+    pub fn from_name(name: &str) -> Result<Self> {
+        let name = name.trim();
+        if let Some((header, code)) = name.split_once('=') {
+            if header.trim().is_empty() || code.trim().is_empty() {
+                return Err(anyhow::anyhow!(
+                    "Invalid CSV column: {} (expected HEADER=CODE)",
+                    name
+                ));
+            }
+            return Ok(Self::new(header.trim(), code.trim()));
+        }
+
+        KNOWN_COLUMNS
+            .iter()
+            .find(|(header, ..)| header.eq_ignore_ascii_case(name))
+            .map(|(header, code, unit)| Self::new(header, code).with_unit(unit))
+            .ok_or_else(|| {
+                let names: Vec<&str> = KNOWN_COLUMNS.iter().map(|(header, ..)| *header).collect();
+                anyhow::anyhow!(
+                    "Invalid CSV column: {} (expected HEADER=CODE or one of {})",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+
+    /// Parse a comma separated column list, e.g. "HR,SpO2,Temp=8310-5"
+    pub fn parse_list(list: &str) -> Result<Vec<Self>> {
+        let columns = list
+            .split(',')
+            .filter(|name| !name.trim().is_empty())
+            .map(Self::from_name)
+            .collect::<Result<Vec<_>>>()?;
+        if columns.is_empty() {
+            return Err(anyhow::anyhow!("CSV column list is empty"));
+        }
+        Ok(columns)
+    }
+}
+
+/// What to write for a column without a value in a row
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MissingValue {
+    /// Leave the cell empty
+    #[default]
+    Empty,
+    /// Write "NA"
+    Na,
+    /// Repeat the last value seen for the column, empty until there is one
+    Last,
+}
+
+impl MissingValue {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "empty" => Ok(Self::Empty),
+            "na" => Ok(Self::Na),
+            "last" => Ok(Self::Last),
+            _ => Err(anyhow::anyhow!(
+                "Invalid missing value policy: {} (expected empty, na or last)",
+                name
+            )),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Empty => "empty",
+            Self::Na => "na",
+            Self::Last => "last",
+        }
+    }
+}
+
+/// Numeric trend export, one row per observation timestamp
+///
+/// Observations come from decoded records and from `KEY=VALUE|...` data
+/// lines. Consecutive observations with the same timestamp share a row; the
+/// row is written when a new timestamp arrives or on flush.
+pub struct CsvSink {
+    out: Box<dyn Write + Send>,
+    columns: Vec<CsvColumn>,
+    missing: MissingValue,
+    header_written: bool,
+    row: Option<(String, Vec<Option<f64>>)>,
+    last: Vec<Option<f64>>,
+}
+
+impl CsvSink {
+    /// Create (or truncate) the CSV file at `path`
+    pub fn create(path: &str) -> Result<Self> {
+        let file = File::create(path)
+            .map_err(|e| anyhow::anyhow!("Failed to create CSV file {}: {}", path, e))?;
+        Ok(Self::with_writer(Box::new(BufWriter::new(file))))
+    }
+
+    pub fn with_writer(out: Box<dyn Write + Send>) -> Self {
+        let columns = CsvColumn::defaults();
+        Self {
+            out,
+            last: vec![None; columns.len()],
+            columns,
+            missing: MissingValue::default(),
+            header_written: false,
+            row: None,
+        }
+    }
+
+    pub fn with_columns(mut self, columns: Vec<CsvColumn>) -> Self {
+        self.last = vec![None; columns.len()];
+        self.columns = columns;
+        self
+    }
+
+    pub fn with_missing(mut self, missing: MissingValue) -> Self {
+        self.missing = missing;
+        self
+    }
+
+    pub fn columns(&self) -> &[CsvColumn] {
+        &self.columns
+    }
+
+    fn add(&mut self, timestamp: &str, observation: &VitalObservation) -> Result<()> {
+        let Some(value) = observation.value.as_f64() else {
+            return Ok(());
+        };
+        let Some(index) = self
+            .columns
+            .iter()
+            .position(|column| column.code == observation.code)
+        else {
+            return Ok(());
+        };
+        let Some(value) = self.columns[index].convert(value, observation.unit.as_deref()) else {
+            return Ok(());
+        };
+
+        let timestamp = observation
+            .observation_time
+            .map(|t| t.format(TIMESTAMP_FORMAT).to_string())
+            .unwrap_or_else(|| timestamp.to_string());
+
+        if self.row.as_ref().is_some_and(|(row, _)| *row != timestamp) {
+            self.write_row()?;
+        }
+        let columns = self.columns.len();
+        let (_, values) = self
+            .row
+            .get_or_insert_with(|| (timestamp, vec![None; columns]));
+        values[index] = Some(value);
+        Ok(())
+    }
+
+    fn write_header(&mut self) -> Result<()> {
+        if self.header_written {
+            return Ok(());
+        }
+        let mut cells = vec!["timestamp".to_string()];
+        cells.extend(self.columns.iter().map(|column| escape(&column.header)));
+        writeln!(self.out, "{}", cells.join(","))?;
+        self.header_written = true;
+        Ok(())
+    }
+
+    fn write_row(&mut self) -> Result<()> {
+        let Some((timestamp, values)) = self.row.take() else {
+            return Ok(());
+        };
+        self.write_header()?;
+
+        let mut cells = vec![escape(&timestamp)];
+        for (i, value) in values.into_iter().enumerate() {
+            let cell = match (value, self.missing) {
+                (Some(value), _) => {
+                    self.last[i] = Some(value);
+                    format_value(value)
+                }
+                (None, MissingValue::Empty) => String::new(),
+                (None, MissingValue::Na) => "NA".to_string(),
+                (None, MissingValue::Last) => self.last[i].map(format_value).unwrap_or_default(),
+            };
+            cells.push(cell);
+        }
+        writeln!(self.out, "{}", cells.join(","))?;
+        Ok(())
+    }
+}
+
+impl Sink for CsvSink {
+    fn write(&mut self, record: &Record) -> Result<()> {
+        match &record.event {
+            Event::Observation(observation) => self.add(&record.timestamp, observation),
+            Event::Data { bytes, .. } => {
+                let text = String::from_utf8_lossy(bytes);
+                for observation in VitalObservation::from_key_values(&text) {
+                    self.add(&record.timestamp, &observation)?;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.write_row()?;
+        self.write_header()?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// A value with at most `VALUE_DECIMALS` decimals, trailing zeros dropped
+fn format_value(value: f64) -> String {
+    let text = format!("{:.*}", VALUE_DECIMALS, value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    match text {
+        "-0" => "0".to_string(),
+        _ => text.to_string(),
+    }
+}
+
+fn escape(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
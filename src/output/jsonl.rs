@@ -0,0 +1,223 @@
+use anyhow::Result;
+use serde_json::{json, Map, Value};
+use std::io::{self, Write};
+
+use super::{Event, Record, Sink};
+use crate::data::hl7::{EncodingCharacters, Message, Segment};
+use crate::data::{BinaryEvent, DataType, ObservationValue, VitalObservation};
+
+/// JSON Lines output, one object per record
+///
+/// Every object has a `timestamp` and a `type`. Received data carries the
+/// raw bytes as hex, the decoded text and, when the line is an HL7 segment,
+/// the parsed segment. Segment lines use the encoding characters of the
+/// last MSH seen.
+pub struct JsonlSink {
+    out: Box<dyn Write + Send>,
+    encoding: EncodingCharacters,
+}
+
+impl JsonlSink {
+    /// Write to stdout
+    pub fn new() -> Self {
+        Self::with_writer(Box::new(io::stdout()))
+    }
+
+    pub fn with_writer(out: Box<dyn Write + Send>) -> Self {
+        Self {
+            out,
+            encoding: EncodingCharacters::default(),
+        }
+    }
+
+    /// The JSON object written for a record
+    pub fn to_json(&mut self, record: &Record) -> Value {
+        let mut object = Map::new();
+        object.insert("timestamp".to_string(), json!(record.timestamp));
+
+        let (kind, fields) = match &record.event {
+            Event::Data { bytes, data_type } => ("data", self.data_json(bytes, *data_type)),
+            Event::Packet(event) => ("packet", packet_json(event)),
+            Event::Message(message) => ("message", message_json(message)),
+            Event::Observation(observation) => ("observation", observation_json(observation)),
+            Event::Setting(observation) => ("setting", observation_json(observation)),
+            Event::Waveform {
+                name,
+                unit,
+                sample_rate,
+                samples,
+            } => (
+                "waveform",
+                json!({
+                    "name": name,
+                    "unit": unit,
+                    "sample_rate": sample_rate,
+                    "samples": samples.iter().map(|s| finite(*s)).collect::<Vec<_>>(),
+                }),
+            ),
+            Event::Device(device) => ("device", json!({ "device": device })),
+            Event::Alarm {
+                priority,
+                code,
+                text,
+            } => (
+                "alarm",
+                json!({ "priority": priority, "code": code, "text": text }),
+            ),
+            Event::Warning(message) => ("warning", json!({ "message": message })),
+            Event::AckSent { code, control_id } => {
+                ("ack", json!({ "code": code, "control_id": control_id }))
+            }
+            Event::CommandSent(command) => ("command", json!({ "command": command })),
+            Event::ConnectionState { state, detail } => {
+                ("state", json!({ "state": state.name(), "detail": detail }))
+            }
+            Event::Disconnected(reason) => ("disconnected", json!({ "reason": reason })),
+            Event::Reconnected { port, attempts } => {
+                ("reconnected", json!({ "port": port, "attempts": attempts }))
+            }
+        };
+
+        object.insert("type".to_string(), json!(kind));
+        if let Value::Object(fields) = fields {
+            object.extend(fields);
+        }
+        Value::Object(object)
+    }
+
+    fn data_json(&mut self, bytes: &[u8], data_type: DataType) -> Value {
+        let text = String::from_utf8_lossy(bytes);
+        let text = text.trim_end_matches(['\r', '\n']);
+
+        let mut fields = json!({
+            "data_type": data_type_name(data_type),
+            "hex": hex(bytes),
+            "text": text,
+        });
+
+        if text.starts_with("MSH") {
+            if let Ok(encoding) = EncodingCharacters::from_msh(text) {
+                self.encoding = encoding;
+            }
+        }
+        if let Ok(segment) = Segment::parse(text, &self.encoding) {
+            fields["segment"] = segment_json(&segment, &self.encoding);
+        }
+        fields
+    }
+}
+
+impl Default for JsonlSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sink for JsonlSink {
+    fn write(&mut self, record: &Record) -> Result<()> {
+        let line = self.to_json(record).to_string();
+        writeln!(self.out, "{}", line)?;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+fn data_type_name(data_type: DataType) -> &'static str {
+    match data_type {
+        DataType::Ascii => "ascii",
+        DataType::Binary => "binary",
+        DataType::Mixed => "mixed",
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+/// JSON has no NaN, invalid samples become null
+fn finite(value: f64) -> Value {
+    if value.is_finite() {
+        json!(value)
+    } else {
+        Value::Null
+    }
+}
+
+fn segment_json(segment: &Segment, encoding: &EncodingCharacters) -> Value {
+    let fields: Vec<String> = segment
+        .fields()
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            // MSH-1 and MSH-2 are the encoding characters themselves
+            if segment.name() == "MSH" && i < 2 {
+                field.value().to_string()
+            } else {
+                field.encode(encoding)
+            }
+        })
+        .collect();
+    json!({ "name": segment.name(), "fields": fields })
+}
+
+fn message_json(message: &Message) -> Value {
+    json!({
+        "message_type": message.message_type(),
+        "control_id": message.control_id(),
+        "version": message.version(),
+        "segments": message
+            .segments()
+            .iter()
+            .map(|s| segment_json(s, message.encoding()))
+            .collect::<Vec<_>>(),
+    })
+}
+
+fn packet_json(event: &BinaryEvent) -> Value {
+    match event {
+        BinaryEvent::Packet(packet) => json!({
+            "sequence": packet.sequence,
+            "samples": packet.samples,
+        }),
+        BinaryEvent::ChecksumError {
+            sequence,
+            expected,
+            received,
+        } => json!({
+            "error": "checksum",
+            "sequence": sequence,
+            "expected": expected,
+            "received": received,
+        }),
+        BinaryEvent::SequenceGap { expected, received } => json!({
+            "error": "sequence_gap",
+            "expected": expected,
+            "received": received,
+        }),
+    }
+}
+
+fn observation_json(observation: &VitalObservation) -> Value {
+    let value = match &observation.value {
+        ObservationValue::Numeric(value) => finite(*value),
+        ObservationValue::Text(text) => json!(text),
+    };
+    json!({
+        "code": observation.code,
+        "name": observation.display_name,
+        "system": observation.coding_system,
+        "value": value,
+        "unit": observation.unit,
+        "reference_range": observation.reference_range,
+        "abnormal_flag": observation.abnormal_flag,
+        "result_status": observation.result_status,
+        "observation_time": observation
+            .observation_time
+            .map(|t| t.format("%Y-%m-%dT%H:%M:%S").to_string()),
+        "producer": observation.producer,
+    })
+}
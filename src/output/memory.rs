@@ -0,0 +1,68 @@
+use anyhow::Result;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+use super::{Record, Sink};
+
+/// Keeps every record in memory
+///
+/// Clones share the same storage: keep one to read back what the session
+/// wrote into the other.
+#[derive(Clone, Default)]
+pub struct MemorySink {
+    records: Arc<Mutex<Vec<Record>>>,
+}
+
+impl MemorySink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records written so far
+    pub fn records(&self) -> Vec<Record> {
+        self.records.lock().map(|r| r.clone()).unwrap_or_default()
+    }
+
+    /// Remove and return the records written so far
+    pub fn take(&self) -> Vec<Record> {
+        self.records
+            .lock()
+            .map(|mut r| std::mem::take(&mut *r))
+            .unwrap_or_default()
+    }
+}
+
+impl Sink for MemorySink {
+    fn write(&mut self, record: &Record) -> Result<()> {
+        self.records
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Memory sink lock poisoned"))?
+            .push(record.clone());
+        Ok(())
+    }
+}
+
+/// Sends every record to another thread
+pub struct ChannelSink {
+    sender: Sender<Record>,
+}
+
+impl ChannelSink {
+    pub fn new(sender: Sender<Record>) -> Self {
+        Self { sender }
+    }
+
+    /// Create a sink and the receiver its records arrive on
+    pub fn channel() -> (Self, Receiver<Record>) {
+        let (sender, receiver) = mpsc::channel();
+        (Self::new(sender), receiver)
+    }
+}
+
+impl Sink for ChannelSink {
+    fn write(&mut self, record: &Record) -> Result<()> {
+        self.sender
+            .send(record.clone())
+            .map_err(|_| anyhow::anyhow!("Record receiver disconnected"))
+    }
+}
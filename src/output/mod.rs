@@ -0,0 +1,13 @@
+mod console;
+mod csv;
+mod jsonl;
+mod memory;
+mod record;
+mod sink;
+
+pub use console::ConsoleSink;
+pub use csv::{CsvColumn, CsvSink, MissingValue};
+pub use jsonl::JsonlSink;
+pub use memory::{ChannelSink, MemorySink};
+pub use record::{Event, Record};
+pub use sink::Sink;
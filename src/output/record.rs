@@ -0,0 +1,102 @@
+use crate::data::hl7::Message;
+use crate::data::{BinaryEvent, DataType, VitalObservation};
+use crate::protocol::DriverEvent;
+use crate::reader::ConnectionState;
+
+/// Something that happened during a session, as passed to sinks
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// A received line (or raw chunk) with the data type detected so far
+    Data {
+        bytes: Vec<u8>,
+        data_type: DataType,
+    },
+    /// A framed binary packet or integrity error
+    Packet(BinaryEvent),
+    /// A complete HL7 message
+    Message(Message),
+    /// A measured value, from an OBX segment or a protocol driver
+    Observation(VitalObservation),
+    /// A device setting reported by a protocol driver
+    Setting(VitalObservation),
+    Waveform {
+        name: String,
+        unit: String,
+        sample_rate: u32,
+        samples: Vec<f64>,
+    },
+    /// The device identified itself
+    Device(String),
+    Alarm {
+        priority: u8,
+        code: String,
+        text: String,
+    },
+    Warning(String),
+    /// An HL7 acknowledgement was sent for a received message
+    AckSent {
+        code: String,
+        control_id: String,
+    },
+    /// A command typed by the user was sent to the device
+    CommandSent(String),
+    /// The connection health changed, with the reason
+    ConnectionState {
+        state: ConnectionState,
+        detail: String,
+    },
+    /// The port was lost, with the reason
+    Disconnected(String),
+    /// The port was opened again after a disconnect
+    Reconnected {
+        port: String,
+        attempts: u32,
+    },
+}
+
+impl From<DriverEvent> for Event {
+    fn from(event: DriverEvent) -> Self {
+        match event {
+            DriverEvent::Identified(device) => Self::Device(device),
+            DriverEvent::Observation(observation) => Self::Observation(observation),
+            DriverEvent::Setting(observation) => Self::Setting(observation),
+            DriverEvent::Alarm {
+                priority,
+                code,
+                text,
+            } => Self::Alarm {
+                priority,
+                code,
+                text,
+            },
+            DriverEvent::Waveform {
+                name,
+                unit,
+                sample_rate,
+                samples,
+            } => Self::Waveform {
+                name,
+                unit,
+                sample_rate,
+                samples,
+            },
+            DriverEvent::Warning(message) => Self::Warning(message),
+        }
+    }
+}
+
+/// A timestamped event
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub timestamp: String,
+    pub event: Event,
+}
+
+impl Record {
+    pub fn new(timestamp: &str, event: Event) -> Self {
+        Self {
+            timestamp: timestamp.to_string(),
+            event,
+        }
+    }
+}
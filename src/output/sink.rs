@@ -0,0 +1,13 @@
+use anyhow::Result;
+
+use super::Record;
+
+/// Destination for session records (console, memory, channel, files...)
+pub trait Sink: Send {
+    fn write(&mut self, record: &Record) -> Result<()>;
+
+    /// Called when the session ends
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
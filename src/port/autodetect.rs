@@ -0,0 +1,197 @@
+use anyhow::Result;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::PortConnection;
+use crate::config::SerialConfig;
+use crate::data::DataParser;
+use crate::protocol::medibus::MedibusCodec;
+use crate::transport::Transport;
+
+/// Baud rates tried by default, most common first
+const CANDIDATE_BAUDS: [u32; 5] = [9600, 19200, 38400, 57600, 115200];
+
+/// Framings tried at each baud rate: data bits, parity, stop bits
+const CANDIDATE_FRAMINGS: [(u8, &str, u8); 3] = [(8, "none", 1), (7, "even", 1), (8, "even", 1)];
+
+/// How much a recognised protocol marker adds to a score
+const MARKER_BONUS: f64 = 0.5;
+
+/// What was received at one candidate setting
+#[derive(Debug, Clone)]
+pub struct CandidateScore {
+    pub config: SerialConfig,
+    pub bytes: u64,
+    /// Share of printable ASCII bytes, as counted by `DataParser`
+    pub ascii_ratio: f64,
+    /// NUL bytes, which is how the driver reports a byte received with a
+    /// framing or parity error
+    pub framing_errors: u64,
+    /// Protocols recognised in the data (hl7, mllp, medibus)
+    pub markers: Vec<&'static str>,
+    pub score: f64,
+}
+
+impl CandidateScore {
+    /// Score `data` received with `config`
+    pub fn new(config: &SerialConfig, data: &[u8]) -> Self {
+        let mut parser = DataParser::new();
+        parser.process_data(data, "");
+        let stats = parser.stats();
+
+        let framing_errors = data.iter().filter(|&&byte| byte == 0).count() as u64;
+        let markers = Self::find_markers(data);
+        let (ascii_ratio, score) = if stats.total_bytes == 0 {
+            (0.0, 0.0)
+        } else {
+            let total = stats.total_bytes as f64;
+            let ascii_ratio = stats.ascii_bytes as f64 / total;
+            let error_ratio = framing_errors as f64 / total;
+            (
+                ascii_ratio,
+                ascii_ratio - 2.0 * error_ratio + MARKER_BONUS * markers.len() as f64,
+            )
+        };
+
+        Self {
+            config: config.clone(),
+            bytes: stats.total_bytes,
+            ascii_ratio,
+            framing_errors,
+            markers,
+            score,
+        }
+    }
+
+    fn find_markers(data: &[u8]) -> Vec<&'static str> {
+        let mut markers = Vec::new();
+        if contains(data, b"MSH|") {
+            markers.push("hl7");
+        }
+        if contains(data, b"\x0bMSH") {
+            markers.push("mllp");
+        }
+        // An ESC or SOH frame with a valid checksum
+        if MedibusCodec::new().decode(data).iter().any(Result::is_ok) {
+            markers.push("medibus");
+        }
+        markers
+    }
+}
+
+fn contains(data: &[u8], pattern: &[u8]) -> bool {
+    data.windows(pattern.len()).any(|window| window == pattern)
+}
+
+/// Finds the baud rate and framing of a device by listening at each
+/// candidate setting in turn and scoring what arrives
+pub struct Autodetect {
+    candidates: Vec<SerialConfig>,
+    window: Duration,
+    stop: Arc<AtomicBool>,
+}
+
+impl Autodetect {
+    pub fn new() -> Self {
+        let candidates = CANDIDATE_BAUDS
+            .iter()
+            .flat_map(|&baud| {
+                CANDIDATE_FRAMINGS
+                    .iter()
+                    .map(move |&(data_bits, parity, stop_bits)| {
+                        SerialConfig::new(baud, data_bits, parity, stop_bits)
+                            .expect("valid candidate framing")
+                    })
+            })
+            .collect();
+
+        Self {
+            candidates,
+            window: Duration::from_secs(2),
+            stop: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn with_candidates(mut self, candidates: Vec<SerialConfig>) -> Self {
+        self.candidates = candidates;
+        self
+    }
+
+    /// How long to listen at each candidate
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Stop listening once `stop` is set, returning the candidates tried so far
+    pub fn with_stop_flag(mut self, stop: Arc<AtomicBool>) -> Self {
+        self.stop = stop;
+        self
+    }
+
+    pub fn candidates(&self) -> &[SerialConfig] {
+        &self.candidates
+    }
+
+    /// Total listening time over all candidates
+    pub fn duration(&self) -> Duration {
+        self.window * self.candidates.len() as u32
+    }
+
+    /// Try every candidate on a serial port, best score first
+    pub fn run(&self, port_name: &str) -> Result<Vec<CandidateScore>> {
+        self.run_with(|config| {
+            Ok(Box::new(PortConnection::open(port_name, config, 100)?) as Box<dyn Transport>)
+        })
+    }
+
+    /// Try every candidate, `open` connecting with the given settings
+    pub fn run_with<F>(&self, mut open: F) -> Result<Vec<CandidateScore>>
+    where
+        F: FnMut(&SerialConfig) -> Result<Box<dyn Transport>>,
+    {
+        let mut results = Vec::new();
+        for config in &self.candidates {
+            if self.stop.load(Ordering::Relaxed) {
+                break;
+            }
+            let mut transport = open(config)?;
+            let data = self.listen(transport.as_mut())?;
+            results.push(CandidateScore::new(config, &data));
+        }
+
+        // Stable sort: equal scores keep the candidate order
+        results.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| b.bytes.cmp(&a.bytes))
+        });
+        Ok(results)
+    }
+
+    /// Best candidate that received anything
+    pub fn best(results: &[CandidateScore]) -> Option<&CandidateScore> {
+        results.first().filter(|result| result.bytes > 0)
+    }
+
+    fn listen(&self, transport: &mut dyn Transport) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        let mut buffer = [0u8; 1024];
+        let deadline = Instant::now() + self.window;
+        while Instant::now() < deadline
+            && transport.is_connected()
+            && !self.stop.load(Ordering::Relaxed)
+        {
+            let n = transport.read(&mut buffer)?;
+            data.extend_from_slice(&buffer[..n]);
+        }
+        Ok(data)
+    }
+}
+
+impl Default for Autodetect {
+    fn default() -> Self {
+        Self::new()
+    }
+}
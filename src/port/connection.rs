@@ -1,40 +1,111 @@
 use crate::config::SerialConfig;
 use anyhow::{Context, Result};
-use serialport::{FlowControl, SerialPort};
+use serialport::{SerialPort, SerialPortBuilder};
+use std::io::ErrorKind;
+use std::path::Path;
 use std::time::Duration;
 
+use crate::transport::ModemState;
+
 /// Manages serial port connection
 pub struct PortConnection {
     port: Box<dyn SerialPort>,
+    port_name: String,
+    failed: bool,
+    write_buffer: usize,
 }
 
 impl PortConnection {
     /// Open a serial port with the given configuration
     pub fn open(port_name: &str, config: &SerialConfig, timeout_ms: u64) -> Result<Self> {
-        let port = serialport::new(port_name, config.baud)
+        config.validate()?;
+        let mut builder = serialport::new(port_name, config.baud)
             .timeout(Duration::from_millis(timeout_ms))
             .data_bits(config.data_bits)
             .parity(config.parity)
             .stop_bits(config.stop_bits)
-            .flow_control(FlowControl::None)
-            .open()
+            .flow_control(config.flow_control);
+        if let Some(dtr) = config.dtr {
+            builder = builder.dtr_on_open(dtr);
+        }
+        let mut port = Self::open_builder(builder, config.stick_parity)
             .context(format!("Failed to open port {}", port_name))?;
+        if let Some(rts) = config.rts {
+            port.write_request_to_send(rts)
+                .context(format!("Failed to set RTS on {}", port_name))?;
+        }
 
-        Ok(Self { port })
+        Ok(Self {
+            port,
+            port_name: port_name.to_string(),
+            failed: false,
+            write_buffer: config.write_buffer,
+        })
+    }
+
+    /// Open the port, with mark or space parity if `stick_parity` is set
+    #[cfg(target_os = "linux")]
+    fn open_builder(builder: SerialPortBuilder, stick_parity: bool) -> Result<Box<dyn SerialPort>> {
+        use std::os::unix::io::AsRawFd;
+
+        let port = builder.open_native()?;
+        if stick_parity {
+            let fd = port.as_raw_fd();
+            // serialport has no mark/space parity, CMSPAR turns odd into mark
+            // and even into space
+            unsafe {
+                let mut termios = std::mem::zeroed::<libc::termios>();
+                if libc::tcgetattr(fd, &mut termios) != 0 {
+                    return Err(std::io::Error::last_os_error().into());
+                }
+                termios.c_cflag |= libc::PARENB | libc::CMSPAR;
+                if libc::tcsetattr(fd, libc::TCSANOW, &termios) != 0 {
+                    return Err(std::io::Error::last_os_error().into());
+                }
+            }
+        }
+        Ok(Box::new(port))
+    }
+
+    /// Open the port, with mark or space parity if `stick_parity` is set
+    #[cfg(not(target_os = "linux"))]
+    fn open_builder(builder: SerialPortBuilder, stick_parity: bool) -> Result<Box<dyn SerialPort>> {
+        if stick_parity {
+            return Err(anyhow::anyhow!(
+                "Mark and space parity are only supported on Linux"
+            ));
+        }
+        Ok(builder.open()?)
     }
 
     /// Read data from the port
     pub fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
         match self.port.read(buffer) {
             Ok(n) => Ok(n),
-            Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => Ok(0),
-            Err(e) => Err(anyhow::anyhow!("Read error: {}", e)),
+            // A signal (e.g. SIGTERM to stop a headless session) interrupts
+            // the wait for data like a timeout
+            Err(ref e) if matches!(e.kind(), ErrorKind::TimedOut | ErrorKind::Interrupted) => Ok(0),
+            Err(e) => {
+                self.failed = true;
+                Err(anyhow::anyhow!("Read error: {}", e))
+            }
         }
     }
 
-    /// Write data to the port
+    /// Write data to the port, at most the configured write buffer at once
     pub fn write(&mut self, data: &[u8]) -> Result<usize> {
-        self.port.write(data).context("Failed to write to port")
+        let mut written = 0;
+        for chunk in data.chunks(self.write_buffer) {
+            if written > 0 {
+                self.flush()?;
+            }
+            let n = self.port.write(chunk).context("Failed to write to port")?;
+            written += n;
+            if n < chunk.len() {
+                break;
+            }
+        }
+        Ok(written)
     }
 
     /// Flush the port
@@ -42,10 +113,20 @@ impl PortConnection {
         self.port.flush().context("Failed to flush port")
     }
 
-    /// Check if port is still valid
+    /// Check if port is still valid: no read has failed and the device
+    /// node still exists (an unplugged USB adapter removes it)
     pub fn is_connected(&self) -> bool {
-        // Simple check - in real impl, could ping the port
-        true
+        !self.failed && (!self.port_name.starts_with('/') || Path::new(&self.port_name).exists())
+    }
+
+    /// CTS, DSR, RI and CD as seen now, `None` if the driver cannot tell
+    pub fn modem_state(&mut self) -> Option<ModemState> {
+        Some(ModemState::from_lines(
+            self.port.read_clear_to_send().ok()?,
+            self.port.read_data_set_ready().ok()?,
+            self.port.read_ring_indicator().ok()?,
+            self.port.read_carrier_detect().ok()?,
+        ))
     }
 
     /// Get the port name
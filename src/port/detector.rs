@@ -1,5 +1,7 @@
 use serialport::SerialPortInfo;
 
+use super::PortMatcher;
+
 pub struct PortDetector;
 
 impl PortDetector {
@@ -77,6 +79,11 @@ impl PortDetector {
         ports.first().map(|p| p.port_name.clone())
     }
 
+    /// Current name of the port identified by `matcher`
+    pub fn find_port(matcher: &PortMatcher) -> Option<String> {
+        matcher.find(&Self::get_available_ports())
+    }
+
     pub fn test_port(port_name: &str, baud: u32) -> Result<String, String> {
         match serialport::new(port_name, baud)
             .timeout(std::time::Duration::from_millis(100))
@@ -0,0 +1,220 @@
+use anyhow::Result;
+use std::ffi::CStr;
+use std::fs::File;
+use std::io::{ErrorKind, Write};
+use std::os::fd::{AsRawFd, FromRawFd};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::data::MllpCodec;
+use crate::fake::{Hl7Generator, VitalSignsGenerator, WaveformGenerator};
+
+/// A pseudo-terminal pair standing in for a cable between a device and
+/// the reader: bytes written to the device side come out of `path()`
+pub struct VirtualPortPair {
+    master: File,
+    // Keeps the pair alive while no reader has the device open
+    _slave: File,
+    path: String,
+}
+
+impl VirtualPortPair {
+    pub fn open() -> Result<Self> {
+        let mut master = -1;
+        let mut slave = -1;
+        let mut name = [0 as libc::c_char; 64];
+
+        // SAFETY: the fds and the name buffer outlive the call, and 64 bytes
+        // is more than the longest /dev/pts path
+        let result = unsafe {
+            libc::openpty(
+                &mut master,
+                &mut slave,
+                name.as_mut_ptr(),
+                std::ptr::null(),
+                std::ptr::null(),
+            )
+        };
+        if result != 0 {
+            return Err(anyhow::anyhow!(
+                "Failed to open a pseudo-terminal pair: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+
+        // SAFETY: openpty returned two open fds that nothing else owns
+        let (master, slave) = unsafe { (File::from_raw_fd(master), File::from_raw_fd(slave)) };
+        // SAFETY: openpty wrote a NUL terminated path into `name`
+        let path = unsafe { CStr::from_ptr(name.as_ptr()) }
+            .to_string_lossy()
+            .into_owned();
+
+        // Raw mode, so CR/LF and control bytes pass through untouched
+        for fd in [master.as_raw_fd(), slave.as_raw_fd()] {
+            // SAFETY: `fd` is open and `termios` is fully written by tcgetattr
+            unsafe {
+                let mut termios = std::mem::zeroed::<libc::termios>();
+                if libc::tcgetattr(fd, &mut termios) == 0 {
+                    libc::cfmakeraw(&mut termios);
+                    libc::tcsetattr(fd, libc::TCSANOW, &termios);
+                }
+            }
+        }
+
+        // A full buffer means nobody is reading: drop data instead of blocking
+        // SAFETY: `master` is an open fd
+        unsafe {
+            let flags = libc::fcntl(master.as_raw_fd(), libc::F_GETFL);
+            libc::fcntl(master.as_raw_fd(), libc::F_SETFL, flags | libc::O_NONBLOCK);
+        }
+
+        Ok(Self {
+            master,
+            _slave: slave,
+            path,
+        })
+    }
+
+    /// Device path for the reader side, e.g. "/dev/pts/3"
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Handle for the device side
+    pub fn device(&self) -> Result<File> {
+        Ok(self.master.try_clone()?)
+    }
+}
+
+/// Simulated device traffic for a loopback session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoopbackPreset {
+    /// HL7 ORU^R01 message as CR terminated lines
+    Hl7,
+    /// The same message in an MLLP frame
+    Mllp,
+    /// `KEY=VALUE|...` vital signs lines
+    Vitals,
+    /// Binary ECG packets
+    Waveform,
+}
+
+impl LoopbackPreset {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "hl7" => Ok(Self::Hl7),
+            "mllp" => Ok(Self::Mllp),
+            "vitals" => Ok(Self::Vitals),
+            "waveform" => Ok(Self::Waveform),
+            _ => Err(anyhow::anyhow!(
+                "Invalid loopback preset: {} (expected hl7, mllp, vitals or waveform)",
+                name
+            )),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Hl7 => "hl7",
+            Self::Mllp => "mllp",
+            Self::Vitals => "vitals",
+            Self::Waveform => "waveform",
+        }
+    }
+
+    /// Protocol decoder reading this traffic, see `DecoderRegistry`
+    pub fn protocol(&self) -> &'static str {
+        match self {
+            Self::Hl7 | Self::Vitals => "hl7",
+            Self::Mllp => "mllp",
+            Self::Waveform => "binary",
+        }
+    }
+
+    /// Default time between two transmissions
+    pub fn period(&self) -> Duration {
+        match self {
+            Self::Hl7 | Self::Mllp | Self::Vitals => Duration::from_secs(1),
+            Self::Waveform => Duration::from_millis(50),
+        }
+    }
+
+    /// The bytes of transmission `i`
+    pub fn build(&self, i: usize) -> Vec<u8> {
+        let now = chrono::Local::now();
+        match self {
+            Self::Hl7 => Hl7Generator::build_message(&now.format("%Y%m%d%H%M%S").to_string())
+                .concat()
+                .into_bytes(),
+            Self::Mllp => MllpCodec::encode(
+                Hl7Generator::build_message(&now.format("%Y%m%d%H%M%S").to_string())
+                    .concat()
+                    .as_bytes(),
+            ),
+            Self::Vitals => {
+                VitalSignsGenerator::build_line(i + 1, &now.format("%H:%M:%S").to_string())
+                    .into_bytes()
+            }
+            Self::Waveform => WaveformGenerator::build_packet(i),
+        }
+    }
+}
+
+/// Background thread writing a preset to the device side of a pair
+pub struct LoopbackGenerator {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<Result<()>>>,
+}
+
+impl LoopbackGenerator {
+    pub fn spawn(mut device: File, preset: LoopbackPreset, period: Duration) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&stop);
+
+        let handle = std::thread::spawn(move || {
+            let mut i = 0;
+            while !flag.load(Ordering::Relaxed) {
+                match device.write_all(&preset.build(i)) {
+                    Ok(()) => {}
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                    Err(e) => return Err(anyhow::anyhow!("Loopback write error: {}", e)),
+                }
+                i += 1;
+
+                let next = Instant::now() + period;
+                while !flag.load(Ordering::Relaxed) && Instant::now() < next {
+                    std::thread::sleep(Duration::from_millis(10).min(period));
+                }
+            }
+            Ok(())
+        });
+
+        Self {
+            stop,
+            handle: Some(handle),
+        }
+    }
+
+    /// Flag stopping the generator when set, e.g. from another thread
+    pub fn stop_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.stop)
+    }
+
+    /// Stop the generator and wait for it, returning its write error if any
+    pub fn stop(mut self) -> Result<()> {
+        self.stop.store(true, Ordering::Relaxed);
+        match self.handle.take().map(|handle| handle.join()) {
+            Some(Ok(result)) => result,
+            Some(Err(_)) => Err(anyhow::anyhow!("Loopback generator panicked")),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for LoopbackGenerator {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
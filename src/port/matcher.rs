@@ -0,0 +1,101 @@
+use anyhow::Result;
+use serialport::{SerialPortInfo, SerialPortType};
+use std::fmt;
+
+use super::PortRule;
+
+/// Identifies a port across unplugs, when it may come back under a new name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PortMatcher {
+    Name(String),
+    /// USB serial number, e.g. of an FTDI adapter
+    SerialNumber(String),
+    /// USB vendor and product ID
+    UsbId {
+        vid: u16,
+        pid: u16,
+    },
+    /// A rule from a port rules file
+    Rule(PortRule),
+}
+
+impl PortMatcher {
+    /// Parse "serial=A12BC3", "usb=0403:6001" or a port name
+    pub fn from_name(spec: &str) -> Result<Self> {
+        if let Some(serial) = spec.strip_prefix("serial=") {
+            if serial.is_empty() {
+                return Err(anyhow::anyhow!("Missing serial number in {}", spec));
+            }
+            return Ok(Self::SerialNumber(serial.to_string()));
+        }
+
+        if let Some(id) = spec.strip_prefix("usb=") {
+            let parse = |hex: &str| u16::from_str_radix(hex, 16).ok();
+            return match id.split_once(':') {
+                Some((vid, pid)) => match (parse(vid), parse(pid)) {
+                    (Some(vid), Some(pid)) => Ok(Self::UsbId { vid, pid }),
+                    _ => Err(anyhow::anyhow!("Invalid USB ID: {} (expected VID:PID)", id)),
+                },
+                None => Err(anyhow::anyhow!("Invalid USB ID: {} (expected VID:PID)", id)),
+            };
+        }
+
+        if spec.is_empty() {
+            return Err(anyhow::anyhow!("Empty port match"));
+        }
+        Ok(Self::Name(spec.to_string()))
+    }
+
+    /// Most specific matcher for `port_name`: its USB serial number, else
+    /// its VID:PID, else the name itself
+    pub fn for_port(port_name: &str, ports: &[SerialPortInfo]) -> Self {
+        let usb = ports.iter().find_map(|port| match &port.port_type {
+            SerialPortType::UsbPort(info) if port.port_name == port_name => Some(info),
+            _ => None,
+        });
+
+        match usb {
+            Some(info) => match &info.serial_number {
+                Some(serial) if !serial.is_empty() => Self::SerialNumber(serial.clone()),
+                _ => Self::UsbId {
+                    vid: info.vid,
+                    pid: info.pid,
+                },
+            },
+            None => Self::Name(port_name.to_string()),
+        }
+    }
+
+    pub fn matches(&self, port: &SerialPortInfo) -> bool {
+        match (self, &port.port_type) {
+            (Self::Rule(rule), _) => rule.matches(port),
+            (Self::Name(name), _) => &port.port_name == name,
+            (Self::SerialNumber(serial), SerialPortType::UsbPort(info)) => {
+                info.serial_number.as_ref() == Some(serial)
+            }
+            (Self::UsbId { vid, pid }, SerialPortType::UsbPort(info)) => {
+                info.vid == *vid && info.pid == *pid
+            }
+            _ => false,
+        }
+    }
+
+    /// Name of the first matching port
+    pub fn find(&self, ports: &[SerialPortInfo]) -> Option<String> {
+        ports
+            .iter()
+            .find(|port| self.matches(port))
+            .map(|port| port.port_name.clone())
+    }
+}
+
+impl fmt::Display for PortMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Name(name) => write!(f, "{}", name),
+            Self::SerialNumber(serial) => write!(f, "serial={}", serial),
+            Self::UsbId { vid, pid } => write!(f, "usb={:04x}:{:04x}", vid, pid),
+            Self::Rule(rule) => write!(f, "rule {}", rule.name),
+        }
+    }
+}
@@ -1,7 +1,19 @@
+mod autodetect;
 mod connection;
 mod detector;
+#[cfg(target_os = "linux")]
+mod loopback;
+mod matcher;
+mod rules;
 mod selector;
+mod watcher;
 
+pub use autodetect::{Autodetect, CandidateScore};
 pub use connection::PortConnection;
 pub use detector::PortDetector;
+#[cfg(target_os = "linux")]
+pub use loopback::{LoopbackGenerator, LoopbackPreset, VirtualPortPair};
+pub use matcher::PortMatcher;
+pub use rules::{PortRule, PortRules};
 pub use selector::PortSelector;
+pub use watcher::{PortChange, PortWatcher};
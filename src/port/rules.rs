@@ -0,0 +1,200 @@
+use anyhow::{Context, Result};
+use serialport::{SerialPortInfo, SerialPortType};
+use toml::{Table, Value};
+
+/// Picks one device among several adapters by its USB descriptors
+///
+/// Every field that is set must match. Manufacturer and product match
+/// case-insensitively on a substring, as drivers report them differently.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PortRule {
+    pub name: String,
+    pub vid: Option<u16>,
+    pub pid: Option<u16>,
+    pub serial_number: Option<String>,
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+    /// Exact port name, for adapters without USB descriptors
+    pub port: Option<String>,
+}
+
+impl PortRule {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn matches(&self, port: &SerialPortInfo) -> bool {
+        if self
+            .port
+            .as_ref()
+            .is_some_and(|name| name != &port.port_name)
+        {
+            return false;
+        }
+
+        let usb = match &port.port_type {
+            SerialPortType::UsbPort(info) => Some(info),
+            _ => None,
+        };
+        let contains = |wanted: &Option<String>, actual: Option<&String>| match wanted {
+            Some(wanted) => {
+                actual.is_some_and(|actual| actual.to_lowercase().contains(&wanted.to_lowercase()))
+            }
+            None => true,
+        };
+
+        self.vid
+            .is_none_or(|vid| usb.is_some_and(|info| info.vid == vid))
+            && self
+                .pid
+                .is_none_or(|pid| usb.is_some_and(|info| info.pid == pid))
+            && self.serial_number.as_ref().is_none_or(|serial| {
+                usb.is_some_and(|info| info.serial_number.as_ref() == Some(serial))
+            })
+            && contains(
+                &self.manufacturer,
+                usb.and_then(|info| info.manufacturer.as_ref()),
+            )
+            && contains(&self.product, usb.and_then(|info| info.product.as_ref()))
+    }
+
+    pub(crate) fn from_table(table: &Table) -> Result<Self> {
+        let name = match table.get("name") {
+            Some(Value::String(name)) if !name.is_empty() => name.clone(),
+            _ => return Err(anyhow::anyhow!("Port rule without a name")),
+        };
+
+        let mut rule = Self::new(&name);
+        for (key, value) in table {
+            let text = || match value {
+                Value::String(text) => Ok(text.clone()),
+                _ => Err(anyhow::anyhow!("Rule {}: {} must be a string", name, key)),
+            };
+            match key.as_str() {
+                "name" => {}
+                "vid" => rule.vid = Some(Self::usb_id(&name, key, value)?),
+                "pid" => rule.pid = Some(Self::usb_id(&name, key, value)?),
+                "serial_number" => rule.serial_number = Some(text()?),
+                "manufacturer" => rule.manufacturer = Some(text()?),
+                "product" => rule.product = Some(text()?),
+                "port" => rule.port = Some(text()?),
+                _ => return Err(anyhow::anyhow!("Rule {}: unknown key {}", name, key)),
+            }
+        }
+
+        if rule == Self::new(&name) {
+            return Err(anyhow::anyhow!("Rule {} matches every port", name));
+        }
+        Ok(rule)
+    }
+
+    /// Table as written to a rules or profile file, VID and PID in hex
+    pub(crate) fn to_table(&self) -> Table {
+        let mut table = Table::new();
+        table.insert("name".to_string(), Value::String(self.name.clone()));
+        let ids = [("vid", self.vid), ("pid", self.pid)];
+        for (key, id) in ids {
+            if let Some(id) = id {
+                table.insert(key.to_string(), Value::String(format!("{:04x}", id)));
+            }
+        }
+        let texts = [
+            ("serial_number", &self.serial_number),
+            ("manufacturer", &self.manufacturer),
+            ("product", &self.product),
+            ("port", &self.port),
+        ];
+        for (key, text) in texts {
+            if let Some(text) = text {
+                table.insert(key.to_string(), Value::String(text.clone()));
+            }
+        }
+        table
+    }
+
+    /// A VID or PID, as a hex string ("0403") or an integer
+    fn usb_id(name: &str, key: &str, value: &Value) -> Result<u16> {
+        let id = match value {
+            Value::String(hex) => u16::from_str_radix(hex.trim_start_matches("0x"), 16).ok(),
+            Value::Integer(id) => u16::try_from(*id).ok(),
+            _ => None,
+        };
+        id.ok_or_else(|| anyhow::anyhow!("Rule {}: invalid {} {}", name, key, value))
+    }
+}
+
+/// Port rules from a TOML file, one `[[rule]]` table per device
+///
+/// ```toml
+/// [[rule]]
+/// name = "monitor"
+/// vid = "0403"
+/// pid = "6001"
+/// serial_number = "A12BC3"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PortRules {
+    rules: Vec<PortRule>,
+}
+
+impl PortRules {
+    pub fn load(path: &str) -> Result<Self> {
+        let text = std::fs::read_to_string(path).context(format!("Failed to read {}", path))?;
+        Self::parse(&text).context(format!("Invalid port rules in {}", path))
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let table: Table = text.parse()?;
+        let rules = match table.get("rule") {
+            Some(Value::Array(rules)) => rules
+                .iter()
+                .map(|rule| match rule {
+                    Value::Table(rule) => PortRule::from_table(rule),
+                    _ => Err(anyhow::anyhow!("Each rule must be a [[rule]] table")),
+                })
+                .collect::<Result<Vec<_>>>()?,
+            Some(_) => return Err(anyhow::anyhow!("Each rule must be a [[rule]] table")),
+            None => Vec::new(),
+        };
+
+        for (i, rule) in rules.iter().enumerate() {
+            if rules[..i].iter().any(|other| other.name == rule.name) {
+                return Err(anyhow::anyhow!("Duplicate rule name {}", rule.name));
+            }
+        }
+        Ok(Self { rules })
+    }
+
+    pub fn rules(&self) -> &[PortRule] {
+        &self.rules
+    }
+
+    pub fn get(&self, name: &str) -> Option<&PortRule> {
+        self.rules.iter().find(|rule| rule.name == name)
+    }
+
+    /// The rule named `name`, or the only rule when no name is given
+    pub fn select(&self, name: Option<&str>) -> Result<&PortRule> {
+        let names = || {
+            self.rules
+                .iter()
+                .map(|rule| rule.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match (name, self.rules.as_slice()) {
+            (Some(name), _) => self
+                .get(name)
+                .ok_or_else(|| anyhow::anyhow!("No rule named {} (have: {})", name, names())),
+            (None, [rule]) => Ok(rule),
+            (None, []) => Err(anyhow::anyhow!("No port rules defined")),
+            (None, _) => Err(anyhow::anyhow!(
+                "Several port rules defined, pick one with --rule ({})",
+                names()
+            )),
+        }
+    }
+}
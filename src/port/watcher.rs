@@ -0,0 +1,82 @@
+use serialport::SerialPortInfo;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::{PortDetector, PortMatcher};
+
+/// A port that appeared or disappeared between two polls
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PortChange {
+    Added(SerialPortInfo),
+    Removed(SerialPortInfo),
+}
+
+/// Notices devices being plugged in and out by polling the port list
+///
+/// The first poll reports every present port as added.
+pub struct PortWatcher {
+    known: Vec<SerialPortInfo>,
+    stop: Arc<AtomicBool>,
+}
+
+impl PortWatcher {
+    pub fn new() -> Self {
+        Self {
+            known: Vec::new(),
+            stop: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Give up waiting once `stop` is set
+    pub fn with_stop_flag(mut self, stop: Arc<AtomicBool>) -> Self {
+        self.stop = stop;
+        self
+    }
+
+    /// Compare with the previous list
+    pub fn update(&mut self, ports: Vec<SerialPortInfo>) -> Vec<PortChange> {
+        let mut changes: Vec<PortChange> = self
+            .known
+            .iter()
+            .filter(|port| !ports.contains(port))
+            .cloned()
+            .map(PortChange::Removed)
+            .collect();
+        changes.extend(
+            ports
+                .iter()
+                .filter(|port| !self.known.contains(port))
+                .cloned()
+                .map(PortChange::Added),
+        );
+        self.known = ports;
+        changes
+    }
+
+    pub fn poll(&mut self) -> Vec<PortChange> {
+        self.update(PortDetector::get_available_ports())
+    }
+
+    /// Block until a port matching `matcher` is present, returning its name,
+    /// or `None` once the stop flag is set
+    pub fn wait_for(&mut self, matcher: &PortMatcher, interval: Duration) -> Option<String> {
+        while !self.stop.load(Ordering::Relaxed) {
+            for change in self.poll() {
+                if let PortChange::Added(port) = change {
+                    if matcher.matches(&port) {
+                        return Some(port.port_name);
+                    }
+                }
+            }
+            std::thread::sleep(interval);
+        }
+        None
+    }
+}
+
+impl Default for PortWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
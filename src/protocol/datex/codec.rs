@@ -0,0 +1,129 @@
+use anyhow::Result;
+
+/// Starts and ends every frame
+pub const FRAME_FLAG: u8 = 0x7E;
+/// Escapes a flag or escape byte inside a frame
+pub const CONTROL_ESCAPE: u8 = 0x7D;
+/// XOR applied to an escaped byte
+const ESCAPE_XOR: u8 = 0x20;
+
+const MAX_FRAME_SIZE: usize = 64 * 1024;
+
+/// Datex-Ohmeda S/5 Computer Interface frame decoder and encoder
+///
+/// Frames are `<7E> stuffed(record checksum) <7E>`. Inside a frame, 0x7E and
+/// 0x7D are sent as 0x7D followed by the byte XOR 0x20. The checksum is the
+/// sum of the unstuffed record bytes modulo 256.
+pub struct DatexCodec {
+    buffer: Vec<u8>,
+    in_frame: bool,
+    escaped: bool,
+    discarded: u64,
+}
+
+impl DatexCodec {
+    pub fn new() -> Self {
+        Self {
+            buffer: Vec::new(),
+            in_frame: false,
+            escaped: false,
+            discarded: 0,
+        }
+    }
+
+    /// Wrap a record in a frame, adding checksum and byte stuffing
+    pub fn encode(record: &[u8]) -> Vec<u8> {
+        let mut frame = Vec::with_capacity(record.len() + 4);
+        frame.push(FRAME_FLAG);
+        for &byte in record
+            .iter()
+            .chain(std::iter::once(&Self::checksum(record)))
+        {
+            if byte == FRAME_FLAG || byte == CONTROL_ESCAPE {
+                frame.push(CONTROL_ESCAPE);
+                frame.push(byte ^ ESCAPE_XOR);
+            } else {
+                frame.push(byte);
+            }
+        }
+        frame.push(FRAME_FLAG);
+        frame
+    }
+
+    pub fn checksum(record: &[u8]) -> u8 {
+        record.iter().fold(0u8, |acc, &b| acc.wrapping_add(b))
+    }
+
+    /// Feed received bytes and return the records of completed frames
+    /// Frames with a bad checksum are returned as errors
+    pub fn decode(&mut self, data: &[u8]) -> Vec<Result<Vec<u8>>> {
+        let mut records = Vec::new();
+
+        for &byte in data {
+            if byte == FRAME_FLAG {
+                // An empty frame is the start flag following an end flag
+                if self.in_frame && !self.buffer.is_empty() {
+                    records.push(Self::verify(std::mem::take(&mut self.buffer)));
+                }
+                self.in_frame = true;
+                self.escaped = false;
+                continue;
+            }
+
+            if !self.in_frame {
+                self.discarded += 1;
+                continue;
+            }
+
+            if byte == CONTROL_ESCAPE {
+                self.escaped = true;
+                continue;
+            }
+
+            let byte = if self.escaped {
+                self.escaped = false;
+                byte ^ ESCAPE_XOR
+            } else {
+                byte
+            };
+            self.buffer.push(byte);
+
+            if self.buffer.len() > MAX_FRAME_SIZE {
+                self.discarded += self.buffer.len() as u64;
+                self.buffer.clear();
+                self.in_frame = false;
+            }
+        }
+
+        records
+    }
+
+    /// Whether a frame has been started but not completed
+    pub fn in_frame(&self) -> bool {
+        self.in_frame && !self.buffer.is_empty()
+    }
+
+    /// Number of bytes received outside of any frame
+    pub fn discarded_bytes(&self) -> u64 {
+        self.discarded
+    }
+
+    fn verify(mut frame: Vec<u8>) -> Result<Vec<u8>> {
+        let received = frame.pop().unwrap_or(0);
+        let expected = Self::checksum(&frame);
+        if received != expected {
+            return Err(anyhow::anyhow!(
+                "Datex checksum mismatch: expected 0x{:02X}, got 0x{:02X}",
+                expected,
+                received
+            ));
+        }
+        Ok(frame)
+    }
+}
+
+impl Default for DatexCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
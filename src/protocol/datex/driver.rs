@@ -0,0 +1,158 @@
+use std::time::{Duration, Instant};
+
+use super::codec::DatexCodec;
+use super::phdb::decode_basic;
+use super::record::{main_type, phdb_type, DatexRecord};
+use super::request::DatexRequest;
+use super::wave::{decode_samples, WaveformType};
+use crate::protocol::{DriverEvent, ProtocolDriver};
+
+const PRODUCER: &str = "DATEX";
+
+/// Subscriptions made by the Datex driver
+#[derive(Debug, Clone)]
+pub struct DatexConfig {
+    /// Transmission interval of displayed values
+    pub interval: Duration,
+    /// Waveform types to stream, see `WAVEFORM_TYPES`
+    pub waveforms: Vec<u8>,
+    /// Subscribe again when nothing was received for this long
+    pub resubscribe_after: Duration,
+}
+
+impl Default for DatexConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(10),
+            waveforms: Vec::new(),
+            resubscribe_after: Duration::from_secs(30),
+        }
+    }
+}
+
+/// GE/Datex-Ohmeda S/5 Computer Interface driver
+///
+/// Subscribes to displayed values (and optionally waveforms), then decodes
+/// the records the monitor pushes. Subscriptions are renewed when the
+/// monitor goes quiet, e.g. after it was restarted.
+pub struct DatexDriver {
+    config: DatexConfig,
+    codec: DatexCodec,
+    subscribed_at: Option<Instant>,
+    last_activity: Option<Instant>,
+    received: bool,
+    events: Vec<DriverEvent>,
+}
+
+impl DatexDriver {
+    pub fn new(config: DatexConfig) -> Self {
+        Self {
+            config,
+            codec: DatexCodec::new(),
+            subscribed_at: None,
+            last_activity: None,
+            received: false,
+            events: Vec::new(),
+        }
+    }
+
+    fn subscription(&mut self) -> Vec<u8> {
+        let interval = self.config.interval.as_secs().clamp(1, u16::MAX as u64) as u16;
+        let mut data = DatexRequest::displayed_values(interval);
+
+        if !self.config.waveforms.is_empty() {
+            match DatexRequest::waveforms(&self.config.waveforms) {
+                Ok(request) => data.extend(request),
+                Err(e) => self.events.push(DriverEvent::Warning(e.to_string())),
+            }
+        }
+        data
+    }
+
+    fn handle_record(&mut self, record: &DatexRecord) {
+        match record.main_type {
+            main_type::PHDB => {
+                for index in 0..record.subrecords.len() {
+                    if let Some((phdb_type::DISPLAYED, subrecord)) = record.subrecord(index) {
+                        self.events.extend(
+                            decode_basic(subrecord, PRODUCER)
+                                .into_iter()
+                                .map(DriverEvent::Observation),
+                        );
+                    }
+                }
+            }
+            main_type::WAVEFORM => {
+                for index in 0..record.subrecords.len() {
+                    let Some((kind, subrecord)) = record.subrecord(index) else {
+                        continue;
+                    };
+                    let Some(wave) = WaveformType::lookup(kind) else {
+                        continue;
+                    };
+                    self.events.push(DriverEvent::Waveform {
+                        name: wave.name.to_string(),
+                        unit: wave.unit.to_string(),
+                        sample_rate: wave.sample_rate,
+                        samples: decode_samples(subrecord, wave.divisor),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl ProtocolDriver for DatexDriver {
+    fn name(&self) -> &'static str {
+        "datex"
+    }
+
+    fn poll(&mut self, now: Instant) -> Vec<u8> {
+        if self.received {
+            self.received = false;
+            self.last_activity = Some(now);
+        }
+
+        let quiet_since = match (self.subscribed_at, self.last_activity) {
+            (None, _) => None,
+            (Some(subscribed), None) => Some(subscribed),
+            (Some(subscribed), Some(activity)) => Some(subscribed.max(activity)),
+        };
+
+        match quiet_since {
+            Some(since) if now.duration_since(since) < self.config.resubscribe_after => Vec::new(),
+            _ => {
+                if self.subscribed_at.is_some() {
+                    self.events.push(DriverEvent::Warning(
+                        "No data from monitor, renewing subscription".to_string(),
+                    ));
+                }
+                self.subscribed_at = Some(now);
+                self.subscription()
+            }
+        }
+    }
+
+    fn receive(&mut self, data: &[u8]) {
+        for frame in self.codec.decode(data) {
+            match frame.and_then(|bytes| DatexRecord::parse(&bytes)) {
+                Ok(record) => {
+                    self.received = true;
+                    self.handle_record(&record);
+                }
+                Err(e) => self.events.push(DriverEvent::Warning(e.to_string())),
+            }
+        }
+    }
+
+    fn take_events(&mut self) -> Vec<DriverEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+impl Default for DatexDriver {
+    fn default() -> Self {
+        Self::new(DatexConfig::default())
+    }
+}
@@ -0,0 +1,13 @@
+mod codec;
+mod driver;
+mod phdb;
+mod record;
+mod request;
+mod wave;
+
+pub use codec::DatexCodec;
+pub use driver::{DatexConfig, DatexDriver};
+pub use phdb::{decode_basic, PhdbField, BASIC_FIELDS};
+pub use record::{main_type, phdb_type, DatexRecord, DATA_INVALID_LIMIT, HEADER_SIZE};
+pub use request::{phdb_class, wave_request, DatexRequest};
+pub use wave::{decode_samples, WaveformType, WAVEFORM_TYPES};
@@ -0,0 +1,163 @@
+use chrono::{DateTime, Local};
+
+use super::record::DatexRecord;
+use crate::data::VitalObservation;
+
+/// Size of the time stamp preceding the class data of a subrecord
+const TIME_SIZE: usize = 4;
+
+/// Offset of the ambient pressure (0.1 mmHg) in the CO2 group
+const AMBIENT_PRESSURE_OFFSET: usize = 144;
+
+/// A value of the basic physiological data class
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PhdbField {
+    /// Offset of the value within the class data
+    pub offset: usize,
+    pub name: &'static str,
+    /// LOINC code, when there is a clear equivalent
+    pub loinc: Option<&'static str>,
+    /// UCUM unit
+    pub unit: &'static str,
+    /// Divisor turning the raw integer into `unit`
+    pub divisor: f64,
+    /// Sent as a percentage and reported as a partial pressure, using the
+    /// ambient pressure of the CO2 group
+    pub partial_pressure: bool,
+}
+
+const fn field(
+    offset: usize,
+    name: &'static str,
+    loinc: Option<&'static str>,
+    unit: &'static str,
+    divisor: f64,
+) -> PhdbField {
+    PhdbField {
+        offset,
+        name,
+        loinc,
+        unit,
+        divisor,
+        partial_pressure: false,
+    }
+}
+
+/// A gas fraction in 0.01 %, reported in mm[Hg]
+const fn partial_pressure(
+    offset: usize,
+    name: &'static str,
+    loinc: Option<&'static str>,
+) -> PhdbField {
+    PhdbField {
+        partial_pressure: true,
+        ..field(offset, name, loinc, "mm[Hg]", 100.0)
+    }
+}
+
+/// Basic class layout: each group starts with a 6 byte header
+/// (status, label) followed by 16 bit values
+pub const BASIC_FIELDS: &[PhdbField] = &[
+    // ECG group
+    field(6, "Heart Rate", Some("8867-4"), "/min", 1.0),
+    field(8, "ST Segment 1", None, "mm", 100.0),
+    field(10, "ST Segment 2", None, "mm", 100.0),
+    field(12, "ST Segment 3", None, "mm", 100.0),
+    field(
+        14,
+        "Impedance Respiratory Rate",
+        Some("9279-1"),
+        "/min",
+        1.0,
+    ),
+    // Invasive pressures P1-P4
+    field(22, "P1 Systolic", None, "mm[Hg]", 100.0),
+    field(24, "P1 Diastolic", None, "mm[Hg]", 100.0),
+    field(26, "P1 Mean", None, "mm[Hg]", 100.0),
+    field(36, "P2 Systolic", None, "mm[Hg]", 100.0),
+    field(38, "P2 Diastolic", None, "mm[Hg]", 100.0),
+    field(40, "P2 Mean", None, "mm[Hg]", 100.0),
+    field(50, "P3 Systolic", None, "mm[Hg]", 100.0),
+    field(52, "P3 Diastolic", None, "mm[Hg]", 100.0),
+    field(54, "P3 Mean", None, "mm[Hg]", 100.0),
+    field(64, "P4 Systolic", None, "mm[Hg]", 100.0),
+    field(66, "P4 Diastolic", None, "mm[Hg]", 100.0),
+    field(68, "P4 Mean", None, "mm[Hg]", 100.0),
+    // Non-invasive blood pressure
+    field(78, "Systolic BP", Some("8480-6"), "mm[Hg]", 100.0),
+    field(80, "Diastolic BP", Some("8462-4"), "mm[Hg]", 100.0),
+    field(82, "Mean BP", Some("8478-0"), "mm[Hg]", 100.0),
+    // Temperatures T1-T4
+    field(92, "Temperature 1", Some("8310-5"), "Cel", 100.0),
+    field(100, "Temperature 2", None, "Cel", 100.0),
+    field(108, "Temperature 3", None, "Cel", 100.0),
+    field(116, "Temperature 4", None, "Cel", 100.0),
+    // SpO2 and pulse
+    field(124, "Oxygen Saturation", Some("2708-6"), "%", 100.0),
+    field(126, "Pulse Rate", Some("8889-8"), "/min", 1.0),
+    // Gases: CO2 also carries respiratory rate and ambient pressure
+    partial_pressure(138, "End Tidal CO2", Some("19889-5")),
+    partial_pressure(140, "Inspired CO2", None),
+    field(152, "End Tidal O2", None, "%", 100.0),
+    field(154, "FiO2", Some("3150-0"), "%", 100.0),
+    field(162, "End Tidal N2O", None, "%", 100.0),
+    field(164, "Inspired N2O", None, "%", 100.0),
+    field(172, "End Tidal Agent", None, "%", 100.0),
+    field(174, "Inspired Agent", None, "%", 100.0),
+    field(176, "MAC Sum", None, "1", 100.0),
+    // Flow and volume
+    field(184, "Respiratory Rate", Some("76270-3"), "/min", 1.0),
+    field(186, "Peak Pressure", Some("76531-8"), "cm[H2O]", 100.0),
+    field(188, "PEEP", Some("76248-9"), "cm[H2O]", 100.0),
+    field(190, "Plateau Pressure", Some("76530-0"), "cm[H2O]", 100.0),
+    field(192, "Inspired Tidal Volume", None, "mL", 10.0),
+    field(194, "Expired Tidal Volume", Some("20112-9"), "mL", 10.0),
+    field(196, "Compliance", None, "mL/cm[H2O]", 100.0),
+    field(
+        198,
+        "Expired Minute Volume",
+        Some("20139-2"),
+        "L/min",
+        100.0,
+    ),
+];
+
+/// Decode a basic-class physiological database subrecord
+/// Values carrying an invalid-data sentinel are left out, CO2 is reported in
+/// % without a LOINC code when the ambient pressure is invalid
+pub fn decode_basic(subrecord: &[u8], producer: &str) -> Vec<VitalObservation> {
+    let time = subrecord
+        .get(..TIME_SIZE)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .and_then(|secs| DateTime::from_timestamp(secs as i64, 0))
+        .map(|t| t.with_timezone(&Local).naive_local());
+    let ambient = DatexRecord::scaled(subrecord, TIME_SIZE + AMBIENT_PRESSURE_OFFSET, 10.0);
+
+    BASIC_FIELDS
+        .iter()
+        .filter_map(|field| {
+            let mut value =
+                DatexRecord::scaled(subrecord, TIME_SIZE + field.offset, field.divisor)?;
+            let (mut loinc, mut unit) = (field.loinc, field.unit);
+            if field.partial_pressure {
+                match ambient {
+                    Some(ambient) => value = value * ambient / 100.0,
+                    None => (loinc, unit) = (None, "%"),
+                }
+            }
+            let mut observation = match loinc {
+                Some(loinc) => VitalObservation::numeric(loinc, field.name, "LN", value),
+                None => VitalObservation::numeric(
+                    &field.name.to_uppercase().replace(' ', "_"),
+                    field.name,
+                    "DATEX",
+                    value,
+                ),
+            };
+            observation.unit = Some(unit.to_string());
+            observation.observation_time = time;
+            observation.producer = Some(producer.to_string());
+            Some(observation)
+        })
+        .collect()
+}
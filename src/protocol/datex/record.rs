@@ -0,0 +1,161 @@
+use anyhow::Result;
+
+/// Size of the record header, including the subrecord descriptors
+pub const HEADER_SIZE: usize = 40;
+/// Maximum number of subrecords in a record
+pub const MAX_SUBRECORDS: usize = 8;
+/// Subrecord type terminating the descriptor list
+pub const END_OF_LIST: u8 = 0xFF;
+
+/// Record main types
+pub mod main_type {
+    /// Physiological database (displayed values and trends)
+    pub const PHDB: u16 = 0;
+    pub const WAVEFORM: u16 = 1;
+    pub const ALARM: u16 = 4;
+    pub const NETWORK: u16 = 5;
+}
+
+/// Physiological database subrecord types
+pub mod phdb_type {
+    pub const DISPLAYED: u8 = 0;
+    pub const TREND_10S: u8 = 1;
+    pub const TREND_60S: u8 = 2;
+    pub const AUX_INFO: u8 = 3;
+}
+
+/// Sentinels at or below this value mean invalid, not updated,
+/// discontinued, under/over range or not calibrated data
+pub const DATA_INVALID_LIMIT: i16 = -32001;
+
+/// A Datex record: 40 byte header followed by up to 8 subrecords
+#[derive(Debug, Clone, PartialEq)]
+pub struct DatexRecord {
+    pub number: u8,
+    pub dri_level: u8,
+    pub plug_id: u16,
+    /// Unix time the record was sent
+    pub time: u32,
+    pub main_type: u16,
+    /// (offset into `data`, type) of each subrecord
+    pub subrecords: Vec<(u16, u8)>,
+    pub data: Vec<u8>,
+}
+
+impl DatexRecord {
+    /// Build a record of the given main type; offsets are computed from the parts
+    pub fn new(main_type: u16, parts: &[(u8, &[u8])]) -> Self {
+        let mut subrecords = Vec::new();
+        let mut data = Vec::new();
+        for (kind, part) in parts.iter().take(MAX_SUBRECORDS) {
+            subrecords.push((data.len() as u16, *kind));
+            data.extend_from_slice(part);
+        }
+
+        Self {
+            number: 0,
+            dri_level: 0,
+            plug_id: 0,
+            time: 0,
+            main_type,
+            subrecords,
+            data,
+        }
+    }
+
+    /// Parse an unframed record (little endian, as sent by the monitor)
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < HEADER_SIZE {
+            return Err(anyhow::anyhow!(
+                "Datex record too short ({} bytes)",
+                bytes.len()
+            ));
+        }
+
+        let length = u16::from_le_bytes([bytes[0], bytes[1]]) as usize;
+        if length < HEADER_SIZE || length > bytes.len() {
+            return Err(anyhow::anyhow!(
+                "Datex record length {} does not match {} received bytes",
+                length,
+                bytes.len()
+            ));
+        }
+
+        let data = bytes[HEADER_SIZE..length].to_vec();
+        let mut subrecords = Vec::new();
+        for descriptor in bytes[16..HEADER_SIZE].chunks_exact(3) {
+            let offset = u16::from_le_bytes([descriptor[0], descriptor[1]]);
+            let kind = descriptor[2];
+            if kind == END_OF_LIST {
+                break;
+            }
+            if offset as usize > data.len() {
+                return Err(anyhow::anyhow!(
+                    "Datex subrecord offset {} beyond record data",
+                    offset
+                ));
+            }
+            subrecords.push((offset, kind));
+        }
+
+        Ok(Self {
+            number: bytes[2],
+            dri_level: bytes[3],
+            plug_id: u16::from_le_bytes([bytes[4], bytes[5]]),
+            time: u32::from_le_bytes([bytes[6], bytes[7], bytes[8], bytes[9]]),
+            main_type: u16::from_le_bytes([bytes[14], bytes[15]]),
+            subrecords,
+            data,
+        })
+    }
+
+    /// Serialize header and data, unframed
+    pub fn encode(&self) -> Vec<u8> {
+        let length = (HEADER_SIZE + self.data.len()) as u16;
+        let mut bytes = Vec::with_capacity(length as usize);
+        bytes.extend_from_slice(&length.to_le_bytes());
+        bytes.push(self.number);
+        bytes.push(self.dri_level);
+        bytes.extend_from_slice(&self.plug_id.to_le_bytes());
+        bytes.extend_from_slice(&self.time.to_le_bytes());
+        // n_subnet, reserved bytes
+        bytes.extend_from_slice(&[0, 0, 0, 0]);
+        bytes.extend_from_slice(&self.main_type.to_le_bytes());
+
+        for index in 0..MAX_SUBRECORDS {
+            match self.subrecords.get(index) {
+                Some((offset, kind)) => {
+                    bytes.extend_from_slice(&offset.to_le_bytes());
+                    bytes.push(*kind);
+                }
+                None => bytes.extend_from_slice(&[0, 0, END_OF_LIST]),
+            }
+        }
+
+        bytes.extend_from_slice(&self.data);
+        bytes
+    }
+
+    /// Type and bytes of the subrecord at `index`
+    pub fn subrecord(&self, index: usize) -> Option<(u8, &[u8])> {
+        let (offset, kind) = *self.subrecords.get(index)?;
+        let end = self
+            .subrecords
+            .iter()
+            .map(|(o, _)| *o as usize)
+            .filter(|&o| o > offset as usize)
+            .min()
+            .unwrap_or(self.data.len());
+        Some((kind, &self.data[offset as usize..end]))
+    }
+
+    /// Read a little endian i16 and scale it down, `None` for sentinel values
+    pub fn scaled(bytes: &[u8], offset: usize, divisor: f64) -> Option<f64> {
+        let raw = i16::from_le_bytes([*bytes.get(offset)?, *bytes.get(offset + 1)?]);
+        if raw <= DATA_INVALID_LIMIT {
+            None
+        } else {
+            Some(raw as f64 / divisor)
+        }
+    }
+}
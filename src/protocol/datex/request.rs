@@ -0,0 +1,71 @@
+use anyhow::Result;
+
+use super::codec::DatexCodec;
+use super::record::{main_type, phdb_type, DatexRecord, END_OF_LIST, MAX_SUBRECORDS};
+
+/// Waveform request commands
+pub mod wave_request {
+    pub const START: u16 = 0;
+    pub const STOP: u16 = 1;
+}
+
+/// Physiological data class bits for `phdb`; the basic class is always sent
+pub mod phdb_class {
+    pub const BASIC: u32 = 0x0000;
+    pub const EXT1: u32 = 0x0100;
+    pub const EXT2: u32 = 0x0200;
+    pub const EXT3: u32 = 0x0400;
+}
+
+/// Builds framed transmission requests for the monitor
+pub struct DatexRequest;
+
+impl DatexRequest {
+    /// Subscribe to physiological database records of `record_type`
+    /// sent every `interval_secs` seconds
+    pub fn phdb(record_type: u8, interval_secs: u16, class_mask: u32) -> Vec<u8> {
+        let mut body = Vec::with_capacity(9);
+        body.push(record_type);
+        body.extend_from_slice(&interval_secs.to_le_bytes());
+        body.extend_from_slice(&class_mask.to_le_bytes());
+        body.extend_from_slice(&[0, 0]);
+
+        let record = DatexRecord::new(main_type::PHDB, &[(record_type, &body)]);
+        DatexCodec::encode(&record.encode())
+    }
+
+    /// Subscribe to basic-class displayed values
+    pub fn displayed_values(interval_secs: u16) -> Vec<u8> {
+        Self::phdb(phdb_type::DISPLAYED, interval_secs, phdb_class::BASIC)
+    }
+
+    /// Start streaming up to 8 waveform types
+    pub fn waveforms(types: &[u8]) -> Result<Vec<u8>> {
+        if types.len() > MAX_SUBRECORDS {
+            return Err(anyhow::anyhow!(
+                "At most {} waveforms can be requested, got {}",
+                MAX_SUBRECORDS,
+                types.len()
+            ));
+        }
+        Ok(Self::waveform_request(wave_request::START, types))
+    }
+
+    /// Stop every waveform stream
+    pub fn stop_waveforms() -> Vec<u8> {
+        Self::waveform_request(wave_request::STOP, &[])
+    }
+
+    fn waveform_request(command: u16, types: &[u8]) -> Vec<u8> {
+        let mut body = Vec::with_capacity(32);
+        body.extend_from_slice(&command.to_le_bytes());
+        body.extend_from_slice(&[0, 0]);
+        for index in 0..MAX_SUBRECORDS {
+            body.push(types.get(index).copied().unwrap_or(END_OF_LIST));
+        }
+        body.extend_from_slice(&[0; 20]);
+
+        let record = DatexRecord::new(main_type::WAVEFORM, &[(0, &body)]);
+        DatexCodec::encode(&record.encode())
+    }
+}
@@ -0,0 +1,87 @@
+use super::record::DATA_INVALID_LIMIT;
+
+/// Waveform subrecord header: sample count, status, reserved
+const WAVE_HEADER_SIZE: usize = 6;
+
+/// A waveform channel the monitor can stream
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WaveformType {
+    /// Subrecord type used in requests and records
+    pub code: u8,
+    pub name: &'static str,
+    /// UCUM unit of the scaled samples
+    pub unit: &'static str,
+    pub divisor: f64,
+    pub sample_rate: u32,
+}
+
+const fn wave(
+    code: u8,
+    name: &'static str,
+    unit: &'static str,
+    divisor: f64,
+    sample_rate: u32,
+) -> WaveformType {
+    WaveformType {
+        code,
+        name,
+        unit,
+        divisor,
+        sample_rate,
+    }
+}
+
+pub const WAVEFORM_TYPES: &[WaveformType] = &[
+    wave(1, "ECG1", "mV", 100.0, 300),
+    wave(2, "ECG2", "mV", 100.0, 300),
+    wave(3, "ECG3", "mV", 100.0, 300),
+    wave(4, "INVP1", "mm[Hg]", 100.0, 100),
+    wave(5, "INVP2", "mm[Hg]", 100.0, 100),
+    wave(6, "INVP3", "mm[Hg]", 100.0, 100),
+    wave(7, "INVP4", "mm[Hg]", 100.0, 100),
+    wave(8, "PLETH", "%", 100.0, 100),
+    wave(9, "CO2", "%", 100.0, 25),
+    wave(10, "O2", "%", 100.0, 25),
+    wave(11, "N2O", "%", 100.0, 25),
+    wave(12, "AA", "%", 100.0, 25),
+    wave(13, "AWP", "cm[H2O]", 10.0, 25),
+    wave(14, "FLOW", "L/min", 10.0, 25),
+    wave(15, "RESP", "Ohm", 100.0, 25),
+    wave(16, "INVP5", "mm[Hg]", 100.0, 100),
+    wave(17, "INVP6", "mm[Hg]", 100.0, 100),
+];
+
+impl WaveformType {
+    pub fn lookup(code: u8) -> Option<&'static WaveformType> {
+        WAVEFORM_TYPES.iter().find(|w| w.code == code)
+    }
+
+    /// Look up a waveform by name, e.g. "ecg1" or "pleth"
+    pub fn from_name(name: &str) -> Option<&'static WaveformType> {
+        WAVEFORM_TYPES
+            .iter()
+            .find(|w| w.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Decode the samples of a waveform subrecord
+/// Invalid samples are returned as NaN so the time base is preserved
+pub fn decode_samples(subrecord: &[u8], divisor: f64) -> Vec<f64> {
+    let Some(header) = subrecord.get(..WAVE_HEADER_SIZE) else {
+        return Vec::new();
+    };
+    let count = u16::from_le_bytes([header[0], header[1]]) as usize;
+
+    subrecord[WAVE_HEADER_SIZE..]
+        .chunks_exact(2)
+        .take(count)
+        .map(|pair| {
+            let raw = i16::from_le_bytes([pair[0], pair[1]]);
+            if raw <= DATA_INVALID_LIMIT {
+                f64::NAN
+            } else {
+                raw as f64 / divisor
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,174 @@
+use anyhow::Result;
+
+/// Beginning of frame
+pub const BOF: u8 = 0xC0;
+/// End of frame
+pub const EOF: u8 = 0xC1;
+/// Escapes BOF, EOF and itself inside a frame
+pub const CONTROL_ESCAPE: u8 = 0x7D;
+const ESCAPE_XOR: u8 = 0x20;
+
+/// Frame header protocol identifier for the IntelliVue protocol
+pub const PROTOCOL_ID: u8 = 0x11;
+/// Frame header message type for Data Export messages
+pub const MESSAGE_TYPE: u8 = 0x01;
+const FRAME_HEADER_SIZE: usize = 4;
+
+const MAX_FRAME_SIZE: usize = 64 * 1024;
+
+/// IntelliVue MIB/RS232 frame decoder and encoder
+///
+/// Frames are `<BOF> stuffed(header user-data FCS) <EOF>`. The 4 byte header
+/// holds the protocol id, message type and big endian user data length; the
+/// FCS is the PPP FCS-16 of header and user data, sent low byte first.
+pub struct IntellivueCodec {
+    buffer: Vec<u8>,
+    in_frame: bool,
+    escaped: bool,
+    discarded: u64,
+}
+
+impl IntellivueCodec {
+    pub fn new() -> Self {
+        Self {
+            buffer: Vec::new(),
+            in_frame: false,
+            escaped: false,
+            discarded: 0,
+        }
+    }
+
+    /// Wrap user data in a frame
+    pub fn encode(user_data: &[u8]) -> Vec<u8> {
+        let length = user_data.len() as u16;
+        let mut content = vec![PROTOCOL_ID, MESSAGE_TYPE];
+        content.extend_from_slice(&length.to_be_bytes());
+        content.extend_from_slice(user_data);
+        content.extend_from_slice(&Self::fcs(&content).to_le_bytes());
+
+        let mut frame = Vec::with_capacity(content.len() + 4);
+        frame.push(BOF);
+        for byte in content {
+            if matches!(byte, BOF | EOF | CONTROL_ESCAPE) {
+                frame.push(CONTROL_ESCAPE);
+                frame.push(byte ^ ESCAPE_XOR);
+            } else {
+                frame.push(byte);
+            }
+        }
+        frame.push(EOF);
+        frame
+    }
+
+    /// PPP FCS-16 (RFC 1662), already complemented
+    pub fn fcs(data: &[u8]) -> u16 {
+        let mut fcs: u16 = 0xFFFF;
+        for &byte in data {
+            fcs ^= byte as u16;
+            for _ in 0..8 {
+                fcs = if fcs & 1 != 0 {
+                    (fcs >> 1) ^ 0x8408
+                } else {
+                    fcs >> 1
+                };
+            }
+        }
+        !fcs
+    }
+
+    /// Feed received bytes and return the user data of completed frames
+    pub fn decode(&mut self, data: &[u8]) -> Vec<Result<Vec<u8>>> {
+        let mut messages = Vec::new();
+
+        for &byte in data {
+            match byte {
+                BOF => {
+                    if self.in_frame {
+                        self.discarded += self.buffer.len() as u64;
+                    }
+                    self.buffer.clear();
+                    self.in_frame = true;
+                    self.escaped = false;
+                }
+                EOF if self.in_frame => {
+                    messages.push(Self::verify(&self.buffer));
+                    self.buffer.clear();
+                    self.in_frame = false;
+                }
+                CONTROL_ESCAPE if self.in_frame => self.escaped = true,
+                _ if self.in_frame => {
+                    let byte = if self.escaped {
+                        self.escaped = false;
+                        byte ^ ESCAPE_XOR
+                    } else {
+                        byte
+                    };
+                    self.buffer.push(byte);
+                    if self.buffer.len() > MAX_FRAME_SIZE {
+                        self.discarded += self.buffer.len() as u64;
+                        self.buffer.clear();
+                        self.in_frame = false;
+                    }
+                }
+                _ => self.discarded += 1,
+            }
+        }
+
+        messages
+    }
+
+    /// Whether a frame has been started but not completed
+    pub fn in_frame(&self) -> bool {
+        self.in_frame
+    }
+
+    /// Number of bytes received outside of any frame
+    pub fn discarded_bytes(&self) -> u64 {
+        self.discarded
+    }
+
+    fn verify(frame: &[u8]) -> Result<Vec<u8>> {
+        if frame.len() < FRAME_HEADER_SIZE + 2 {
+            return Err(anyhow::anyhow!(
+                "IntelliVue frame too short ({} bytes)",
+                frame.len()
+            ));
+        }
+
+        let (content, fcs) = frame.split_at(frame.len() - 2);
+        let received = u16::from_le_bytes([fcs[0], fcs[1]]);
+        let expected = Self::fcs(content);
+        if received != expected {
+            return Err(anyhow::anyhow!(
+                "IntelliVue FCS mismatch: expected 0x{:04X}, got 0x{:04X}",
+                expected,
+                received
+            ));
+        }
+
+        if content[0] != PROTOCOL_ID || content[1] != MESSAGE_TYPE {
+            return Err(anyhow::anyhow!(
+                "Unsupported IntelliVue frame header {:02X} {:02X}",
+                content[0],
+                content[1]
+            ));
+        }
+
+        let length = u16::from_be_bytes([content[2], content[3]]) as usize;
+        let user_data = &content[FRAME_HEADER_SIZE..];
+        if length != user_data.len() {
+            return Err(anyhow::anyhow!(
+                "IntelliVue frame length {} does not match {} received bytes",
+                length,
+                user_data.len()
+            ));
+        }
+        Ok(user_data.to_vec())
+    }
+}
+
+impl Default for IntellivueCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
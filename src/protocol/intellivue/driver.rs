@@ -0,0 +1,243 @@
+use chrono::{Local, NaiveDateTime};
+use std::time::{Duration, Instant};
+
+use super::codec::IntellivueCodec;
+use super::nomenclature::{oid, session, unit_name, VitalSign, STATE_INVALID, STATE_UNAVAILABLE};
+use super::pdu::{DataExportMessage, NumericValue, Pdu};
+use crate::data::VitalObservation;
+use crate::protocol::{DriverEvent, ProtocolDriver};
+
+const PRODUCER: &str = "PHILIPS^INTELLIVUE";
+
+/// Polling behaviour of the IntelliVue driver
+#[derive(Debug, Clone)]
+pub struct IntellivueConfig {
+    /// Time between two numerics poll requests
+    pub poll_interval: Duration,
+    /// Time between association attempts
+    pub association_retry: Duration,
+    /// Associate again when the monitor was silent for this long
+    pub silence_timeout: Duration,
+}
+
+impl Default for IntellivueConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(1),
+            association_retry: Duration::from_secs(5),
+            silence_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Where the driver is in the Data Export session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntellivueState {
+    /// Sending association requests
+    Associating,
+    /// Associated, waiting for the MDS create event report
+    WaitingForMds,
+    /// Polling numerics
+    Polling,
+}
+
+/// Philips IntelliVue MIB/RS232 Data Export driver
+///
+/// Sends an association request, confirms the MDS create event report,
+/// then polls numerics. A refused, released or aborted association, or a
+/// silent monitor, starts a new association.
+pub struct IntellivueDriver {
+    config: IntellivueConfig,
+    codec: IntellivueCodec,
+    state: IntellivueState,
+    outgoing: Vec<u8>,
+    events: Vec<DriverEvent>,
+    last_request: Option<Instant>,
+    last_activity: Option<Instant>,
+    received: bool,
+    invoke_id: u16,
+    poll_number: u16,
+}
+
+impl IntellivueDriver {
+    pub fn new(config: IntellivueConfig) -> Self {
+        Self {
+            config,
+            codec: IntellivueCodec::new(),
+            state: IntellivueState::Associating,
+            outgoing: Vec::new(),
+            events: Vec::new(),
+            last_request: None,
+            last_activity: None,
+            received: false,
+            invoke_id: 0,
+            poll_number: 0,
+        }
+    }
+
+    pub fn state(&self) -> IntellivueState {
+        self.state
+    }
+
+    fn disconnect(&mut self, reason: &str) {
+        self.events.push(DriverEvent::Warning(reason.to_string()));
+        self.state = IntellivueState::Associating;
+        self.last_request = None;
+    }
+
+    fn handle_message(&mut self, message: &[u8]) {
+        match message.first().copied() {
+            Some(session::ACCEPT) => {
+                if self.state == IntellivueState::Associating {
+                    self.state = IntellivueState::WaitingForMds;
+                }
+            }
+            Some(session::REFUSE) => self.disconnect("Association refused by monitor"),
+            Some(session::RELEASE_REQUEST) | Some(session::RELEASE_RESPONSE) => {
+                self.disconnect("Association released by monitor")
+            }
+            Some(session::ABORT) => self.disconnect("Association aborted by monitor"),
+            Some(session::DATA_EXPORT) => match Pdu::parse(message) {
+                Ok(message) => self.handle_data_export(message),
+                Err(e) => self.events.push(DriverEvent::Warning(e.to_string())),
+            },
+            _ => self.events.push(DriverEvent::Warning(format!(
+                "Unknown IntelliVue message type 0x{:02X}",
+                message.first().copied().unwrap_or(0)
+            ))),
+        }
+    }
+
+    fn handle_data_export(&mut self, message: DataExportMessage) {
+        match message {
+            DataExportMessage::MdsCreate {
+                invoke_id,
+                managed_object,
+                event_time,
+                model,
+            } => {
+                let result = Pdu::mds_create_result(invoke_id, &managed_object, event_time);
+                self.outgoing.extend(IntellivueCodec::encode(&result));
+                self.events.push(DriverEvent::Identified(
+                    model.unwrap_or_else(|| "IntelliVue".to_string()),
+                ));
+                self.state = IntellivueState::Polling;
+                self.last_request = None;
+            }
+            DataExportMessage::PollResult { time, numerics, .. } => {
+                let time = time.unwrap_or_else(|| Local::now().naive_local());
+                for numeric in numerics {
+                    if let Some(observation) = Self::observation(&numeric, time) {
+                        self.events.push(DriverEvent::Observation(observation));
+                    }
+                }
+            }
+            DataExportMessage::Error { invoke_id, error } => {
+                self.events.push(DriverEvent::Warning(format!(
+                    "Remote operation error {} for invoke id {}",
+                    error, invoke_id
+                )))
+            }
+            DataExportMessage::Other => {}
+        }
+    }
+
+    fn observation(numeric: &NumericValue, time: NaiveDateTime) -> Option<VitalObservation> {
+        if numeric.state & (STATE_INVALID | STATE_UNAVAILABLE) != 0 {
+            return None;
+        }
+        let value = numeric.value?;
+
+        let mut observation = match VitalSign::lookup(numeric.physio_id) {
+            Some(VitalSign {
+                name,
+                loinc: Some(loinc),
+                ..
+            }) => VitalObservation::numeric(loinc, name, "LN", value),
+            Some(VitalSign { name, .. }) => VitalObservation::numeric(
+                &format!("0x{:04X}", numeric.physio_id),
+                name,
+                "MDC",
+                value,
+            ),
+            None => VitalObservation::numeric(
+                &format!("0x{:04X}", numeric.physio_id),
+                &format!("Physio 0x{:04X}", numeric.physio_id),
+                "MDC",
+                value,
+            ),
+        };
+        observation.unit = unit_name(numeric.unit_code).map(str::to_string);
+        observation.observation_time = Some(time);
+        observation.producer = Some(PRODUCER.to_string());
+        Some(observation)
+    }
+
+    fn due(&self, now: Instant, interval: Duration) -> bool {
+        self.last_request
+            .is_none_or(|sent| now.duration_since(sent) >= interval)
+    }
+}
+
+impl ProtocolDriver for IntellivueDriver {
+    fn name(&self) -> &'static str {
+        "intellivue"
+    }
+
+    fn poll(&mut self, now: Instant) -> Vec<u8> {
+        if self.received {
+            self.received = false;
+            self.last_activity = Some(now);
+        }
+
+        if self.state != IntellivueState::Associating {
+            let silent = self
+                .last_activity
+                .is_none_or(|activity| now.duration_since(activity) >= self.config.silence_timeout);
+            if silent {
+                self.disconnect("Monitor silent, associating again");
+            }
+        }
+
+        let mut data = std::mem::take(&mut self.outgoing);
+        match self.state {
+            IntellivueState::Associating if self.due(now, self.config.association_retry) => {
+                data.extend(IntellivueCodec::encode(&Pdu::association_request()));
+                self.last_request = Some(now);
+                self.last_activity = Some(now);
+            }
+            IntellivueState::Polling if self.due(now, self.config.poll_interval) => {
+                self.invoke_id = self.invoke_id.wrapping_add(1);
+                self.poll_number = self.poll_number.wrapping_add(1);
+                let request =
+                    Pdu::poll_request(self.invoke_id, self.poll_number, oid::MOC_VMO_METRIC_NU);
+                data.extend(IntellivueCodec::encode(&request));
+                self.last_request = Some(now);
+            }
+            _ => {}
+        }
+        data
+    }
+
+    fn receive(&mut self, data: &[u8]) {
+        for frame in self.codec.decode(data) {
+            match frame {
+                Ok(message) => {
+                    self.received = true;
+                    self.handle_message(&message);
+                }
+                Err(e) => self.events.push(DriverEvent::Warning(e.to_string())),
+            }
+        }
+    }
+
+    fn take_events(&mut self) -> Vec<DriverEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+impl Default for IntellivueDriver {
+    fn default() -> Self {
+        Self::new(IntellivueConfig::default())
+    }
+}
@@ -0,0 +1,9 @@
+mod codec;
+mod driver;
+mod nomenclature;
+mod pdu;
+
+pub use codec::IntellivueCodec;
+pub use driver::{IntellivueConfig, IntellivueDriver, IntellivueState};
+pub use nomenclature::{command, oid, ro_type, session, unit_name, VitalSign, VITAL_SIGNS};
+pub use pdu::{DataExportMessage, ManagedObject, NumericValue, Pdu};
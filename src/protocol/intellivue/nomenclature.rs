@@ -0,0 +1,146 @@
+/// Session layer message types (first byte of non data-export messages)
+pub mod session {
+    pub const CONNECT: u8 = 0x0D;
+    pub const ACCEPT: u8 = 0x0E;
+    pub const REFUSE: u8 = 0x0C;
+    pub const RELEASE_REQUEST: u8 = 0x09;
+    pub const RELEASE_RESPONSE: u8 = 0x0A;
+    pub const ABORT: u8 = 0x19;
+    /// First byte of the session id of data export messages
+    pub const DATA_EXPORT: u8 = 0xE1;
+}
+
+/// Remote operation APDU types
+pub mod ro_type {
+    pub const INVOKE: u16 = 1;
+    pub const RESULT: u16 = 2;
+    pub const ERROR: u16 = 3;
+    pub const LINKED_RESULT: u16 = 5;
+}
+
+/// CMIP command types
+pub mod command {
+    pub const EVENT_REPORT: u16 = 0;
+    pub const CONFIRMED_EVENT_REPORT: u16 = 1;
+    pub const GET: u16 = 3;
+    pub const CONFIRMED_ACTION: u16 = 7;
+}
+
+/// Object classes, events, actions and attributes
+pub mod oid {
+    pub const PART_OBJ: u16 = 1;
+    pub const MOC_VMO_METRIC_NU: u16 = 6;
+    pub const MOC_VMO_METRIC_SA_RT: u16 = 9;
+    pub const MOC_VMS_MDS: u16 = 33;
+    pub const NOTI_MDS_CREAT: u16 = 0x0D06;
+    pub const ACT_POLL_MDIB_DATA: u16 = 0x0C16;
+    pub const ATTR_ID_MODEL: u16 = 0x0928;
+    pub const ATTR_NU_CMPD_VAL_OBS: u16 = 0x094B;
+    pub const ATTR_NU_VAL_OBS: u16 = 0x0950;
+}
+
+/// Measurement state bits that make a numeric unusable
+pub const STATE_INVALID: u16 = 0x8000;
+pub const STATE_UNAVAILABLE: u16 = 0x2000;
+
+/// A physiological identifier mapped to a named vital sign
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VitalSign {
+    pub physio_id: u16,
+    pub name: &'static str,
+    /// LOINC code, when there is a clear equivalent
+    pub loinc: Option<&'static str>,
+}
+
+pub const VITAL_SIGNS: &[VitalSign] = &[
+    VitalSign {
+        physio_id: 0x4182,
+        name: "Heart Rate",
+        loinc: Some("8867-4"),
+    },
+    VitalSign {
+        physio_id: 0x4261,
+        name: "PVC Count",
+        loinc: None,
+    },
+    VitalSign {
+        physio_id: 0x480A,
+        name: "Pulse Rate",
+        loinc: Some("8889-8"),
+    },
+    VitalSign {
+        physio_id: 0x4A05,
+        name: "Systolic BP",
+        loinc: Some("8480-6"),
+    },
+    VitalSign {
+        physio_id: 0x4A06,
+        name: "Diastolic BP",
+        loinc: Some("8462-4"),
+    },
+    VitalSign {
+        physio_id: 0x4A07,
+        name: "Mean BP",
+        loinc: Some("8478-0"),
+    },
+    VitalSign {
+        physio_id: 0x4A15,
+        name: "Arterial Systolic BP",
+        loinc: Some("76213-3"),
+    },
+    VitalSign {
+        physio_id: 0x4A16,
+        name: "Arterial Diastolic BP",
+        loinc: Some("76214-1"),
+    },
+    VitalSign {
+        physio_id: 0x4A17,
+        name: "Arterial Mean BP",
+        loinc: Some("76215-8"),
+    },
+    VitalSign {
+        physio_id: 0x4B48,
+        name: "Temperature",
+        loinc: Some("8310-5"),
+    },
+    VitalSign {
+        physio_id: 0x4BB8,
+        name: "Oxygen Saturation",
+        loinc: Some("2708-6"),
+    },
+    VitalSign {
+        physio_id: 0x500A,
+        name: "Respiratory Rate",
+        loinc: Some("9279-1"),
+    },
+    VitalSign {
+        physio_id: 0x5012,
+        name: "Airway Respiratory Rate",
+        loinc: Some("76270-3"),
+    },
+    VitalSign {
+        physio_id: 0x50B0,
+        name: "End Tidal CO2",
+        loinc: Some("19889-5"),
+    },
+];
+
+impl VitalSign {
+    pub fn lookup(physio_id: u16) -> Option<&'static VitalSign> {
+        VITAL_SIGNS.iter().find(|v| v.physio_id == physio_id)
+    }
+}
+
+/// UCUM unit of an IntelliVue unit code
+pub fn unit_name(unit_code: u16) -> Option<&'static str> {
+    match unit_code {
+        0x0200 => Some("1"),
+        0x0220 => Some("%"),
+        0x0AA0 => Some("/min"),
+        0x0AE0 => Some("/min"),
+        0x0F03 => Some("kPa"),
+        0x0F20 => Some("mm[Hg]"),
+        0x17A0 => Some("Cel"),
+        _ => None,
+    }
+}
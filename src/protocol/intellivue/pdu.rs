@@ -0,0 +1,411 @@
+use anyhow::Result;
+use chrono::{NaiveDate, NaiveDateTime};
+
+use super::nomenclature::{command, oid, ro_type, session};
+
+/// Session and presentation data of the association request
+const ASSOC_SESSION_DATA: &[u8] = &[
+    0x05, 0x08, 0x13, 0x01, 0x00, 0x16, 0x01, 0x02, 0x80, 0x00, 0x14, 0x02, 0x00, 0x02,
+];
+const ASSOC_PRESENTATION_HEADER: &[u8] = &[
+    0x31, 0x80, 0xA0, 0x80, 0x80, 0x01, 0x01, 0x00, 0x00, 0xA2, 0x80, 0xA0, 0x03, 0x00, 0x00, 0x01,
+    0xA4, 0x80, 0x30, 0x80, 0x02, 0x01, 0x01, 0x06, 0x04, 0x52, 0x01, 0x00, 0x01, 0x30, 0x80, 0x06,
+    0x02, 0x51, 0x01, 0x00, 0x00, 0x00, 0x00, 0x30, 0x80, 0x02, 0x01, 0x02, 0x06, 0x0C, 0x2A, 0x86,
+    0x48, 0xCE, 0x14, 0x02, 0x01, 0x00, 0x00, 0x00, 0x01, 0x01, 0x30, 0x80, 0x06, 0x0C, 0x2A, 0x86,
+    0x48, 0xCE, 0x14, 0x02, 0x01, 0x00, 0x00, 0x00, 0x02, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x61, 0x80, 0x30, 0x80, 0x02, 0x01, 0x01, 0xA0, 0x80, 0x60, 0x80, 0xA1, 0x80, 0x06, 0x0C, 0x2A,
+    0x86, 0x48, 0xCE, 0x14, 0x02, 0x01, 0x00, 0x00, 0x00, 0x03, 0x01, 0x00, 0x00, 0xBE, 0x80, 0x28,
+    0x80, 0x06, 0x0C, 0x2A, 0x86, 0x48, 0xCE, 0x14, 0x02, 0x01, 0x00, 0x00, 0x00, 0x01, 0x01, 0x02,
+    0x01, 0x02, 0x81,
+];
+const ASSOC_PRESENTATION_TRAILER: &[u8] = &[0; 16];
+
+/// Session id and presentation context of every data export message
+const DATA_EXPORT_HEADER: [u8; 4] = [session::DATA_EXPORT, 0x00, 0x00, 0x02];
+
+/// FLOAT-Type special values
+const FLOAT_NAN: u32 = 0x007F_FFFF;
+const FLOAT_NRES: u32 = 0x0080_0000;
+const FLOAT_POS_INFINITY: u32 = 0x007F_FFFE;
+const FLOAT_NEG_INFINITY: u32 = 0x0080_0002;
+const FLOAT_RESERVED: u32 = 0x0080_0001;
+
+/// Managed object id: object class, context id, handle
+pub type ManagedObject = [u8; 6];
+
+/// A numeric observation (NuObsValue)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NumericValue {
+    pub physio_id: u16,
+    pub state: u16,
+    pub unit_code: u16,
+    /// `None` for NaN, NRes and infinities
+    pub value: Option<f64>,
+}
+
+/// The data export messages the driver reacts to
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataExportMessage {
+    /// The monitor announces its MDS and expects a result
+    MdsCreate {
+        invoke_id: u16,
+        managed_object: ManagedObject,
+        event_time: u32,
+        model: Option<String>,
+    },
+    /// Numerics returned by a poll request
+    PollResult {
+        poll_number: u16,
+        time: Option<NaiveDateTime>,
+        numerics: Vec<NumericValue>,
+    },
+    /// A remote operation error
+    Error {
+        invoke_id: u16,
+        error: u16,
+    },
+    Other,
+}
+
+/// Builders and parsers for the IntelliVue Data Export PDUs
+/// All values are big endian
+pub struct Pdu;
+
+impl Pdu {
+    /// Association request announcing a polling client
+    pub fn association_request() -> Vec<u8> {
+        let user_info = Self::user_info();
+
+        let mut presentation = ASSOC_PRESENTATION_HEADER.to_vec();
+        presentation.extend(Self::length_indicator(user_info.len()));
+        presentation.extend_from_slice(&user_info);
+        presentation.extend_from_slice(ASSOC_PRESENTATION_TRAILER);
+
+        let mut body = ASSOC_SESSION_DATA.to_vec();
+        body.push(0xC1);
+        body.extend(Self::length_indicator(presentation.len()));
+        body.extend(presentation);
+
+        let mut message = vec![session::CONNECT];
+        message.extend(Self::length_indicator(body.len()));
+        message.extend(body);
+        message
+    }
+
+    /// Result confirming the MDS create event report
+    pub fn mds_create_result(
+        invoke_id: u16,
+        managed_object: &ManagedObject,
+        event_time: u32,
+    ) -> Vec<u8> {
+        let mut result = managed_object.to_vec();
+        result.extend_from_slice(&event_time.to_be_bytes());
+        result.extend_from_slice(&oid::NOTI_MDS_CREAT.to_be_bytes());
+        result.extend_from_slice(&0u16.to_be_bytes());
+
+        Self::remote_operation(
+            ro_type::RESULT,
+            invoke_id,
+            command::CONFIRMED_EVENT_REPORT,
+            &result,
+        )
+    }
+
+    /// Single poll request for every object of class `object_code`
+    /// (e.g. `MOC_VMO_METRIC_NU` for numerics)
+    pub fn poll_request(invoke_id: u16, poll_number: u16, object_code: u16) -> Vec<u8> {
+        let mut argument = Vec::with_capacity(22);
+        // MDS managed object, scope
+        argument.extend_from_slice(&oid::MOC_VMS_MDS.to_be_bytes());
+        argument.extend_from_slice(&[0, 0, 0, 0]);
+        argument.extend_from_slice(&[0, 0, 0, 0]);
+        argument.extend_from_slice(&oid::ACT_POLL_MDIB_DATA.to_be_bytes());
+        argument.extend_from_slice(&8u16.to_be_bytes());
+        // PollMdibDataReq: all attribute groups
+        argument.extend_from_slice(&poll_number.to_be_bytes());
+        argument.extend_from_slice(&oid::PART_OBJ.to_be_bytes());
+        argument.extend_from_slice(&object_code.to_be_bytes());
+        argument.extend_from_slice(&0u16.to_be_bytes());
+
+        Self::remote_operation(
+            ro_type::INVOKE,
+            invoke_id,
+            command::CONFIRMED_ACTION,
+            &argument,
+        )
+    }
+
+    /// Decode a 32 bit FLOAT-Type: 8 bit exponent, 24 bit mantissa
+    pub fn decode_float(raw: u32) -> Option<f64> {
+        if matches!(
+            raw,
+            FLOAT_NAN | FLOAT_NRES | FLOAT_POS_INFINITY | FLOAT_NEG_INFINITY | FLOAT_RESERVED
+        ) {
+            return None;
+        }
+
+        let exponent = (raw >> 24) as i8 as i32;
+        // Sign-extend the 24 bit mantissa
+        let mantissa = (((raw & 0x00FF_FFFF) << 8) as i32 >> 8) as f64;
+        if exponent < 0 {
+            Some(mantissa / 10f64.powi(-exponent))
+        } else {
+            Some(mantissa * 10f64.powi(exponent))
+        }
+    }
+
+    /// Parse a data export message (session id 0xE100)
+    pub fn parse(data: &[u8]) -> Result<DataExportMessage> {
+        let mut reader = Reader::new(data);
+        if reader.bytes(4)? != DATA_EXPORT_HEADER {
+            return Err(anyhow::anyhow!("Not an IntelliVue data export message"));
+        }
+
+        let operation = reader.u16()?;
+        reader.u16()?;
+        match operation {
+            ro_type::INVOKE => {
+                let invoke_id = reader.u16()?;
+                let command_type = reader.u16()?;
+                reader.u16()?;
+                if !matches!(
+                    command_type,
+                    command::EVENT_REPORT | command::CONFIRMED_EVENT_REPORT
+                ) {
+                    return Ok(DataExportMessage::Other);
+                }
+                Self::parse_event_report(&mut reader, invoke_id)
+            }
+            ro_type::RESULT | ro_type::LINKED_RESULT => {
+                if operation == ro_type::LINKED_RESULT {
+                    // linked id state and count
+                    reader.u16()?;
+                }
+                reader.u16()?;
+                let command_type = reader.u16()?;
+                reader.u16()?;
+                if command_type != command::CONFIRMED_ACTION {
+                    return Ok(DataExportMessage::Other);
+                }
+                Self::parse_action_result(&mut reader)
+            }
+            ro_type::ERROR => Ok(DataExportMessage::Error {
+                invoke_id: reader.u16()?,
+                error: reader.u16()?,
+            }),
+            _ => Ok(DataExportMessage::Other),
+        }
+    }
+
+    fn parse_event_report(reader: &mut Reader, invoke_id: u16) -> Result<DataExportMessage> {
+        let managed_object: ManagedObject = reader.bytes(6)?.try_into()?;
+        let event_time = reader.u32()?;
+        let event_type = reader.u16()?;
+        reader.u16()?;
+        if event_type != oid::NOTI_MDS_CREAT {
+            return Ok(DataExportMessage::Other);
+        }
+
+        // MdsCreateInfo: managed object followed by the MDS attributes
+        reader.bytes(6)?;
+        let mut model = None;
+        for (id, value) in Self::attributes(reader)? {
+            if id == oid::ATTR_ID_MODEL {
+                model = Self::parse_model(value);
+            }
+        }
+
+        Ok(DataExportMessage::MdsCreate {
+            invoke_id,
+            managed_object,
+            event_time,
+            model,
+        })
+    }
+
+    fn parse_action_result(reader: &mut Reader) -> Result<DataExportMessage> {
+        reader.bytes(6)?;
+        let action_type = reader.u16()?;
+        reader.u16()?;
+        if action_type != oid::ACT_POLL_MDIB_DATA {
+            return Ok(DataExportMessage::Other);
+        }
+
+        let poll_number = reader.u16()?;
+        // relative time stamp
+        reader.u32()?;
+        let time = Self::parse_absolute_time(reader.bytes(8)?);
+        // polled object type and attribute group
+        reader.bytes(6)?;
+
+        let mut numerics = Vec::new();
+        let contexts = reader.u16()?;
+        reader.u16()?;
+        for _ in 0..contexts {
+            // context id
+            reader.u16()?;
+            let observations = reader.u16()?;
+            reader.u16()?;
+            for _ in 0..observations {
+                // object handle
+                reader.u16()?;
+                for (id, value) in Self::attributes(reader)? {
+                    match id {
+                        oid::ATTR_NU_VAL_OBS => {
+                            numerics.push(Self::parse_numeric(&mut Reader::new(value))?)
+                        }
+                        oid::ATTR_NU_CMPD_VAL_OBS => {
+                            let mut compound = Reader::new(value);
+                            let count = compound.u16()?;
+                            compound.u16()?;
+                            for _ in 0..count {
+                                numerics.push(Self::parse_numeric(&mut compound)?);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        Ok(DataExportMessage::PollResult {
+            poll_number,
+            time,
+            numerics,
+        })
+    }
+
+    fn parse_numeric(reader: &mut Reader) -> Result<NumericValue> {
+        Ok(NumericValue {
+            physio_id: reader.u16()?,
+            state: reader.u16()?,
+            unit_code: reader.u16()?,
+            value: Self::decode_float(reader.u32()?),
+        })
+    }
+
+    /// AttributeList: count, length, then (id, length, value) entries
+    fn attributes<'a>(reader: &mut Reader<'a>) -> Result<Vec<(u16, &'a [u8])>> {
+        let count = reader.u16()?;
+        reader.u16()?;
+        let mut attributes = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let id = reader.u16()?;
+            let length = reader.u16()? as usize;
+            attributes.push((id, reader.bytes(length)?));
+        }
+        Ok(attributes)
+    }
+
+    /// SystemModel: manufacturer and model number as length-prefixed strings
+    fn parse_model(value: &[u8]) -> Option<String> {
+        let mut reader = Reader::new(value);
+        let mut label = || -> Option<String> {
+            let length = reader.u16().ok()? as usize;
+            let bytes = reader.bytes(length).ok()?;
+            Some(
+                String::from_utf8_lossy(bytes)
+                    .trim_end_matches('\0')
+                    .trim()
+                    .to_string(),
+            )
+        };
+        let manufacturer = label()?;
+        let model = label()?;
+        Some(format!("{} {}", manufacturer, model).trim().to_string())
+    }
+
+    /// AbsoluteTime: BCD century, year, month, day, hour, minute, second, fraction
+    fn parse_absolute_time(bcd: &[u8]) -> Option<NaiveDateTime> {
+        let digit = |b: u8| -> Option<u32> {
+            let (high, low) = ((b >> 4) as u32, (b & 0x0F) as u32);
+            (high < 10 && low < 10).then_some(high * 10 + low)
+        };
+        let year = digit(bcd[0])? * 100 + digit(bcd[1])?;
+        NaiveDate::from_ymd_opt(year as i32, digit(bcd[2])?, digit(bcd[3])?)?.and_hms_opt(
+            digit(bcd[4])?,
+            digit(bcd[5])?,
+            digit(bcd[6])?,
+        )
+    }
+
+    fn remote_operation(operation: u16, invoke_id: u16, command_type: u16, body: &[u8]) -> Vec<u8> {
+        let mut message = DATA_EXPORT_HEADER.to_vec();
+        message.extend_from_slice(&operation.to_be_bytes());
+        message.extend_from_slice(&(body.len() as u16 + 6).to_be_bytes());
+        message.extend_from_slice(&invoke_id.to_be_bytes());
+        message.extend_from_slice(&command_type.to_be_bytes());
+        message.extend_from_slice(&(body.len() as u16).to_be_bytes());
+        message.extend_from_slice(body);
+        message
+    }
+
+    /// MDSEUserInfoStd for a polling client
+    fn user_info() -> Vec<u8> {
+        let mut info = Vec::with_capacity(72);
+        // protocol version, nomenclature version, functional units,
+        // system type (client), startup mode (cold start)
+        for value in [0x8000_0000u32, 0x4000_0000, 0, 0x8000_0000, 0x2000_0000] {
+            info.extend_from_slice(&value.to_be_bytes());
+        }
+        // empty option list
+        info.extend_from_slice(&[0, 0, 0, 0]);
+        // one supported profile: poll profile support, 40 bytes
+        info.extend_from_slice(&[0x00, 0x01, 0x00, 0x2C, 0x00, 0x01, 0x00, 0x28]);
+        // revision, min poll period (1/8 ms), max rx/tx MTU, max tx bandwidth,
+        // options (dynamic create/delete objects)
+        for value in [0x8000_0000u32, 8000, 1000, 1000, 0xFFFF_FFFF, 0x6000_0000] {
+            info.extend_from_slice(&value.to_be_bytes());
+        }
+        // optional package: poll profile extension with 1 s numerics
+        info.extend_from_slice(&[0x00, 0x01, 0x00, 0x0C, 0xF0, 0x01, 0x00, 0x08]);
+        info.extend_from_slice(&[0x88, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+        info
+    }
+
+    /// Session/presentation length indicator: one byte, or 0xFF and two bytes
+    fn length_indicator(length: usize) -> Vec<u8> {
+        if length < 0xFF {
+            vec![length as u8]
+        } else {
+            let mut indicator = vec![0xFF];
+            indicator.extend_from_slice(&(length as u16).to_be_bytes());
+            indicator
+        }
+    }
+}
+
+/// Big endian cursor over a PDU
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8]> {
+        let end = self.position + count;
+        if end > self.data.len() {
+            return Err(anyhow::anyhow!(
+                "IntelliVue message truncated at byte {}",
+                self.position
+            ));
+        }
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        let bytes = self.bytes(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let bytes = self.bytes(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
@@ -0,0 +1,166 @@
+use anyhow::Result;
+
+/// Starts a command
+pub const ESC: u8 = 0x1B;
+/// Starts a response
+pub const SOH: u8 = 0x01;
+/// Ends every message
+pub const CR: u8 = 0x0D;
+/// Cancels the message in progress
+pub const CAN: u8 = 0x18;
+/// Flow-control characters that may be interleaved with message bytes
+const XON: u8 = 0x11;
+const XOFF: u8 = 0x13;
+
+const MAX_FRAME_SIZE: usize = 4096;
+
+/// Whether a frame is a request or the answer to one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameKind {
+    Command,
+    Response,
+}
+
+/// A checksum-verified MEDIBUS message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MedibusFrame {
+    pub kind: FrameKind,
+    pub command: u8,
+    pub data: Vec<u8>,
+}
+
+/// MEDIBUS message decoder and encoder
+///
+/// Messages are `<ESC|SOH> command data checksum <CR>`, where the checksum is
+/// the sum of all preceding bytes modulo 256 as two uppercase hex digits.
+/// Bytes outside a message are discarded and counted.
+pub struct MedibusCodec {
+    buffer: Vec<u8>,
+    in_frame: bool,
+    discarded: u64,
+}
+
+impl MedibusCodec {
+    pub fn new() -> Self {
+        Self {
+            buffer: Vec::new(),
+            in_frame: false,
+            discarded: 0,
+        }
+    }
+
+    /// Build a command sent to the other side
+    pub fn encode_command(command: u8, data: &[u8]) -> Vec<u8> {
+        Self::encode(ESC, command, data)
+    }
+
+    /// Build the response to a command received from the other side
+    pub fn encode_response(command: u8, data: &[u8]) -> Vec<u8> {
+        Self::encode(SOH, command, data)
+    }
+
+    /// Two ASCII hex digits of the byte sum modulo 256
+    pub fn checksum(bytes: &[u8]) -> [u8; 2] {
+        let sum = bytes.iter().fold(0u8, |acc, &b| acc.wrapping_add(b));
+        let hex = format!("{:02X}", sum);
+        [hex.as_bytes()[0], hex.as_bytes()[1]]
+    }
+
+    /// Feed received bytes and return completed frames
+    /// Frames with a bad checksum or no command byte are returned as errors
+    pub fn decode(&mut self, data: &[u8]) -> Vec<Result<MedibusFrame>> {
+        let mut frames = Vec::new();
+
+        for &byte in data {
+            match byte {
+                XON | XOFF => {}
+                ESC | SOH => {
+                    if self.in_frame {
+                        self.discarded += self.buffer.len() as u64;
+                    }
+                    self.buffer.clear();
+                    self.buffer.push(byte);
+                    self.in_frame = true;
+                }
+                CAN => {
+                    self.discarded += self.buffer.len() as u64 + 1;
+                    self.buffer.clear();
+                    self.in_frame = false;
+                }
+                CR if self.in_frame => {
+                    frames.push(Self::parse(&self.buffer));
+                    self.buffer.clear();
+                    self.in_frame = false;
+                }
+                _ if self.in_frame => {
+                    self.buffer.push(byte);
+                    if self.buffer.len() > MAX_FRAME_SIZE {
+                        self.discarded += self.buffer.len() as u64;
+                        self.buffer.clear();
+                        self.in_frame = false;
+                    }
+                }
+                _ => self.discarded += 1,
+            }
+        }
+
+        frames
+    }
+
+    /// Whether a message has been started but not completed
+    pub fn in_frame(&self) -> bool {
+        self.in_frame
+    }
+
+    /// Number of bytes received outside of any message
+    pub fn discarded_bytes(&self) -> u64 {
+        self.discarded
+    }
+
+    fn encode(start: u8, command: u8, data: &[u8]) -> Vec<u8> {
+        let mut frame = Vec::with_capacity(data.len() + 5);
+        frame.push(start);
+        frame.push(command);
+        frame.extend_from_slice(data);
+        frame.extend_from_slice(&Self::checksum(&frame));
+        frame.push(CR);
+        frame
+    }
+
+    fn parse(buffer: &[u8]) -> Result<MedibusFrame> {
+        // start byte, command byte and two checksum digits
+        if buffer.len() < 4 {
+            return Err(anyhow::anyhow!(
+                "MEDIBUS message too short ({} bytes)",
+                buffer.len()
+            ));
+        }
+
+        let (body, received) = buffer.split_at(buffer.len() - 2);
+        let expected = Self::checksum(body);
+        if !received.eq_ignore_ascii_case(&expected) {
+            return Err(anyhow::anyhow!(
+                "MEDIBUS checksum mismatch for command 0x{:02X}: expected {}, got {}",
+                body[1],
+                String::from_utf8_lossy(&expected),
+                String::from_utf8_lossy(received)
+            ));
+        }
+
+        Ok(MedibusFrame {
+            kind: if body[0] == ESC {
+                FrameKind::Command
+            } else {
+                FrameKind::Response
+            },
+            command: body[1],
+            data: body[2..].to_vec(),
+        })
+    }
+}
+
+impl Default for MedibusCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
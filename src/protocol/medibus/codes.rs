@@ -0,0 +1,107 @@
+/// MEDIBUS command bytes
+pub mod command {
+    /// No operation, used as keepalive
+    pub const NOP: u8 = 0x30;
+    /// Initialize communication
+    pub const ICC: u8 = 0x51;
+    /// Request device identification
+    pub const DEVICE_ID: u8 = 0x52;
+    /// Request current measured data (codepage 1)
+    pub const MEASURED_DATA: u8 = 0x24;
+    /// Request current low alarm limits (codepage 1)
+    pub const LOW_ALARM_LIMITS: u8 = 0x25;
+    /// Request current high alarm limits (codepage 1)
+    pub const HIGH_ALARM_LIMITS: u8 = 0x26;
+    /// Request current alarms (codepage 1)
+    pub const ALARMS: u8 = 0x27;
+    /// Request current date and time
+    pub const DATE_TIME: u8 = 0x28;
+    /// Request current device settings
+    pub const DEVICE_SETTINGS: u8 = 0x29;
+    /// Request text messages
+    pub const TEXT_MESSAGES: u8 = 0x2A;
+    /// Stop communication
+    pub const STOP: u8 = 0x55;
+}
+
+/// How a codepage 1 measured-data code maps to an observation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MeasurementCode {
+    pub code: u8,
+    /// LOINC code, when there is a clear equivalent
+    pub loinc: Option<&'static str>,
+    pub name: &'static str,
+    /// UCUM unit
+    pub unit: &'static str,
+}
+
+/// Common ventilator measurements; other codes are still reported
+/// with the MEDIBUS code as identifier
+pub const MEASUREMENT_CODES: &[MeasurementCode] = &[
+    MeasurementCode {
+        code: 0x06,
+        loinc: None,
+        name: "Dynamic Compliance",
+        unit: "mL/mbar",
+    },
+    MeasurementCode {
+        code: 0x0B,
+        loinc: None,
+        name: "Resistance",
+        unit: "mbar/L/s",
+    },
+    MeasurementCode {
+        code: 0x73,
+        loinc: None,
+        name: "Mean Pressure",
+        unit: "mbar",
+    },
+    MeasurementCode {
+        code: 0x74,
+        loinc: Some("76530-0"),
+        name: "Plateau Pressure",
+        unit: "mbar",
+    },
+    MeasurementCode {
+        code: 0x78,
+        loinc: Some("76248-9"),
+        name: "PEEP",
+        unit: "mbar",
+    },
+    MeasurementCode {
+        code: 0x7D,
+        loinc: Some("76531-8"),
+        name: "Peak Pressure",
+        unit: "mbar",
+    },
+    MeasurementCode {
+        code: 0x88,
+        loinc: Some("20112-9"),
+        name: "Tidal Volume",
+        unit: "mL",
+    },
+    MeasurementCode {
+        code: 0xB9,
+        loinc: Some("20139-2"),
+        name: "Minute Volume",
+        unit: "L/min",
+    },
+    MeasurementCode {
+        code: 0xD6,
+        loinc: Some("9279-1"),
+        name: "Respiratory Rate",
+        unit: "/min",
+    },
+    MeasurementCode {
+        code: 0xF0,
+        loinc: Some("3150-0"),
+        name: "FiO2",
+        unit: "%",
+    },
+];
+
+impl MeasurementCode {
+    pub fn lookup(code: u8) -> Option<&'static MeasurementCode> {
+        MEASUREMENT_CODES.iter().find(|m| m.code == code)
+    }
+}
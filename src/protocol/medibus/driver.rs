@@ -0,0 +1,349 @@
+use chrono::Local;
+use std::time::{Duration, Instant};
+
+use super::codec::{FrameKind, MedibusCodec, MedibusFrame};
+use super::codes::{command, MeasurementCode};
+use crate::data::VitalObservation;
+use crate::protocol::{DriverEvent, ProtocolDriver};
+
+/// Measured data entries: 2 hex code digits + 4 value characters
+const MEASUREMENT_ENTRY: usize = 6;
+/// Device settings entries: 2 hex code digits + 5 value characters
+const SETTING_ENTRY: usize = 7;
+/// Alarm entries: priority + 2 hex code digits + 12 character phrase
+const ALARM_ENTRY: usize = 15;
+
+/// Polling behaviour of the MEDIBUS driver
+#[derive(Debug, Clone)]
+pub struct MedibusConfig {
+    /// Time between the starts of two polling cycles
+    pub poll_interval: Duration,
+    /// How long to wait for a response before retrying
+    pub response_timeout: Duration,
+    /// Send a NOP when nothing was sent for this long
+    pub keepalive_interval: Duration,
+    /// Consecutive timeouts before communication is re-initialized
+    pub max_retries: u32,
+    /// Commands sent on every polling cycle
+    pub requests: Vec<u8>,
+    /// Our answer to the device identification request
+    pub identification: String,
+}
+
+impl Default for MedibusConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(1),
+            response_timeout: Duration::from_secs(3),
+            keepalive_interval: Duration::from_secs(5),
+            max_retries: 3,
+            requests: vec![
+                command::MEASURED_DATA,
+                command::ALARMS,
+                command::DEVICE_SETTINGS,
+            ],
+            identification: "0000'VITAL-READER'01.00".to_string(),
+        }
+    }
+}
+
+/// Where the driver is in the MEDIBUS session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MedibusState {
+    /// Sending ICC until the device answers
+    Initializing,
+    /// Waiting for the device identification
+    Identifying,
+    /// Cycling through the configured requests
+    Polling,
+}
+
+/// MEDIBUS polling state machine
+///
+/// Initializes communication with ICC, reads the device identification,
+/// then polls measured data, alarms and settings. Commands sent by the
+/// device (ICC, identification, NOP) are answered on the next `poll`.
+pub struct MedibusDriver {
+    config: MedibusConfig,
+    codec: MedibusCodec,
+    state: MedibusState,
+    outstanding: Option<(u8, Instant)>,
+    replies: Vec<u8>,
+    events: Vec<DriverEvent>,
+    next_request: usize,
+    cycle_started: Option<Instant>,
+    last_sent: Option<Instant>,
+    failures: u32,
+    device: Option<String>,
+}
+
+impl MedibusDriver {
+    pub fn new(config: MedibusConfig) -> Self {
+        Self {
+            config,
+            codec: MedibusCodec::new(),
+            state: MedibusState::Initializing,
+            outstanding: None,
+            replies: Vec::new(),
+            events: Vec::new(),
+            next_request: 0,
+            cycle_started: None,
+            last_sent: None,
+            failures: 0,
+            device: None,
+        }
+    }
+
+    pub fn state(&self) -> MedibusState {
+        self.state
+    }
+
+    /// Device name from the identification response
+    pub fn device(&self) -> Option<&str> {
+        self.device.as_deref()
+    }
+
+    fn next_command(&mut self, now: Instant) -> Option<u8> {
+        match self.state {
+            MedibusState::Initializing => Some(command::ICC),
+            MedibusState::Identifying => Some(command::DEVICE_ID),
+            MedibusState::Polling => {
+                if self.config.requests.is_empty() {
+                    return self.keepalive(now);
+                }
+
+                if self.next_request == 0 {
+                    if let Some(started) = self.cycle_started {
+                        if now.duration_since(started) < self.config.poll_interval {
+                            return self.keepalive(now);
+                        }
+                    }
+                    self.cycle_started = Some(now);
+                }
+
+                let request = self.config.requests[self.next_request];
+                self.next_request = (self.next_request + 1) % self.config.requests.len();
+                Some(request)
+            }
+        }
+    }
+
+    fn keepalive(&self, now: Instant) -> Option<u8> {
+        match self.last_sent {
+            Some(sent) if now.duration_since(sent) < self.config.keepalive_interval => None,
+            _ => Some(command::NOP),
+        }
+    }
+
+    fn check_timeout(&mut self, now: Instant) -> bool {
+        let Some((sent_command, sent_at)) = self.outstanding else {
+            return false;
+        };
+        if now.duration_since(sent_at) < self.config.response_timeout {
+            return true;
+        }
+
+        self.outstanding = None;
+        self.failures += 1;
+        self.events.push(DriverEvent::Warning(format!(
+            "No response to command 0x{:02X}",
+            sent_command
+        )));
+
+        if self.failures >= self.config.max_retries {
+            self.events.push(DriverEvent::Warning(
+                "Device not responding, re-initializing communication".to_string(),
+            ));
+            self.reset();
+        }
+        false
+    }
+
+    fn reset(&mut self) {
+        self.state = MedibusState::Initializing;
+        self.outstanding = None;
+        self.next_request = 0;
+        self.cycle_started = None;
+        self.failures = 0;
+    }
+
+    fn handle_command(&mut self, frame: &MedibusFrame) {
+        let data = match frame.command {
+            command::DEVICE_ID => self.config.identification.as_bytes().to_vec(),
+            _ => Vec::new(),
+        };
+        self.replies
+            .extend(MedibusCodec::encode_response(frame.command, &data));
+
+        if frame.command == command::STOP {
+            self.events.push(DriverEvent::Warning(
+                "Device stopped communication".to_string(),
+            ));
+            self.reset();
+        }
+    }
+
+    fn handle_response(&mut self, frame: &MedibusFrame) {
+        match self.outstanding {
+            Some((expected, _)) if expected == frame.command => {}
+            _ => {
+                self.events.push(DriverEvent::Warning(format!(
+                    "Unexpected response to command 0x{:02X}",
+                    frame.command
+                )));
+                return;
+            }
+        }
+        self.outstanding = None;
+        self.failures = 0;
+
+        match frame.command {
+            command::ICC => self.state = MedibusState::Identifying,
+            command::DEVICE_ID => {
+                let device = Self::parse_identification(&frame.data);
+                self.device = Some(device.clone());
+                self.events.push(DriverEvent::Identified(device));
+                self.state = MedibusState::Polling;
+                self.next_request = 0;
+                self.cycle_started = None;
+            }
+            command::MEASURED_DATA => {
+                for entry in frame.data.chunks_exact(MEASUREMENT_ENTRY) {
+                    if let Some(observation) = self.parse_measurement(entry) {
+                        self.events.push(DriverEvent::Observation(observation));
+                    }
+                }
+            }
+            command::DEVICE_SETTINGS => {
+                for entry in frame.data.chunks_exact(SETTING_ENTRY) {
+                    if let Some(setting) = self.parse_setting(entry) {
+                        self.events.push(DriverEvent::Setting(setting));
+                    }
+                }
+            }
+            command::ALARMS => {
+                for entry in frame.data.chunks_exact(ALARM_ENTRY) {
+                    self.events.push(DriverEvent::Alarm {
+                        priority: entry[0].wrapping_sub(b'0'),
+                        code: String::from_utf8_lossy(&entry[1..3]).to_string(),
+                        text: String::from_utf8_lossy(&entry[3..]).trim().to_string(),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Identification is `<4 digit id>'<device name>'<revision>`
+    fn parse_identification(data: &[u8]) -> String {
+        let text = String::from_utf8_lossy(data);
+        let mut parts = text.splitn(3, '\'');
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(id), Some(name), Some(revision)) => {
+                format!("{} ({}) {}", name.trim(), id.trim(), revision.trim())
+                    .trim_end()
+                    .to_string()
+            }
+            _ => text.trim().to_string(),
+        }
+    }
+
+    fn parse_measurement(&self, entry: &[u8]) -> Option<VitalObservation> {
+        let code = Self::parse_code(&entry[..2])?;
+        let value = Self::parse_value(&entry[2..])?;
+
+        let mut observation = match MeasurementCode::lookup(code) {
+            Some(known) => {
+                let mut observation = match known.loinc {
+                    Some(loinc) => VitalObservation::numeric(loinc, known.name, "LN", value),
+                    None => VitalObservation::numeric(
+                        &format!("{:02X}", code),
+                        known.name,
+                        "MEDIBUS",
+                        value,
+                    ),
+                };
+                observation.unit = Some(known.unit.to_string());
+                observation
+            }
+            None => VitalObservation::numeric(
+                &format!("{:02X}", code),
+                &format!("Measurement 0x{:02X}", code),
+                "MEDIBUS",
+                value,
+            ),
+        };
+        self.stamp(&mut observation);
+        Some(observation)
+    }
+
+    fn parse_setting(&self, entry: &[u8]) -> Option<VitalObservation> {
+        let code = Self::parse_code(&entry[..2])?;
+        let value = Self::parse_value(&entry[2..])?;
+
+        let mut setting = VitalObservation::numeric(
+            &format!("{:02X}", code),
+            &format!("Setting 0x{:02X}", code),
+            "MEDIBUS-SETTING",
+            value,
+        );
+        self.stamp(&mut setting);
+        Some(setting)
+    }
+
+    fn stamp(&self, observation: &mut VitalObservation) {
+        observation.observation_time = Some(Local::now().naive_local());
+        observation.producer = Some(self.device.clone().unwrap_or_else(|| "MEDIBUS".into()));
+    }
+
+    fn parse_code(digits: &[u8]) -> Option<u8> {
+        u8::from_str_radix(std::str::from_utf8(digits).ok()?, 16).ok()
+    }
+
+    /// Values are right-aligned ASCII; dashes or blanks mean "not available"
+    fn parse_value(raw: &[u8]) -> Option<f64> {
+        std::str::from_utf8(raw).ok()?.trim().parse().ok()
+    }
+}
+
+impl ProtocolDriver for MedibusDriver {
+    fn name(&self) -> &'static str {
+        "medibus"
+    }
+
+    fn poll(&mut self, now: Instant) -> Vec<u8> {
+        let mut data = std::mem::take(&mut self.replies);
+
+        if !self.check_timeout(now) {
+            if let Some(request) = self.next_command(now) {
+                data.extend(MedibusCodec::encode_command(request, &[]));
+                self.outstanding = Some((request, now));
+            }
+        }
+
+        if !data.is_empty() {
+            self.last_sent = Some(now);
+        }
+        data
+    }
+
+    fn receive(&mut self, data: &[u8]) {
+        for frame in self.codec.decode(data) {
+            match frame {
+                Ok(frame) if frame.kind == FrameKind::Command => self.handle_command(&frame),
+                Ok(frame) => self.handle_response(&frame),
+                Err(e) => self.events.push(DriverEvent::Warning(e.to_string())),
+            }
+        }
+    }
+
+    fn take_events(&mut self) -> Vec<DriverEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+impl Default for MedibusDriver {
+    fn default() -> Self {
+        Self::new(MedibusConfig::default())
+    }
+}
@@ -0,0 +1,7 @@
+mod codec;
+mod codes;
+mod driver;
+
+pub use codec::{FrameKind, MedibusCodec, MedibusFrame};
+pub use codes::{command, MeasurementCode, MEASUREMENT_CODES};
+pub use driver::{MedibusConfig, MedibusDriver, MedibusState};
@@ -0,0 +1,86 @@
+pub mod datex;
+pub mod intellivue;
+pub mod medibus;
+mod preset;
+
+use anyhow::Result;
+use std::time::{Duration, Instant};
+
+use crate::data::VitalObservation;
+
+pub use preset::{DevicePreset, DEVICE_PRESETS};
+
+/// Something a protocol driver decoded from the device
+#[derive(Debug, Clone, PartialEq)]
+pub enum DriverEvent {
+    /// The device answered the identification request
+    Identified(String),
+    /// A measured value
+    Observation(VitalObservation),
+    /// A device setting, reported with the same model as measured values
+    Setting(VitalObservation),
+    /// An active alarm
+    Alarm {
+        priority: u8,
+        code: String,
+        text: String,
+    },
+    /// A block of waveform samples; invalid samples are NaN
+    Waveform {
+        name: String,
+        unit: String,
+        sample_rate: u32,
+        samples: Vec<f64>,
+    },
+    /// A recoverable protocol problem (checksum error, timeout, ...)
+    Warning(String),
+}
+
+/// A request/response protocol that has to be driven from the host side
+///
+/// Drivers do no I/O: the session feeds them received bytes and writes
+/// whatever `poll` returns, which keeps them testable without a port.
+pub trait ProtocolDriver {
+    fn name(&self) -> &'static str;
+
+    /// Bytes to send now: replies, handshakes, poll requests or keepalives
+    fn poll(&mut self, now: Instant) -> Vec<u8>;
+
+    /// Feed bytes received from the device
+    fn receive(&mut self, data: &[u8]);
+
+    /// Take the events decoded or raised since the last call
+    fn take_events(&mut self) -> Vec<DriverEvent>;
+}
+
+/// Create a driver from its name: "medibus", "datex" or "intellivue"
+/// `interval` overrides the driver's default poll/transmission interval
+pub fn driver_from_name(name: &str, interval: Option<Duration>) -> Result<Box<dyn ProtocolDriver>> {
+    match name.to_lowercase().as_str() {
+        "medibus" => {
+            let mut config = medibus::MedibusConfig::default();
+            if let Some(interval) = interval {
+                config.poll_interval = interval;
+            }
+            Ok(Box::new(medibus::MedibusDriver::new(config)))
+        }
+        "datex" | "s5" => {
+            let mut config = datex::DatexConfig::default();
+            if let Some(interval) = interval {
+                config.interval = interval;
+            }
+            Ok(Box::new(datex::DatexDriver::new(config)))
+        }
+        "intellivue" | "philips" => {
+            let mut config = intellivue::IntellivueConfig::default();
+            if let Some(interval) = interval {
+                config.poll_interval = interval;
+            }
+            Ok(Box::new(intellivue::IntellivueDriver::new(config)))
+        }
+        _ => Err(anyhow::anyhow!(
+            "Invalid driver: {} (expected medibus, datex or intellivue)",
+            name
+        )),
+    }
+}
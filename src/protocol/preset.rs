@@ -0,0 +1,186 @@
+use anyhow::Result;
+use std::time::Duration;
+
+use super::medibus::{command, MedibusConfig, MedibusDriver};
+use super::{driver_from_name, ProtocolDriver};
+use crate::config::{Profile, SerialConfig};
+use crate::data::{Decoder, DecoderRegistry};
+use crate::output::CsvColumn;
+
+/// Factory settings of a supported device model
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DevicePreset {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Serial settings as shipped, as a config string
+    pub config: &'static str,
+    /// Decoder for what the device sends on its own
+    pub protocol: &'static str,
+    pub driver: Option<&'static str>,
+    /// MEDIBUS commands sent on every polling cycle
+    pub requests: &'static [u8],
+    /// Parameters the device reports, as CSV columns
+    pub parameters: &'static [&'static str],
+}
+
+const VENTILATOR_PARAMETERS: &[&str] = &[
+    "TV",
+    "MV=20139-2",
+    "RR",
+    "PEEP",
+    "PPEAK=76531-8",
+    "PPLAT=76530-0",
+    "FiO2",
+];
+
+/// Devices selectable with `--device`
+pub const DEVICE_PRESETS: &[DevicePreset] = &[
+    DevicePreset {
+        name: "ge-monitor",
+        description: "GE patient monitor, HL7 over RS232",
+        config: "115200,0,8,1",
+        protocol: "hl7",
+        driver: None,
+        requests: &[],
+        parameters: &[
+            "HR",
+            "SpO2",
+            "NIBP_SYS",
+            "NIBP_DIA",
+            "NIBP_MEAN",
+            "RR",
+            "TEMP",
+            "EtCO2",
+            "BIS",
+        ],
+    },
+    DevicePreset {
+        name: "ge-carescape",
+        description: "GE Carescape / Datex-Ohmeda S/5, Computer Interface",
+        config: "19200,0,8,1",
+        protocol: "raw",
+        driver: Some("datex"),
+        requests: &[],
+        parameters: &[
+            "HR",
+            "SpO2",
+            "PR",
+            "NIBP_SYS",
+            "NIBP_DIA",
+            "NIBP_MEAN",
+            "TEMP",
+            "EtCO2",
+        ],
+    },
+    DevicePreset {
+        name: "drager-evita",
+        description: "Dräger Evita ventilator, MEDIBUS",
+        config: "19200,2,8,1",
+        protocol: "raw",
+        driver: Some("medibus"),
+        requests: &[
+            command::MEASURED_DATA,
+            command::ALARMS,
+            command::DEVICE_SETTINGS,
+            command::TEXT_MESSAGES,
+        ],
+        parameters: VENTILATOR_PARAMETERS,
+    },
+    DevicePreset {
+        name: "drager-savina",
+        description: "Dräger Savina ventilator, MEDIBUS",
+        config: "9600,2,8,1",
+        protocol: "raw",
+        driver: Some("medibus"),
+        requests: &[
+            command::MEASURED_DATA,
+            command::ALARMS,
+            command::DEVICE_SETTINGS,
+        ],
+        parameters: VENTILATOR_PARAMETERS,
+    },
+    DevicePreset {
+        name: "drager-fabius",
+        description: "Dräger Fabius anesthesia workstation, MEDIBUS",
+        config: "9600,2,8,1",
+        protocol: "raw",
+        driver: Some("medibus"),
+        requests: &[command::MEASURED_DATA, command::ALARMS],
+        parameters: VENTILATOR_PARAMETERS,
+    },
+    DevicePreset {
+        name: "philips-intellivue",
+        description: "Philips IntelliVue MX/MP, MIB RS232 Data Export",
+        config: "115200,0,8,1",
+        protocol: "raw",
+        driver: Some("intellivue"),
+        requests: &[],
+        parameters: &[
+            "HR",
+            "PR",
+            "SpO2",
+            "NIBP_SYS",
+            "NIBP_DIA",
+            "NIBP_MEAN",
+            "RR",
+            "TEMP",
+            "EtCO2",
+        ],
+    },
+];
+
+impl DevicePreset {
+    pub fn from_name(name: &str) -> Result<&'static Self> {
+        DEVICE_PRESETS
+            .iter()
+            .find(|preset| preset.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                let names: Vec<&str> = DEVICE_PRESETS.iter().map(|preset| preset.name).collect();
+                anyhow::anyhow!("Invalid device: {} (expected {})", name, names.join(", "))
+            })
+    }
+
+    pub fn serial_config(&self) -> SerialConfig {
+        SerialConfig::from_string(self.config).expect("valid preset config")
+    }
+
+    pub fn decoder(&self) -> Result<Box<dyn Decoder>> {
+        DecoderRegistry::new().create(self.protocol)
+    }
+
+    /// The polling driver, with this model's requests for MEDIBUS devices
+    /// `interval` overrides the driver's default poll/transmission interval
+    pub fn create_driver(
+        &self,
+        interval: Option<Duration>,
+    ) -> Result<Option<Box<dyn ProtocolDriver>>> {
+        match self.driver {
+            Some("medibus") if !self.requests.is_empty() => {
+                let mut config = MedibusConfig {
+                    requests: self.requests.to_vec(),
+                    ..MedibusConfig::default()
+                };
+                if let Some(interval) = interval {
+                    config.poll_interval = interval;
+                }
+                Ok(Some(Box::new(MedibusDriver::new(config))))
+            }
+            Some(name) => driver_from_name(name, interval).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub fn csv_columns(&self) -> Vec<CsvColumn> {
+        CsvColumn::parse_list(&self.parameters.join(",")).expect("valid preset parameters")
+    }
+
+    /// The preset as a profile, for settings not given otherwise
+    pub fn profile(&self) -> Profile {
+        let mut profile = Profile::new(self.name);
+        profile.config = Some(self.config.to_string());
+        profile.protocol = Some(self.protocol.to_string());
+        profile.driver = self.driver.map(str::to_string);
+        profile.csv_columns = Some(self.parameters.join(","));
+        profile
+    }
+}
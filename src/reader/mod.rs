@@ -1,5 +1,15 @@
+mod output_mode;
+mod pipeline;
+mod reconnect;
+mod replay;
 mod session;
 mod stats;
+mod watchdog;
 
+pub use output_mode::OutputMode;
+pub use pipeline::{Pipeline, ACK_QUIET_PERIOD};
+pub use reconnect::{ReconnectPolicy, Reconnector};
+pub use replay::{ReplaySession, ReplaySpeed};
 pub use session::ReaderSession;
 pub use stats::SessionStats;
+pub use watchdog::{ConnectionState, Watchdog};
@@ -0,0 +1,36 @@
+use anyhow::Result;
+
+/// What the reader prints for received data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputMode {
+    /// Every received line, formatted as ASCII/BINARY/MIXED
+    #[default]
+    Raw,
+    /// Vital-sign observations decoded from HL7 OBX segments
+    Observations,
+    /// One JSON object per record on stdout, session messages on stderr
+    Jsonl,
+}
+
+impl OutputMode {
+    /// Parse an output mode name: "raw", "observations" or "jsonl"
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "raw" => Ok(Self::Raw),
+            "observations" | "obs" => Ok(Self::Observations),
+            "jsonl" | "json" => Ok(Self::Jsonl),
+            _ => Err(anyhow::anyhow!(
+                "Invalid output mode: {} (expected raw, observations or jsonl)",
+                name
+            )),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Raw => "raw",
+            Self::Observations => "observations",
+            Self::Jsonl => "jsonl",
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use anyhow::Result;
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::OutputMode;
+use crate::data::hl7::{AckConfig, AckGenerator};
+use crate::data::{DataParser, Decoder, Framing, VitalObservation};
+use crate::output::{ConsoleSink, Event, JsonlSink, Record, Sink};
+use crate::protocol::ProtocolDriver;
+
+/// Quiet time after which the message in progress is acknowledged, well
+/// above the pauses some senders leave between segments
+pub const ACK_QUIET_PERIOD: Duration = Duration::from_secs(1);
+
+/// Turns received bytes into records for the console and sinks
+///
+/// Shared by live and replayed sessions so both produce the same output.
+/// Methods taking received data return the replies (ACKs) to send back.
+pub struct Pipeline {
+    parser: DataParser,
+    acks: Option<AckGenerator>,
+    ack_quiet_period: Duration,
+    output_mode: OutputMode,
+    console: Box<dyn Sink>,
+    sinks: Vec<Box<dyn Sink>>,
+    status_out: Option<Mutex<Box<dyn Write + Send>>>,
+    driver: Option<Box<dyn ProtocolDriver>>,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Self {
+            parser: DataParser::new(),
+            acks: None,
+            ack_quiet_period: ACK_QUIET_PERIOD,
+            output_mode: OutputMode::Raw,
+            console: Box::new(ConsoleSink::new(OutputMode::Raw)),
+            sinks: Vec::new(),
+            status_out: None,
+            driver: None,
+        }
+    }
+
+    pub fn set_framing(&mut self, framing: Framing) {
+        self.parser = DataParser::with_framing(framing);
+    }
+
+    pub fn set_decoder(&mut self, decoder: Box<dyn Decoder>) {
+        self.parser = DataParser::with_decoder(decoder);
+    }
+
+    pub fn set_output_mode(&mut self, mode: OutputMode) {
+        self.output_mode = mode;
+        self.console = match mode {
+            OutputMode::Jsonl => Box::new(JsonlSink::new()),
+            _ => Box::new(ConsoleSink::new(mode)),
+        };
+    }
+
+    /// Replace the sink used for the output mode, e.g. to capture it
+    pub fn set_console(&mut self, console: Box<dyn Sink>) {
+        self.console = console;
+    }
+
+    pub fn add_sink(&mut self, sink: Box<dyn Sink>) {
+        self.sinks.push(sink);
+    }
+
+    /// Write status lines here instead of stdout (stderr in jsonl mode)
+    pub fn set_status_writer(&mut self, out: Box<dyn Write + Send>) {
+        self.status_out = Some(Mutex::new(out));
+    }
+
+    pub fn set_ack(&mut self, config: AckConfig) {
+        self.acks = Some(AckGenerator::new(config));
+    }
+
+    /// Quiet time before a line-framed message is completed and acknowledged
+    pub fn set_ack_quiet_period(&mut self, period: Duration) {
+        self.ack_quiet_period = period;
+    }
+
+    /// Quiet time that completes a message, `None` when ACKs are off
+    pub fn ack_quiet_period(&self) -> Option<Duration> {
+        self.acks.as_ref().map(|_| self.ack_quiet_period)
+    }
+
+    pub fn set_driver(&mut self, driver: Box<dyn ProtocolDriver>) {
+        self.driver = Some(driver);
+    }
+
+    pub fn parser(&self) -> &DataParser {
+        &self.parser
+    }
+
+    pub fn output_mode(&self) -> OutputMode {
+        self.output_mode
+    }
+
+    /// Request to send to a polled device, empty when there is none due
+    pub fn poll_driver(&mut self, now: Instant) -> Vec<u8> {
+        match self.driver.as_mut() {
+            Some(driver) => driver.poll(now),
+            None => Vec::new(),
+        }
+    }
+
+    /// Decode a received chunk
+    pub fn receive(&mut self, data: &[u8], timestamp: &str) -> Result<Vec<Vec<u8>>> {
+        self.parser.process_data(data, timestamp);
+        let replies = self.handle_messages(timestamp)?;
+        if let Some(driver) = self.driver.as_mut() {
+            driver.receive(data);
+        }
+        self.handle_driver_events(timestamp)?;
+        Ok(replies)
+    }
+
+    /// Nothing received for `quiet`
+    ///
+    /// With ACKs on, a quiet period completes the message in progress, the
+    /// sender may be waiting for its ACK. Otherwise messages only end at the
+    /// next MSH or the end of the session, so pauses between segments do
+    /// not cut them.
+    pub fn idle(&mut self, quiet: Duration, timestamp: &str) -> Result<Vec<Vec<u8>>> {
+        if self
+            .ack_quiet_period()
+            .is_some_and(|period| quiet >= period)
+        {
+            self.parser.complete_message();
+        }
+        let replies = self.handle_messages(timestamp)?;
+        self.handle_driver_events(timestamp)?;
+        Ok(replies)
+    }
+
+    /// End of the session: flush the partial line and the message in progress
+    pub fn finish(&mut self, timestamp: &str) -> Result<Vec<Vec<u8>>> {
+        self.parser.finish(timestamp);
+        self.handle_messages(timestamp)
+    }
+
+    pub fn emit(&mut self, record: Record) -> Result<()> {
+        self.console.write(&record)?;
+        for sink in &mut self.sinks {
+            sink.write(&record)?;
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        self.console.flush()?;
+        for sink in &mut self.sinks {
+            sink.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Print session messages, on stderr when stdout carries JSON Lines
+    pub fn status(&self, text: &str) {
+        if let Some(out) = &self.status_out {
+            if let Ok(mut out) = out.lock() {
+                let _ = writeln!(out, "{}", text);
+            }
+        } else if self.output_mode == OutputMode::Jsonl {
+            eprintln!("{}", text);
+        } else {
+            println!("{}", text);
+        }
+    }
+
+    fn handle_messages(&mut self, timestamp: &str) -> Result<Vec<Vec<u8>>> {
+        for record in self.parser.take_records() {
+            self.emit(record)?;
+        }
+
+        let mut replies = Vec::new();
+        for message in self.parser.take_messages() {
+            for observation in VitalObservation::from_message(&message) {
+                self.emit(Record::new(timestamp, Event::Observation(observation)))?;
+            }
+
+            let ack = self.acks.as_mut().map(|acks| acks.acknowledge(&message));
+            self.emit(Record::new(timestamp, Event::Message(message)))?;
+
+            let Some(ack) = ack else {
+                continue;
+            };
+
+            let encoded = ack.encode();
+            replies.push(self.parser.encode_reply(encoded.as_bytes()));
+
+            if let Some(msa) = ack.segment("MSA") {
+                let event = Event::AckSent {
+                    code: msa.value(1).to_string(),
+                    control_id: msa.value(2).to_string(),
+                };
+                self.emit(Record::new(timestamp, event))?;
+            }
+        }
+        Ok(replies)
+    }
+
+    fn handle_driver_events(&mut self, timestamp: &str) -> Result<()> {
+        let Some(driver) = self.driver.as_mut() else {
+            return Ok(());
+        };
+
+        for event in driver.take_events() {
+            self.emit(Record::new(timestamp, event.into()))?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
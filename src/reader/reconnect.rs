@@ -0,0 +1,121 @@
+use anyhow::Result;
+use std::time::Duration;
+
+use crate::port::{PortDetector, PortMatcher};
+use crate::transport::{Endpoint, Transport};
+
+/// When to retry after the port disappears
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconnectPolicy {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    /// Delay growth between attempts
+    pub multiplier: f64,
+    /// Give up after this many failed attempts, `None` retries forever
+    pub max_attempts: Option<u32>,
+}
+
+impl ReconnectPolicy {
+    pub fn new() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            multiplier: 2.0,
+            max_attempts: None,
+        }
+    }
+
+    pub fn with_max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = Some(attempts);
+        self
+    }
+
+    pub fn with_initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+        self
+    }
+
+    pub fn with_max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Wait before attempt `attempt`, counting from 1
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = self.multiplier.powi(attempt.saturating_sub(1) as i32);
+        self.initial_delay
+            .mul_f64(factor.min(u32::MAX as f64))
+            .min(self.max_delay)
+    }
+
+    /// Whether attempt `attempt`, counting from 1, may be made
+    pub fn allows(&self, attempt: u32) -> bool {
+        self.max_attempts.is_none_or(|max| attempt <= max)
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Opens the session transport again after a disconnect
+///
+/// A serial port is looked up by its matcher first, so a USB adapter that
+/// comes back under a different name is still found.
+pub struct Reconnector {
+    endpoint: Endpoint,
+    matcher: Option<PortMatcher>,
+    policy: ReconnectPolicy,
+    timeout_ms: u64,
+}
+
+impl Reconnector {
+    /// Fails for files, pipes and listeners, which have nothing to reopen
+    pub fn new(endpoint: Endpoint, timeout_ms: u64) -> Result<Self> {
+        match endpoint {
+            Endpoint::Serial { .. } | Endpoint::TcpClient(_) | Endpoint::Rfc2217 { .. } => {
+                Ok(Self {
+                    endpoint,
+                    matcher: None,
+                    policy: ReconnectPolicy::new(),
+                    timeout_ms,
+                })
+            }
+            _ => Err(anyhow::anyhow!(
+                "Reconnect needs a serial port, tcp:// or rfc2217:// input"
+            )),
+        }
+    }
+
+    pub fn with_policy(mut self, policy: ReconnectPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Find the serial port by serial number, USB ID or name
+    pub fn with_matcher(mut self, matcher: PortMatcher) -> Self {
+        self.matcher = Some(matcher);
+        self
+    }
+
+    pub fn policy(&self) -> &ReconnectPolicy {
+        &self.policy
+    }
+
+    pub fn connect(&self) -> Result<Box<dyn Transport>> {
+        match (&self.endpoint, &self.matcher) {
+            (Endpoint::Serial { config, .. }, Some(matcher)) => {
+                let port = PortDetector::find_port(matcher)
+                    .ok_or_else(|| anyhow::anyhow!("No port matches {}", matcher))?;
+                Endpoint::Serial {
+                    port,
+                    config: config.clone(),
+                }
+                .open(self.timeout_ms)
+            }
+            (endpoint, _) => endpoint.open(self.timeout_ms),
+        }
+    }
+}
@@ -0,0 +1,319 @@
+use anyhow::Result;
+use chrono::{Local, NaiveDateTime};
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::session::HEALTH_CHECK_INTERVAL;
+use super::{OutputMode, Pipeline, Watchdog};
+use crate::capture::{CaptureEntry, CaptureReader};
+use crate::data::hl7::AckConfig;
+use crate::data::{DataFormatter, Decoder, Framing};
+use crate::output::{Event, Record, Sink};
+use crate::protocol::ProtocolDriver;
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3f";
+
+/// Longest sleep between two checks of the stop flag
+const STOP_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How fast a capture is replayed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaySpeed {
+    /// Recorded timing divided by the factor, 1.0 is real time
+    Scaled(f64),
+    /// No waiting between chunks
+    Max,
+}
+
+impl ReplaySpeed {
+    /// Parse "max", "realtime" or a multiplier such as "2", "2x" or "0.5"
+    pub fn from_name(name: &str) -> Result<Self> {
+        let name = name.trim().to_lowercase();
+        match name.as_str() {
+            "max" => return Ok(Self::Max),
+            "realtime" => return Ok(Self::Scaled(1.0)),
+            _ => {}
+        }
+
+        match name.trim_end_matches('x').parse::<f64>() {
+            Ok(factor) if factor.is_finite() && factor > 0.0 => Ok(Self::Scaled(factor)),
+            _ => Err(anyhow::anyhow!(
+                "Invalid replay speed: {} (expected max, realtime or a multiplier such as 2x)",
+                name
+            )),
+        }
+    }
+}
+
+impl Default for ReplaySpeed {
+    fn default() -> Self {
+        Self::Scaled(1.0)
+    }
+}
+
+/// Feeds a recorded capture through the same pipeline as a live session
+///
+/// Record timestamps are rebuilt from the recording start time. With ACKs
+/// on, a gap between received chunks longer than the quiet period completes
+/// the HL7 message in progress, like on a live port. Recorded sent bytes
+/// are not replayed; ACKs are generated again when enabled. A watchdog runs
+/// on the recorded times, so idle, stale and connected records match the
+/// live session; lost connections are not recorded and not reported.
+pub struct ReplaySession {
+    reader: CaptureReader,
+    pipeline: Pipeline,
+    speed: ReplaySpeed,
+    idle_timeout: Duration,
+    show_stats: bool,
+    stop: Arc<AtomicBool>,
+    clock: Instant,
+    started: NaiveDateTime,
+    last_received: Option<Duration>,
+    watchdog: Watchdog,
+    last_health_check: Duration,
+    total_bytes: u64,
+    duration: Duration,
+}
+
+impl ReplaySession {
+    pub fn open(path: &str, show_stats: bool) -> Result<Self> {
+        Ok(Self::new(CaptureReader::open(path)?, show_stats))
+    }
+
+    pub fn new(reader: CaptureReader, show_stats: bool) -> Self {
+        Self {
+            reader,
+            pipeline: Pipeline::new(),
+            speed: ReplaySpeed::default(),
+            idle_timeout: Duration::from_millis(100),
+            show_stats,
+            stop: Arc::new(AtomicBool::new(false)),
+            clock: Instant::now(),
+            started: Local::now().naive_local(),
+            last_received: None,
+            watchdog: Watchdog::new(),
+            last_health_check: Duration::ZERO,
+            total_bytes: 0,
+            duration: Duration::ZERO,
+        }
+    }
+
+    pub fn with_speed(mut self, speed: ReplaySpeed) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Read timeout of the recorded session; with ACKs on, a gap longer
+    /// than it and the ACK quiet period completes the message in progress
+    pub fn with_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = timeout;
+        self
+    }
+
+    /// Report the connection as idle or stale using these thresholds, as
+    /// the live session did
+    pub fn with_watchdog(mut self, watchdog: Watchdog) -> Self {
+        self.watchdog = watchdog;
+        self
+    }
+
+    /// Select how incoming messages are framed (CR/LF lines or MLLP blocks)
+    pub fn with_framing(mut self, framing: Framing) -> Self {
+        self.pipeline.set_framing(framing);
+        self
+    }
+
+    /// Decode recorded data with the given protocol decoder
+    pub fn with_decoder(mut self, decoder: Box<dyn Decoder>) -> Self {
+        self.pipeline.set_decoder(decoder);
+        self
+    }
+
+    /// Select between raw lines, decoded observations and JSON Lines
+    pub fn with_output_mode(mut self, mode: OutputMode) -> Self {
+        self.pipeline.set_output_mode(mode);
+        self
+    }
+
+    /// Write status lines and statistics here instead of the terminal
+    pub fn with_status_writer(mut self, out: Box<dyn Write + Send>) -> Self {
+        self.pipeline.set_status_writer(out);
+        self
+    }
+
+    /// Replace the output mode sink, e.g. to capture the console output
+    pub fn with_console(mut self, console: Box<dyn Sink>) -> Self {
+        self.pipeline.set_console(console);
+        self
+    }
+
+    /// Also send every record to this sink, next to the console
+    pub fn with_sink(mut self, sink: Box<dyn Sink>) -> Self {
+        self.pipeline.add_sink(sink);
+        self
+    }
+
+    /// Report the ACKs the live session would have sent
+    pub fn with_ack(mut self, config: AckConfig) -> Self {
+        self.pipeline.set_ack(config);
+        self
+    }
+
+    /// Quiet time before a line-framed message is acknowledged
+    pub fn with_ack_quiet_period(mut self, period: Duration) -> Self {
+        self.pipeline.set_ack_quiet_period(period);
+        self
+    }
+
+    /// Decode the answers of a polled device with its protocol driver
+    pub fn with_driver(mut self, driver: Box<dyn ProtocolDriver>) -> Self {
+        self.pipeline.set_driver(driver);
+        self
+    }
+
+    /// End the replay early once `stop` is set, still flushing the sinks
+    pub fn with_stop_flag(mut self, stop: Arc<AtomicBool>) -> Self {
+        self.stop = stop;
+        self
+    }
+
+    pub fn run(&mut self) -> Result<()> {
+        self.clock = Instant::now();
+        self.watchdog.reset(self.clock);
+
+        let result = self.replay();
+        let timestamp = self.timestamp(self.duration);
+        let result = result
+            .and(self.pipeline.finish(&timestamp).map(|_| ()))
+            .and(self.pipeline.flush());
+
+        if self.show_stats {
+            self.print_stats();
+        }
+
+        result
+    }
+
+    fn replay(&mut self) -> Result<()> {
+        while let Some(record) = self.reader.next_record()? {
+            let elapsed = record.elapsed;
+
+            if let CaptureEntry::Received(_) | CaptureEntry::Stop = record.entry {
+                // The live session went quiet long enough to acknowledge
+                // the message before this entry
+                if let Some(last) = self.last_received {
+                    let quiet = self
+                        .pipeline
+                        .ack_quiet_period()
+                        .map_or(self.idle_timeout, |period| period.max(self.idle_timeout));
+                    let idle_at = last + quiet;
+                    if elapsed > idle_at {
+                        self.check_health(idle_at)?;
+                        self.wait(idle_at);
+                        self.pipeline.idle(quiet, &self.timestamp(idle_at))?;
+                    }
+                }
+            }
+
+            self.check_health(elapsed)?;
+            self.wait(elapsed);
+            if self.stop.load(Ordering::Relaxed) {
+                break;
+            }
+            self.duration = self.duration.max(elapsed);
+            let timestamp = self.timestamp(elapsed);
+
+            match record.entry {
+                CaptureEntry::Start(metadata) => {
+                    if let Ok(started) =
+                        NaiveDateTime::parse_from_str(&metadata.started, TIMESTAMP_FORMAT)
+                    {
+                        self.started = started - chrono::Duration::from_std(elapsed)?;
+                    }
+                    self.pipeline.status(&format!(
+                        "[{}] Replaying {} ({} baud, {} data bits, parity {}, {} stop bits)",
+                        self.timestamp(elapsed),
+                        metadata.port,
+                        metadata.baud,
+                        metadata.data_bits,
+                        metadata.parity,
+                        metadata.stop_bits
+                    ));
+                    self.pipeline
+                        .status("────────────────────────────────────────────────────────────────");
+                }
+                CaptureEntry::Received(data) => {
+                    self.last_received = Some(elapsed);
+                    self.watchdog.data(self.clock + elapsed);
+                    self.total_bytes += data.len() as u64;
+                    self.pipeline.receive(&data, &timestamp)?;
+                }
+                CaptureEntry::Sent(_) => {}
+                CaptureEntry::Stop => {
+                    self.pipeline.finish(&timestamp)?;
+                    self.last_received = None;
+                }
+            }
+        }
+        if self.reader.truncated() {
+            self.pipeline
+                .status("Warning: capture ends with a truncated entry, replayed up to it");
+        }
+        Ok(())
+    }
+
+    /// Run the health checks the live session made up to a recorded time,
+    /// reporting changes of the connection state
+    fn check_health(&mut self, until: Duration) -> Result<()> {
+        while self.last_health_check + HEALTH_CHECK_INTERVAL <= until {
+            self.last_health_check += HEALTH_CHECK_INTERVAL;
+            let at = self.last_health_check;
+            let Some((state, detail)) = self.watchdog.update(self.clock + at, true, None) else {
+                continue;
+            };
+            self.wait(at);
+            if self.stop.load(Ordering::Relaxed) {
+                break;
+            }
+            self.pipeline.emit(Record::new(
+                &self.timestamp(at),
+                Event::ConnectionState { state, detail },
+            ))?;
+        }
+        Ok(())
+    }
+
+    /// Sleep until the replay clock reaches a recorded time
+    fn wait(&self, elapsed: Duration) {
+        let ReplaySpeed::Scaled(factor) = self.speed else {
+            return;
+        };
+        let target = elapsed.div_f64(factor);
+        while !self.stop.load(Ordering::Relaxed) {
+            let now = self.clock.elapsed();
+            if target <= now {
+                break;
+            }
+            std::thread::sleep((target - now).min(STOP_CHECK_INTERVAL));
+        }
+    }
+
+    /// Wall clock time of the live session at a recorded time
+    fn timestamp(&self, elapsed: Duration) -> String {
+        let offset = chrono::Duration::from_std(elapsed).unwrap_or_default();
+        (self.started + offset).format(TIMESTAMP_FORMAT).to_string()
+    }
+
+    fn print_stats(&self) {
+        let status = |text: &str| self.pipeline.status(text);
+        status("\n────────────────────────────────────────────────────────────────");
+        status("Statistics:");
+        status(&format!("  Total bytes replayed: {}", self.total_bytes));
+        status(&format!("  Capture duration:     {:?}", self.duration));
+        status(&DataFormatter::format_stats(
+            &self.pipeline.parser().stats(),
+        ));
+    }
+}
@@ -1,58 +1,195 @@
 use anyhow::Result;
 use chrono::Local;
 use crossterm::{
-    event::{self, Event, KeyCode, KeyEvent},
+    event::{self, KeyCode, KeyEvent},
     terminal::{disable_raw_mode, enable_raw_mode},
 };
 use std::io::{self, Write};
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use super::SessionStats;
+use super::{OutputMode, Pipeline, Reconnector, SessionStats, Watchdog};
+use crate::capture::{CaptureEntry, CaptureMetadata, CaptureWriter};
 use crate::config::SerialConfig;
-use crate::data::DataParser;
-use crate::port::PortConnection;
+use crate::data::hl7::AckConfig;
+use crate::data::{DataFormatter, Decoder, Framing};
+use crate::output::{Event, Record, Sink};
+use crate::protocol::ProtocolDriver;
+use crate::transport::{Endpoint, Transport};
+
+/// How often the connection health is checked
+pub(super) const HEALTH_CHECK_INTERVAL: Duration = Duration::from_millis(250);
 
 pub struct ReaderSession {
-    port: PortConnection,
-    parser: DataParser,
+    port: Box<dyn Transport>,
+    config: SerialConfig,
+    pipeline: Pipeline,
     stats: SessionStats,
     show_stats: bool,
+    recorder: Option<CaptureWriter>,
+    reconnector: Option<Reconnector>,
+    watchdog: Watchdog,
+    last_health_check: Instant,
+    last_received: Instant,
+    headless: bool,
+    stop: Arc<AtomicBool>,
 }
 
 impl ReaderSession {
+    /// Open a serial port name or a transport URI, see `Endpoint::parse`
     pub fn new(
         port_name: &str,
         config: &SerialConfig,
         timeout_ms: u64,
         show_stats: bool,
     ) -> Result<Self> {
-        let port = PortConnection::open(port_name, config, timeout_ms)?;
+        let endpoint = Endpoint::parse(port_name, config)?;
+        let port = endpoint.open(timeout_ms)?;
+        let config = endpoint.serial_config().unwrap_or(config);
+
+        Ok(Self::from_transport(port, config, show_stats))
+    }
 
-        Ok(Self {
+    /// Read from an already open transport
+    pub fn from_transport(
+        port: Box<dyn Transport>,
+        config: &SerialConfig,
+        show_stats: bool,
+    ) -> Self {
+        Self {
             port,
-            parser: DataParser::new(),
+            config: config.clone(),
+            pipeline: Pipeline::new(),
             stats: SessionStats::new(),
             show_stats,
-        })
+            recorder: None,
+            reconnector: None,
+            watchdog: Watchdog::new(),
+            last_health_check: Instant::now(),
+            last_received: Instant::now(),
+            headless: false,
+            stop: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Select how incoming messages are framed (CR/LF lines or MLLP blocks)
+    pub fn with_framing(mut self, framing: Framing) -> Self {
+        self.pipeline.set_framing(framing);
+        self
+    }
+
+    /// Decode incoming data with the given protocol decoder
+    pub fn with_decoder(mut self, decoder: Box<dyn Decoder>) -> Self {
+        self.pipeline.set_decoder(decoder);
+        self
+    }
+
+    /// Select between raw lines, decoded observations and JSON Lines
+    pub fn with_output_mode(mut self, mode: OutputMode) -> Self {
+        self.pipeline.set_output_mode(mode);
+        self
+    }
+
+    /// Replace the output mode sink, e.g. to capture the console output
+    pub fn with_console(mut self, console: Box<dyn Sink>) -> Self {
+        self.pipeline.set_console(console);
+        self
+    }
+
+    /// Write status lines and statistics here instead of the terminal
+    pub fn with_status_writer(mut self, out: Box<dyn Write + Send>) -> Self {
+        self.pipeline.set_status_writer(out);
+        self
+    }
+
+    /// Also send every record to this sink, next to the console
+    pub fn with_sink(mut self, sink: Box<dyn Sink>) -> Self {
+        self.pipeline.add_sink(sink);
+        self
+    }
+
+    /// Answer every received HL7 message with an MSA acknowledgement
+    pub fn with_ack(mut self, config: AckConfig) -> Self {
+        self.pipeline.set_ack(config);
+        self
+    }
+
+    /// Quiet time before a line-framed message is acknowledged, see `with_ack`
+    pub fn with_ack_quiet_period(mut self, period: Duration) -> Self {
+        self.pipeline.set_ack_quiet_period(period);
+        self
+    }
+
+    /// Drive a polled protocol (e.g. MEDIBUS) instead of passively listening
+    pub fn with_driver(mut self, driver: Box<dyn ProtocolDriver>) -> Self {
+        self.pipeline.set_driver(driver);
+        self
+    }
+
+    /// Record every received chunk and sent byte to a capture file
+    pub fn with_recorder(mut self, recorder: CaptureWriter) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// Reopen the port when it is lost instead of ending the session
+    pub fn with_reconnect(mut self, reconnector: Reconnector) -> Self {
+        self.reconnector = Some(reconnector);
+        self
+    }
+
+    /// Report the connection as idle, stale or lost using these thresholds
+    pub fn with_watchdog(mut self, watchdog: Watchdog) -> Self {
+        self.watchdog = watchdog;
+        self
+    }
+
+    /// Run without a terminal: no raw mode or key commands, and the
+    /// statistics are printed when the session ends
+    pub fn with_headless(mut self, headless: bool) -> Self {
+        self.headless = headless;
+        self
+    }
+
+    /// End the session cleanly once `stop` is set, e.g. by a signal handler
+    pub fn with_stop_flag(mut self, stop: Arc<AtomicBool>) -> Self {
+        self.stop = stop;
+        self
     }
 
     pub fn run(&mut self) -> Result<()> {
-        println!(
+        let port_name = self.port.name().unwrap_or_default();
+        self.record(CaptureEntry::Start(CaptureMetadata::new(
+            &port_name,
+            &self.config,
+        )))?;
+
+        self.pipeline.status(&format!(
             "[{}] Connected to {}",
             Self::format_timestamp(),
-            self.port.name().unwrap_or_default()
-        );
-        println!("────────────────────────────────────────────────────────────────");
+            port_name
+        ));
+        self.pipeline
+            .status("────────────────────────────────────────────────────────────────");
 
-        let mut buffer = vec![0u8; 1024];
+        let mut buffer = vec![0u8; self.config.read_buffer];
 
-        enable_raw_mode()?;
-
-        let result = self.read_loop(&mut buffer);
+        let result = if self.headless {
+            self.read_loop(&mut buffer)
+        } else {
+            enable_raw_mode()?;
+            let result = self.read_loop(&mut buffer);
+            disable_raw_mode()?;
+            result
+        };
 
-        disable_raw_mode()?;
+        let result = result
+            .and(self.finish())
+            .and(self.pipeline.flush())
+            .and(self.stop_recording());
 
-        if self.show_stats {
+        if self.show_stats || self.headless {
             self.print_session_stats();
         }
 
@@ -61,11 +198,14 @@ impl ReaderSession {
 
     fn read_loop(&mut self, buffer: &mut [u8]) -> Result<()> {
         loop {
-            // Check for keyboard input
+            // Check for keyboard input or a stop request
             if let Some(cmd) = self.check_for_input()? {
                 match cmd.as_str() {
                     "QUIT" => {
-                        println!("\n\n[{}] Disconnecting...", Self::format_timestamp());
+                        self.pipeline.status(&format!(
+                            "\n\n[{}] Disconnecting...",
+                            Self::format_timestamp()
+                        ));
                         break;
                     }
                     "HELP" => self.print_help(),
@@ -74,19 +214,54 @@ impl ReaderSession {
                 }
             }
 
-            // Read from serial port
-            match self.port.read(buffer) {
+            let data = self.pipeline.poll_driver(Instant::now());
+            if !data.is_empty() {
+                self.write_port(&data)?;
+            }
+
+            // Read from the port
+            let replies = match self.port.read(buffer) {
                 Ok(n) if n > 0 => {
                     self.stats.add_bytes(n);
-                    self.parser
-                        .process_data(&buffer[..n], &Self::format_timestamp());
+                    self.last_received = Instant::now();
+                    self.watchdog.data(self.last_received);
+                    self.record(CaptureEntry::Received(buffer[..n].to_vec()))?;
+                    self.pipeline
+                        .receive(&buffer[..n], &Self::format_timestamp())?
                 }
-                Ok(_) => {
-                    // No data available
+                // No data available, the sender may be waiting for an ACK
+                Ok(_) => self
+                    .pipeline
+                    .idle(self.last_received.elapsed(), &Self::format_timestamp())?,
+                Err(e) if self.reconnector.is_some() => {
+                    self.check_health()?;
+                    if !self.reconnect(&e.to_string())? {
+                        break;
+                    }
+                    continue;
                 }
                 Err(e) => {
+                    self.check_health()?;
                     return Err(anyhow::anyhow!("Read error: {}", e));
                 }
+            };
+            for reply in replies {
+                self.write_port(&reply)?;
+            }
+
+            if self.last_health_check.elapsed() >= HEALTH_CHECK_INTERVAL {
+                self.check_health()?;
+            }
+
+            // End of a file or pipe, or the remote end closed the connection
+            if !self.port.is_connected() {
+                self.check_health()?;
+                if self.reconnector.is_some() && self.reconnect("Connection closed")? {
+                    continue;
+                }
+                self.pipeline
+                    .status(&format!("\n[{}] Input ended", Self::format_timestamp()));
+                break;
             }
 
             std::thread::sleep(Duration::from_millis(10));
@@ -94,9 +269,113 @@ impl ReaderSession {
         Ok(())
     }
 
+    /// Report a change of the connection state
+    fn check_health(&mut self) -> Result<()> {
+        self.last_health_check = Instant::now();
+        let connected = self.port.is_connected();
+        let modem = self.port.modem_state();
+        match self.watchdog.update(Instant::now(), connected, modem) {
+            Some((state, detail)) => self.pipeline.emit(Record::new(
+                &Self::format_timestamp(),
+                Event::ConnectionState { state, detail },
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Retry opening the port as the policy allows, keeping the parser state
+    /// so a message split by the gap can still complete. Returns false when
+    /// the user quit while waiting.
+    fn reconnect(&mut self, reason: &str) -> Result<bool> {
+        let Some(reconnector) = self.reconnector.take() else {
+            return Ok(false);
+        };
+        self.pipeline.emit(Record::new(
+            &Self::format_timestamp(),
+            Event::Disconnected(reason.to_string()),
+        ))?;
+        self.pipeline.flush()?;
+
+        let policy = reconnector.policy().clone();
+        let mut attempt = 1;
+        let result = loop {
+            if !policy.allows(attempt) {
+                break Err(anyhow::anyhow!(
+                    "Gave up reconnecting after {} attempt(s): {}",
+                    attempt - 1,
+                    reason
+                ));
+            }
+            if !self.wait(policy.delay(attempt))? {
+                break Ok(false);
+            }
+            if let Ok(port) = reconnector.connect() {
+                self.port = port;
+                self.watchdog.reset(Instant::now());
+                let event = Event::Reconnected {
+                    port: self.port.name().unwrap_or_default(),
+                    attempts: attempt,
+                };
+                self.pipeline
+                    .emit(Record::new(&Self::format_timestamp(), event))?;
+                break Ok(true);
+            }
+            attempt += 1;
+        };
+
+        self.reconnector = Some(reconnector);
+        result
+    }
+
+    /// Sleep while watching for [q] or a stop request, returning false on one
+    fn wait(&self, delay: Duration) -> Result<bool> {
+        let until = Instant::now() + delay;
+        while Instant::now() < until {
+            if self.check_for_input()?.as_deref() == Some("QUIT") {
+                return Ok(false);
+            }
+            std::thread::sleep(Duration::from_millis(50).min(delay));
+        }
+        Ok(true)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        for reply in self.pipeline.finish(&Self::format_timestamp())? {
+            self.write_port(&reply)?;
+        }
+        Ok(())
+    }
+
+    fn write_port(&mut self, data: &[u8]) -> Result<()> {
+        self.port.write(data)?;
+        self.port.flush()?;
+        self.record(CaptureEntry::Sent(data.to_vec()))
+    }
+
+    fn record(&mut self, entry: CaptureEntry) -> Result<()> {
+        match self.recorder.as_mut() {
+            Some(recorder) => recorder.write(&entry),
+            None => Ok(()),
+        }
+    }
+
+    fn stop_recording(&mut self) -> Result<()> {
+        self.record(CaptureEntry::Stop)?;
+        match self.recorder.as_mut() {
+            Some(recorder) => recorder.flush(),
+            None => Ok(()),
+        }
+    }
+
     fn check_for_input(&self) -> Result<Option<String>> {
+        if self.stop.load(Ordering::Relaxed) {
+            return Ok(Some("QUIT".to_string()));
+        }
+        if self.headless {
+            return Ok(None);
+        }
         if event::poll(Duration::from_millis(0))? {
-            if let Event::Key(KeyEvent { code, .. }) = event::read()? {
+            if let event::Event::Key(KeyEvent { code, .. }) = event::read()? {
                 match code {
                     KeyCode::Char('q') => return Ok(Some("QUIT".to_string())),
                     KeyCode::Char('s') => return Ok(Some("SEND".to_string())),
@@ -109,19 +388,25 @@ impl ReaderSession {
     }
 
     fn print_help(&self) {
-        println!("\n╔════════════════════════════════════════╗");
-        println!("║        VITAL READER COMMANDS          ║");
-        println!("╠════════════════════════════════════════╣");
-        println!("║ [q] - Quit application                ║");
-        println!("║ [s] - Send command to device          ║");
-        println!("║ [h] - Show this help                  ║");
-        println!("╚════════════════════════════════════════╝\n");
+        let status = |text: &str| self.pipeline.status(text);
+        status("\n╔════════════════════════════════════════╗");
+        status("║        VITAL READER COMMANDS          ║");
+        status("╠════════════════════════════════════════╣");
+        status("║ [q] - Quit application                ║");
+        status("║ [s] - Send command to device          ║");
+        status("║ [h] - Show this help                  ║");
+        status("╚════════════════════════════════════════╝\n");
     }
 
     fn handle_send_command(&mut self) -> Result<()> {
         disable_raw_mode()?;
-        print!("\nEnter command to send: ");
-        io::stdout().flush()?;
+        if self.pipeline.output_mode() == OutputMode::Jsonl {
+            eprint!("\nEnter command to send: ");
+            io::stderr().flush()?;
+        } else {
+            print!("\nEnter command to send: ");
+            io::stdout().flush()?;
+        }
         let mut input = String::new();
         io::stdin().read_line(&mut input)?;
         let input = input.trim();
@@ -134,22 +419,31 @@ impl ReaderSession {
 
     fn send_command(&mut self, command: &str) -> Result<()> {
         let data = format!("{}\r\n", command);
-        self.port.write(data.as_bytes())?;
-        self.port.flush()?;
-        println!("[{}] SENT: {}", Self::format_timestamp(), command);
-        Ok(())
+        self.write_port(data.as_bytes())?;
+        let event = Event::CommandSent(command.to_string());
+        self.pipeline
+            .emit(Record::new(&Self::format_timestamp(), event))
     }
 
     fn print_session_stats(&self) {
-        println!("\n────────────────────────────────────────────────────────────────");
-        println!("Statistics:");
-        println!("  Total bytes received: {}", self.stats.total_bytes());
-        println!("  Connection time:      {:?}", self.stats.elapsed());
-        println!(
+        let status = |text: &str| self.pipeline.status(text);
+        status("\n────────────────────────────────────────────────────────────────");
+        status("Statistics:");
+        status(&format!(
+            "  Total bytes received: {}",
+            self.stats.total_bytes()
+        ));
+        status(&format!(
+            "  Connection time:      {:?}",
+            self.stats.elapsed()
+        ));
+        status(&format!(
             "  Average rate:         {:.2} bytes/sec",
             self.stats.average_rate()
-        );
-        self.parser.print_stats();
+        ));
+        status(&DataFormatter::format_stats(
+            &self.pipeline.parser().stats(),
+        ));
     }
 
     fn format_timestamp() -> String {
@@ -0,0 +1,132 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::transport::ModemState;
+
+/// Health of the link to the device, as shown to the user and to sinks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// Data arrived recently
+    Connected,
+    /// Open, but quiet for a few seconds
+    Idle,
+    /// Quiet for longer than the watchdog allows
+    Stale,
+    /// The port is gone or the device dropped DSR/CD
+    Lost,
+}
+
+impl ConnectionState {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Connected => "connected",
+            Self::Idle => "idle",
+            Self::Stale => "stale",
+            Self::Lost => "lost",
+        }
+    }
+}
+
+impl fmt::Display for ConnectionState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Derives the connection state from data arrival, the transport and the
+/// modem lines, reporting only changes
+///
+/// DSR and CD only count once seen asserted: many cables do not wire them.
+pub struct Watchdog {
+    idle_after: Duration,
+    stale_after: Duration,
+    state: ConnectionState,
+    last_data: Instant,
+    carrier_seen: bool,
+}
+
+impl Watchdog {
+    pub fn new() -> Self {
+        Self {
+            idle_after: Duration::from_secs(5),
+            stale_after: Duration::from_secs(30),
+            state: ConnectionState::Connected,
+            last_data: Instant::now(),
+            carrier_seen: false,
+        }
+    }
+
+    pub fn with_idle_after(mut self, after: Duration) -> Self {
+        self.idle_after = after;
+        self
+    }
+
+    /// No data for this long makes the connection stale
+    pub fn with_stale_after(mut self, after: Duration) -> Self {
+        self.stale_after = after;
+        self
+    }
+
+    pub fn state(&self) -> ConnectionState {
+        self.state
+    }
+
+    /// Data was received
+    pub fn data(&mut self, now: Instant) {
+        self.last_data = now;
+    }
+
+    /// Start over on a new connection, e.g. after a reconnect
+    pub fn reset(&mut self, now: Instant) {
+        self.last_data = now;
+        self.carrier_seen = false;
+    }
+
+    /// The new state and why, if it changed
+    pub fn update(
+        &mut self,
+        now: Instant,
+        connected: bool,
+        modem: Option<ModemState>,
+    ) -> Option<(ConnectionState, String)> {
+        let carrier = modem.map(|lines| lines.dsr() || lines.dcd());
+        if carrier == Some(true) {
+            self.carrier_seen = true;
+        }
+        let quiet = now.saturating_duration_since(self.last_data);
+
+        let (state, detail) = if !connected {
+            (ConnectionState::Lost, "port closed or removed".to_string())
+        } else if self.carrier_seen && carrier == Some(false) {
+            (ConnectionState::Lost, "device dropped DSR/CD".to_string())
+        } else if quiet >= self.stale_after {
+            (
+                ConnectionState::Stale,
+                format!("no data for {} s", quiet.as_secs()),
+            )
+        } else if quiet >= self.idle_after {
+            (
+                ConnectionState::Idle,
+                format!("no data for {} s", quiet.as_secs()),
+            )
+        } else {
+            let detail = match self.state {
+                ConnectionState::Lost => "link restored",
+                _ => "receiving data",
+            };
+            (ConnectionState::Connected, detail.to_string())
+        };
+
+        if state == self.state {
+            return None;
+        }
+        self.state = state;
+        Some((state, detail))
+    }
+}
+
+impl Default for Watchdog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,141 @@
+use anyhow::Result;
+
+use super::{
+    FileTransport, Rfc2217Transport, StdinTransport, TcpClientTransport, TcpListenerTransport,
+    Transport,
+};
+use crate::config::SerialConfig;
+use crate::port::PortConnection;
+
+/// Where a session reads from, parsed from a `--port` value
+///
+/// Accepted forms:
+/// - `COM3`, `/dev/ttyUSB0` or `serial:///dev/ttyUSB0?baud=9600&parity=even`
+/// - `tcp://10.0.0.5:4001` to connect to a device server
+/// - `rfc2217://10.0.0.5:4001?baud=9600` to also set its line settings
+/// - `tcp-listen://0.0.0.0:4001` to wait for one to connect
+/// - `stdin://` or `-` for piped input
+/// - `file://capture.bin` for a raw byte dump
+#[derive(Debug, Clone)]
+pub enum Endpoint {
+    Serial {
+        port: String,
+        config: SerialConfig,
+    },
+    TcpClient(String),
+    Rfc2217 {
+        address: String,
+        config: SerialConfig,
+    },
+    TcpListener(String),
+    Stdin,
+    File(String),
+}
+
+impl Endpoint {
+    /// Parse a URI, `config` being the serial settings from the command line
+    pub fn parse(uri: &str, config: &SerialConfig) -> Result<Self> {
+        if uri == "-" {
+            return Ok(Self::Stdin);
+        }
+
+        let Some((scheme, rest)) = uri.split_once("://") else {
+            return Ok(Self::Serial {
+                port: uri.to_string(),
+                config: config.clone(),
+            });
+        };
+
+        match scheme.to_lowercase().as_str() {
+            "serial" => Self::parse_serial(rest, config),
+            "tcp" => Ok(Self::TcpClient(Self::parse_address(uri, rest)?)),
+            "rfc2217" => {
+                let (address, query) = rest.split_once('?').unwrap_or((rest, ""));
+                Ok(Self::Rfc2217 {
+                    address: Self::parse_address(uri, address)?,
+                    config: Self::apply_options(query, config)?,
+                })
+            }
+            "tcp-listen" => Ok(Self::TcpListener(Self::parse_address(uri, rest)?)),
+            "stdin" if rest.is_empty() => Ok(Self::Stdin),
+            "file" if !rest.is_empty() => Ok(Self::File(rest.to_string())),
+            _ => Err(anyhow::anyhow!(
+                "Invalid port URI: {} (expected serial://, tcp://, rfc2217://, tcp-listen://, stdin:// or file://)",
+                uri
+            )),
+        }
+    }
+
+    /// Open the transport, `timeout_ms` bounding each read
+    pub fn open(&self, timeout_ms: u64) -> Result<Box<dyn Transport>> {
+        Ok(match self {
+            Self::Serial { port, config } => {
+                Box::new(PortConnection::open(port, config, timeout_ms)?)
+            }
+            Self::TcpClient(address) => Box::new(TcpClientTransport::connect(address, timeout_ms)?),
+            Self::Rfc2217 { address, config } => {
+                Box::new(Rfc2217Transport::connect(address, config, timeout_ms)?)
+            }
+            Self::TcpListener(address) => {
+                Box::new(TcpListenerTransport::bind(address, timeout_ms)?)
+            }
+            Self::Stdin => Box::new(StdinTransport::new(timeout_ms)),
+            Self::File(path) => Box::new(FileTransport::open(path)?),
+        })
+    }
+
+    /// Serial settings in effect, `None` for inputs without line settings
+    pub fn serial_config(&self) -> Option<&SerialConfig> {
+        match self {
+            Self::Serial { config, .. } | Self::Rfc2217 { config, .. } => Some(config),
+            _ => None,
+        }
+    }
+
+    fn parse_serial(rest: &str, config: &SerialConfig) -> Result<Self> {
+        let (port, query) = rest.split_once('?').unwrap_or((rest, ""));
+        if port.is_empty() {
+            return Err(anyhow::anyhow!("Missing port name in serial://{}", rest));
+        }
+
+        Ok(Self::Serial {
+            port: port.to_string(),
+            config: Self::apply_options(query, config)?,
+        })
+    }
+
+    /// Apply `baud=9600&parity=even&flow=rtscts` style options on top of `config`
+    ///
+    /// Keys are those of `SerialConfig::set_option`.
+    fn apply_options(query: &str, config: &SerialConfig) -> Result<SerialConfig> {
+        let mut config = config.clone();
+        let mut options = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                pair.split_once('=')
+                    .ok_or_else(|| anyhow::anyhow!("Invalid serial option: {}", pair))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        // 1.5 stop bits depend on the data bits, wherever they are given
+        options.sort_by_key(|(key, _)| *key == "stop_bits");
+        for (key, value) in options {
+            config.set_option(key, value)?;
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn parse_address(uri: &str, rest: &str) -> Result<String> {
+        let address = rest.trim_end_matches('/');
+        match address.rsplit_once(':') {
+            Some((host, port)) if !host.is_empty() && port.parse::<u16>().is_ok() => {
+                Ok(address.to_string())
+            }
+            _ => Err(anyhow::anyhow!(
+                "Invalid address in {} (expected host:port)",
+                uri
+            )),
+        }
+    }
+}
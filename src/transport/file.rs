@@ -0,0 +1,52 @@
+use anyhow::Result;
+use std::fs::File;
+use std::io::{BufReader, Read};
+
+use super::Transport;
+
+/// Reads a file of raw bytes, e.g. a dump made with another tool
+/// Writes are discarded and the transport disconnects at the end of file
+pub struct FileTransport {
+    path: String,
+    input: Option<BufReader<File>>,
+}
+
+impl FileTransport {
+    pub fn open(path: &str) -> Result<Self> {
+        let file =
+            File::open(path).map_err(|e| anyhow::anyhow!("Failed to open {}: {}", path, e))?;
+        Ok(Self {
+            path: path.to_string(),
+            input: Some(BufReader::new(file)),
+        })
+    }
+}
+
+impl Transport for FileTransport {
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        let Some(input) = self.input.as_mut() else {
+            return Ok(0);
+        };
+        let n = input.read(buffer)?;
+        if n == 0 {
+            self.input = None;
+        }
+        Ok(n)
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<usize> {
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn name(&self) -> Option<String> {
+        Some(self.path.clone())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.input.is_some()
+    }
+}
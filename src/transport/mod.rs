@@ -0,0 +1,39 @@
+mod endpoint;
+mod file;
+mod modem;
+pub(crate) mod rfc2217;
+mod serial;
+mod stdin;
+mod tcp;
+pub(crate) mod telnet;
+
+pub use endpoint::Endpoint;
+pub use file::FileTransport;
+pub use modem::ModemState;
+pub use rfc2217::{ComPortSettings, Rfc2217Transport};
+pub use stdin::StdinTransport;
+pub use tcp::{TcpClientTransport, TcpListenerTransport};
+
+use anyhow::Result;
+
+/// A byte stream a session reads from and replies to
+///
+/// `read` waits at most the transport timeout and returns `Ok(0)` when
+/// nothing arrived. Once `is_connected` is false nothing more will be read.
+pub trait Transport: Send {
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize>;
+
+    fn write(&mut self, data: &[u8]) -> Result<usize>;
+
+    fn flush(&mut self) -> Result<()>;
+
+    /// Port name or address, for display and capture metadata
+    fn name(&self) -> Option<String>;
+
+    fn is_connected(&self) -> bool;
+
+    /// Modem input lines, `None` when the transport has none
+    fn modem_state(&mut self) -> Option<ModemState> {
+        None
+    }
+}
@@ -0,0 +1,33 @@
+/// Modem input lines, in the RFC 2217 NOTIFY-MODEMSTATE bit layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ModemState(pub u8);
+
+impl ModemState {
+    const CTS: u8 = 0x10;
+    const DSR: u8 = 0x20;
+    const RING: u8 = 0x40;
+    const DCD: u8 = 0x80;
+
+    pub fn from_lines(cts: bool, dsr: bool, ring: bool, dcd: bool) -> Self {
+        let bit = |on: bool, mask: u8| if on { mask } else { 0 };
+        Self(
+            bit(cts, Self::CTS) | bit(dsr, Self::DSR) | bit(ring, Self::RING) | bit(dcd, Self::DCD),
+        )
+    }
+
+    pub fn cts(&self) -> bool {
+        self.0 & Self::CTS != 0
+    }
+
+    pub fn dsr(&self) -> bool {
+        self.0 & Self::DSR != 0
+    }
+
+    pub fn ring(&self) -> bool {
+        self.0 & Self::RING != 0
+    }
+
+    pub fn dcd(&self) -> bool {
+        self.0 & Self::DCD != 0
+    }
+}
@@ -0,0 +1,319 @@
+use anyhow::Result;
+use serialport::{DataBits, FlowControl, Parity, StopBits};
+use std::fmt;
+use std::io::Write;
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+
+use super::tcp::{connect_stream, read_stream};
+use super::telnet::{
+    self, TelnetEvent, TelnetParser, BINARY, COM_PORT_OPTION, DO, DONT, SUPPRESS_GO_AHEAD, WILL,
+    WONT,
+};
+use super::{ModemState, Transport};
+use crate::config::SerialConfig;
+
+// COM-PORT-OPTION commands sent by the client, the server answers with +100
+pub(crate) const SET_BAUDRATE: u8 = 1;
+pub(crate) const SET_DATASIZE: u8 = 2;
+pub(crate) const SET_PARITY: u8 = 3;
+pub(crate) const SET_STOPSIZE: u8 = 4;
+pub(crate) const SET_CONTROL: u8 = 5;
+pub(crate) const NOTIFY_MODEMSTATE: u8 = 7;
+pub(crate) const SET_LINESTATE_MASK: u8 = 10;
+pub(crate) const SET_MODEMSTATE_MASK: u8 = 11;
+pub(crate) const SERVER_OFFSET: u8 = 100;
+
+// SET-CONTROL values
+pub(crate) const FLOW_NONE: u8 = 1;
+pub(crate) const FLOW_XONXOFF: u8 = 2;
+pub(crate) const FLOW_HARDWARE: u8 = 3;
+pub(crate) const DTR_ON: u8 = 8;
+pub(crate) const DTR_OFF: u8 = 9;
+pub(crate) const RTS_ON: u8 = 11;
+pub(crate) const RTS_OFF: u8 = 12;
+
+/// How long the server gets to answer the option and settings negotiation
+const NEGOTIATION_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Line settings as RFC 2217 encodes them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ComPortSettings {
+    pub baud: u32,
+    pub data_size: u8,
+    /// 1 none, 2 odd, 3 even, 4 mark, 5 space
+    pub parity: u8,
+    /// 1 one, 2 two, 3 one and a half
+    pub stop_size: u8,
+}
+
+impl ComPortSettings {
+    pub fn from_config(config: &SerialConfig) -> Self {
+        Self {
+            baud: config.baud,
+            data_size: match config.data_bits {
+                DataBits::Five => 5,
+                DataBits::Six => 6,
+                DataBits::Seven => 7,
+                DataBits::Eight => 8,
+            },
+            parity: match (config.parity, config.stick_parity) {
+                (Parity::None, _) => 1,
+                (Parity::Odd, false) => 2,
+                (Parity::Even, false) => 3,
+                (Parity::Odd, true) => 4,
+                (Parity::Even, true) => 5,
+            },
+            stop_size: match (config.stop_bits, config.data_bits) {
+                (StopBits::One, _) => 1,
+                (StopBits::Two, DataBits::Five) => 3,
+                (StopBits::Two, _) => 2,
+            },
+        }
+    }
+}
+
+impl fmt::Display for ComPortSettings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let parity = match self.parity {
+            1 => "N",
+            2 => "O",
+            3 => "E",
+            4 => "M",
+            5 => "S",
+            _ => "?",
+        };
+        let stop = match self.stop_size {
+            1 => "1",
+            2 => "2",
+            3 => "1.5",
+            _ => "?",
+        };
+        write!(f, "{} {}{}{}", self.baud, self.data_size, parity, stop)
+    }
+}
+
+/// Serial device server port controlled over Telnet (RFC 2217)
+///
+/// The line settings are sent on connect, so the server port follows the
+/// `SerialConfig` instead of its web UI configuration. Data is IAC escaped
+/// both ways and modem line changes are tracked in `modem_state`.
+pub struct Rfc2217Transport {
+    address: String,
+    stream: TcpStream,
+    parser: TelnetParser,
+    pending: Vec<u8>,
+    connected: bool,
+    com_port: Option<bool>,
+    applied: ComPortSettings,
+    modem_state: ModemState,
+}
+
+impl Rfc2217Transport {
+    pub fn connect(address: &str, config: &SerialConfig, timeout_ms: u64) -> Result<Self> {
+        let mut transport = Self {
+            address: address.to_string(),
+            stream: connect_stream(address, timeout_ms)?,
+            parser: TelnetParser::new(),
+            pending: Vec::new(),
+            connected: true,
+            com_port: None,
+            applied: ComPortSettings::default(),
+            modem_state: ModemState::default(),
+        };
+        transport.negotiate(ComPortSettings::from_config(config))?;
+        transport.set_flow_control(config.flow_control)?;
+        if let Some(dtr) = config.dtr {
+            transport.set_dtr(dtr)?;
+        }
+        if let Some(rts) = config.rts {
+            transport.set_rts(rts)?;
+        }
+        Ok(transport)
+    }
+
+    /// Line settings confirmed by the server
+    pub fn settings(&self) -> ComPortSettings {
+        self.applied
+    }
+
+    pub fn set_flow_control(&mut self, flow_control: FlowControl) -> Result<()> {
+        let value = match flow_control {
+            FlowControl::None => FLOW_NONE,
+            FlowControl::Software => FLOW_XONXOFF,
+            FlowControl::Hardware => FLOW_HARDWARE,
+        };
+        self.send_command(SET_CONTROL, &[value])
+    }
+
+    pub fn set_dtr(&mut self, on: bool) -> Result<()> {
+        self.send_command(SET_CONTROL, &[if on { DTR_ON } else { DTR_OFF }])
+    }
+
+    pub fn set_rts(&mut self, on: bool) -> Result<()> {
+        self.send_command(SET_CONTROL, &[if on { RTS_ON } else { RTS_OFF }])
+    }
+
+    fn negotiate(&mut self, settings: ComPortSettings) -> Result<()> {
+        let mut request = Vec::new();
+        for (command, option) in [
+            (WILL, COM_PORT_OPTION),
+            (WILL, BINARY),
+            (DO, BINARY),
+            (WILL, SUPPRESS_GO_AHEAD),
+            (DO, SUPPRESS_GO_AHEAD),
+        ] {
+            request.extend_from_slice(&telnet::negotiation(command, option));
+        }
+        self.send(&request)?;
+
+        self.wait_for(|t| t.com_port.is_some())?;
+        if self.com_port != Some(true) {
+            return Err(anyhow::anyhow!(
+                "Serial server at {} refused COM-PORT-OPTION (RFC 2217)",
+                self.address
+            ));
+        }
+
+        // Masks first, so the confirmed settings imply they are in effect
+        self.send_command(SET_LINESTATE_MASK, &[0])?;
+        self.send_command(SET_MODEMSTATE_MASK, &[0xFF])?;
+        self.send_command(SET_BAUDRATE, &settings.baud.to_be_bytes())?;
+        self.send_command(SET_DATASIZE, &[settings.data_size])?;
+        self.send_command(SET_PARITY, &[settings.parity])?;
+        self.send_command(SET_STOPSIZE, &[settings.stop_size])?;
+
+        let applied = |t: &Self| {
+            let ComPortSettings {
+                baud,
+                data_size,
+                parity,
+                stop_size,
+            } = t.applied;
+            baud != 0 && data_size != 0 && parity != 0 && stop_size != 0
+        };
+        self.wait_for(applied)?;
+        if self.applied != settings {
+            return Err(anyhow::anyhow!(
+                "Serial server at {} applied {} instead of {}",
+                self.address,
+                self.applied,
+                settings
+            ));
+        }
+        Ok(())
+    }
+
+    /// Read until `done` holds, failing after the negotiation timeout
+    fn wait_for(&mut self, done: impl Fn(&Self) -> bool) -> Result<()> {
+        let deadline = Instant::now() + NEGOTIATION_TIMEOUT;
+        while !done(self) {
+            if !self.connected {
+                return Err(anyhow::anyhow!(
+                    "Serial server at {} closed the connection during negotiation",
+                    self.address
+                ));
+            }
+            if Instant::now() >= deadline {
+                return Err(anyhow::anyhow!(
+                    "No RFC 2217 answer from serial server at {}",
+                    self.address
+                ));
+            }
+            self.receive()?;
+        }
+        Ok(())
+    }
+
+    /// Read the socket once, keeping data in `pending`
+    fn receive(&mut self) -> Result<()> {
+        let mut buffer = [0u8; 1024];
+        let Some(n) = read_stream(&mut self.stream, &mut buffer)? else {
+            self.connected = false;
+            return Ok(());
+        };
+        for event in self.parser.feed(&buffer[..n], &mut self.pending) {
+            self.handle(event)?;
+        }
+        Ok(())
+    }
+
+    fn handle(&mut self, event: TelnetEvent) -> Result<()> {
+        match event {
+            TelnetEvent::Negotiation { command, option } => match (command, option) {
+                (DO, COM_PORT_OPTION) => self.com_port = Some(true),
+                (DONT, COM_PORT_OPTION) => self.com_port = Some(false),
+                // Answers to our own requests
+                (_, BINARY | SUPPRESS_GO_AHEAD) => {}
+                (DO, _) => self.send(&telnet::negotiation(WONT, option))?,
+                (WILL, _) => self.send(&telnet::negotiation(DONT, option))?,
+                _ => {}
+            },
+            TelnetEvent::Subnegotiation(payload) => {
+                if let [COM_PORT_OPTION, command, value @ ..] = payload.as_slice() {
+                    self.handle_com_port(command.wrapping_sub(SERVER_OFFSET), value);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_com_port(&mut self, command: u8, value: &[u8]) {
+        match (command, value) {
+            (SET_BAUDRATE, [a, b, c, d]) => {
+                self.applied.baud = u32::from_be_bytes([*a, *b, *c, *d])
+            }
+            (SET_DATASIZE, [size]) => self.applied.data_size = *size,
+            (SET_PARITY, [parity]) => self.applied.parity = *parity,
+            (SET_STOPSIZE, [size]) => self.applied.stop_size = *size,
+            (NOTIFY_MODEMSTATE, [state]) => self.modem_state = ModemState(*state),
+            _ => {}
+        }
+    }
+
+    fn send_command(&mut self, command: u8, value: &[u8]) -> Result<()> {
+        self.send(&telnet::com_port_command(command, value))
+    }
+
+    fn send(&mut self, data: &[u8]) -> Result<()> {
+        self.stream
+            .write_all(data)
+            .map_err(|e| anyhow::anyhow!("Failed to write to {}: {}", self.address, e))
+    }
+}
+
+impl Transport for Rfc2217Transport {
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        if self.pending.is_empty() && self.connected {
+            self.receive()?;
+        }
+        let n = self.pending.len().min(buffer.len());
+        buffer[..n].copy_from_slice(&self.pending[..n]);
+        self.pending.drain(..n);
+        Ok(n)
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<usize> {
+        self.send(&telnet::escape(data))?;
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.stream
+            .flush()
+            .map_err(|e| anyhow::anyhow!("Failed to flush {}: {}", self.address, e))
+    }
+
+    fn name(&self) -> Option<String> {
+        Some(format!("rfc2217://{}", self.address))
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected || !self.pending.is_empty()
+    }
+
+    /// Lines as last reported by the server
+    fn modem_state(&mut self) -> Option<ModemState> {
+        Some(self.modem_state)
+    }
+}
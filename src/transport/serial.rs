@@ -0,0 +1,30 @@
+use anyhow::Result;
+
+use super::{ModemState, Transport};
+use crate::port::PortConnection;
+
+impl Transport for PortConnection {
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        PortConnection::read(self, buffer)
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<usize> {
+        PortConnection::write(self, data)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        PortConnection::flush(self)
+    }
+
+    fn name(&self) -> Option<String> {
+        PortConnection::name(self)
+    }
+
+    fn is_connected(&self) -> bool {
+        PortConnection::is_connected(self)
+    }
+
+    fn modem_state(&mut self) -> Option<ModemState> {
+        PortConnection::modem_state(self)
+    }
+}
@@ -0,0 +1,72 @@
+use anyhow::Result;
+use std::io::Read;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+use super::Transport;
+
+/// Reads piped standard input, e.g. `socat` or `cat capture.bin |`
+///
+/// A background thread does the blocking reads so `read` can time out like
+/// a serial port. Writes are discarded, as stdout carries the output.
+pub struct StdinTransport {
+    chunks: Receiver<Vec<u8>>,
+    pending: Vec<u8>,
+    timeout: Duration,
+    connected: bool,
+}
+
+impl StdinTransport {
+    pub fn new(timeout_ms: u64) -> Self {
+        let (sender, chunks) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut stdin = std::io::stdin();
+            let mut buffer = [0u8; 1024];
+            while let Ok(n @ 1..) = stdin.read(&mut buffer) {
+                if sender.send(buffer[..n].to_vec()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            chunks,
+            pending: Vec::new(),
+            timeout: Duration::from_millis(timeout_ms),
+            connected: true,
+        }
+    }
+}
+
+impl Transport for StdinTransport {
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        if self.pending.is_empty() && self.connected {
+            match self.chunks.recv_timeout(self.timeout) {
+                Ok(chunk) => self.pending = chunk,
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => self.connected = false,
+            }
+        }
+
+        let n = self.pending.len().min(buffer.len());
+        buffer[..n].copy_from_slice(&self.pending[..n]);
+        self.pending.drain(..n);
+        Ok(n)
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<usize> {
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn name(&self) -> Option<String> {
+        Some("stdin".to_string())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected || !self.pending.is_empty()
+    }
+}
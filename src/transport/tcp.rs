@@ -0,0 +1,191 @@
+use anyhow::{Context, Result};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use super::Transport;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Read `stream`, mapping a timeout or signal to `Ok(0)` and end of stream
+/// to `None`
+pub(super) fn read_stream(stream: &mut TcpStream, buffer: &mut [u8]) -> Result<Option<usize>> {
+    match stream.read(buffer) {
+        Ok(0) => Ok(None),
+        Ok(n) => Ok(Some(n)),
+        Err(e)
+            if matches!(
+                e.kind(),
+                ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::Interrupted
+            ) =>
+        {
+            Ok(Some(0))
+        }
+        Err(e) if e.kind() == ErrorKind::ConnectionReset => Ok(None),
+        Err(e) => Err(anyhow::anyhow!("Read error: {}", e)),
+    }
+}
+
+fn read_timeout(timeout_ms: u64) -> Option<Duration> {
+    Some(Duration::from_millis(timeout_ms.max(1)))
+}
+
+/// Connect to `address`, each read waiting at most `timeout_ms`
+pub(super) fn connect_stream(address: &str, timeout_ms: u64) -> Result<TcpStream> {
+    let addr = address
+        .to_socket_addrs()
+        .context(format!("Invalid address {}", address))?
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("Invalid address {}", address))?;
+    let stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)
+        .context(format!("Failed to connect to {}", address))?;
+    stream.set_read_timeout(read_timeout(timeout_ms))?;
+    stream.set_nodelay(true)?;
+    Ok(stream)
+}
+
+/// Connects to a device server, e.g. a Moxa or Lantronix port in raw TCP mode
+pub struct TcpClientTransport {
+    address: String,
+    stream: TcpStream,
+    connected: bool,
+}
+
+impl TcpClientTransport {
+    pub fn connect(address: &str, timeout_ms: u64) -> Result<Self> {
+        Ok(Self {
+            address: address.to_string(),
+            stream: connect_stream(address, timeout_ms)?,
+            connected: true,
+        })
+    }
+}
+
+impl Transport for TcpClientTransport {
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        if !self.connected {
+            return Ok(0);
+        }
+        match read_stream(&mut self.stream, buffer)? {
+            Some(n) => Ok(n),
+            None => {
+                self.connected = false;
+                Ok(0)
+            }
+        }
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<usize> {
+        // A partial write would cut an ACK or poll short
+        self.stream
+            .write_all(data)
+            .context("Failed to write to socket")?;
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.stream.flush().context("Failed to flush socket")
+    }
+
+    fn name(&self) -> Option<String> {
+        Some(format!("tcp://{}", self.address))
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected
+    }
+}
+
+/// Waits for a device server to connect, one client at a time
+///
+/// While no client is connected `read` returns `Ok(0)` after the timeout
+/// and writes are discarded; a new client is accepted after a disconnect.
+pub struct TcpListenerTransport {
+    listener: TcpListener,
+    client: Option<TcpStream>,
+    timeout_ms: u64,
+}
+
+impl TcpListenerTransport {
+    pub fn bind(address: &str, timeout_ms: u64) -> Result<Self> {
+        let listener =
+            TcpListener::bind(address).context(format!("Failed to listen on {}", address))?;
+        listener.set_nonblocking(true)?;
+
+        Ok(Self {
+            listener,
+            client: None,
+            timeout_ms,
+        })
+    }
+
+    /// Address actually bound, useful with port 0
+    pub fn local_addr(&self) -> Result<std::net::SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    fn accept(&mut self) -> Result<()> {
+        match self.listener.accept() {
+            Ok((stream, _)) => {
+                stream.set_nonblocking(false)?;
+                stream.set_read_timeout(read_timeout(self.timeout_ms))?;
+                stream.set_nodelay(true)?;
+                self.client = Some(stream);
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                std::thread::sleep(Duration::from_millis(self.timeout_ms.max(1)));
+            }
+            Err(e) => return Err(anyhow::anyhow!("Accept error: {}", e)),
+        }
+        Ok(())
+    }
+}
+
+impl Transport for TcpListenerTransport {
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        if self.client.is_none() {
+            self.accept()?;
+        }
+        let Some(stream) = self.client.as_mut() else {
+            return Ok(0);
+        };
+        match read_stream(stream, buffer)? {
+            Some(n) => Ok(n),
+            None => {
+                self.client = None;
+                Ok(0)
+            }
+        }
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<usize> {
+        match self.client.as_mut() {
+            Some(stream) => {
+                stream
+                    .write_all(data)
+                    .context("Failed to write to socket")?;
+                Ok(data.len())
+            }
+            None => Ok(data.len()),
+        }
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        match self.client.as_mut() {
+            Some(stream) => stream.flush().context("Failed to flush socket"),
+            None => Ok(()),
+        }
+    }
+
+    fn name(&self) -> Option<String> {
+        self.listener
+            .local_addr()
+            .ok()
+            .map(|addr| format!("tcp-listen://{}", addr))
+    }
+
+    /// A listener keeps accepting clients, it never ends on its own
+    fn is_connected(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,115 @@
+// Telnet commands (RFC 854)
+pub(crate) const IAC: u8 = 255;
+pub(crate) const DONT: u8 = 254;
+pub(crate) const DO: u8 = 253;
+pub(crate) const WONT: u8 = 252;
+pub(crate) const WILL: u8 = 251;
+pub(crate) const SB: u8 = 250;
+pub(crate) const SE: u8 = 240;
+
+// Telnet options
+pub(crate) const BINARY: u8 = 0;
+pub(crate) const SUPPRESS_GO_AHEAD: u8 = 3;
+pub(crate) const COM_PORT_OPTION: u8 = 44;
+
+/// Something other than data found in a Telnet stream
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TelnetEvent {
+    /// WILL, WONT, DO or DONT for an option
+    Negotiation { command: u8, option: u8 },
+    /// Subnegotiation payload, starting with the option
+    Subnegotiation(Vec<u8>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Data,
+    Iac,
+    Negotiation(u8),
+    Sub,
+    SubIac,
+}
+
+/// Splits a Telnet stream into data and commands, across chunk boundaries
+pub(crate) struct TelnetParser {
+    state: State,
+    sub: Vec<u8>,
+}
+
+impl TelnetParser {
+    pub(crate) fn new() -> Self {
+        Self {
+            state: State::Data,
+            sub: Vec::new(),
+        }
+    }
+
+    /// Append the data bytes of `input` to `data`, returning the commands
+    pub(crate) fn feed(&mut self, input: &[u8], data: &mut Vec<u8>) -> Vec<TelnetEvent> {
+        let mut events = Vec::new();
+        for &byte in input {
+            self.state = match (self.state, byte) {
+                (State::Data, IAC) => State::Iac,
+                (State::Data, _) => {
+                    data.push(byte);
+                    State::Data
+                }
+                (State::Iac, IAC) => {
+                    data.push(IAC);
+                    State::Data
+                }
+                (State::Iac, WILL | WONT | DO | DONT) => State::Negotiation(byte),
+                (State::Iac, SB) => {
+                    self.sub.clear();
+                    State::Sub
+                }
+                // NOP, GA and the other single byte commands carry nothing
+                (State::Iac, _) => State::Data,
+                (State::Negotiation(command), option) => {
+                    events.push(TelnetEvent::Negotiation { command, option });
+                    State::Data
+                }
+                (State::Sub, IAC) => State::SubIac,
+                (State::Sub, _) => {
+                    self.sub.push(byte);
+                    State::Sub
+                }
+                (State::SubIac, IAC) => {
+                    self.sub.push(IAC);
+                    State::Sub
+                }
+                (State::SubIac, SE) => {
+                    events.push(TelnetEvent::Subnegotiation(std::mem::take(&mut self.sub)));
+                    State::Data
+                }
+                // Malformed subnegotiation, drop it
+                (State::SubIac, _) => State::Data,
+            };
+        }
+        events
+    }
+}
+
+/// Double every IAC byte so data cannot be mistaken for a command
+pub(crate) fn escape(data: &[u8]) -> Vec<u8> {
+    let mut escaped = Vec::with_capacity(data.len());
+    for &byte in data {
+        escaped.push(byte);
+        if byte == IAC {
+            escaped.push(IAC);
+        }
+    }
+    escaped
+}
+
+pub(crate) fn negotiation(command: u8, option: u8) -> [u8; 3] {
+    [IAC, command, option]
+}
+
+/// A COM-PORT-OPTION subnegotiation carrying `command` and `value`
+pub(crate) fn com_port_command(command: u8, value: &[u8]) -> Vec<u8> {
+    let mut frame = vec![IAC, SB, COM_PORT_OPTION, command];
+    frame.extend_from_slice(&escape(value));
+    frame.extend_from_slice(&[IAC, SE]);
+    frame
+}
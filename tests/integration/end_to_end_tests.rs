@@ -56,3 +56,40 @@ fn test_data_processing_pipeline() {
     // Mixed data
     parser.process_data(b"Mixed\x00\xFF\r", "12:00:03");
 }
+
+#[test]
+fn test_hl7_generator_mllp_round_trip() {
+    use vital_reader::data::{DataParser, Framing};
+    use vital_reader::fake::Hl7Generator;
+
+    let mut wire = Vec::new();
+    Hl7Generator::send_mllp(&mut wire).unwrap();
+
+    let mut parser = DataParser::with_framing(Framing::Mllp);
+    parser.process_data(&wire, "12:00:00");
+
+    let messages = parser.take_messages();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].message_type().as_deref(), Some("ORU^R01"));
+    assert_eq!(messages[0].segments_named("OBX").count(), 32);
+}
+
+#[test]
+fn test_hl7_generator_observations() {
+    use vital_reader::data::hl7::Message;
+    use vital_reader::data::{ObservationValue, VitalObservation};
+    use vital_reader::fake::Hl7Generator;
+
+    let message = Message::parse(&Hl7Generator::build_message("20250104120000").concat()).unwrap();
+    let observations = VitalObservation::from_message(&message);
+    assert_eq!(observations.len(), 32);
+
+    let spo2 = observations.iter().find(|o| o.code == "2708-6").unwrap();
+    assert_eq!(spo2.value, ObservationValue::Numeric(98.0));
+    assert_eq!(spo2.producer.as_deref(), Some("GE_MONITOR^SPO2_MODULE"));
+
+    let rhythm = observations.iter().find(|o| o.code == "8884-9").unwrap();
+    assert_eq!(rhythm.value, ObservationValue::Text("NSR".to_string()));
+    assert_eq!(rhythm.abnormal_flag.as_deref(), Some("N"));
+    assert_eq!(rhythm.result_status.as_deref(), Some("F"));
+}
@@ -0,0 +1,95 @@
+use std::fs::File;
+use std::io::Read;
+use std::time::{Duration, Instant};
+
+use vital_reader::config::SerialConfig;
+use vital_reader::data::DecoderRegistry;
+use vital_reader::output::{MemorySink, Record};
+use vital_reader::port::{LoopbackGenerator, LoopbackPreset, PortConnection, VirtualPortPair};
+use vital_reader::reader::Pipeline;
+
+/// A simulated device on one side of a pseudo-terminal pair and the reader
+/// pipeline of a live session on the other, for tests without hardware
+pub struct Loopback {
+    pair: VirtualPortPair,
+    generator: Option<LoopbackGenerator>,
+    port: PortConnection,
+    pipeline: Pipeline,
+    records: MemorySink,
+}
+
+impl Loopback {
+    /// Start the preset generator and open the reader side
+    pub fn start(preset: LoopbackPreset) -> Self {
+        Self::with_pipeline(preset, Pipeline::new())
+    }
+
+    /// Like `start`, with a pipeline configured by the test (ACKs, sinks)
+    pub fn with_pipeline(preset: LoopbackPreset, mut pipeline: Pipeline) -> Self {
+        let pair = VirtualPortPair::open().unwrap();
+        let port = PortConnection::open(pair.path(), &SerialConfig::default(), 20).unwrap();
+        let generator = LoopbackGenerator::spawn(pair.device().unwrap(), preset, preset.period());
+
+        let records = MemorySink::new();
+        pipeline.set_decoder(DecoderRegistry::new().create(preset.protocol()).unwrap());
+        pipeline.set_console(Box::new(records.clone()));
+
+        Self {
+            pair,
+            generator: Some(generator),
+            port,
+            pipeline,
+            records,
+        }
+    }
+
+    /// Read like a live session until `done` holds for the records so far,
+    /// panicking after `timeout`
+    pub fn read_until(
+        &mut self,
+        timeout: Duration,
+        done: impl Fn(&[Record]) -> bool,
+    ) -> Vec<Record> {
+        let deadline = Instant::now() + timeout;
+        let mut buffer = [0u8; 1024];
+        let mut last_received = Instant::now();
+
+        while !done(&self.records.records()) {
+            assert!(Instant::now() < deadline, "loopback timed out");
+
+            let replies = match self.port.read(&mut buffer).unwrap() {
+                0 => self
+                    .pipeline
+                    .idle(last_received.elapsed(), "loopback")
+                    .unwrap(),
+                n => {
+                    last_received = Instant::now();
+                    self.pipeline.receive(&buffer[..n], "loopback").unwrap()
+                }
+            };
+            for reply in replies {
+                self.port.write(&reply).unwrap();
+                self.port.flush().unwrap();
+            }
+        }
+        self.records.take()
+    }
+
+    /// Bytes the reader sent back to the device so far
+    pub fn device_received(&self) -> Vec<u8> {
+        let mut device: File = self.pair.device().unwrap();
+        let mut received = Vec::new();
+        let mut buffer = [0u8; 1024];
+        while let Ok(n @ 1..) = device.read(&mut buffer) {
+            received.extend_from_slice(&buffer[..n]);
+        }
+        received
+    }
+
+    /// Stop the generator, failing on any write error it hit
+    pub fn stop(mut self) {
+        if let Some(generator) = self.generator.take() {
+            generator.stop().unwrap();
+        }
+    }
+}
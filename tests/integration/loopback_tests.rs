@@ -0,0 +1,123 @@
+use std::time::Duration;
+
+use super::loopback::Loopback;
+use vital_reader::data::hl7::AckConfig;
+use vital_reader::data::{BinaryEvent, MllpCodec};
+use vital_reader::output::{Event, Record};
+use vital_reader::port::{LoopbackPreset, PortConnection, VirtualPortPair};
+use vital_reader::reader::Pipeline;
+use vital_reader::SerialConfig;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+fn has_message(records: &[Record]) -> bool {
+    records
+        .iter()
+        .any(|record| matches!(record.event, Event::Message(_)))
+}
+
+#[test]
+fn test_loopback_preset_from_name() {
+    for name in ["hl7", "mllp", "vitals", "waveform"] {
+        assert_eq!(LoopbackPreset::from_name(name).unwrap().name(), name);
+    }
+    assert_eq!(LoopbackPreset::Waveform.protocol(), "binary");
+    assert!(LoopbackPreset::from_name("ecg").is_err());
+}
+
+#[test]
+fn test_virtual_pair_has_device_path() {
+    let pair = VirtualPortPair::open().unwrap();
+    assert!(pair.path().starts_with("/dev/pts/"), "{}", pair.path());
+}
+
+#[test]
+fn test_loopback_hl7_lines() {
+    let mut loopback = Loopback::start(LoopbackPreset::Hl7);
+    let records = loopback.read_until(TIMEOUT, has_message);
+    loopback.stop();
+
+    let message = records
+        .iter()
+        .find_map(|record| match &record.event {
+            Event::Message(message) => Some(message),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(message.message_type().as_deref(), Some("ORU^R01"));
+    assert!(records
+        .iter()
+        .any(|record| matches!(&record.event, Event::Observation(o) if o.code == "8867-4")));
+}
+
+#[test]
+fn test_loopback_mllp_with_ack() {
+    let mut pipeline = Pipeline::new();
+    pipeline.set_ack(AckConfig::default());
+    let mut loopback = Loopback::with_pipeline(LoopbackPreset::Mllp, pipeline);
+
+    let records = loopback.read_until(TIMEOUT, |records| {
+        records
+            .iter()
+            .any(|record| matches!(record.event, Event::AckSent { .. }))
+    });
+    let reply = loopback.device_received();
+    loopback.stop();
+
+    assert!(has_message(&records));
+    let mut codec = MllpCodec::new();
+    let frames = codec.decode(&reply);
+    assert!(!frames.is_empty());
+    assert!(String::from_utf8_lossy(&frames[0]).contains("MSA|AA|MSG000001"));
+}
+
+#[test]
+fn test_loopback_vitals_and_waveform() {
+    let mut loopback = Loopback::start(LoopbackPreset::Vitals);
+    let records = loopback.read_until(TIMEOUT, |records| !records.is_empty());
+    loopback.stop();
+    assert!(matches!(
+        &records[0].event,
+        Event::Data { bytes, .. } if bytes.starts_with(b"PATIENT_ID=12345|HR=")
+    ));
+
+    let mut loopback = Loopback::start(LoopbackPreset::Waveform);
+    let records = loopback.read_until(TIMEOUT, |records| records.len() >= 5);
+    loopback.stop();
+    assert!(records
+        .iter()
+        .all(|record| matches!(record.event, Event::Packet(BinaryEvent::Packet(_)))));
+}
+
+#[test]
+fn test_port_connection_health() {
+    let pair = VirtualPortPair::open().unwrap();
+    let mut port = PortConnection::open(pair.path(), &SerialConfig::default(), 50).unwrap();
+    assert!(port.is_connected());
+
+    // A pseudo-terminal has no modem lines to report
+    let _ = port.modem_state();
+
+    let mut buffer = [0u8; 16];
+    assert_eq!(port.read(&mut buffer).unwrap(), 0);
+    assert!(port.is_connected());
+}
+
+#[test]
+fn test_port_connection_serial_options() {
+    let pair = VirtualPortPair::open().unwrap();
+    let config = SerialConfig::from_string("9600,M,7,1,xonxoff,wbuf=4").unwrap();
+    let mut port = PortConnection::open(pair.path(), &config, 50).unwrap();
+
+    // Longer writes go out in write buffer sized pieces, all of them
+    assert_eq!(port.write(b"0123456789").unwrap(), 10);
+    port.flush().unwrap();
+
+    let mut device = pair.device().unwrap();
+    let mut received = Vec::new();
+    let mut buffer = [0u8; 16];
+    while let Ok(n @ 1..) = std::io::Read::read(&mut device, &mut buffer) {
+        received.extend_from_slice(&buffer[..n]);
+    }
+    assert_eq!(received, b"0123456789");
+}
@@ -1,2 +1,6 @@
 pub mod end_to_end_tests;
 pub mod coverage_helpers;
+#[cfg(target_os = "linux")]
+pub mod loopback;
+#[cfg(target_os = "linux")]
+mod loopback_tests;
@@ -0,0 +1,273 @@
+use std::io::{BufWriter, Cursor, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serialport::FlowControl;
+use vital_reader::capture::{
+    CaptureEntry, CaptureMetadata, CaptureReader, CaptureRecord, CaptureWriter, CAPTURE_VERSION,
+    MAX_ENTRY_SIZE,
+};
+use vital_reader::SerialConfig;
+
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    fn bytes(&self) -> Vec<u8> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn metadata() -> CaptureMetadata {
+    let config = SerialConfig::new(9600, 7, "even", 2).unwrap();
+    let mut metadata = CaptureMetadata::new("/dev/ttyUSB0", &config);
+    metadata.started = "2025-01-04 12:00:00.000".to_string();
+    metadata
+}
+
+fn reader(bytes: Vec<u8>) -> CaptureReader {
+    CaptureReader::with_reader(Box::new(Cursor::new(bytes))).unwrap()
+}
+
+#[test]
+fn test_metadata_round_trip() {
+    let metadata = metadata();
+    assert_eq!(metadata.baud, 9600);
+    assert_eq!(metadata.data_bits, 7);
+    assert_eq!(metadata.parity, "even");
+    assert_eq!(metadata.stop_bits, 2);
+    assert_eq!(metadata.reader_version, env!("CARGO_PKG_VERSION"));
+
+    let decoded = CaptureMetadata::decode(&metadata.encode()).unwrap();
+    assert_eq!(decoded, metadata);
+    assert_eq!(decoded.serial_config().unwrap().baud, 9600);
+}
+
+#[test]
+fn test_metadata_keeps_full_config() {
+    let config = SerialConfig::from_string("57600,0,8,1,rtscts,dtr=on,rbuf=4096").unwrap();
+    let metadata = CaptureMetadata::new("/dev/ttyUSB0", &config);
+    assert_eq!(
+        metadata.config.as_deref(),
+        Some("57600,0,8,1,rtscts,dtr=on,rbuf=4096")
+    );
+
+    let decoded = CaptureMetadata::decode(&metadata.encode()).unwrap();
+    let restored = decoded.serial_config().unwrap();
+    assert_eq!(restored.flow_control, FlowControl::Hardware);
+    assert_eq!(restored.dtr, Some(true));
+    assert_eq!(restored.read_buffer, 4096);
+    assert_eq!(restored.to_config_string(), config.to_config_string());
+}
+
+#[test]
+fn test_metadata_without_config_key() {
+    let decoded =
+        CaptureMetadata::decode(b"port=COM3\nbaud=9600\ndata_bits=7\nparity=even\nstop_bits=2\n")
+            .unwrap();
+    assert_eq!(decoded.config, None);
+    assert!(!String::from_utf8(decoded.encode())
+        .unwrap()
+        .contains("config="));
+
+    let config = decoded.serial_config().unwrap();
+    assert_eq!(config.to_config_string(), "9600,2,7,2");
+}
+
+#[test]
+fn test_metadata_ignores_unknown_keys() {
+    let decoded = CaptureMetadata::decode(b"port=COM3\nbaud=57600\nflow=hardware\n").unwrap();
+    assert_eq!(decoded.port, "COM3");
+    assert_eq!(decoded.baud, 57600);
+    assert!(CaptureMetadata::decode(b"baud=fast\n").is_err());
+}
+
+#[test]
+fn test_metadata_rejects_out_of_range_numbers() {
+    let error = CaptureMetadata::decode(b"data_bits=264\n")
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("data_bits=264"));
+    assert!(CaptureMetadata::decode(b"stop_bits=-1\n").is_err());
+    assert!(CaptureMetadata::decode(b"baud=4294967296\n").is_err());
+}
+
+#[test]
+fn test_write_read_round_trip() {
+    let buffer = SharedBuffer::default();
+    let mut writer = CaptureWriter::with_writer(Box::new(buffer.clone())).unwrap();
+    let records = vec![
+        CaptureRecord {
+            elapsed: Duration::ZERO,
+            entry: CaptureEntry::Start(metadata()),
+        },
+        CaptureRecord {
+            elapsed: Duration::from_micros(1500),
+            entry: CaptureEntry::Received(b"MSH|^~\\&|A\r".to_vec()),
+        },
+        CaptureRecord {
+            elapsed: Duration::from_millis(20),
+            entry: CaptureEntry::Sent(b"HELLO\r\n".to_vec()),
+        },
+        CaptureRecord {
+            elapsed: Duration::from_secs(3),
+            entry: CaptureEntry::Stop,
+        },
+    ];
+    for record in &records {
+        writer.write_at(record.elapsed, &record.entry).unwrap();
+    }
+    writer.flush().unwrap();
+
+    let mut reader = reader(buffer.bytes());
+    assert_eq!(reader.version(), CAPTURE_VERSION);
+    let read: Vec<CaptureRecord> = reader.by_ref().map(|r| r.unwrap()).collect();
+    assert_eq!(read, records);
+    assert!(reader.next().is_none());
+}
+
+#[test]
+fn test_file_layout() {
+    let buffer = SharedBuffer::default();
+    let mut writer = CaptureWriter::with_writer(Box::new(buffer.clone())).unwrap();
+    writer
+        .write_at(
+            Duration::from_micros(258),
+            &CaptureEntry::Received(vec![0xAA]),
+        )
+        .unwrap();
+
+    assert_eq!(
+        buffer.bytes(),
+        vec![
+            b'V', b'R', b'C', b'A', b'P', 1, 0, // magic, version 1
+            2, // received
+            0x02, 0x01, 0, 0, 0, 0, 0, 0, // 258 us
+            1, 0, 0, 0,    // length
+            0xAA, // payload
+        ]
+    );
+}
+
+#[test]
+fn test_writer_flushes_each_entry() {
+    let buffer = SharedBuffer::default();
+    let mut writer = CaptureWriter::with_writer(Box::new(BufWriter::new(buffer.clone()))).unwrap();
+    writer
+        .write(&CaptureEntry::Received(vec![1, 2, 3]))
+        .unwrap();
+
+    let read: Vec<CaptureEntry> = reader(buffer.bytes()).map(|r| r.unwrap().entry).collect();
+    assert_eq!(read, vec![CaptureEntry::Received(vec![1, 2, 3])]);
+}
+
+#[test]
+fn test_writer_timestamps_are_monotonic() {
+    let buffer = SharedBuffer::default();
+    let mut writer = CaptureWriter::with_writer(Box::new(buffer.clone())).unwrap();
+    writer.write(&CaptureEntry::Received(vec![1])).unwrap();
+    std::thread::sleep(Duration::from_millis(2));
+    writer.write(&CaptureEntry::Received(vec![2])).unwrap();
+
+    let times: Vec<Duration> = reader(buffer.bytes()).map(|r| r.unwrap().elapsed).collect();
+    assert!(times[1] >= times[0] + Duration::from_millis(2));
+}
+
+#[test]
+fn test_reader_skips_unknown_entries() {
+    let mut bytes = b"VRCAP\x01\x00".to_vec();
+    bytes.extend_from_slice(&[99, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 1, 2]);
+    bytes.extend_from_slice(&[4, 5, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+    let read: Vec<CaptureRecord> = reader(bytes).map(|r| r.unwrap()).collect();
+    assert_eq!(
+        read,
+        vec![CaptureRecord {
+            elapsed: Duration::from_micros(5),
+            entry: CaptureEntry::Stop,
+        }]
+    );
+}
+
+#[test]
+fn test_reader_rejects_bad_files() {
+    let open = |bytes: &[u8]| {
+        CaptureReader::with_reader(Box::new(Cursor::new(bytes.to_vec())))
+            .err()
+            .unwrap()
+            .to_string()
+    };
+    assert!(open(b"VRC").contains("header too short"));
+    assert!(open(b"PCAP\x01\x00\x00").contains("bad magic"));
+    assert!(open(b"VRCAP\x09\x00").contains("Unsupported capture version: 9"));
+}
+
+#[test]
+fn test_reader_ends_at_truncated_entry() {
+    let mut bytes = b"VRCAP\x01\x00".to_vec();
+    bytes.extend_from_slice(&[2, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 7]);
+    bytes.extend_from_slice(&[2, 0, 0, 0, 0, 0, 0, 0, 0, 10, 0, 0, 0, 1, 2]);
+
+    let mut cut = reader(bytes);
+    assert!(!cut.truncated());
+    assert_eq!(
+        cut.next().unwrap().unwrap().entry,
+        CaptureEntry::Received(vec![7])
+    );
+    assert!(cut.next().is_none());
+    assert!(cut.truncated());
+
+    let mut header_only = reader(b"VRCAP\x01\x00\x02\x00\x00".to_vec());
+    assert!(header_only.next().is_none());
+    assert!(header_only.truncated());
+}
+
+#[test]
+fn test_reader_rejects_oversized_entry() {
+    let mut bytes = b"VRCAP\x01\x00".to_vec();
+    bytes.extend_from_slice(&[2, 0, 0, 0, 0, 0, 0, 0, 0]);
+    bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+
+    let mut reader = reader(bytes);
+    let error = reader.next().unwrap().unwrap_err().to_string();
+    assert!(error.contains("Capture entry too large"));
+    assert!(reader.next().is_none());
+
+    let mut writer = CaptureWriter::with_writer(Box::new(SharedBuffer::default())).unwrap();
+    let entry = CaptureEntry::Received(vec![0; MAX_ENTRY_SIZE + 1]);
+    assert!(writer.write(&entry).is_err());
+}
+
+#[test]
+fn test_create_and_open_file() {
+    let path = std::env::temp_dir().join(format!("vital-reader-{}.vrcap", std::process::id()));
+    let path = path.to_str().unwrap();
+
+    let mut writer = CaptureWriter::create(path).unwrap();
+    writer.write(&CaptureEntry::Start(metadata())).unwrap();
+    writer.write(&CaptureEntry::Stop).unwrap();
+    writer.flush().unwrap();
+    drop(writer);
+
+    let entries: Vec<CaptureEntry> = CaptureReader::open(path)
+        .unwrap()
+        .map(|r| r.unwrap().entry)
+        .collect();
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(
+        entries,
+        vec![CaptureEntry::Start(metadata()), CaptureEntry::Stop]
+    );
+    assert!(CaptureReader::open(path).is_err());
+}
@@ -1 +1,2 @@
+mod profile_tests;
 mod serial_config_tests;
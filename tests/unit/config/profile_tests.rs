@@ -0,0 +1,187 @@
+use vital_reader::config::{Profile, Profiles};
+use vital_reader::port::PortRule;
+
+const PROFILES: &str = r#"
+[profile.drager-evita]
+config = "19200,2,8,1"
+driver = "medibus"
+interval = 5
+output = "observations"
+timeout = 200
+ack = true
+ack_app = "VITALS"
+
+[profile.drager-evita.rule]
+vid = "0403"
+serial_number = "A12BC3"
+
+[profile.ge-monitor]
+port = "/dev/ttyUSB0"
+baud = 115200
+parity = "none"
+protocol = "mllp"
+stats = true
+"#;
+
+fn temp_path(name: &str) -> String {
+    std::env::temp_dir()
+        .join(format!("vital-reader-{}-{}.toml", name, std::process::id()))
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[test]
+fn test_parse_profiles() {
+    let profiles = Profiles::parse(PROFILES).unwrap();
+    assert_eq!(profiles.profiles().len(), 2);
+
+    let evita = profiles.get("drager-evita").unwrap();
+    assert_eq!(evita.config.as_deref(), Some("19200,2,8,1"));
+    assert_eq!(evita.driver.as_deref(), Some("medibus"));
+    assert_eq!(evita.interval, Some(5));
+    assert_eq!(evita.timeout, Some(200));
+    assert_eq!(evita.ack, Some(true));
+    assert_eq!(evita.ack_app.as_deref(), Some("VITALS"));
+    assert_eq!(evita.port, None);
+
+    let rule = evita.rule.as_ref().unwrap();
+    assert_eq!(rule.name, "drager-evita");
+    assert_eq!(rule.vid, Some(0x0403));
+    assert_eq!(rule.serial_number.as_deref(), Some("A12BC3"));
+
+    let ge = profiles.get("ge-monitor").unwrap();
+    assert_eq!(ge.port.as_deref(), Some("/dev/ttyUSB0"));
+    assert_eq!(ge.baud.as_deref(), Some("115200"));
+    assert_eq!(ge.protocol.as_deref(), Some("mllp"));
+    assert_eq!(ge.stats, Some(true));
+    assert!(ge.rule.is_none());
+}
+
+#[test]
+fn test_parse_empty_file() {
+    assert!(Profiles::parse("").unwrap().profiles().is_empty());
+}
+
+#[test]
+fn test_parse_auto_baud() {
+    let profiles = Profiles::parse("[profile.unknown]\nbaud = \"auto\"\n").unwrap();
+    assert_eq!(
+        profiles.get("unknown").unwrap().baud.as_deref(),
+        Some("auto")
+    );
+}
+
+#[test]
+fn test_parse_rejects_invalid_values() {
+    let cases = [
+        ("config = \"19200,3,8,1\"", "invalid config"),
+        ("parity = \"sometimes\"", "Invalid parity"),
+        ("data_bits = 9", "Invalid data bits"),
+        ("timeout = \"fast\"", "timeout must be a positive integer"),
+        ("ack = 1", "ack must be true or false"),
+        ("baud = \"fast\"", "baud must be a positive integer"),
+        ("colour = \"blue\"", "unknown key colour"),
+        ("rule = \"usb\"", "rule must be a table"),
+    ];
+    for (line, message) in cases {
+        let text = format!("[profile.bad]\n{}\n", line);
+        let error = format!("{:#}", Profiles::parse(&text).unwrap_err());
+        assert!(error.contains(message), "{}: {}", line, error);
+    }
+}
+
+#[test]
+fn test_parse_rejects_empty_rule() {
+    let result = Profiles::parse("[profile.any]\n[profile.any.rule]\n");
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_select_unknown_profile() {
+    let profiles = Profiles::parse(PROFILES).unwrap();
+    assert_eq!(profiles.select("ge-monitor").unwrap().name, "ge-monitor");
+    let error = profiles.select("philips").unwrap_err().to_string();
+    assert!(error.contains("No profile named philips"));
+    assert!(error.contains("drager-evita"));
+}
+
+#[test]
+fn test_save_and_load_round_trip() {
+    let path = temp_path("profiles");
+    let _ = std::fs::remove_file(&path);
+
+    let mut profile = Profile::new("bench");
+    profile.port = Some("/dev/ttyUSB1".to_string());
+    profile.config = Some("9600,2,7,1".to_string());
+    profile.baud = Some("auto".to_string());
+    profile.watchdog = Some(60);
+    profile.reconnect = Some(true);
+    let mut rule = PortRule::new("bench");
+    rule.vid = Some(0x067b);
+    rule.product = Some("PL2303".to_string());
+    profile.rule = Some(rule);
+
+    Profiles::save(&path, &profile).unwrap();
+    let loaded = Profiles::load(&path).unwrap();
+    assert_eq!(loaded.get("bench"), Some(&profile));
+
+    // Saving again replaces the profile and keeps the others
+    let mut other = Profile::new("spare");
+    other.output = Some("jsonl".to_string());
+    Profiles::save(&path, &other).unwrap();
+    profile.watchdog = Some(10);
+    Profiles::save(&path, &profile).unwrap();
+
+    let loaded = Profiles::load(&path).unwrap();
+    assert_eq!(loaded.profiles().len(), 2);
+    assert_eq!(loaded.get("bench").unwrap().watchdog, Some(10));
+    assert_eq!(loaded.get("spare"), Some(&other));
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_save_refuses_invalid_profile() {
+    let path = temp_path("invalid-profile");
+    let mut profile = Profile::new("broken");
+    profile.config = Some("fast".to_string());
+    assert!(Profiles::save(&path, &profile).is_err());
+    assert!(!std::path::Path::new(&path).exists());
+}
+
+#[test]
+fn test_load_missing_file() {
+    let error = Profiles::load("/nonexistent/config.toml").unwrap_err();
+    assert!(error.to_string().contains("/nonexistent/config.toml"));
+}
+
+#[test]
+fn test_parse_device() {
+    let profiles = Profiles::parse("[profile.icu]\ndevice = \"drager-evita\"\n").unwrap();
+    assert_eq!(
+        profiles.get("icu").unwrap().device.as_deref(),
+        Some("drager-evita")
+    );
+}
+
+#[test]
+fn test_with_defaults() {
+    let mut defaults = Profile::new("drager-evita");
+    defaults.config = Some("19200,2,8,1".to_string());
+    defaults.driver = Some("medibus".to_string());
+    defaults.output = Some("raw".to_string());
+
+    let mut profile = Profile::new("icu");
+    profile.output = Some("jsonl".to_string());
+    let merged = profile.clone().with_defaults(&defaults);
+    assert_eq!(merged.name, "icu");
+    assert_eq!(merged.config.as_deref(), Some("19200,2,8,1"));
+    assert_eq!(merged.driver.as_deref(), Some("medibus"));
+    assert_eq!(merged.output.as_deref(), Some("jsonl"));
+
+    // A baud rate alone does not pick up the defaults' parity
+    profile.baud = Some("9600".to_string());
+    let merged = profile.with_defaults(&defaults);
+    assert_eq!(merged.config, None);
+    assert_eq!(merged.baud.as_deref(), Some("9600"));
+}
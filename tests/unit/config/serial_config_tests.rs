@@ -193,4 +193,91 @@ fn test_parse_invalid_parity_values() {
     for config_str in invalid_parities {
         assert!(SerialConfig::from_string(config_str).is_err());
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_config_display() {
+    assert_eq!(SerialConfig::default().to_string(), "115200 8N1");
+    let config = SerialConfig::from_string("9600,2,7,2").unwrap();
+    assert_eq!(config.to_string(), "9600 7E2");
+}
+
+#[test]
+fn test_config_string_options() {
+    let config = SerialConfig::from_string("9600,E,7,1,rtscts,dtr=on,rbuf=256,wbuf=64").unwrap();
+    assert_eq!(config.parity, serialport::Parity::Even);
+    assert_eq!(config.data_bits, serialport::DataBits::Seven);
+    assert_eq!(config.flow_control, serialport::FlowControl::Hardware);
+    assert_eq!(config.dtr, Some(true));
+    assert_eq!(config.rts, None);
+    assert_eq!(config.read_buffer, 256);
+    assert_eq!(config.write_buffer, 64);
+
+    let config = SerialConfig::from_string("19200,n,8,1,xonxoff,rts=off").unwrap();
+    assert_eq!(config.flow_control, serialport::FlowControl::Software);
+    assert_eq!(config.rts, Some(false));
+
+    let defaults = SerialConfig::from_string("115200,0,8,1").unwrap();
+    assert_eq!(defaults.flow_control, serialport::FlowControl::None);
+    assert_eq!(defaults.dtr, None);
+    assert_eq!(defaults.read_buffer, 1024);
+}
+
+#[test]
+fn test_mark_space_parity_and_one_and_a_half_stop_bits() {
+    let mark = SerialConfig::from_string("9600,M,7,1").unwrap();
+    assert!(mark.stick_parity);
+    assert_eq!(mark.to_string(), "9600 7M1");
+
+    let space = SerialConfig::new(9600, 8, "space", 1).unwrap();
+    assert_eq!(space.to_string(), "9600 8S1");
+
+    let config = SerialConfig::from_string("1200,0,5,1.5").unwrap();
+    assert_eq!(config.stop_bits, serialport::StopBits::Two);
+    assert_eq!(config.to_string(), "1200 5N1.5");
+    assert!(SerialConfig::from_string("9600,0,8,1.5").is_err());
+}
+
+#[test]
+fn test_config_string_rejects_bad_options() {
+    let cases = [
+        ("9600,0,8,1,", "Unknown serial option"),
+        ("9600,0,8,1,ctsrts", "Unknown serial option"),
+        ("9600,0,8,1,dtr=maybe", "Invalid dtr level"),
+        ("9600,0,8,1,rbuf=0", "Invalid rbuf size"),
+        ("9600,0,8,1,wbuf=big", "Invalid wbuf size"),
+        ("9600,0,8,1,rtscts,rts=on", "RTS is driven by the port"),
+        ("9600,X,8,1", "Parity must be"),
+    ];
+    for (config, message) in cases {
+        let error = SerialConfig::from_string(config).unwrap_err().to_string();
+        assert!(error.contains(message), "{}: {}", config, error);
+    }
+}
+
+#[test]
+fn test_config_string_round_trip() {
+    for text in [
+        "115200,0,8,1",
+        "9600,2,7,2,rtscts,dtr=off",
+        "1200,S,5,1.5,xonxoff,rts=on,rbuf=64,wbuf=16",
+    ] {
+        let config = SerialConfig::from_string(text).unwrap();
+        assert_eq!(config.to_config_string(), text);
+    }
+}
+
+#[test]
+fn test_validate_rejects_empty_buffers() {
+    let config = SerialConfig {
+        write_buffer: 0,
+        ..SerialConfig::default()
+    };
+    assert!(config.validate().is_err());
+
+    let config = SerialConfig {
+        read_buffer: 0,
+        ..SerialConfig::default()
+    };
+    assert!(config.validate().is_err());
+}
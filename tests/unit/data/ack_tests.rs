@@ -0,0 +1,115 @@
+use vital_reader::data::hl7::{AckCode, AckConfig, AckGenerator, Message};
+
+const ORU: &str = "MSH|^~\\&|GE_MONITOR|ICU_01|VITAL_REC|HOSPITAL|20250104120000||ORU^R01|MSG000001|P|2.5\rOBX|1|NM|8867-4^Heart Rate^LN||72|bpm|60-100|N|||F\r";
+
+fn parse(text: &str) -> Message {
+    Message::parse(text).unwrap()
+}
+
+#[test]
+fn test_ack_code_strings() {
+    assert_eq!(AckCode::Accept.as_str(), "AA");
+    assert_eq!(AckCode::Error.as_str(), "AE");
+    assert_eq!(AckCode::Reject.as_str(), "AR");
+}
+
+#[test]
+fn test_evaluate_valid_message() {
+    assert_eq!(AckCode::evaluate(&parse(ORU)), (AckCode::Accept, None));
+}
+
+#[test]
+fn test_evaluate_missing_message_type() {
+    let (code, text) = AckCode::evaluate(&parse("MSH|^~\\&|A|B|C|D|20250104|||1|P|2.5\r"));
+    assert_eq!(code, AckCode::Reject);
+    assert!(text.unwrap().contains("MSH-9"));
+}
+
+#[test]
+fn test_evaluate_unsupported_version() {
+    let (code, _) = AckCode::evaluate(&parse("MSH|^~\\&|A|B|C|D|20250104||ORU^R01|1|P|3.0\r"));
+    assert_eq!(code, AckCode::Reject);
+}
+
+#[test]
+fn test_evaluate_unsupported_processing_id() {
+    let (code, text) = AckCode::evaluate(&parse("MSH|^~\\&|A|B|C|D|20250104||ORU^R01|1|X|2.5\r"));
+    assert_eq!(code, AckCode::Reject);
+    assert!(text.unwrap().contains("X"));
+}
+
+#[test]
+fn test_evaluate_missing_control_id() {
+    let (code, _) = AckCode::evaluate(&parse("MSH|^~\\&|A|B|C|D|20250104||ORU^R01||P|2.5\r"));
+    assert_eq!(code, AckCode::Error);
+}
+
+#[test]
+fn test_ack_copies_control_id_and_swaps_endpoints() {
+    let mut generator = AckGenerator::default();
+    let ack = generator.acknowledge(&parse(ORU));
+
+    let msh = ack.segment("MSH").unwrap();
+    assert_eq!(msh.value(3), "VITAL_REC");
+    assert_eq!(msh.value(4), "HOSPITAL");
+    assert_eq!(msh.value(5), "GE_MONITOR");
+    assert_eq!(msh.value(6), "ICU_01");
+    assert_eq!(ack.message_type().as_deref(), Some("ACK^R01^ACK"));
+    assert_eq!(msh.value(11), "P");
+    assert_eq!(ack.version(), Some("2.5"));
+
+    let msa = ack.segment("MSA").unwrap();
+    assert_eq!(msa.value(1), "AA");
+    assert_eq!(msa.value(2), "MSG000001");
+}
+
+#[test]
+fn test_ack_uses_configured_identity() {
+    let mut generator = AckGenerator::new(AckConfig {
+        application: Some("COLLECTOR".to_string()),
+        facility: Some("WARD_3".to_string()),
+    });
+    let ack = generator.acknowledge(&parse(ORU));
+    let msh = ack.segment("MSH").unwrap();
+    assert_eq!(msh.value(3), "COLLECTOR");
+    assert_eq!(msh.value(4), "WARD_3");
+    assert_eq!(msh.value(5), "GE_MONITOR");
+}
+
+#[test]
+fn test_ack_control_ids_are_unique() {
+    let mut generator = AckGenerator::default();
+    let message = parse(ORU);
+    let first = generator.acknowledge(&message);
+    let second = generator.acknowledge(&message);
+    assert_ne!(first.control_id(), second.control_id());
+}
+
+#[test]
+fn test_ack_with_explicit_code_and_text() {
+    let mut generator = AckGenerator::default();
+    let ack = generator.build(&parse(ORU), AckCode::Error, Some("Unknown a|b"));
+    let msa = ack.segment("MSA").unwrap();
+    assert_eq!(msa.value(1), "AE");
+    assert_eq!(msa.value(3), "Unknown a|b");
+    assert!(ack.encode().contains("Unknown a\\F\\b"));
+}
+
+#[test]
+fn test_ack_round_trips_through_parser() {
+    let mut generator = AckGenerator::default();
+    let ack = generator.acknowledge(&parse(ORU));
+    let reparsed = Message::parse(&ack.encode()).unwrap();
+    assert_eq!(reparsed, ack);
+    assert!(ack.encode().starts_with("MSH|^~\\&|"));
+}
+
+#[test]
+fn test_ack_keeps_sender_encoding_characters() {
+    let mut generator = AckGenerator::default();
+    let message = parse("MSH#:*!@#APP#FAC#REC#RFAC#20250104##ORU:R01#42#P#2.5\r");
+    let ack = generator.acknowledge(&message);
+    let encoded = ack.encode();
+    assert!(encoded.starts_with("MSH#:*!@#REC#RFAC#APP#FAC#"));
+    assert!(encoded.contains("MSA#AA#42"));
+}
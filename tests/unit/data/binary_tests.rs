@@ -0,0 +1,228 @@
+use vital_reader::data::{
+    BinaryDecoder, BinaryEvent, BinaryFrameConfig, BinaryPacket, ChecksumKind, DataParser, Framing,
+    PacketSize,
+};
+
+fn simulator_packet(seq: u8, value: u8) -> Vec<u8> {
+    vec![0x02, seq, value, seq.wrapping_add(value), 0x03]
+}
+
+fn packet(sequence: u8, samples: &[u16]) -> BinaryEvent {
+    BinaryEvent::Packet(BinaryPacket {
+        sequence: Some(sequence),
+        samples: samples.to_vec(),
+    })
+}
+
+#[test]
+fn test_decode_simulator_packet() {
+    let mut decoder = BinaryDecoder::default();
+    let events = decoder.decode(&simulator_packet(7, 128));
+
+    assert_eq!(events, vec![packet(7, &[128])]);
+    assert_eq!(decoder.packets(), 1);
+    assert_eq!(decoder.discarded_bytes(), 0);
+}
+
+#[test]
+fn test_decode_simulator_stream() {
+    let mut decoder = BinaryDecoder::default();
+    let mut stream = Vec::new();
+    for i in 0..300u32 {
+        let value = (128.0 + 127.0 * (i as f64 * 0.1).sin()) as u8;
+        stream.extend(simulator_packet(i as u8, value));
+    }
+
+    let events = decoder.decode(&stream);
+
+    assert_eq!(events.len(), 300);
+    assert!(events.iter().all(|e| matches!(e, BinaryEvent::Packet(_))));
+    assert_eq!(decoder.packets(), 300);
+    assert_eq!(decoder.sequence_gaps(), 0);
+    assert_eq!(decoder.checksum_errors(), 0);
+}
+
+#[test]
+fn test_decode_framing_bytes_inside_packet() {
+    let mut decoder = BinaryDecoder::default();
+    let events = decoder.decode(&[0x02, 0x02, 0x03, 0x05, 0x03]);
+    assert_eq!(events, vec![packet(2, &[3])]);
+}
+
+#[test]
+fn test_decode_packet_split_across_chunks() {
+    let mut decoder = BinaryDecoder::default();
+    let data = simulator_packet(1, 50);
+
+    assert!(decoder.decode(&data[..2]).is_empty());
+    assert_eq!(decoder.decode(&data[2..]), vec![packet(1, &[50])]);
+}
+
+#[test]
+fn test_decode_checksum_error() {
+    let mut decoder = BinaryDecoder::default();
+    let events = decoder.decode(&[0x02, 0x04, 0x10, 0xFF, 0x03]);
+
+    assert_eq!(
+        events,
+        vec![BinaryEvent::ChecksumError {
+            sequence: Some(4),
+            expected: 0x14,
+            received: 0xFF,
+        }]
+    );
+    assert_eq!(decoder.checksum_errors(), 1);
+    assert_eq!(decoder.packets(), 0);
+}
+
+#[test]
+fn test_decode_checksum_error_is_not_a_sequence_gap() {
+    let mut decoder = BinaryDecoder::default();
+    let mut stream = simulator_packet(10, 1);
+    stream.extend([0x02, 11, 0x10, 0xFF, 0x03]);
+    stream.extend(simulator_packet(12, 3));
+
+    let events = decoder.decode(&stream);
+
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[2], packet(12, &[3]));
+    assert_eq!(decoder.checksum_errors(), 1);
+    assert_eq!(decoder.sequence_gaps(), 0);
+    assert_eq!(decoder.lost_packets(), 0);
+}
+
+#[test]
+fn test_decode_sequence_gap() {
+    let mut decoder = BinaryDecoder::default();
+    let mut stream = simulator_packet(10, 1);
+    stream.extend(simulator_packet(11, 2));
+    stream.extend(simulator_packet(15, 3));
+
+    let events = decoder.decode(&stream);
+
+    assert_eq!(
+        events,
+        vec![
+            packet(10, &[1]),
+            packet(11, &[2]),
+            BinaryEvent::SequenceGap {
+                expected: 12,
+                received: 15,
+            },
+            packet(15, &[3]),
+        ]
+    );
+    assert_eq!(decoder.sequence_gaps(), 1);
+    assert_eq!(decoder.lost_packets(), 3);
+}
+
+#[test]
+fn test_decode_sequence_wraps_without_gap() {
+    let mut decoder = BinaryDecoder::default();
+    let mut stream = simulator_packet(255, 1);
+    stream.extend(simulator_packet(0, 2));
+
+    decoder.decode(&stream);
+
+    assert_eq!(decoder.packets(), 2);
+    assert_eq!(decoder.sequence_gaps(), 0);
+}
+
+#[test]
+fn test_decode_resyncs_after_missing_end_byte() {
+    let mut decoder = BinaryDecoder::default();
+    let mut stream = vec![0x02, 0x01, 0x20, 0x21];
+    stream.extend(simulator_packet(2, 0x30));
+
+    let events = decoder.decode(&stream);
+
+    assert_eq!(events, vec![packet(2, &[0x30])]);
+    assert_eq!(decoder.discarded_bytes(), 3);
+}
+
+#[test]
+fn test_decode_discards_noise_between_packets() {
+    let mut decoder = BinaryDecoder::default();
+    let mut stream = vec![0xAA, 0x55];
+    stream.extend(simulator_packet(0, 9));
+
+    assert_eq!(decoder.decode(&stream), vec![packet(0, &[9])]);
+    assert_eq!(decoder.discarded_bytes(), 2);
+}
+
+#[test]
+fn test_decode_length_prefixed_xor_packets() {
+    let config = BinaryFrameConfig {
+        start: 0xA5,
+        end: Some(0x5A),
+        size: PacketSize::LengthPrefixed,
+        checksum: ChecksumKind::Xor8,
+        sequence: true,
+        sample_width: 2,
+    };
+    let mut decoder = BinaryDecoder::new(config);
+    let body = [0x05, 0x09, 0x01, 0x2C, 0x00, 0x64];
+    let mut stream = vec![0xA5];
+    stream.extend(body);
+    stream.push(ChecksumKind::Xor8.compute(&body));
+    stream.push(0x5A);
+
+    assert_eq!(decoder.decode(&stream), vec![packet(9, &[300, 100])]);
+}
+
+#[test]
+fn test_decode_without_end_byte_or_checksum() {
+    let config = BinaryFrameConfig {
+        start: 0xFF,
+        end: None,
+        size: PacketSize::Fixed(2),
+        checksum: ChecksumKind::None,
+        sequence: false,
+        sample_width: 1,
+    };
+    let mut decoder = BinaryDecoder::new(config);
+
+    let events = decoder.decode(&[0xFF, 0x10, 0x20, 0xFF, 0x30, 0x40]);
+
+    assert_eq!(
+        events,
+        vec![
+            BinaryEvent::Packet(BinaryPacket {
+                sequence: None,
+                samples: vec![0x10, 0x20],
+            }),
+            BinaryEvent::Packet(BinaryPacket {
+                sequence: None,
+                samples: vec![0x30, 0x40],
+            }),
+        ]
+    );
+}
+
+#[test]
+fn test_checksum_from_name() {
+    assert_eq!(ChecksumKind::from_name("sum8").unwrap(), ChecksumKind::Sum8);
+    assert_eq!(ChecksumKind::from_name("XOR").unwrap(), ChecksumKind::Xor8);
+    assert_eq!(ChecksumKind::from_name("none").unwrap(), ChecksumKind::None);
+    assert!(ChecksumKind::from_name("crc32").is_err());
+}
+
+#[test]
+fn test_framing_binary_from_name() {
+    assert_eq!(Framing::from_name("binary").unwrap(), Framing::Binary);
+    assert_eq!(Framing::Binary.name(), "binary");
+}
+
+#[test]
+fn test_parser_binary_framing_decodes_packets() {
+    let mut parser = DataParser::with_framing(Framing::Binary);
+    let mut stream = simulator_packet(0, 100);
+    stream.extend(simulator_packet(2, 101));
+
+    parser.process_data(&stream, "12:00:00.000");
+
+    let stats = parser.decoder().stats();
+    assert!(stats.contains(&("Packets", 2)));
+    assert!(stats.contains(&("Lost packets", 1)));
+    assert!(parser.take_messages().is_empty());
+}
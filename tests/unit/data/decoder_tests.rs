@@ -0,0 +1,192 @@
+use vital_reader::data::{
+    DataParser, Decoder, DecoderRegistry, Frame, Framing, Hl7Decoder, LineDecoder, MllpCodec,
+    MllpDecoder, RawDecoder, VitalObservation,
+};
+use vital_reader::output::Event;
+
+const MESSAGE: &str = "MSH|^~\\&|GE_MONITOR|ICU_01|VITAL_REC|HOSPITAL|20250104120000||ORU^R01|MSG000001|P|2.5\rOBX|1|NM|8867-4^Heart Rate^LN||72|bpm|60-100|N|||F\r";
+
+/// Turns `HR=<value>` lines into observations, like an in-house device would
+struct HeartRateDecoder {
+    lines: LineDecoder,
+}
+
+impl Decoder for HeartRateDecoder {
+    fn name(&self) -> &'static str {
+        "heart-rate"
+    }
+
+    fn feed(&mut self, data: &[u8]) -> Vec<Frame> {
+        self.lines
+            .feed(data)
+            .into_iter()
+            .filter_map(|frame| match frame {
+                Frame::Line(line) => {
+                    let text = String::from_utf8(line).ok()?;
+                    let value = text.trim().strip_prefix("HR=")?.parse().ok()?;
+                    Some(Frame::Observation(VitalObservation::numeric(
+                        "8867-4",
+                        "Heart Rate",
+                        "LN",
+                        value,
+                    )))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_line_decoder_splits_on_cr_lf() {
+    let mut decoder = LineDecoder::new();
+    let frames = decoder.feed(b"one\r\ntwo\nthree\rfour");
+
+    assert_eq!(
+        frames,
+        vec![
+            Frame::Line(b"one\r".to_vec()),
+            Frame::Line(b"two\n".to_vec()),
+            Frame::Line(b"three\r".to_vec()),
+        ]
+    );
+    assert_eq!(decoder.finish(), vec![Frame::Line(b"four".to_vec())]);
+    assert!(decoder.finish().is_empty());
+}
+
+#[test]
+fn test_line_decoder_crlf_split_across_chunks() {
+    let mut decoder = LineDecoder::new();
+    assert_eq!(decoder.feed(b"abc\r"), vec![Frame::Line(b"abc\r".to_vec())]);
+    assert!(decoder.feed(b"\n").is_empty());
+}
+
+#[test]
+fn test_hl7_decoder_emits_lines_then_message() {
+    let mut decoder = Hl7Decoder::new();
+    let frames = decoder.feed(MESSAGE.as_bytes());
+
+    assert_eq!(frames.len(), 2);
+    assert!(matches!(frames[0], Frame::Line(_)));
+
+    let frames = decoder.idle();
+    assert!(matches!(&frames[..], [Frame::Message(m)] if m.segments().len() == 2));
+    assert!(decoder.idle().is_empty());
+}
+
+#[test]
+fn test_hl7_decoder_finish_flushes_partial_line() {
+    let mut decoder = Hl7Decoder::new();
+    decoder.feed(b"MSH|^~\\&|A|B\rPID|1||123");
+
+    let frames = decoder.finish();
+
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0], Frame::Line(b"PID|1||123".to_vec()));
+    assert!(matches!(&frames[1], Frame::Message(m) if m.segments().len() == 2));
+}
+
+#[test]
+fn test_mllp_decoder_frames_and_encodes() {
+    let mut decoder = MllpDecoder::new();
+    let frames = decoder.feed(&MllpCodec::encode(MESSAGE.as_bytes()));
+
+    assert_eq!(frames.len(), 3);
+    assert!(matches!(frames[2], Frame::Message(_)));
+    assert_eq!(decoder.encode(b"ACK"), MllpCodec::encode(b"ACK"));
+}
+
+#[test]
+fn test_raw_decoder_passes_chunks_through() {
+    let mut decoder = RawDecoder;
+    assert_eq!(
+        decoder.feed(&[0x01, b'A', b'\r']),
+        vec![Frame::Chunk(vec![0x01, b'A', b'\r'])]
+    );
+    assert!(decoder.feed(&[]).is_empty());
+    assert_eq!(decoder.encode(b"ACK"), b"ACK".to_vec());
+}
+
+#[test]
+fn test_registry_creates_builtin_decoders() {
+    let registry = DecoderRegistry::new();
+    assert_eq!(
+        registry.names(),
+        vec!["hl7", "line", "mllp", "binary", "raw"]
+    );
+    for name in registry.names() {
+        assert_eq!(registry.create(name).unwrap().name(), name);
+    }
+    assert_eq!(registry.create("MLLP").unwrap().name(), "mllp");
+}
+
+#[test]
+fn test_registry_rejects_unknown_protocol() {
+    let error = DecoderRegistry::default()
+        .create("medibus")
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(error.contains("Invalid protocol: medibus"));
+    assert!(error.contains("hl7, line, mllp, binary, raw"));
+}
+
+#[test]
+fn test_registry_registers_custom_decoder() {
+    let mut registry = DecoderRegistry::new();
+    registry.register("heart-rate", || {
+        Box::new(HeartRateDecoder {
+            lines: LineDecoder::new(),
+        })
+    });
+
+    assert_eq!(registry.names().last(), Some(&"heart-rate"));
+    assert_eq!(registry.create("heart-rate").unwrap().name(), "heart-rate");
+}
+
+#[test]
+fn test_registry_register_replaces_existing() {
+    let mut registry = DecoderRegistry::new();
+    registry.register("hl7", || Box::new(RawDecoder));
+
+    assert_eq!(registry.names().len(), 5);
+    assert_eq!(registry.create("hl7").unwrap().name(), "raw");
+}
+
+#[test]
+fn test_framing_protocol_names() {
+    assert_eq!(Framing::Line.protocol(), "hl7");
+    assert_eq!(Framing::Mllp.protocol(), "mllp");
+    assert_eq!(Framing::Binary.protocol(), "binary");
+}
+
+#[test]
+fn test_parser_with_custom_decoder_collects_observations() {
+    let mut parser = DataParser::with_decoder(Box::new(HeartRateDecoder {
+        lines: LineDecoder::new(),
+    }));
+
+    parser.process_data(b"HR=72\r\nHR=bad\r\nHR=75\r\n", "12:00:00");
+
+    let values: Vec<String> = parser
+        .take_records()
+        .into_iter()
+        .filter_map(|record| match record.event {
+            Event::Observation(o) => Some(o.value.to_string()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(values, vec!["72", "75"]);
+    assert!(parser.take_records().is_empty());
+    assert_eq!(parser.framing(), Framing::Line);
+}
+
+#[test]
+fn test_parser_encode_reply_uses_decoder_framing() {
+    let parser = DataParser::with_framing(Framing::Mllp);
+    assert_eq!(parser.encode_reply(b"ACK"), MllpCodec::encode(b"ACK"));
+
+    let parser = DataParser::new();
+    assert_eq!(parser.decoder().name(), "hl7");
+    assert_eq!(parser.encode_reply(b"ACK"), b"ACK".to_vec());
+}
@@ -170,4 +170,20 @@ fn test_format_empty_mixed() {
     let data = b"";
     let result = DataFormatter::format_mixed(data, "12:00:00");
     assert!(result.contains("MIXED"));
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_format_observation() {
+    use vital_reader::data::VitalObservation;
+
+    let mut observation = VitalObservation::numeric("2708-6", "Oxygen Saturation", "LN", 98.0);
+    observation.unit = Some("%".to_string());
+    observation.reference_range = Some("95-100".to_string());
+    observation.producer = Some("GE_MONITOR^SPO2_MODULE".to_string());
+
+    let result = DataFormatter::format_observation(&observation, "12:00:00");
+    assert_eq!(
+        result,
+        "[12:00:00] OBS: Oxygen Saturation (2708-6) = 98 % [95-100] from GE_MONITOR^SPO2_MODULE"
+    );
+}
@@ -0,0 +1,263 @@
+use vital_reader::data::hl7::{EncodingCharacters, Field, Message, MessageAssembler, Segment};
+use vital_reader::data::DataParser;
+
+const ORU: &str =
+    "MSH|^~\\&|GE_MONITOR|ICU_01|VITAL_REC|HOSPITAL|20250104120000||ORU^R01|MSG000001|P|2.5\r\
+PID|1||123456^^^HOSPITAL^MR||DOE^JOHN^A||19800515|M\r\
+OBX|1|NM|8867-4^Heart Rate^LN||72|bpm^beats/min^UCUM|60-100|N|||F\r\
+OBX|2|NM|2708-6^Oxygen Saturation^LN||98|%^percent^UCUM|95-100|N|||F\r";
+
+#[test]
+fn test_encoding_from_msh_default() {
+    let encoding = EncodingCharacters::from_msh("MSH|^~\\&|APP").unwrap();
+    assert_eq!(encoding, EncodingCharacters::default());
+    assert_eq!(encoding.msh2(), "^~\\&");
+}
+
+#[test]
+fn test_encoding_from_msh_custom() {
+    let encoding = EncodingCharacters::from_msh("MSH#:*!@#APP").unwrap();
+    assert_eq!(encoding.field, '#');
+    assert_eq!(encoding.component, ':');
+    assert_eq!(encoding.repetition, '*');
+    assert_eq!(encoding.escape, '!');
+    assert_eq!(encoding.subcomponent, '@');
+}
+
+#[test]
+fn test_encoding_with_truncation_character() {
+    let encoding = EncodingCharacters::from_msh("MSH|^~\\&#|APP").unwrap();
+    assert_eq!(encoding.truncation, Some('#'));
+    assert_eq!(encoding.msh2(), "^~\\&#");
+}
+
+#[test]
+fn test_encoding_rejects_invalid_headers() {
+    assert!(EncodingCharacters::from_msh("PID|1").is_err());
+    assert!(EncodingCharacters::from_msh("MSH").is_err());
+    assert!(EncodingCharacters::from_msh("MSH|^~|APP").is_err());
+    assert!(EncodingCharacters::from_msh("MSH|^^\\&|APP").is_err());
+}
+
+#[test]
+fn test_unescape_delimiters() {
+    let encoding = EncodingCharacters::default();
+    assert_eq!(encoding.unescape("A\\F\\B"), "A|B");
+    assert_eq!(encoding.unescape("A\\S\\B"), "A^B");
+    assert_eq!(encoding.unescape("A\\T\\B"), "A&B");
+    assert_eq!(encoding.unescape("A\\R\\B"), "A~B");
+    assert_eq!(encoding.unescape("A\\E\\B"), "A\\B");
+}
+
+#[test]
+fn test_unescape_hex() {
+    let encoding = EncodingCharacters::default();
+    assert_eq!(encoding.unescape("\\X414243\\"), "ABC");
+    assert_eq!(encoding.unescape("\\XC3A9\\"), "é");
+}
+
+#[test]
+fn test_unescape_keeps_unknown_and_unterminated_sequences() {
+    let encoding = EncodingCharacters::default();
+    assert_eq!(encoding.unescape("line\\.br\\next"), "line\\.br\\next");
+    assert_eq!(encoding.unescape("bad\\X4\\"), "bad\\X4\\");
+    assert_eq!(encoding.unescape("open\\F"), "open\\F");
+}
+
+#[test]
+fn test_escape_round_trip() {
+    let encoding = EncodingCharacters::default();
+    let raw = "a|b^c&d~e\\f";
+    let escaped = encoding.escape(raw);
+    assert_eq!(escaped, "a\\F\\b\\S\\c\\T\\d\\R\\e\\E\\f");
+    assert_eq!(encoding.unescape(&escaped), raw);
+}
+
+#[test]
+fn test_parse_message_segments() {
+    let message = Message::parse(ORU).unwrap();
+    assert_eq!(message.segments().len(), 4);
+    assert_eq!(message.message_type().as_deref(), Some("ORU^R01"));
+    assert_eq!(message.control_id(), Some("MSG000001"));
+    assert_eq!(message.version(), Some("2.5"));
+    assert_eq!(message.segments_named("OBX").count(), 2);
+}
+
+#[test]
+fn test_msh_field_numbering() {
+    let message = Message::parse(ORU).unwrap();
+    let msh = message.segment("MSH").unwrap();
+    assert_eq!(msh.value(1), "|");
+    assert_eq!(msh.value(2), "^~\\&");
+    assert_eq!(msh.value(3), "GE_MONITOR");
+    assert_eq!(msh.value(7), "20250104120000");
+    assert_eq!(msh.component(9, 2), Some("R01"));
+}
+
+#[test]
+fn test_obx_components() {
+    let message = Message::parse(ORU).unwrap();
+    let obx = message.segments_named("OBX").next().unwrap();
+    assert_eq!(obx.value(2), "NM");
+    assert_eq!(obx.component(3, 1), Some("8867-4"));
+    assert_eq!(obx.component(3, 2), Some("Heart Rate"));
+    assert_eq!(obx.component(3, 3), Some("LN"));
+    assert_eq!(obx.value(5), "72");
+    assert_eq!(obx.value(20), "");
+    assert!(obx.field(4).unwrap().is_empty());
+}
+
+#[test]
+fn test_repetitions_and_subcomponents() {
+    let encoding = EncodingCharacters::default();
+    let segment = Segment::parse("PID|1||123^^^HOSP&1.2.3&ISO^MR~456^^^LAB^PI", &encoding).unwrap();
+    let field = segment.field(3).unwrap();
+    assert_eq!(field.repetitions().len(), 2);
+
+    let first = &field.repetitions()[0];
+    let authority = first.component(4).unwrap();
+    assert_eq!(authority.value(), "HOSP");
+    assert_eq!(authority.subcomponent(2), Some("1.2.3"));
+    assert_eq!(authority.subcomponent(3), Some("ISO"));
+    assert_eq!(authority.subcomponent(4), None);
+
+    let second = &field.repetitions()[1];
+    assert_eq!(second.component(1).unwrap().value(), "456");
+    assert_eq!(second.component(5).unwrap().value(), "PI");
+}
+
+#[test]
+fn test_escaped_values_are_decoded() {
+    let message = Message::parse("MSH|^~\\&|APP\rNTE|1||Ratio 1\\S\\2 \\F\\ ok\r").unwrap();
+    assert_eq!(message.segment("NTE").unwrap().value(3), "Ratio 1^2 | ok");
+}
+
+#[test]
+fn test_custom_encoding_message() {
+    let message = Message::parse("MSH#:*!@#APP#FAC\rOBX#1#NM#8867-4:Heart Rate:LN##72\r").unwrap();
+    let obx = message.segment("OBX").unwrap();
+    assert_eq!(obx.component(3, 2), Some("Heart Rate"));
+    assert_eq!(obx.value(5), "72");
+}
+
+#[test]
+fn test_message_accepts_lf_and_crlf() {
+    let text = ORU.replace('\r', "\r\n");
+    let message = Message::parse(&text).unwrap();
+    assert_eq!(message.segments().len(), 4);
+}
+
+#[test]
+fn test_message_requires_msh() {
+    assert!(Message::parse("PID|1||123\r").is_err());
+    assert!(Message::parse("").is_err());
+}
+
+#[test]
+fn test_message_rejects_invalid_segment() {
+    assert!(Message::parse("MSH|^~\\&|APP\rnot a segment\r").is_err());
+}
+
+#[test]
+fn test_encode_round_trip() {
+    let message = Message::parse(ORU).unwrap();
+    assert_eq!(message.encode(), ORU);
+    assert_eq!(Message::parse(&message.encode()).unwrap(), message);
+}
+
+#[test]
+fn test_encode_escapes_values() {
+    let encoding = EncodingCharacters::default();
+    let mut segment = Segment::new("NTE");
+    segment.set_field(1, Field::new("1"));
+    segment.set_field(3, Field::new("a|b"));
+    assert_eq!(segment.encode(&encoding), "NTE|1||a\\F\\b");
+}
+
+#[test]
+fn test_set_field_pads_and_ignores_zero() {
+    let mut segment = Segment::new("MSA");
+    segment.set_field(0, Field::new("ignored"));
+    segment.set_field(2, Field::from_components(&["A", "B"]));
+    assert_eq!(segment.fields().len(), 2);
+    assert_eq!(segment.component(2, 2), Some("B"));
+}
+
+#[test]
+fn test_assembler_groups_segments_until_next_msh() {
+    let mut assembler = MessageAssembler::new();
+    let mut completed = Vec::new();
+    for line in ORU.split('\r').filter(|l| !l.is_empty()) {
+        completed.extend(assembler.push_line(line));
+    }
+    assert!(completed.is_empty());
+    assert!(assembler.in_progress());
+
+    let next = assembler
+        .push_line("MSH|^~\\&|GE_MONITOR|ICU_01|||20250104120001||ORU^R01|MSG000002|P|2.5");
+    let message = next.unwrap();
+    assert_eq!(message.control_id(), Some("MSG000001"));
+    assert_eq!(message.segments().len(), 4);
+
+    let last = assembler.finish().unwrap();
+    assert_eq!(last.control_id(), Some("MSG000002"));
+    assert!(assembler.finish().is_none());
+}
+
+#[test]
+fn test_assembler_ignores_segments_before_msh() {
+    let mut assembler = MessageAssembler::new();
+    assert!(assembler.push_line("OBX|1|NM|X||1").is_none());
+    assert!(!assembler.in_progress());
+    assert!(assembler.finish().is_none());
+}
+
+#[test]
+fn test_assembler_ends_message_on_non_segment_line() {
+    let mut assembler = MessageAssembler::new();
+    assembler.push_line("MSH|^~\\&|APP|||||ORU^R01|1|P|2.5\r");
+    assembler.push_line("OBX|1|NM|X||1\r");
+    let message = assembler.push_line("PATIENT_ID=12345|HR=72").unwrap();
+    assert_eq!(message.segments().len(), 2);
+    assert!(!assembler.in_progress());
+}
+
+#[test]
+fn test_parser_collects_hl7_messages() {
+    let mut parser = DataParser::new();
+    parser.process_data(ORU.as_bytes(), "12:00:00");
+    assert!(parser.take_messages().is_empty());
+
+    parser.finish("12:00:01");
+    let messages = parser.take_messages();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].segments_named("OBX").count(), 2);
+    assert!(parser.take_messages().is_empty());
+}
+
+#[test]
+fn test_parser_splits_consecutive_messages() {
+    let mut parser = DataParser::new();
+    parser.process_data(ORU.as_bytes(), "12:00:00");
+    parser.process_data(ORU.as_bytes(), "12:00:01");
+    assert_eq!(parser.take_messages().len(), 1);
+    parser.finish("12:00:02");
+    assert_eq!(parser.take_messages().len(), 1);
+}
+
+#[test]
+fn test_parser_complete_message_keeps_partial_line() {
+    let mut parser = DataParser::new();
+    parser.process_data(ORU.as_bytes(), "12:00:00");
+    parser.process_data(b"MSH|^~\\&|PARTIAL", "12:00:01");
+
+    parser.complete_message();
+    assert_eq!(parser.take_messages().len(), 1);
+
+    // The partial MSH line is still buffered and starts the next message
+    parser.process_data(b"||||||ORU^R01|2|P|2.5\r", "12:00:02");
+    parser.complete_message();
+    let messages = parser.take_messages();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].control_id(), Some("2"));
+}
@@ -0,0 +1,110 @@
+use vital_reader::data::{DataParser, Framing, MllpCodec};
+
+const MESSAGE: &[u8] = b"MSH|^~\\&|GE_MONITOR|ICU_01|VITAL_REC|HOSPITAL|20250104120000||ORU^R01|MSG000001|P|2.5\rOBX|1|NM|8867-4^Heart Rate^LN||72|bpm|60-100|N|||F\r";
+
+#[test]
+fn test_encode_wraps_payload() {
+    let frame = MllpCodec::encode(b"ABC");
+    assert_eq!(frame, vec![0x0B, b'A', b'B', b'C', 0x1C, 0x0D]);
+}
+
+#[test]
+fn test_decode_single_frame() {
+    let mut codec = MllpCodec::new();
+    let frames = codec.decode(&MllpCodec::encode(MESSAGE));
+    assert_eq!(frames, vec![MESSAGE.to_vec()]);
+    assert!(!codec.in_frame());
+    assert_eq!(codec.discarded_bytes(), 0);
+}
+
+#[test]
+fn test_decode_frame_split_across_chunks() {
+    let mut codec = MllpCodec::new();
+    let frame = MllpCodec::encode(MESSAGE);
+    let (first, second) = frame.split_at(20);
+
+    assert!(codec.decode(first).is_empty());
+    assert!(codec.in_frame());
+    assert_eq!(codec.decode(second), vec![MESSAGE.to_vec()]);
+}
+
+#[test]
+fn test_decode_end_block_split_from_cr() {
+    let mut codec = MllpCodec::new();
+    assert_eq!(codec.decode(&[0x0B, b'X', 0x1C]), vec![b"X".to_vec()]);
+    assert!(codec.decode(&[0x0D]).is_empty());
+    assert_eq!(codec.discarded_bytes(), 0);
+}
+
+#[test]
+fn test_decode_multiple_frames_in_one_chunk() {
+    let mut codec = MllpCodec::new();
+    let mut data = MllpCodec::encode(b"ONE");
+    data.extend(MllpCodec::encode(b"TWO"));
+    assert_eq!(codec.decode(&data), vec![b"ONE".to_vec(), b"TWO".to_vec()]);
+}
+
+#[test]
+fn test_decode_discards_noise_between_frames() {
+    let mut codec = MllpCodec::new();
+    let mut data = b"noise".to_vec();
+    data.extend(MllpCodec::encode(b"ONE"));
+    assert_eq!(codec.decode(&data), vec![b"ONE".to_vec()]);
+    assert_eq!(codec.discarded_bytes(), 5);
+}
+
+#[test]
+fn test_decode_accepts_missing_trailing_cr() {
+    let mut codec = MllpCodec::new();
+    let frames = codec.decode(&[0x0B, b'A', 0x1C, 0x0B, b'B', 0x1C, 0x0D]);
+    assert_eq!(frames, vec![b"A".to_vec(), b"B".to_vec()]);
+}
+
+#[test]
+fn test_decode_restarts_on_unexpected_start_block() {
+    let mut codec = MllpCodec::new();
+    let frames = codec.decode(&[0x0B, b'A', b'B', 0x0B, b'C', 0x1C, 0x0D]);
+    assert_eq!(frames, vec![b"C".to_vec()]);
+    assert_eq!(codec.discarded_bytes(), 2);
+}
+
+#[test]
+fn test_framing_from_name() {
+    assert_eq!(Framing::from_name("line").unwrap(), Framing::Line);
+    assert_eq!(Framing::from_name("MLLP").unwrap(), Framing::Mllp);
+    assert!(Framing::from_name("stx").is_err());
+    assert_eq!(Framing::default(), Framing::Line);
+    assert_eq!(Framing::Mllp.name(), "mllp");
+}
+
+#[test]
+fn test_parser_mllp_recovers_whole_message() {
+    let mut parser = DataParser::with_framing(Framing::Mllp);
+    assert_eq!(parser.framing(), Framing::Mllp);
+
+    let frame = MllpCodec::encode(MESSAGE);
+    parser.process_data(&frame[..10], "12:00:00");
+    assert!(parser.take_messages().is_empty());
+    parser.process_data(&frame[10..], "12:00:01");
+
+    let messages = parser.take_messages();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].control_id(), Some("MSG000001"));
+    assert_eq!(messages[0].segments().len(), 2);
+}
+
+#[test]
+fn test_parser_mllp_ignores_invalid_payload() {
+    let mut parser = DataParser::with_framing(Framing::Mllp);
+    parser.process_data(&MllpCodec::encode(b"not hl7"), "12:00:00");
+    assert!(parser.take_messages().is_empty());
+}
+
+#[test]
+fn test_parser_line_mode_does_not_decode_mllp() {
+    let mut parser = DataParser::new();
+    parser.process_data(&MllpCodec::encode(MESSAGE), "12:00:00");
+    parser.finish("12:00:01");
+    // The leading VT byte makes the first line an invalid MSH segment
+    assert!(parser.take_messages().is_empty());
+}
@@ -1,2 +1,10 @@
+mod ack_tests;
+mod binary_tests;
+mod decoder_tests;
 mod formatter_tests;
+mod hl7_tests;
+mod mllp_tests;
+mod observation_tests;
+mod parser_record_tests;
+#[allow(clippy::byte_char_slices)]
 mod parser_tests;
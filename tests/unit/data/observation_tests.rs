@@ -0,0 +1,175 @@
+use chrono::NaiveDate;
+use vital_reader::data::hl7::{EncodingCharacters, Message, Segment};
+use vital_reader::data::{ObservationValue, VitalObservation};
+
+const ORU: &str = "MSH|^~\\&|GE_MONITOR|ICU_01|VITAL_REC|HOSPITAL|20250104120000||ORU^R01|MSG000001|P|2.5\r\
+OBR|1|ORD123456|RES123456|VS^VITAL SIGNS^LOCAL|||20250104115900\r\
+OBX|1|NM|8867-4^Heart Rate^LN||72|bpm^beats/min^UCUM|60-100|N|||F|||20250104120005||GE_MONITOR^ECG_MODULE\r\
+OBX|2|ST|8884-9^ECG Rhythm^LN||NSR|||N|||F|||20250104120005||GE_MONITOR^ECG_MODULE\r\
+OBX|3|NM|2708-6^Oxygen Saturation^LN||98|%^percent^UCUM|95-100|N|||F\r";
+
+fn obx(line: &str) -> Segment {
+    Segment::parse(line, &EncodingCharacters::default()).unwrap()
+}
+
+#[test]
+fn test_from_obx_numeric() {
+    let segment = obx("OBX|1|NM|8867-4^Heart Rate^LN||72|bpm^beats/min^UCUM|60-100|N|||F|||20250104120005||GE_MONITOR^ECG_MODULE");
+    let observation = VitalObservation::from_obx(&segment, &EncodingCharacters::default()).unwrap();
+
+    assert_eq!(observation.code, "8867-4");
+    assert_eq!(observation.display_name, "Heart Rate");
+    assert_eq!(observation.coding_system, "LN");
+    assert_eq!(observation.value, ObservationValue::Numeric(72.0));
+    assert_eq!(observation.unit.as_deref(), Some("bpm"));
+    assert_eq!(observation.reference_range.as_deref(), Some("60-100"));
+    assert_eq!(observation.abnormal_flag.as_deref(), Some("N"));
+    assert_eq!(observation.result_status.as_deref(), Some("F"));
+    assert_eq!(
+        observation.producer.as_deref(),
+        Some("GE_MONITOR^ECG_MODULE")
+    );
+    assert_eq!(
+        observation.observation_time,
+        NaiveDate::from_ymd_opt(2025, 1, 4)
+            .unwrap()
+            .and_hms_opt(12, 0, 5)
+    );
+}
+
+#[test]
+fn test_from_obx_text_value() {
+    let segment = obx("OBX|29|ST|76334-7^I:E Ratio^LN||1:2.5|||N|||F");
+    let observation = VitalObservation::from_obx(&segment, &EncodingCharacters::default()).unwrap();
+    assert_eq!(
+        observation.value,
+        ObservationValue::Text("1:2.5".to_string())
+    );
+    assert_eq!(observation.value.as_f64(), None);
+    assert_eq!(observation.unit, None);
+}
+
+#[test]
+fn test_from_obx_structured_numeric() {
+    let segment = obx("OBX|1|SN|19889-5^End Tidal CO2^LN||^38|mm[Hg]");
+    let observation = VitalObservation::from_obx(&segment, &EncodingCharacters::default()).unwrap();
+    assert_eq!(observation.value.as_f64(), Some(38.0));
+}
+
+#[test]
+fn test_from_obx_unparseable_numeric_is_text() {
+    let segment = obx("OBX|1|NM|8867-4^Heart Rate^LN||---|bpm");
+    let observation = VitalObservation::from_obx(&segment, &EncodingCharacters::default()).unwrap();
+    assert_eq!(observation.value, ObservationValue::Text("---".to_string()));
+}
+
+#[test]
+fn test_from_obx_prefers_obx15_producer() {
+    let segment = obx("OBX|1|NM|8867-4^Heart Rate^LN||72|bpm|||||F||||PRODUCER^X|OBSERVER");
+    let observation = VitalObservation::from_obx(&segment, &EncodingCharacters::default()).unwrap();
+    assert_eq!(observation.producer.as_deref(), Some("PRODUCER^X"));
+}
+
+#[test]
+fn test_from_obx_rejects_invalid_segments() {
+    let encoding = EncodingCharacters::default();
+    assert!(VitalObservation::from_obx(&obx("PID|1||123"), &encoding).is_err());
+    assert!(VitalObservation::from_obx(&obx("OBX|1|NM|||72"), &encoding).is_err());
+    assert!(VitalObservation::from_obx(&obx("OBX|1|NM|8867-4^HR^LN||"), &encoding).is_err());
+}
+
+#[test]
+fn test_from_message_time_fallbacks() {
+    let message = Message::parse(ORU).unwrap();
+    let observations = VitalObservation::from_message(&message);
+    assert_eq!(observations.len(), 3);
+
+    let date = NaiveDate::from_ymd_opt(2025, 1, 4).unwrap();
+    assert_eq!(observations[0].observation_time, date.and_hms_opt(12, 0, 5));
+    // OBX|3 has no OBX-14, so OBR-7 is used
+    assert_eq!(
+        observations[2].observation_time,
+        date.and_hms_opt(11, 59, 0)
+    );
+}
+
+#[test]
+fn test_from_message_uses_msh_time_without_obr() {
+    let message = Message::parse(
+        "MSH|^~\\&|A|B|C|D|20250104120000||ORU^R01|1|P|2.5\rOBX|1|NM|8867-4^HR^LN||72\r",
+    )
+    .unwrap();
+    let observations = VitalObservation::from_message(&message);
+    assert_eq!(
+        observations[0].observation_time,
+        NaiveDate::from_ymd_opt(2025, 1, 4)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+    );
+}
+
+#[test]
+fn test_parse_hl7_timestamp_precisions() {
+    let date = NaiveDate::from_ymd_opt(2025, 1, 4).unwrap();
+    assert_eq!(
+        VitalObservation::parse_hl7_timestamp("2025"),
+        NaiveDate::from_ymd_opt(2025, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+    );
+    assert_eq!(
+        VitalObservation::parse_hl7_timestamp("202501041230"),
+        date.and_hms_opt(12, 30, 0)
+    );
+    assert_eq!(
+        VitalObservation::parse_hl7_timestamp("20250104123045.25+0100"),
+        date.and_hms_milli_opt(12, 30, 45, 250)
+    );
+}
+
+#[test]
+fn test_parse_hl7_timestamp_invalid() {
+    assert_eq!(VitalObservation::parse_hl7_timestamp(""), None);
+    assert_eq!(VitalObservation::parse_hl7_timestamp("202"), None);
+    assert_eq!(VitalObservation::parse_hl7_timestamp("2025010"), None);
+    assert_eq!(VitalObservation::parse_hl7_timestamp("20251304"), None);
+    assert_eq!(VitalObservation::parse_hl7_timestamp("2025AB04"), None);
+}
+
+#[test]
+fn test_numeric_constructor() {
+    let observation = VitalObservation::numeric("HR", "Heart Rate", "LOCAL", 60.0);
+    assert_eq!(observation.value.as_f64(), Some(60.0));
+    assert_eq!(observation.value.to_string(), "60");
+    assert!(observation.producer.is_none());
+}
+
+#[test]
+fn test_from_key_values() {
+    let observations = VitalObservation::from_key_values(
+        "PATIENT_ID=12345|HR=72|SPO2=98|BP=120/80|TEMP=36.9|TIME=12:00:00\n",
+    );
+
+    let values: Vec<(&str, String)> = observations
+        .iter()
+        .map(|o| (o.code.as_str(), o.value.to_string()))
+        .collect();
+    assert_eq!(
+        values,
+        vec![
+            ("8867-4", "72".to_string()),
+            ("2708-6", "98".to_string()),
+            ("8480-6", "120".to_string()),
+            ("8462-4", "80".to_string()),
+            ("8310-5", "36.9".to_string()),
+        ]
+    );
+    assert_eq!(observations[0].unit.as_deref(), Some("bpm"));
+    assert_eq!(observations[0].coding_system, "LN");
+}
+
+#[test]
+fn test_from_key_values_skips_invalid_pairs() {
+    assert!(VitalObservation::from_key_values("HR=abc|BP=120|SPO2|OBX|1|NM").is_empty());
+    assert_eq!(VitalObservation::from_key_values("hr = 65").len(), 1);
+}
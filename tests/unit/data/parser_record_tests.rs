@@ -0,0 +1,87 @@
+use vital_reader::data::{DataFormatter, DataParser, DataType};
+use vital_reader::output::Event;
+
+fn data_lines(parser: &mut DataParser) -> Vec<Vec<u8>> {
+    parser
+        .take_records()
+        .into_iter()
+        .filter_map(|record| match record.event {
+            Event::Data { bytes, .. } => Some(bytes),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_parser_records_lines_with_timestamp() {
+    let mut parser = DataParser::new();
+    parser.process_data(b"Line1\r\nLine2\nLine3", "12:00:00");
+
+    let records = parser.take_records();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].timestamp, "12:00:00");
+    assert_eq!(
+        records[0].event,
+        Event::Data {
+            bytes: b"Line1\r".to_vec(),
+            data_type: DataType::Mixed,
+        }
+    );
+
+    parser.finish("12:00:01");
+    let records = parser.take_records();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].timestamp, "12:00:01");
+}
+
+#[test]
+fn test_parser_records_split_long_line() {
+    let mut parser = DataParser::new();
+    parser.process_data(&vec![b'A'; 70000], "12:00:00");
+
+    let lines = data_lines(&mut parser);
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0].len(), 65537);
+}
+
+#[test]
+fn test_parser_records_carry_detected_type() {
+    let mut parser = DataParser::new();
+    parser.process_data(&b"Pure ASCII text data\n".repeat(15), "12:00:00");
+    parser.take_records();
+    assert_eq!(parser.detected_type(), DataType::Ascii);
+
+    parser.process_data(b"next\n", "12:00:01");
+    assert!(matches!(
+        parser.take_records()[0].event,
+        Event::Data {
+            data_type: DataType::Ascii,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_parser_detects_binary() {
+    let mut parser = DataParser::new();
+    parser.process_data(&[0xFFu8; 100], "12:00:00");
+    assert_eq!(parser.detected_type(), DataType::Binary);
+}
+
+#[test]
+fn test_parser_stats() {
+    let mut parser = DataParser::new();
+    parser.process_data(b"aab\n", "12:00:00");
+
+    let stats = parser.stats();
+    assert_eq!(stats.total_bytes, 4);
+    assert_eq!(stats.ascii_bytes, 4);
+    assert_eq!(stats.binary_bytes, 0);
+    assert_eq!(stats.protocol, "hl7");
+    assert_eq!(stats.most_common[0], (b'a', 2));
+    assert_eq!(stats.most_common.len(), 3);
+
+    let report = DataFormatter::format_stats(&stats);
+    assert!(report.contains("Total bytes:      4"));
+    assert!(report.contains("0x61 'a' : 2 times (50.0%)"));
+}
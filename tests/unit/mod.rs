@@ -1,4 +1,8 @@
+pub mod capture;
 pub mod config;
 pub mod data;
+pub mod output;
 pub mod port;
-pub mod reader;
\ No newline at end of file
+pub mod protocol;
+pub mod reader;
+pub mod transport;
\ No newline at end of file
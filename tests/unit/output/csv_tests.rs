@@ -0,0 +1,289 @@
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use vital_reader::data::{DataParser, DataType, VitalObservation};
+use vital_reader::output::{CsvColumn, CsvSink, Event, MissingValue, Record, Sink};
+
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    fn text(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn csv_sink(columns: &str, missing: MissingValue) -> (CsvSink, SharedBuffer) {
+    let buffer = SharedBuffer::default();
+    let sink = CsvSink::with_writer(Box::new(buffer.clone()))
+        .with_columns(CsvColumn::parse_list(columns).unwrap())
+        .with_missing(missing);
+    (sink, buffer)
+}
+
+fn observation(timestamp: &str, code: &str, value: f64, unit: &str) -> Record {
+    let mut observation = VitalObservation::numeric(code, "", "LN", value);
+    observation.unit = Some(unit.to_string());
+    Record::new(timestamp, Event::Observation(observation))
+}
+
+fn data(timestamp: &str, text: &str) -> Record {
+    Record::new(
+        timestamp,
+        Event::Data {
+            bytes: text.as_bytes().to_vec(),
+            data_type: DataType::Ascii,
+        },
+    )
+}
+
+#[test]
+fn test_default_columns() {
+    let headers: Vec<String> = CsvColumn::defaults()
+        .into_iter()
+        .map(|column| column.header)
+        .collect();
+    assert_eq!(
+        headers,
+        vec![
+            "HR",
+            "SpO2",
+            "NIBP_SYS",
+            "NIBP_DIA",
+            "NIBP_MEAN",
+            "EtCO2",
+            "BIS",
+            "TV",
+            "PEEP",
+            "FiO2"
+        ]
+    );
+}
+
+#[test]
+fn test_parse_column_list() {
+    let columns = CsvColumn::parse_list("hr, Temp=8310-5").unwrap();
+    assert_eq!(
+        columns,
+        vec![
+            CsvColumn::new("HR", "8867-4").with_unit("/min"),
+            CsvColumn::new("Temp", "8310-5")
+        ]
+    );
+
+    assert!(CsvColumn::parse_list("").is_err());
+    assert!(CsvColumn::parse_list("HR,=8310-5").is_err());
+    let error = CsvColumn::from_name("glucose").unwrap_err().to_string();
+    assert!(error.contains("Invalid CSV column: glucose"));
+}
+
+#[test]
+fn test_missing_value_from_name() {
+    assert_eq!(
+        MissingValue::from_name("EMPTY").unwrap(),
+        MissingValue::Empty
+    );
+    assert_eq!(MissingValue::from_name("na").unwrap(), MissingValue::Na);
+    assert_eq!(MissingValue::from_name("last").unwrap(), MissingValue::Last);
+    assert_eq!(MissingValue::Last.name(), "last");
+    assert!(MissingValue::from_name("zero").is_err());
+}
+
+#[test]
+fn test_observations_with_same_timestamp_share_a_row() {
+    let (mut sink, buffer) = csv_sink("HR,SpO2,NIBP_SYS", MissingValue::Empty);
+
+    sink.write(&observation("12:00:00", "8867-4", 72.0, "/min"))
+        .unwrap();
+    sink.write(&observation("12:00:00", "2708-6", 98.0, "%"))
+        .unwrap();
+    sink.write(&observation("12:00:01", "8867-4", 74.5, "/min"))
+        .unwrap();
+    sink.write(&observation("12:00:01", "9999-9", 1.0, "1"))
+        .unwrap();
+    sink.flush().unwrap();
+
+    assert_eq!(
+        buffer.text(),
+        "timestamp,HR,SpO2,NIBP_SYS\n12:00:00,72,98,\n12:00:01,74.5,,\n"
+    );
+}
+
+#[test]
+fn test_missing_value_policies() {
+    let (mut sink, buffer) = csv_sink("HR,SpO2", MissingValue::Na);
+    sink.write(&observation("1", "8867-4", 72.0, "/min"))
+        .unwrap();
+    sink.flush().unwrap();
+    assert_eq!(buffer.text(), "timestamp,HR,SpO2\n1,72,NA\n");
+
+    let (mut sink, buffer) = csv_sink("HR,SpO2", MissingValue::Last);
+    sink.write(&observation("1", "8867-4", 72.0, "/min"))
+        .unwrap();
+    sink.write(&observation("2", "2708-6", 97.0, "%")).unwrap();
+    sink.write(&observation("3", "8867-4", 75.0, "/min"))
+        .unwrap();
+    sink.flush().unwrap();
+    assert_eq!(
+        buffer.text(),
+        "timestamp,HR,SpO2\n1,72,\n2,72,97\n3,75,97\n"
+    );
+}
+
+#[test]
+fn test_key_value_lines_become_rows() {
+    let (mut sink, buffer) = csv_sink("HR,SpO2,NIBP_SYS,NIBP_DIA", MissingValue::Empty);
+
+    sink.write(&data(
+        "12:00:00",
+        "PATIENT_ID=12345|HR=61|SPO2=96|BP=111/71|TEMP=36.6|TIME=12:00:00\n",
+    ))
+    .unwrap();
+    sink.write(&data("12:00:01", "no vital signs here\n"))
+        .unwrap();
+    sink.flush().unwrap();
+
+    assert_eq!(
+        buffer.text(),
+        "timestamp,HR,SpO2,NIBP_SYS,NIBP_DIA\n12:00:00,61,96,111,71\n"
+    );
+}
+
+#[test]
+fn test_hl7_message_observations_use_obx_time() {
+    let message =
+        "MSH|^~\\&|GE_MONITOR|ICU_01|VITAL_REC|HOSPITAL|20250104120000||ORU^R01|MSG000001|P|2.5\r\
+OBX|1|NM|8867-4^Heart Rate^LN||72|bpm|60-100|N|||F|||20250104120005\r\
+OBX|2|ST|8884-9^ECG Rhythm^LN||NSR|||N|||F|||20250104120005\r\
+OBX|3|NM|2708-6^Oxygen Saturation^LN||98|%|95-100|N|||F|||20250104120005\r";
+    let mut parser = DataParser::new();
+    parser.process_data(message.as_bytes(), "ts");
+    parser.finish("ts");
+
+    let (mut sink, buffer) = csv_sink("HR,SpO2", MissingValue::Empty);
+    for message in parser.take_messages() {
+        for observation in VitalObservation::from_message(&message) {
+            sink.write(&Record::new("ts", Event::Observation(observation)))
+                .unwrap();
+        }
+    }
+    sink.flush().unwrap();
+
+    assert_eq!(
+        buffer.text(),
+        "timestamp,HR,SpO2\n2025-01-04 12:00:05.000,72,98\n"
+    );
+}
+
+#[test]
+fn test_row_timestamps_share_one_format() {
+    let mut timed = VitalObservation::numeric("8867-4", "", "LN", 72.0);
+    timed.unit = Some("/min".to_string());
+    timed.observation_time = chrono::NaiveDate::from_ymd_opt(2025, 1, 4)
+        .unwrap()
+        .and_hms_opt(12, 0, 5);
+
+    let (mut sink, buffer) = csv_sink("HR,TEMP", MissingValue::Empty);
+    sink.write(&Record::new(
+        "2025-01-04 12:00:06.000",
+        Event::Observation(timed),
+    ))
+    .unwrap();
+    sink.write(&observation(
+        "2025-01-04 12:00:06.250",
+        "8310-5",
+        36.849_999,
+        "Cel",
+    ))
+    .unwrap();
+    sink.write(&observation(
+        "2025-01-04 12:00:07.000",
+        "8310-5",
+        -0.001,
+        "Cel",
+    ))
+    .unwrap();
+    sink.flush().unwrap();
+
+    assert_eq!(
+        buffer.text(),
+        "timestamp,HR,TEMP\n\
+2025-01-04 12:00:05.000,72,\n\
+2025-01-04 12:00:06.250,,36.85\n\
+2025-01-04 12:00:07.000,,0\n"
+    );
+}
+
+#[test]
+fn test_header_is_written_without_rows_and_escaped() {
+    let (mut sink, buffer) = csv_sink("HR \"ECG\"=8867-4", MissingValue::Empty);
+    sink.flush().unwrap();
+    sink.flush().unwrap();
+    assert_eq!(buffer.text(), "timestamp,\"HR \"\"ECG\"\"\"\n");
+}
+
+#[test]
+fn test_columns_keep_one_unit() {
+    let (mut sink, buffer) = csv_sink("EtCO2,NIBP_SYS", MissingValue::Empty);
+
+    sink.write(&observation("1", "19889-5", 38.0, "mm[Hg]"))
+        .unwrap();
+    sink.write(&observation("2", "19889-5", 5.0, "kPa"))
+        .unwrap();
+    sink.write(&observation("3", "19889-5", 5.1, "%")).unwrap();
+    sink.write(&observation("3", "8480-6", 120.0, "mm[Hg]"))
+        .unwrap();
+    sink.flush().unwrap();
+
+    assert_eq!(
+        buffer.text(),
+        "timestamp,EtCO2,NIBP_SYS\n1,38,\n2,37.5,\n3,,120\n"
+    );
+}
+
+#[test]
+fn test_known_columns_have_units() {
+    let units: Vec<String> = CsvColumn::parse_list("HR,SpO2,EtCO2,BIS,TV,PEEP,FiO2,RR,TEMP,PR")
+        .unwrap()
+        .into_iter()
+        .map(|column| column.unit.unwrap())
+        .collect();
+    assert_eq!(
+        units,
+        vec!["/min", "%", "mm[Hg]", "{score}", "mL", "cm[H2O]", "%", "/min", "Cel", "/min"]
+    );
+    assert_eq!(CsvColumn::from_name("Glucose=2339-0").unwrap().unit, None);
+}
+
+#[test]
+fn test_values_in_other_units_are_dropped() {
+    let temp = CsvColumn::from_name("TEMP").unwrap();
+    assert_eq!(temp.convert(37.5, Some("Cel")), Some(37.5));
+    assert_eq!(temp.convert(99.5, Some("[degF]")), None);
+    assert_eq!(temp.convert(37.5, None), None);
+
+    let tv = CsvColumn::from_name("TV").unwrap();
+    assert_eq!(tv.convert(0.45, Some("L")), Some(450.0));
+    assert_eq!(tv.convert(450.0, Some("mL")), Some(450.0));
+    assert_eq!(
+        CsvColumn::from_name("HR")
+            .unwrap()
+            .convert(72.0, Some("bpm")),
+        Some(72.0)
+    );
+
+    let custom = CsvColumn::from_name("Glucose=2339-0").unwrap();
+    assert_eq!(custom.convert(5.4, None), Some(5.4));
+    assert_eq!(custom.convert(97.0, Some("mg/dL")), Some(97.0));
+}
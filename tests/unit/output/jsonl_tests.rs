@@ -0,0 +1,204 @@
+use serde_json::{json, Value};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use vital_reader::data::hl7::Message;
+use vital_reader::data::{BinaryEvent, DataParser, DataType, VitalObservation};
+use vital_reader::output::{Event, JsonlSink, Record, Sink};
+use vital_reader::reader::ConnectionState;
+
+const MESSAGE: &str = "MSH|^~\\&|GE_MONITOR|ICU_01|VITAL_REC|HOSPITAL|20250104120000||ORU^R01|MSG000001|P|2.5\rOBX|1|NM|8867-4^Heart Rate^LN||72|bpm|60-100|N|||F\r";
+
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn sink() -> JsonlSink {
+    JsonlSink::with_writer(Box::new(std::io::sink()))
+}
+
+fn data(bytes: &[u8], data_type: DataType) -> Record {
+    Record::new(
+        "2025-01-04 12:00:00.000",
+        Event::Data {
+            bytes: bytes.to_vec(),
+            data_type,
+        },
+    )
+}
+
+#[test]
+fn test_data_record_has_hex_and_text() {
+    let value = sink().to_json(&data(b"HR=72\r", DataType::Ascii));
+
+    assert_eq!(
+        value,
+        json!({
+            "timestamp": "2025-01-04 12:00:00.000",
+            "type": "data",
+            "data_type": "ascii",
+            "hex": "48523D37320D",
+            "text": "HR=72",
+        })
+    );
+}
+
+#[test]
+fn test_binary_data_is_lossy_text() {
+    let value = sink().to_json(&data(&[0xFF, 0x41], DataType::Binary));
+    assert_eq!(value["data_type"], "binary");
+    assert_eq!(value["hex"], "FF41");
+    assert_eq!(value["text"], "\u{FFFD}A");
+}
+
+#[test]
+fn test_segment_line_is_parsed() {
+    let value = sink().to_json(&data(
+        b"OBX|1|NM|8867-4^Heart Rate^LN||72|bpm\r",
+        DataType::Ascii,
+    ));
+
+    assert_eq!(value["segment"]["name"], "OBX");
+    assert_eq!(
+        value["segment"]["fields"],
+        json!(["1", "NM", "8867-4^Heart Rate^LN", "", "72", "bpm"])
+    );
+}
+
+#[test]
+fn test_segment_uses_encoding_of_last_msh() {
+    let mut sink = sink();
+    let msh = sink.to_json(&data(b"MSH#^~\\&#APP#FAC\r", DataType::Ascii));
+    assert_eq!(
+        msh["segment"]["fields"],
+        json!(["#", "^~\\&", "APP", "FAC"])
+    );
+
+    let obx = sink.to_json(&data(b"OBX#1#NM\r", DataType::Ascii));
+    assert_eq!(obx["segment"]["fields"], json!(["1", "NM"]));
+}
+
+#[test]
+fn test_non_segment_line_has_no_segment() {
+    let value = sink().to_json(&data(b"HR=72|SPO2=98", DataType::Ascii));
+    assert!(value.get("segment").is_none());
+}
+
+#[test]
+fn test_message_record_lists_segments() {
+    let message = Message::parse(MESSAGE).unwrap();
+    let value = sink().to_json(&Record::new("ts", Event::Message(message)));
+
+    assert_eq!(value["type"], "message");
+    assert_eq!(value["message_type"], "ORU^R01");
+    assert_eq!(value["control_id"], "MSG000001");
+    assert_eq!(value["segments"].as_array().unwrap().len(), 2);
+    assert_eq!(value["segments"][1]["fields"][4], "72");
+}
+
+#[test]
+fn test_observation_record() {
+    let mut observation = VitalObservation::numeric("8867-4", "Heart Rate", "LN", 72.0);
+    observation.unit = Some("bpm".to_string());
+    let value = sink().to_json(&Record::new("ts", Event::Observation(observation)));
+
+    assert_eq!(value["type"], "observation");
+    assert_eq!(value["code"], "8867-4");
+    assert_eq!(value["value"], 72.0);
+    assert_eq!(value["unit"], "bpm");
+    assert_eq!(value["producer"], Value::Null);
+}
+
+#[test]
+fn test_packet_and_waveform_records() {
+    let gap = Record::new(
+        "ts",
+        Event::Packet(BinaryEvent::SequenceGap {
+            expected: 3,
+            received: 5,
+        }),
+    );
+    assert_eq!(
+        sink().to_json(&gap),
+        json!({"timestamp": "ts", "type": "packet", "error": "sequence_gap", "expected": 3, "received": 5})
+    );
+
+    let wave = Record::new(
+        "ts",
+        Event::Waveform {
+            name: "ECG1".to_string(),
+            unit: "mV".to_string(),
+            sample_rate: 300,
+            samples: vec![0.5, f64::NAN],
+        },
+    );
+    assert_eq!(sink().to_json(&wave)["samples"], json!([0.5, null]));
+}
+
+#[test]
+fn test_sink_writes_one_line_per_record() {
+    let buffer = SharedBuffer::default();
+    let mut sink = JsonlSink::with_writer(Box::new(buffer.clone()));
+
+    let mut parser = DataParser::new();
+    parser.process_data(MESSAGE.as_bytes(), "ts");
+    for record in parser.take_records() {
+        sink.write(&record).unwrap();
+    }
+    sink.write(&Record::new("ts", Event::Warning("Timeout".to_string())))
+        .unwrap();
+    sink.flush().unwrap();
+
+    let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    let lines: Vec<Value> = output
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0]["segment"]["name"], "MSH");
+    assert_eq!(lines[1]["segment"]["name"], "OBX");
+    assert_eq!(lines[2]["type"], "warning");
+}
+
+#[test]
+fn test_connection_records() {
+    let lost = Record::new("ts", Event::Disconnected("Broken pipe".to_string()));
+    assert_eq!(
+        sink().to_json(&lost),
+        json!({"timestamp": "ts", "type": "disconnected", "reason": "Broken pipe"})
+    );
+
+    let back = Record::new(
+        "ts",
+        Event::Reconnected {
+            port: "/dev/ttyUSB1".to_string(),
+            attempts: 2,
+        },
+    );
+    assert_eq!(
+        sink().to_json(&back),
+        json!({"timestamp": "ts", "type": "reconnected", "port": "/dev/ttyUSB1", "attempts": 2})
+    );
+
+    let lost = Record::new(
+        "ts",
+        Event::ConnectionState {
+            state: ConnectionState::Lost,
+            detail: "device dropped DSR/CD".to_string(),
+        },
+    );
+    assert_eq!(
+        sink().to_json(&lost),
+        json!({"timestamp": "ts", "type": "state", "state": "lost", "detail": "device dropped DSR/CD"})
+    );
+}
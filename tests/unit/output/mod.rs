@@ -0,0 +1,3 @@
+mod csv_tests;
+mod jsonl_tests;
+mod sink_tests;
@@ -0,0 +1,203 @@
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use vital_reader::data::{BinaryEvent, BinaryPacket, DataType, VitalObservation};
+use vital_reader::output::{ChannelSink, ConsoleSink, Event, MemorySink, Record, Sink};
+use vital_reader::protocol::DriverEvent;
+use vital_reader::reader::{ConnectionState, OutputMode};
+
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    fn text(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn data_record(text: &str) -> Record {
+    Record::new(
+        "12:00:00",
+        Event::Data {
+            bytes: text.as_bytes().to_vec(),
+            data_type: DataType::Ascii,
+        },
+    )
+}
+
+fn observation_record() -> Record {
+    let mut observation = VitalObservation::numeric("8867-4", "Heart Rate", "LN", 72.0);
+    observation.unit = Some("bpm".to_string());
+    Record::new("12:00:00", Event::Observation(observation))
+}
+
+#[test]
+fn test_console_raw_mode_formats_data() {
+    let sink = ConsoleSink::new(OutputMode::Raw);
+    assert_eq!(sink.mode(), OutputMode::Raw);
+    assert_eq!(
+        sink.format(&data_record("HR=72\r")),
+        Some("[12:00:00] ASCII: HR=72".to_string())
+    );
+    assert_eq!(sink.format(&observation_record()), None);
+}
+
+#[test]
+fn test_console_raw_mode_formats_packets() {
+    let sink = ConsoleSink::new(OutputMode::Raw);
+    let record = Record::new(
+        "12:00:00",
+        Event::Packet(BinaryEvent::Packet(BinaryPacket {
+            sequence: Some(3),
+            samples: vec![10, 20],
+        })),
+    );
+    assert_eq!(
+        sink.format(&record),
+        Some("[12:00:00] PACKET #3: 10 20".to_string())
+    );
+}
+
+#[test]
+fn test_console_observation_mode_formats_observations() {
+    let sink = ConsoleSink::new(OutputMode::Observations);
+    assert_eq!(
+        sink.format(&observation_record()),
+        Some("[12:00:00] OBS: Heart Rate (8867-4) = 72 bpm".to_string())
+    );
+    assert_eq!(sink.format(&data_record("HR=72")), None);
+}
+
+#[test]
+fn test_console_always_formats_device_events() {
+    for mode in [OutputMode::Raw, OutputMode::Observations] {
+        let sink = ConsoleSink::new(mode);
+        let alarm = Record::new(
+            "12:00:00",
+            Event::Alarm {
+                priority: 2,
+                code: "17".to_string(),
+                text: "Apnea".to_string(),
+            },
+        );
+        assert_eq!(
+            sink.format(&alarm),
+            Some("[12:00:00] ALARM: Apnea (code 17, priority 2)".to_string())
+        );
+
+        let ack = Record::new(
+            "12:00:00",
+            Event::AckSent {
+                code: "AA".to_string(),
+                control_id: "MSG001".to_string(),
+            },
+        );
+        assert_eq!(
+            sink.format(&ack),
+            Some("[12:00:00] SENT ACK: AA for MSG001".to_string())
+        );
+    }
+}
+
+#[test]
+fn test_console_writes_lines_to_writer() {
+    let buffer = SharedBuffer::default();
+    let mut sink = ConsoleSink::with_writer(OutputMode::Raw, Box::new(buffer.clone()));
+
+    sink.write(&data_record("first")).unwrap();
+    sink.write(&observation_record()).unwrap();
+    sink.write(&Record::new(
+        "12:00:01",
+        Event::Warning("No reply".to_string()),
+    ))
+    .unwrap();
+    sink.flush().unwrap();
+
+    assert_eq!(
+        buffer.text(),
+        "[12:00:00] ASCII: first\n[12:00:01] WARNING: No reply\n"
+    );
+}
+
+#[test]
+fn test_memory_sink_clones_share_records() {
+    let sink = MemorySink::new();
+    let mut writer = sink.clone();
+
+    writer.write(&data_record("one")).unwrap();
+    writer.write(&observation_record()).unwrap();
+
+    assert_eq!(sink.records().len(), 2);
+    assert_eq!(sink.take()[0], data_record("one"));
+    assert!(sink.records().is_empty());
+}
+
+#[test]
+fn test_channel_sink_sends_records() {
+    let (mut sink, receiver) = ChannelSink::channel();
+
+    sink.write(&data_record("one")).unwrap();
+
+    assert_eq!(receiver.try_recv().unwrap(), data_record("one"));
+    drop(receiver);
+    assert!(sink.write(&data_record("two")).is_err());
+}
+
+#[test]
+fn test_event_from_driver_event() {
+    assert_eq!(
+        Event::from(DriverEvent::Identified("Evita 4".to_string())),
+        Event::Device("Evita 4".to_string())
+    );
+    assert_eq!(
+        Event::from(DriverEvent::Warning("Timeout".to_string())),
+        Event::Warning("Timeout".to_string())
+    );
+}
+
+#[test]
+fn test_console_always_formats_connection_events() {
+    for mode in [OutputMode::Raw, OutputMode::Observations] {
+        let sink = ConsoleSink::new(mode);
+        let lost = Record::new("12:00:00", Event::Disconnected("Broken pipe".to_string()));
+        assert_eq!(
+            sink.format(&lost),
+            Some("[12:00:00] DISCONNECTED: Broken pipe".to_string())
+        );
+
+        let back = Record::new(
+            "12:00:05",
+            Event::Reconnected {
+                port: "/dev/ttyUSB1".to_string(),
+                attempts: 3,
+            },
+        );
+        assert_eq!(
+            sink.format(&back),
+            Some("[12:00:05] RECONNECTED: /dev/ttyUSB1 after 3 attempt(s)".to_string())
+        );
+
+        let stale = Record::new(
+            "12:01:00",
+            Event::ConnectionState {
+                state: ConnectionState::Stale,
+                detail: "no data for 30 s".to_string(),
+            },
+        );
+        assert_eq!(
+            sink.format(&stale),
+            Some("[12:01:00] STATE: stale (no data for 30 s)".to_string())
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use anyhow::Result;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use vital_reader::port::{Autodetect, CandidateScore};
+use vital_reader::protocol::medibus::{command, MedibusCodec};
+use vital_reader::transport::Transport;
+use vital_reader::SerialConfig;
+
+/// Hands out fixed bytes once, then reports the input as ended
+struct CannedTransport {
+    data: Vec<u8>,
+}
+
+impl Transport for CannedTransport {
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        let n = self.data.len().min(buffer.len());
+        buffer[..n].copy_from_slice(&self.data[..n]);
+        self.data.drain(..n);
+        Ok(n)
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<usize> {
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn name(&self) -> Option<String> {
+        None
+    }
+
+    fn is_connected(&self) -> bool {
+        !self.data.is_empty()
+    }
+}
+
+fn config(text: &str) -> SerialConfig {
+    SerialConfig::from_string(text).unwrap()
+}
+
+#[test]
+fn test_score_prefers_clean_ascii() {
+    let clean = CandidateScore::new(&config("9600,0,8,1"), b"HR=72|SPO2=98\r\n");
+    assert_eq!(clean.bytes, 15);
+    assert_eq!(clean.ascii_ratio, 1.0);
+    assert_eq!(clean.framing_errors, 0);
+
+    let garbled = CandidateScore::new(&config("115200,0,8,1"), &[0x00, 0xf8, 0x80, 0x00, b'x']);
+    assert_eq!(garbled.framing_errors, 2);
+    assert!(garbled.score < clean.score);
+}
+
+#[test]
+fn test_score_without_data() {
+    let score = CandidateScore::new(&config("9600,0,8,1"), b"");
+    assert_eq!(score.bytes, 0);
+    assert_eq!(score.score, 0.0);
+    assert!(score.markers.is_empty());
+}
+
+#[test]
+fn test_score_markers() {
+    let hl7 = CandidateScore::new(&config("9600,0,8,1"), b"\x0bMSH|^~\\&|GE\r\x1c\r");
+    assert_eq!(hl7.markers, vec!["hl7", "mllp"]);
+
+    let frame = MedibusCodec::encode_command(command::DEVICE_ID, b"");
+    let medibus = CandidateScore::new(&config("9600,2,8,1"), &frame);
+    assert_eq!(medibus.markers, vec!["medibus"]);
+    assert!(medibus.score > 1.0);
+}
+
+#[test]
+fn test_default_candidates() {
+    let autodetect = Autodetect::new().with_window(Duration::from_millis(500));
+    let names: Vec<String> = autodetect
+        .candidates()
+        .iter()
+        .map(|config| config.to_string())
+        .collect();
+    assert_eq!(names.len(), 15);
+    assert!(names.contains(&"9600 7E1".to_string()));
+    assert!(names.contains(&"115200 8N1".to_string()));
+    assert_eq!(autodetect.duration(), Duration::from_millis(7500));
+}
+
+#[test]
+fn test_run_picks_best_candidate() {
+    let autodetect = Autodetect::new()
+        .with_candidates(vec![
+            config("115200,0,8,1"),
+            config("9600,2,7,1"),
+            config("19200,0,8,1"),
+        ])
+        .with_window(Duration::from_secs(1));
+
+    let results = autodetect
+        .run_with(|config| {
+            let data = match config.baud {
+                9600 => b"MSH|^~\\&|MONITOR\rOBX|1|NM|HR||72\r".to_vec(),
+                115200 => vec![0x00, 0xe0, 0x00, 0xfe, 0x80],
+                _ => Vec::new(),
+            };
+            Ok(Box::new(CannedTransport { data }) as Box<dyn Transport>)
+        })
+        .unwrap();
+
+    assert_eq!(results.len(), 3);
+    let best = Autodetect::best(&results).unwrap();
+    assert_eq!(best.config.to_string(), "9600 7E1");
+    assert_eq!(best.markers, vec!["hl7"]);
+    assert_eq!(results[2].config.baud, 115200);
+}
+
+#[test]
+fn test_run_without_data_has_no_best() {
+    let autodetect = Autodetect::new()
+        .with_candidates(vec![config("9600,0,8,1")])
+        .with_window(Duration::from_millis(50));
+    let results = autodetect
+        .run_with(|_| Ok(Box::new(CannedTransport { data: Vec::new() }) as Box<dyn Transport>))
+        .unwrap();
+    assert!(Autodetect::best(&results).is_none());
+}
+
+#[test]
+fn test_run_open_failure() {
+    let autodetect = Autodetect::new().with_window(Duration::from_millis(50));
+    let result = autodetect.run("/nonexistent/ttyUSB9");
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_run_stops_on_flag() {
+    let autodetect = Autodetect::new()
+        .with_window(Duration::from_secs(10))
+        .with_stop_flag(Arc::new(AtomicBool::new(true)));
+
+    let started = Instant::now();
+    let results = autodetect
+        .run_with(|_| Ok(Box::new(CannedTransport { data: Vec::new() }) as Box<dyn Transport>))
+        .unwrap();
+
+    assert!(results.is_empty());
+    assert!(started.elapsed() < Duration::from_secs(1));
+}
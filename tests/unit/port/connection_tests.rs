@@ -111,3 +111,13 @@ fn test_connection_name_method() {
     // Can't get name without valid connection, but API is tested
     assert!(result.is_err());
 }
+
+#[test]
+fn test_connection_rejects_empty_write_buffer() {
+    let config = SerialConfig {
+        write_buffer: 0,
+        ..SerialConfig::default()
+    };
+    let error = PortConnection::open("FAKE", &config, 100).err().unwrap();
+    assert!(error.to_string().contains("buffers need at least one byte"));
+}
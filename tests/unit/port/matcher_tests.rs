@@ -0,0 +1,100 @@
+use serialport::{SerialPortInfo, SerialPortType, UsbPortInfo};
+use vital_reader::port::PortMatcher;
+
+fn usb_port(name: &str, vid: u16, pid: u16, serial: Option<&str>) -> SerialPortInfo {
+    SerialPortInfo {
+        port_name: name.to_string(),
+        port_type: SerialPortType::UsbPort(UsbPortInfo {
+            vid,
+            pid,
+            serial_number: serial.map(str::to_string),
+            manufacturer: Some("FTDI".to_string()),
+            product: None,
+        }),
+    }
+}
+
+fn ports() -> Vec<SerialPortInfo> {
+    vec![
+        SerialPortInfo {
+            port_name: "/dev/ttyS0".to_string(),
+            port_type: SerialPortType::PciPort,
+        },
+        usb_port("/dev/ttyUSB0", 0x067b, 0x2303, None),
+        usb_port("/dev/ttyUSB1", 0x0403, 0x6001, Some("A12BC3")),
+    ]
+}
+
+#[test]
+fn test_from_name() {
+    assert_eq!(
+        PortMatcher::from_name("serial=A12BC3").unwrap(),
+        PortMatcher::SerialNumber("A12BC3".to_string())
+    );
+    assert_eq!(
+        PortMatcher::from_name("usb=0403:6001").unwrap(),
+        PortMatcher::UsbId {
+            vid: 0x0403,
+            pid: 0x6001
+        }
+    );
+    assert_eq!(
+        PortMatcher::from_name("COM3").unwrap(),
+        PortMatcher::Name("COM3".to_string())
+    );
+
+    assert!(PortMatcher::from_name("serial=").is_err());
+    assert!(PortMatcher::from_name("usb=0403").is_err());
+    assert!(PortMatcher::from_name("usb=xyz:6001").is_err());
+    assert!(PortMatcher::from_name("").is_err());
+}
+
+#[test]
+fn test_display_round_trips() {
+    for spec in ["serial=A12BC3", "usb=0403:6001", "/dev/ttyUSB0"] {
+        assert_eq!(PortMatcher::from_name(spec).unwrap().to_string(), spec);
+    }
+}
+
+#[test]
+fn test_for_port_prefers_serial_number() {
+    let ports = ports();
+    assert_eq!(
+        PortMatcher::for_port("/dev/ttyUSB1", &ports),
+        PortMatcher::SerialNumber("A12BC3".to_string())
+    );
+    assert_eq!(
+        PortMatcher::for_port("/dev/ttyUSB0", &ports),
+        PortMatcher::UsbId {
+            vid: 0x067b,
+            pid: 0x2303
+        }
+    );
+    assert_eq!(
+        PortMatcher::for_port("/dev/ttyS0", &ports),
+        PortMatcher::Name("/dev/ttyS0".to_string())
+    );
+    assert_eq!(
+        PortMatcher::for_port("/dev/ttyACM0", &ports),
+        PortMatcher::Name("/dev/ttyACM0".to_string())
+    );
+}
+
+#[test]
+fn test_find_after_renumbering() {
+    let matcher = PortMatcher::for_port("/dev/ttyUSB1", &ports());
+
+    // Replugged adapters come back under new names
+    let replugged = vec![
+        usb_port("/dev/ttyUSB0", 0x0403, 0x6001, Some("A12BC3")),
+        usb_port("/dev/ttyUSB1", 0x067b, 0x2303, None),
+    ];
+    assert_eq!(matcher.find(&replugged), Some("/dev/ttyUSB0".to_string()));
+    assert_eq!(
+        PortMatcher::from_name("usb=067b:2303")
+            .unwrap()
+            .find(&replugged),
+        Some("/dev/ttyUSB1".to_string())
+    );
+    assert_eq!(matcher.find(&replugged[1..]), None);
+}
@@ -1,3 +1,7 @@
+mod autodetect_tests;
 mod connection_tests;
+#[allow(clippy::default_constructed_unit_structs)]
 mod detector_tests;
+mod matcher_tests;
+mod rules_tests;
 mod selector_tests;
@@ -0,0 +1,185 @@
+use serialport::{SerialPortInfo, SerialPortType, UsbPortInfo};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+use vital_reader::port::{PortChange, PortMatcher, PortRule, PortRules, PortWatcher};
+
+const RULES: &str = r#"
+[[rule]]
+name = "monitor"
+vid = "0403"
+pid = "6001"
+serial_number = "A12BC3"
+
+[[rule]]
+name = "ventilator"
+manufacturer = "prolific"
+product = "USB-Serial"
+
+[[rule]]
+name = "legacy"
+port = "/dev/ttyS0"
+"#;
+
+fn ftdi(name: &str, serial: &str) -> SerialPortInfo {
+    SerialPortInfo {
+        port_name: name.to_string(),
+        port_type: SerialPortType::UsbPort(UsbPortInfo {
+            vid: 0x0403,
+            pid: 0x6001,
+            serial_number: Some(serial.to_string()),
+            manufacturer: Some("FTDI".to_string()),
+            product: Some("FT232R USB UART".to_string()),
+        }),
+    }
+}
+
+fn prolific(name: &str) -> SerialPortInfo {
+    SerialPortInfo {
+        port_name: name.to_string(),
+        port_type: SerialPortType::UsbPort(UsbPortInfo {
+            vid: 0x067b,
+            pid: 0x2303,
+            serial_number: None,
+            manufacturer: Some("Prolific Technology Inc.".to_string()),
+            product: Some("USB-Serial Controller".to_string()),
+        }),
+    }
+}
+
+fn pci(name: &str) -> SerialPortInfo {
+    SerialPortInfo {
+        port_name: name.to_string(),
+        port_type: SerialPortType::PciPort,
+    }
+}
+
+#[test]
+fn test_parse_rules() {
+    let rules = PortRules::parse(RULES).unwrap();
+    assert_eq!(rules.rules().len(), 3);
+
+    let monitor = rules.get("monitor").unwrap();
+    assert_eq!(monitor.vid, Some(0x0403));
+    assert_eq!(monitor.pid, Some(0x6001));
+    assert_eq!(monitor.serial_number.as_deref(), Some("A12BC3"));
+    assert_eq!(monitor.manufacturer, None);
+
+    assert_eq!(
+        rules.get("legacy").unwrap().port.as_deref(),
+        Some("/dev/ttyS0")
+    );
+    assert!(rules.get("printer").is_none());
+}
+
+#[test]
+fn test_integer_usb_ids() {
+    let rules = PortRules::parse("[[rule]]\nname = \"a\"\nvid = 1027\npid = 0x6001\n").unwrap();
+    assert_eq!(rules.rules()[0].vid, Some(0x0403));
+    assert_eq!(rules.rules()[0].pid, Some(0x6001));
+}
+
+#[test]
+fn test_invalid_rules() {
+    for text in [
+        "[[rule]]\nvid = \"0403\"\n",
+        "[[rule]]\nname = \"a\"\n",
+        "[[rule]]\nname = \"a\"\nvid = \"xyz\"\n",
+        "[[rule]]\nname = \"a\"\npid = 70000\n",
+        "[[rule]]\nname = \"a\"\ncolor = \"red\"\n",
+        "[[rule]]\nname = \"a\"\nproduct = 5\n",
+        "rule = \"a\"\n",
+        "[[rule]]\nname = \"a\"\nvid = 1\n[[rule]]\nname = \"a\"\nvid = 2\n",
+        "[[rule]\n",
+    ] {
+        assert!(PortRules::parse(text).is_err(), "{}", text);
+    }
+    assert_eq!(PortRules::parse("").unwrap().rules().len(), 0);
+}
+
+#[test]
+fn test_rule_matching() {
+    let rules = PortRules::parse(RULES).unwrap();
+    let monitor = rules.get("monitor").unwrap();
+    let ventilator = rules.get("ventilator").unwrap();
+    let legacy = rules.get("legacy").unwrap();
+
+    assert!(monitor.matches(&ftdi("/dev/ttyUSB2", "A12BC3")));
+    // Same adapter model, different unit
+    assert!(!monitor.matches(&ftdi("/dev/ttyUSB0", "FT99XYZ")));
+    assert!(!monitor.matches(&pci("/dev/ttyS0")));
+
+    // Case-insensitive substrings
+    assert!(ventilator.matches(&prolific("/dev/ttyUSB1")));
+    assert!(!ventilator.matches(&ftdi("/dev/ttyUSB1", "A12BC3")));
+
+    assert!(legacy.matches(&pci("/dev/ttyS0")));
+    assert!(!legacy.matches(&pci("/dev/ttyS1")));
+}
+
+#[test]
+fn test_select_rule() {
+    let rules = PortRules::parse(RULES).unwrap();
+    assert_eq!(rules.select(Some("ventilator")).unwrap().name, "ventilator");
+    assert!(rules.select(Some("printer")).is_err());
+    let error = rules.select(None).unwrap_err().to_string();
+    assert!(error.contains("monitor, ventilator, legacy"), "{}", error);
+
+    let single = PortRules::parse("[[rule]]\nname = \"only\"\nvid = 1\n").unwrap();
+    assert_eq!(single.select(None).unwrap().name, "only");
+    assert!(PortRules::default().select(None).is_err());
+}
+
+#[test]
+fn test_matcher_finds_rule_among_several_adapters() {
+    let mut rule = PortRule::new("monitor");
+    rule.serial_number = Some("A12BC3".to_string());
+    let matcher = PortMatcher::Rule(rule);
+
+    let ports = vec![
+        ftdi("/dev/ttyUSB0", "FT99XYZ"),
+        prolific("/dev/ttyUSB1"),
+        ftdi("/dev/ttyUSB2", "A12BC3"),
+    ];
+    assert_eq!(matcher.find(&ports), Some("/dev/ttyUSB2".to_string()));
+    assert_eq!(matcher.to_string(), "rule monitor");
+}
+
+#[test]
+fn test_watcher_reports_changes() {
+    let mut watcher = PortWatcher::new();
+
+    let first = vec![pci("/dev/ttyS0"), ftdi("/dev/ttyUSB0", "A12BC3")];
+    assert_eq!(
+        watcher.update(first.clone()),
+        vec![
+            PortChange::Added(pci("/dev/ttyS0")),
+            PortChange::Added(ftdi("/dev/ttyUSB0", "A12BC3")),
+        ]
+    );
+    assert_eq!(watcher.update(first), vec![]);
+
+    // Unplugged, then back under a new name next to another adapter
+    assert_eq!(
+        watcher.update(vec![pci("/dev/ttyS0")]),
+        vec![PortChange::Removed(ftdi("/dev/ttyUSB0", "A12BC3"))]
+    );
+    assert_eq!(
+        watcher.update(vec![
+            pci("/dev/ttyS0"),
+            prolific("/dev/ttyUSB0"),
+            ftdi("/dev/ttyUSB1", "A12BC3"),
+        ]),
+        vec![
+            PortChange::Added(prolific("/dev/ttyUSB0")),
+            PortChange::Added(ftdi("/dev/ttyUSB1", "A12BC3")),
+        ]
+    );
+}
+
+#[test]
+fn test_watcher_wait_gives_up_on_stop() {
+    let mut watcher = PortWatcher::new().with_stop_flag(Arc::new(AtomicBool::new(true)));
+    let matcher = PortMatcher::Name("/dev/ttyNONE".to_string());
+    assert_eq!(watcher.wait_for(&matcher, Duration::from_secs(10)), None);
+}
@@ -0,0 +1,382 @@
+use std::time::{Duration, Instant};
+use vital_reader::data::ObservationValue;
+use vital_reader::protocol::datex::{
+    decode_basic, decode_samples, main_type, phdb_type, DatexCodec, DatexConfig, DatexDriver,
+    DatexRecord, DatexRequest, WaveformType, HEADER_SIZE,
+};
+use vital_reader::protocol::{DriverEvent, ProtocolDriver};
+
+const INVALID: i16 = -32767;
+
+/// A displayed-values subrecord where every value is invalid except `values`
+fn phdb_subrecord(time: u32, values: &[(usize, i16)]) -> Vec<u8> {
+    let mut subrecord = time.to_le_bytes().to_vec();
+    for _ in 0..135 {
+        subrecord.extend_from_slice(&INVALID.to_le_bytes());
+    }
+    subrecord.extend_from_slice(&[0, 0, 0, 0]);
+    for &(offset, value) in values {
+        subrecord[4 + offset..6 + offset].copy_from_slice(&value.to_le_bytes());
+    }
+    subrecord
+}
+
+/// Groups of the basic class in the order of the spec's `basic_phdb`
+/// struct, each a 6 byte header (status, label) and its 16 bit values
+const BASIC_GROUPS: &[(&str, &[&str])] = &[
+    ("ecg", &["hr", "st1", "st2", "st3", "imp_rr"]),
+    ("p1", &["sys", "dia", "mean", "hr"]),
+    ("p2", &["sys", "dia", "mean", "hr"]),
+    ("p3", &["sys", "dia", "mean", "hr"]),
+    ("p4", &["sys", "dia", "mean", "hr"]),
+    ("nibp", &["sys", "dia", "mean", "hr"]),
+    ("t1", &["temp"]),
+    ("t2", &["temp"]),
+    ("t3", &["temp"]),
+    ("t4", &["temp"]),
+    ("spo2", &["spo2", "pr", "ir_amp", "svo2"]),
+    ("co2", &["et", "fi", "rr", "amb_press"]),
+    ("o2", &["et", "fi"]),
+    ("n2o", &["et", "fi"]),
+    ("aa", &["et", "fi", "mac_sum"]),
+    (
+        "flow_vol",
+        &[
+            "rr",
+            "ppeak",
+            "peep",
+            "pplat",
+            "tv_insp",
+            "tv_exp",
+            "compliance",
+            "mv_exp",
+        ],
+    ),
+];
+
+/// A basic-class subrecord laid out group by group, with `value` for each
+/// "group.member" and an invalid value everywhere else
+fn basic_subrecord(value: impl Fn(&str) -> Option<i16>) -> Vec<u8> {
+    let mut subrecord = 0u32.to_le_bytes().to_vec();
+    for (group, members) in BASIC_GROUPS {
+        subrecord.extend_from_slice(&[0; 6]);
+        for member in *members {
+            let raw = value(&format!("{}.{}", group, member)).unwrap_or(INVALID);
+            subrecord.extend_from_slice(&raw.to_le_bytes());
+        }
+    }
+    subrecord
+}
+
+fn wave_subrecord(samples: &[i16]) -> Vec<u8> {
+    let mut subrecord = (samples.len() as u16).to_le_bytes().to_vec();
+    subrecord.extend_from_slice(&[0, 0, 0, 0]);
+    for sample in samples {
+        subrecord.extend_from_slice(&sample.to_le_bytes());
+    }
+    subrecord
+}
+
+#[test]
+fn test_codec_round_trip_with_stuffing() {
+    let record = vec![0x01, 0x7E, 0x02, 0x7D, 0x03];
+    let frame = DatexCodec::encode(&record);
+    assert_eq!(
+        frame,
+        vec![0x7E, 0x01, 0x7D, 0x5E, 0x02, 0x7D, 0x5D, 0x03, 0x01, 0x7E]
+    );
+
+    let mut codec = DatexCodec::new();
+    let records = codec.decode(&frame);
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].as_ref().unwrap(), &record);
+}
+
+#[test]
+fn test_codec_split_frames_and_noise() {
+    let mut codec = DatexCodec::new();
+    let mut data = vec![0x55, 0xAA];
+    data.extend(DatexCodec::encode(b"first"));
+    data.extend(DatexCodec::encode(b"second"));
+    let (a, b) = data.split_at(12);
+
+    let mut records = codec.decode(a);
+    assert!(codec.in_frame());
+    records.extend(codec.decode(b));
+    let records: Vec<_> = records.into_iter().map(|r| r.unwrap()).collect();
+    assert_eq!(records, vec![b"first".to_vec(), b"second".to_vec()]);
+    assert_eq!(codec.discarded_bytes(), 2);
+}
+
+#[test]
+fn test_codec_checksum_error() {
+    let mut codec = DatexCodec::new();
+    let mut frame = DatexCodec::encode(b"data");
+    frame[1] = b'D';
+    let records = codec.decode(&frame);
+    assert!(records[0]
+        .as_ref()
+        .unwrap_err()
+        .to_string()
+        .contains("checksum"));
+}
+
+#[test]
+fn test_record_encode_parse_round_trip() {
+    let mut record = DatexRecord::new(
+        main_type::PHDB,
+        &[
+            (phdb_type::DISPLAYED, b"abcd"),
+            (phdb_type::TREND_10S, b"ef"),
+        ],
+    );
+    record.time = 1_700_000_000;
+
+    let bytes = record.encode();
+    assert_eq!(bytes.len(), HEADER_SIZE + 6);
+    assert_eq!(u16::from_le_bytes([bytes[0], bytes[1]]), 46);
+
+    let parsed = DatexRecord::parse(&bytes).unwrap();
+    assert_eq!(parsed, record);
+    assert_eq!(
+        parsed.subrecord(0),
+        Some((phdb_type::DISPLAYED, &b"abcd"[..]))
+    );
+    assert_eq!(
+        parsed.subrecord(1),
+        Some((phdb_type::TREND_10S, &b"ef"[..]))
+    );
+    assert_eq!(parsed.subrecord(2), None);
+}
+
+#[test]
+fn test_record_parse_errors() {
+    assert!(DatexRecord::parse(&[0; 10]).is_err());
+
+    let mut bytes = DatexRecord::new(main_type::PHDB, &[(0, b"x")]).encode();
+    bytes[0] = 100;
+    assert!(DatexRecord::parse(&bytes).is_err());
+}
+
+#[test]
+fn test_scaled_sentinels() {
+    let bytes = [0x10, 0x27, 0x01, 0x80, 0xFF, 0x82, 0x00, 0x83];
+    assert_eq!(DatexRecord::scaled(&bytes, 0, 100.0), Some(100.0));
+    // -32767 (invalid) and -32001 (limit) are sentinels, -32000 is a value
+    assert_eq!(DatexRecord::scaled(&bytes, 2, 1.0), None);
+    assert_eq!(DatexRecord::scaled(&bytes, 4, 1.0), None);
+    assert_eq!(DatexRecord::scaled(&bytes, 6, 1.0), Some(-32000.0));
+    assert_eq!(DatexRecord::scaled(&bytes, 7, 1.0), None);
+}
+
+#[test]
+fn test_decode_basic_displayed_values() {
+    let subrecord = phdb_subrecord(1_700_000_000, &[(6, 72), (124, 9800), (194, 4505)]);
+    let observations = decode_basic(&subrecord, "DATEX");
+    assert_eq!(observations.len(), 3);
+
+    assert_eq!(observations[0].code, "8867-4");
+    assert_eq!(observations[0].value, ObservationValue::Numeric(72.0));
+    assert_eq!(observations[0].unit.as_deref(), Some("/min"));
+    assert!(observations[0].observation_time.is_some());
+
+    assert_eq!(observations[1].code, "2708-6");
+    assert_eq!(observations[1].value.as_f64(), Some(98.0));
+
+    assert_eq!(observations[2].display_name, "Expired Tidal Volume");
+    assert_eq!(observations[2].value.as_f64(), Some(450.5));
+    assert_eq!(observations[2].producer.as_deref(), Some("DATEX"));
+}
+
+#[test]
+fn test_decode_basic_without_loinc() {
+    let subrecord = phdb_subrecord(0, &[(22, 12000)]);
+    let observations = decode_basic(&subrecord, "DATEX");
+    assert_eq!(observations[0].code, "P1_SYSTOLIC");
+    assert_eq!(observations[0].coding_system, "DATEX");
+    assert_eq!(observations[0].value.as_f64(), Some(120.0));
+}
+
+#[test]
+fn test_decode_basic_follows_spec_layout() {
+    let expected = [
+        ("ecg.hr", "Heart Rate", 72, 72.0),
+        ("nibp.sys", "Systolic BP", 12000, 120.0),
+        ("t4.temp", "Temperature 4", 3650, 36.5),
+        ("spo2.pr", "Pulse Rate", 71, 71.0),
+        // 5 % and 0.5 % of 760 mmHg ambient pressure
+        ("co2.et", "End Tidal CO2", 500, 38.0),
+        ("co2.fi", "Inspired CO2", 50, 3.8),
+        ("o2.et", "End Tidal O2", 4500, 45.0),
+        ("o2.fi", "FiO2", 5000, 50.0),
+        ("n2o.et", "End Tidal N2O", 3000, 30.0),
+        ("n2o.fi", "Inspired N2O", 3500, 35.0),
+        ("aa.et", "End Tidal Agent", 110, 1.1),
+        ("aa.fi", "Inspired Agent", 130, 1.3),
+        ("aa.mac_sum", "MAC Sum", 90, 0.9),
+        ("flow_vol.rr", "Respiratory Rate", 14, 14.0),
+        ("flow_vol.ppeak", "Peak Pressure", 2200, 22.0),
+        ("flow_vol.peep", "PEEP", 500, 5.0),
+        ("flow_vol.pplat", "Plateau Pressure", 1800, 18.0),
+        ("flow_vol.tv_insp", "Inspired Tidal Volume", 4600, 460.0),
+        ("flow_vol.tv_exp", "Expired Tidal Volume", 4505, 450.5),
+        ("flow_vol.compliance", "Compliance", 4000, 40.0),
+        ("flow_vol.mv_exp", "Expired Minute Volume", 620, 6.2),
+    ];
+    let subrecord = basic_subrecord(|member| {
+        match member {
+            // Not decoded, must not show up as a neighbouring value
+            "co2.rr" => Some(15),
+            "co2.amb_press" => Some(7600),
+            _ => expected
+                .iter()
+                .find(|(name, ..)| *name == member)
+                .map(|&(_, _, raw, _)| raw),
+        }
+    });
+
+    let observations = decode_basic(&subrecord, "DATEX");
+    let decoded: Vec<_> = observations
+        .iter()
+        .map(|o| (o.display_name.as_str(), o.value.as_f64().unwrap()))
+        .collect();
+    let expected: Vec<_> = expected
+        .iter()
+        .map(|&(_, name, _, value)| (name, value))
+        .collect();
+    assert_eq!(decoded, expected);
+}
+
+#[test]
+fn test_decode_basic_co2_without_ambient_pressure() {
+    let subrecord = basic_subrecord(|member| (member == "co2.et").then_some(510));
+    let observations = decode_basic(&subrecord, "DATEX");
+
+    // Only the fraction is known, which is not what the LOINC code measures
+    assert_eq!(observations.len(), 1);
+    assert_eq!(observations[0].code, "END_TIDAL_CO2");
+    assert_eq!(observations[0].unit.as_deref(), Some("%"));
+    assert_eq!(observations[0].value.as_f64(), Some(5.1));
+}
+
+#[test]
+fn test_decode_samples() {
+    let samples = decode_samples(&wave_subrecord(&[100, -50, INVALID]), 100.0);
+    assert_eq!(samples[..2], [1.0, -0.5]);
+    assert!(samples[2].is_nan());
+    assert!(decode_samples(&[1, 0], 1.0).is_empty());
+}
+
+#[test]
+fn test_waveform_lookup() {
+    assert_eq!(WaveformType::from_name("pleth").unwrap().code, 8);
+    assert_eq!(WaveformType::lookup(1).unwrap().name, "ECG1");
+    assert!(WaveformType::lookup(200).is_none());
+}
+
+#[test]
+fn test_displayed_values_request() {
+    let mut codec = DatexCodec::new();
+    let record = codec
+        .decode(&DatexRequest::displayed_values(10))
+        .remove(0)
+        .unwrap();
+    let record = DatexRecord::parse(&record).unwrap();
+
+    assert_eq!(record.main_type, main_type::PHDB);
+    assert_eq!(record.subrecords, vec![(0, phdb_type::DISPLAYED)]);
+    assert_eq!(record.data, vec![0, 10, 0, 0, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn test_waveform_request() {
+    let mut codec = DatexCodec::new();
+    let frame = DatexRequest::waveforms(&[1, 8]).unwrap();
+    let record = DatexRecord::parse(&codec.decode(&frame).remove(0).unwrap()).unwrap();
+
+    assert_eq!(record.main_type, main_type::WAVEFORM);
+    assert_eq!(record.data.len(), 32);
+    assert_eq!(&record.data[..6], &[0, 0, 0, 0, 1, 8]);
+    assert_eq!(record.data[6], 0xFF);
+
+    assert!(DatexRequest::waveforms(&[1; 9]).is_err());
+    let stop = DatexRecord::parse(
+        &codec
+            .decode(&DatexRequest::stop_waveforms())
+            .remove(0)
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(&stop.data[..2], &[1, 0]);
+}
+
+#[test]
+fn test_driver_subscribes_and_decodes() {
+    let now = Instant::now();
+    let config = DatexConfig {
+        interval: Duration::from_secs(5),
+        waveforms: vec![1],
+        ..DatexConfig::default()
+    };
+    let mut driver = DatexDriver::new(config);
+
+    let mut expected = DatexRequest::displayed_values(5);
+    expected.extend(DatexRequest::waveforms(&[1]).unwrap());
+    assert_eq!(driver.poll(now), expected);
+    assert!(driver.poll(now + Duration::from_secs(1)).is_empty());
+
+    let phdb = DatexRecord::new(
+        main_type::PHDB,
+        &[(phdb_type::DISPLAYED, &phdb_subrecord(0, &[(6, 60)]))],
+    );
+    let wave = DatexRecord::new(main_type::WAVEFORM, &[(1, &wave_subrecord(&[100, 200]))]);
+    let mut data = DatexCodec::encode(&phdb.encode());
+    data.extend(DatexCodec::encode(&wave.encode()));
+    driver.receive(&data);
+
+    let events = driver.take_events();
+    assert_eq!(events.len(), 2);
+    assert!(matches!(&events[0], DriverEvent::Observation(o) if o.code == "8867-4"));
+    assert_eq!(
+        events[1],
+        DriverEvent::Waveform {
+            name: "ECG1".to_string(),
+            unit: "mV".to_string(),
+            sample_rate: 300,
+            samples: vec![1.0, 2.0],
+        }
+    );
+}
+
+#[test]
+fn test_driver_resubscribes_when_quiet() {
+    let now = Instant::now();
+    let mut driver = DatexDriver::default();
+    driver.poll(now);
+
+    // Data keeps the subscription alive
+    driver.receive(&DatexCodec::encode(
+        &DatexRecord::new(main_type::ALARM, &[]).encode(),
+    ));
+    assert!(driver.poll(now + Duration::from_secs(20)).is_empty());
+    assert!(driver.poll(now + Duration::from_secs(40)).is_empty());
+
+    assert_eq!(
+        driver.poll(now + Duration::from_secs(50)),
+        DatexRequest::displayed_values(10)
+    );
+    assert!(matches!(
+        &driver.take_events()[..],
+        [DriverEvent::Warning(w)] if w.contains("renewing")
+    ));
+}
+
+#[test]
+fn test_driver_reports_bad_records() {
+    let mut driver = DatexDriver::default();
+    driver.receive(&DatexCodec::encode(b"short"));
+    assert!(matches!(
+        &driver.take_events()[..],
+        [DriverEvent::Warning(w)] if w.contains("too short")
+    ));
+}
@@ -0,0 +1,356 @@
+use chrono::NaiveDate;
+use std::time::{Duration, Instant};
+use vital_reader::protocol::intellivue::{
+    oid, session, DataExportMessage, IntellivueCodec, IntellivueConfig, IntellivueDriver,
+    IntellivueState, Pdu,
+};
+use vital_reader::protocol::{DriverEvent, ProtocolDriver};
+
+const MDS: [u8; 6] = [0x00, 0x21, 0x00, 0x00, 0x00, 0x00];
+
+/// Append `body` preceded by its big endian u16 length
+fn with_length(out: &mut Vec<u8>, body: &[u8]) {
+    out.extend_from_slice(&(body.len() as u16).to_be_bytes());
+    out.extend_from_slice(body);
+}
+
+fn ava(id: u16, value: &[u8]) -> Vec<u8> {
+    let mut out = id.to_be_bytes().to_vec();
+    with_length(&mut out, value);
+    out
+}
+
+fn attribute_list(avas: &[Vec<u8>]) -> Vec<u8> {
+    let mut out = (avas.len() as u16).to_be_bytes().to_vec();
+    with_length(&mut out, &avas.concat());
+    out
+}
+
+fn numeric(physio_id: u16, state: u16, unit: u16, value: u32) -> Vec<u8> {
+    let mut out = Vec::new();
+    for part in [physio_id, state, unit] {
+        out.extend_from_slice(&part.to_be_bytes());
+    }
+    out.extend_from_slice(&value.to_be_bytes());
+    out
+}
+
+fn data_export(ro_type: u16, invoke_id: u16, command: u16, body: &[u8]) -> Vec<u8> {
+    let mut apdu = invoke_id.to_be_bytes().to_vec();
+    apdu.extend_from_slice(&command.to_be_bytes());
+    with_length(&mut apdu, body);
+
+    let mut out = vec![0xE1, 0x00, 0x00, 0x02];
+    out.extend_from_slice(&ro_type.to_be_bytes());
+    with_length(&mut out, &apdu);
+    out
+}
+
+fn mds_create_event() -> Vec<u8> {
+    let mut model = Vec::new();
+    with_length(&mut model, b"Philips\0");
+    with_length(&mut model, b"MX800\0");
+
+    let mut info = MDS.to_vec();
+    info.extend(attribute_list(&[ava(oid::ATTR_ID_MODEL, &model)]));
+
+    let mut argument = MDS.to_vec();
+    argument.extend_from_slice(&0x1234u32.to_be_bytes());
+    argument.extend_from_slice(&oid::NOTI_MDS_CREAT.to_be_bytes());
+    with_length(&mut argument, &info);
+    data_export(1, 7, 1, &argument)
+}
+
+fn poll_result(attributes: &[Vec<u8>]) -> Vec<u8> {
+    let mut observation = 0x0042u16.to_be_bytes().to_vec();
+    observation.extend(attribute_list(attributes));
+
+    let mut context = vec![0x00, 0x00, 0x00, 0x01];
+    with_length(&mut context, &observation);
+
+    let mut reply = vec![0x00, 0x01, 0x00, 0x00, 0x00, 0x00];
+    reply.extend_from_slice(&[0x20, 0x25, 0x01, 0x04, 0x12, 0x00, 0x05, 0x00]);
+    reply.extend_from_slice(&[0x00, 0x01, 0x00, 0x06, 0x08, 0x03]);
+    reply.extend_from_slice(&[0x00, 0x01]);
+    with_length(&mut reply, &context);
+
+    let mut result = MDS.to_vec();
+    result.extend_from_slice(&oid::ACT_POLL_MDIB_DATA.to_be_bytes());
+    with_length(&mut result, &reply);
+    data_export(2, 1, 7, &result)
+}
+
+fn polling_driver(now: Instant) -> IntellivueDriver {
+    let mut driver = IntellivueDriver::default();
+    driver.poll(now);
+    driver.receive(&IntellivueCodec::encode(&[session::ACCEPT, 0x00]));
+    driver.receive(&IntellivueCodec::encode(&mds_create_event()));
+    driver.poll(now);
+    driver.take_events();
+    driver
+}
+
+#[test]
+fn test_fcs_residual() {
+    let data = b"123456789";
+    // Check value of the PPP FCS-16
+    assert_eq!(IntellivueCodec::fcs(data), 0x906E);
+
+    let mut with_fcs = data.to_vec();
+    with_fcs.extend_from_slice(&IntellivueCodec::fcs(data).to_le_bytes());
+    assert_eq!(!IntellivueCodec::fcs(&with_fcs), 0xF0B8);
+}
+
+#[test]
+fn test_codec_round_trip_with_escapes() {
+    let payload = vec![0xC0, 0x01, 0xC1, 0x7D, 0x02];
+    let frame = IntellivueCodec::encode(&payload);
+    assert_eq!(frame[0], 0xC0);
+    assert_eq!(*frame.last().unwrap(), 0xC1);
+    assert_eq!(&frame[1..5], &[0x11, 0x01, 0x00, 0x05]);
+    assert_eq!(&frame[5..8], &[0x7D, 0xE0, 0x01]);
+
+    let mut codec = IntellivueCodec::new();
+    let (first, second) = frame.split_at(7);
+    assert!(codec.decode(first).is_empty());
+    assert!(codec.in_frame());
+    let messages = codec.decode(second);
+    assert_eq!(messages[0].as_ref().unwrap(), &payload);
+}
+
+#[test]
+fn test_codec_rejects_bad_frames() {
+    let mut codec = IntellivueCodec::new();
+    let mut frame = IntellivueCodec::encode(b"data");
+    frame[5] = b'D';
+    assert!(codec.decode(&frame)[0]
+        .as_ref()
+        .unwrap_err()
+        .to_string()
+        .contains("FCS"));
+
+    assert!(codec.decode(&[0xC0, 0x11, 0xC1])[0].is_err());
+    assert_eq!(codec.decode(b"noise").len(), 0);
+    assert_eq!(codec.discarded_bytes(), 5);
+}
+
+#[test]
+fn test_decode_float() {
+    assert_eq!(Pdu::decode_float(0x0000_0048), Some(72.0));
+    // 985 * 10^-1
+    assert_eq!(Pdu::decode_float(0xFF00_03D9), Some(98.5));
+    // -5 * 10^2
+    assert_eq!(Pdu::decode_float(0x02FF_FFFB), Some(-500.0));
+    assert_eq!(Pdu::decode_float(0x007F_FFFF), None);
+    assert_eq!(Pdu::decode_float(0x0080_0000), None);
+    assert_eq!(Pdu::decode_float(0x007F_FFFE), None);
+}
+
+#[test]
+fn test_association_request_lengths() {
+    let request = Pdu::association_request();
+    assert_eq!(request[0], session::CONNECT);
+    assert_eq!(request[1] as usize, request.len() - 2);
+
+    // Presentation header follows the 14 bytes of session data
+    assert_eq!(request[16], 0xC1);
+    assert_eq!(request[17] as usize, request.len() - 18);
+    // User info length
+    assert!(request.windows(2).any(|w| w == [0x81, 0x48]));
+}
+
+#[test]
+fn test_poll_request_layout() {
+    let request = Pdu::poll_request(3, 9, oid::MOC_VMO_METRIC_NU);
+    assert_eq!(
+        request,
+        vec![
+            0xE1, 0x00, 0x00, 0x02, // session, context
+            0x00, 0x01, 0x00, 0x1C, // ROIV, length
+            0x00, 0x03, 0x00, 0x07, 0x00, 0x16, // invoke id, confirmed action
+            0x00, 0x21, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // MDS, scope
+            0x0C, 0x16, 0x00, 0x08, // poll action
+            0x00, 0x09, 0x00, 0x01, 0x00, 0x06, 0x00, 0x00,
+        ]
+    );
+}
+
+#[test]
+fn test_parse_mds_create() {
+    let message = Pdu::parse(&mds_create_event()).unwrap();
+    assert_eq!(
+        message,
+        DataExportMessage::MdsCreate {
+            invoke_id: 7,
+            managed_object: MDS,
+            event_time: 0x1234,
+            model: Some("Philips MX800".to_string()),
+        }
+    );
+
+    let result = Pdu::mds_create_result(7, &MDS, 0x1234);
+    assert_eq!(&result[4..6], &[0x00, 0x02]);
+    assert_eq!(&result[8..12], &[0x00, 0x07, 0x00, 0x01]);
+    assert_eq!(&result[result.len() - 4..], &[0x0D, 0x06, 0x00, 0x00]);
+}
+
+#[test]
+fn test_parse_poll_result() {
+    let mut compound = vec![0x00, 0x03, 0x00, 0x1E];
+    compound.extend(numeric(0x4A05, 0, 0x0F20, 120));
+    compound.extend(numeric(0x4A06, 0, 0x0F20, 80));
+    compound.extend(numeric(0x4A07, 0, 0x0F20, 93));
+
+    let message = Pdu::parse(&poll_result(&[
+        ava(oid::ATTR_NU_VAL_OBS, &numeric(0x4182, 0, 0x0AA0, 72)),
+        ava(0x0924, &[0, 0, 0, 1]),
+        ava(oid::ATTR_NU_CMPD_VAL_OBS, &compound),
+    ]))
+    .unwrap();
+
+    let DataExportMessage::PollResult {
+        poll_number,
+        time,
+        numerics,
+    } = message
+    else {
+        panic!("expected a poll result");
+    };
+    assert_eq!(poll_number, 1);
+    assert_eq!(
+        time,
+        NaiveDate::from_ymd_opt(2025, 1, 4)
+            .unwrap()
+            .and_hms_opt(12, 0, 5)
+    );
+    assert_eq!(numerics.len(), 4);
+    assert_eq!(numerics[0].physio_id, 0x4182);
+    assert_eq!(numerics[0].value, Some(72.0));
+    assert_eq!(numerics[3].physio_id, 0x4A07);
+}
+
+#[test]
+fn test_parse_truncated_message() {
+    let mut message = mds_create_event();
+    message.truncate(20);
+    assert!(Pdu::parse(&message)
+        .unwrap_err()
+        .to_string()
+        .contains("truncated"));
+    assert!(Pdu::parse(&[0x0E, 0x00]).is_err());
+}
+
+#[test]
+fn test_driver_association_and_polling() {
+    let now = Instant::now();
+    let mut driver = IntellivueDriver::default();
+    assert_eq!(driver.state(), IntellivueState::Associating);
+
+    assert_eq!(
+        driver.poll(now),
+        IntellivueCodec::encode(&Pdu::association_request())
+    );
+    assert!(driver.poll(now + Duration::from_secs(1)).is_empty());
+
+    driver.receive(&IntellivueCodec::encode(&[session::ACCEPT, 0x00]));
+    assert_eq!(driver.state(), IntellivueState::WaitingForMds);
+
+    driver.receive(&IntellivueCodec::encode(&mds_create_event()));
+    assert_eq!(driver.state(), IntellivueState::Polling);
+    assert_eq!(
+        driver.take_events(),
+        vec![DriverEvent::Identified("Philips MX800".to_string())]
+    );
+
+    let mut expected = IntellivueCodec::encode(&Pdu::mds_create_result(7, &MDS, 0x1234));
+    expected.extend(IntellivueCodec::encode(&Pdu::poll_request(
+        1,
+        1,
+        oid::MOC_VMO_METRIC_NU,
+    )));
+    let later = now + Duration::from_secs(1);
+    assert_eq!(driver.poll(later), expected);
+    assert!(driver.poll(later).is_empty());
+    assert_eq!(
+        driver.poll(later + Duration::from_secs(1)),
+        IntellivueCodec::encode(&Pdu::poll_request(2, 2, oid::MOC_VMO_METRIC_NU))
+    );
+}
+
+#[test]
+fn test_driver_maps_numerics() {
+    let now = Instant::now();
+    let mut driver = polling_driver(now);
+
+    driver.receive(&IntellivueCodec::encode(&poll_result(&[
+        ava(
+            oid::ATTR_NU_VAL_OBS,
+            &numeric(0x4BB8, 0, 0x0220, 0xFF00_03D9),
+        ),
+        ava(oid::ATTR_NU_VAL_OBS, &numeric(0x4182, 0x8000, 0x0AA0, 72)),
+        ava(
+            oid::ATTR_NU_VAL_OBS,
+            &numeric(0x4261, 0, 0x0200, 0x007F_FFFF),
+        ),
+        ava(oid::ATTR_NU_VAL_OBS, &numeric(0x1234, 0, 0x9999, 5)),
+    ])));
+
+    let observations: Vec<_> = driver
+        .take_events()
+        .into_iter()
+        .map(|event| match event {
+            DriverEvent::Observation(observation) => observation,
+            other => panic!("unexpected event {:?}", other),
+        })
+        .collect();
+
+    // Invalid state and NaN values are dropped
+    assert_eq!(observations.len(), 2);
+    assert_eq!(observations[0].code, "2708-6");
+    assert_eq!(observations[0].value.as_f64(), Some(98.5));
+    assert_eq!(observations[0].unit.as_deref(), Some("%"));
+    assert_eq!(
+        observations[0].producer.as_deref(),
+        Some("PHILIPS^INTELLIVUE")
+    );
+    assert_eq!(observations[1].code, "0x1234");
+    assert_eq!(observations[1].coding_system, "MDC");
+    assert_eq!(observations[1].unit, None);
+}
+
+#[test]
+fn test_driver_reassociates() {
+    let now = Instant::now();
+    let mut driver = polling_driver(now);
+
+    driver.receive(&IntellivueCodec::encode(&[session::ABORT, 0x00]));
+    assert_eq!(driver.state(), IntellivueState::Associating);
+    assert!(matches!(
+        &driver.take_events()[..],
+        [DriverEvent::Warning(w)] if w.contains("aborted")
+    ));
+    assert_eq!(
+        driver.poll(now),
+        IntellivueCodec::encode(&Pdu::association_request())
+    );
+}
+
+#[test]
+fn test_driver_silence_timeout() {
+    let now = Instant::now();
+    let config = IntellivueConfig {
+        silence_timeout: Duration::from_secs(3),
+        ..IntellivueConfig::default()
+    };
+    let mut driver = IntellivueDriver::new(config);
+    driver.poll(now);
+    driver.receive(&IntellivueCodec::encode(&[session::ACCEPT, 0x00]));
+    driver.poll(now);
+
+    let data = driver.poll(now + Duration::from_secs(3));
+    assert_eq!(data, IntellivueCodec::encode(&Pdu::association_request()));
+    assert!(matches!(
+        &driver.take_events()[..],
+        [DriverEvent::Warning(w)] if w.contains("silent")
+    ));
+}
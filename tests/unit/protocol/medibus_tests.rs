@@ -0,0 +1,322 @@
+use std::time::{Duration, Instant};
+use vital_reader::data::ObservationValue;
+use vital_reader::protocol::medibus::{
+    command, FrameKind, MedibusCodec, MedibusConfig, MedibusDriver, MedibusState,
+};
+use vital_reader::protocol::{driver_from_name, DriverEvent, ProtocolDriver};
+
+fn identified_driver(now: Instant) -> MedibusDriver {
+    let mut driver = MedibusDriver::default();
+    driver.poll(now);
+    driver.receive(&MedibusCodec::encode_response(command::ICC, b""));
+    driver.poll(now);
+    driver.receive(&MedibusCodec::encode_response(
+        command::DEVICE_ID,
+        b"6321'Evita4'03.10",
+    ));
+    driver.take_events();
+    driver
+}
+
+#[test]
+fn test_checksum() {
+    // ESC + '0' = 0x1B + 0x30 = 0x4B
+    assert_eq!(MedibusCodec::checksum(&[0x1B, 0x30]), *b"4B");
+    assert_eq!(MedibusCodec::checksum(&[0xFF, 0x02]), *b"01");
+}
+
+#[test]
+fn test_encode_command_and_response() {
+    assert_eq!(
+        MedibusCodec::encode_command(command::NOP, b""),
+        vec![0x1B, 0x30, b'4', b'B', 0x0D]
+    );
+    assert_eq!(
+        MedibusCodec::encode_response(command::ICC, b""),
+        vec![0x01, 0x51, b'5', b'2', 0x0D]
+    );
+}
+
+#[test]
+fn test_decode_round_trip() {
+    let mut codec = MedibusCodec::new();
+    let mut data = MedibusCodec::encode_response(command::MEASURED_DATA, b"88 450");
+    data.extend(MedibusCodec::encode_command(command::NOP, b""));
+
+    let frames: Vec<_> = codec
+        .decode(&data)
+        .into_iter()
+        .map(|f| f.unwrap())
+        .collect();
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].kind, FrameKind::Response);
+    assert_eq!(frames[0].command, command::MEASURED_DATA);
+    assert_eq!(frames[0].data, b"88 450");
+    assert_eq!(frames[1].kind, FrameKind::Command);
+    assert!(frames[1].data.is_empty());
+}
+
+#[test]
+fn test_decode_split_and_noise() {
+    let mut codec = MedibusCodec::new();
+    let frame = MedibusCodec::encode_response(command::ICC, b"");
+    let (first, second) = frame.split_at(2);
+
+    assert!(codec.decode(b"xx").is_empty());
+    assert!(codec.decode(first).is_empty());
+    assert!(codec.in_frame());
+    assert_eq!(codec.decode(second).len(), 1);
+    assert_eq!(codec.discarded_bytes(), 2);
+}
+
+#[test]
+fn test_decode_checksum_error() {
+    let mut codec = MedibusCodec::new();
+    let mut frame = MedibusCodec::encode_response(command::ICC, b"");
+    frame[2] = b'0';
+
+    let frames = codec.decode(&frame);
+    assert_eq!(frames.len(), 1);
+    assert!(frames[0]
+        .as_ref()
+        .unwrap_err()
+        .to_string()
+        .contains("checksum"));
+}
+
+#[test]
+fn test_decode_cancel_and_flow_control() {
+    let mut codec = MedibusCodec::new();
+    assert!(codec.decode(&[0x1B, 0x30, 0x18]).is_empty());
+    assert!(!codec.in_frame());
+
+    let mut frame = MedibusCodec::encode_command(command::NOP, b"");
+    frame.insert(2, 0x11);
+    assert!(codec.decode(&frame)[0].is_ok());
+}
+
+#[test]
+fn test_decode_too_short() {
+    let mut codec = MedibusCodec::new();
+    assert!(codec.decode(&[0x01, 0x51, 0x0D])[0].is_err());
+}
+
+#[test]
+fn test_driver_handshake() {
+    let now = Instant::now();
+    let mut driver = MedibusDriver::default();
+    assert_eq!(driver.state(), MedibusState::Initializing);
+
+    assert_eq!(
+        driver.poll(now),
+        MedibusCodec::encode_command(command::ICC, b"")
+    );
+    // Waiting for the ICC response
+    assert!(driver.poll(now).is_empty());
+
+    driver.receive(&MedibusCodec::encode_response(command::ICC, b""));
+    assert_eq!(driver.state(), MedibusState::Identifying);
+    assert_eq!(
+        driver.poll(now),
+        MedibusCodec::encode_command(command::DEVICE_ID, b"")
+    );
+
+    driver.receive(&MedibusCodec::encode_response(
+        command::DEVICE_ID,
+        b"6321'Evita4'03.10",
+    ));
+    assert_eq!(driver.state(), MedibusState::Polling);
+    assert_eq!(driver.device(), Some("Evita4 (6321) 03.10"));
+    assert_eq!(
+        driver.take_events(),
+        vec![DriverEvent::Identified("Evita4 (6321) 03.10".to_string())]
+    );
+}
+
+#[test]
+fn test_driver_polling_cycle() {
+    let now = Instant::now();
+    let mut driver = identified_driver(now);
+
+    for expected in [
+        command::MEASURED_DATA,
+        command::ALARMS,
+        command::DEVICE_SETTINGS,
+    ] {
+        assert_eq!(
+            driver.poll(now),
+            MedibusCodec::encode_command(expected, b"")
+        );
+        driver.receive(&MedibusCodec::encode_response(expected, b""));
+    }
+
+    // Next cycle waits for the poll interval
+    assert!(driver.poll(now).is_empty());
+    assert_eq!(
+        driver.poll(now + Duration::from_secs(1)),
+        MedibusCodec::encode_command(command::MEASURED_DATA, b"")
+    );
+}
+
+#[test]
+fn test_driver_measured_data() {
+    let now = Instant::now();
+    let mut driver = identified_driver(now);
+    driver.poll(now);
+    driver.receive(&MedibusCodec::encode_response(
+        command::MEASURED_DATA,
+        b"88 450B9 7.2D6  14F0----42  12",
+    ));
+
+    let observations: Vec<_> = driver
+        .take_events()
+        .into_iter()
+        .map(|event| match event {
+            DriverEvent::Observation(observation) => observation,
+            other => panic!("unexpected event {:?}", other),
+        })
+        .collect();
+    assert_eq!(observations.len(), 4);
+
+    assert_eq!(observations[0].code, "20112-9");
+    assert_eq!(observations[0].coding_system, "LN");
+    assert_eq!(observations[0].value, ObservationValue::Numeric(450.0));
+    assert_eq!(observations[0].unit.as_deref(), Some("mL"));
+    assert_eq!(
+        observations[0].producer.as_deref(),
+        Some("Evita4 (6321) 03.10")
+    );
+    assert!(observations[0].observation_time.is_some());
+
+    assert_eq!(observations[1].value.as_f64(), Some(7.2));
+    assert_eq!(observations[2].display_name, "Respiratory Rate");
+
+    // FiO2 "----" is skipped, unknown codes keep the MEDIBUS code
+    assert_eq!(observations[3].code, "42");
+    assert_eq!(observations[3].coding_system, "MEDIBUS");
+}
+
+#[test]
+fn test_driver_alarms_and_settings() {
+    let now = Instant::now();
+    let mut driver = identified_driver(now);
+
+    driver.poll(now);
+    driver.receive(&MedibusCodec::encode_response(command::MEASURED_DATA, b""));
+    driver.poll(now);
+    driver.receive(&MedibusCodec::encode_response(
+        command::ALARMS,
+        b"31AAPAW HIGH    ",
+    ));
+    driver.poll(now);
+    driver.receive(&MedibusCodec::encode_response(
+        command::DEVICE_SETTINGS,
+        b"01   40",
+    ));
+
+    let events = driver.take_events();
+    assert_eq!(
+        events[0],
+        DriverEvent::Alarm {
+            priority: 3,
+            code: "1A".to_string(),
+            text: "APAW HIGH".to_string(),
+        }
+    );
+    match &events[1] {
+        DriverEvent::Setting(setting) => {
+            assert_eq!(setting.code, "01");
+            assert_eq!(setting.coding_system, "MEDIBUS-SETTING");
+            assert_eq!(setting.value.as_f64(), Some(40.0));
+        }
+        other => panic!("unexpected event {:?}", other),
+    }
+}
+
+#[test]
+fn test_driver_answers_device_commands() {
+    let now = Instant::now();
+    let mut driver = identified_driver(now);
+
+    let mut data = MedibusCodec::encode_command(command::ICC, b"");
+    data.extend(MedibusCodec::encode_command(command::DEVICE_ID, b""));
+    data.extend(MedibusCodec::encode_command(command::NOP, b""));
+    driver.receive(&data);
+
+    let mut expected = MedibusCodec::encode_response(command::ICC, b"");
+    expected.extend(MedibusCodec::encode_response(
+        command::DEVICE_ID,
+        b"0000'VITAL-READER'01.00",
+    ));
+    expected.extend(MedibusCodec::encode_response(command::NOP, b""));
+    expected.extend(MedibusCodec::encode_command(command::MEASURED_DATA, b""));
+    assert_eq!(driver.poll(now), expected);
+    assert_eq!(driver.state(), MedibusState::Polling);
+}
+
+#[test]
+fn test_driver_keepalive() {
+    let now = Instant::now();
+    let config = MedibusConfig {
+        poll_interval: Duration::from_secs(60),
+        requests: vec![command::MEASURED_DATA],
+        ..MedibusConfig::default()
+    };
+    let mut driver = MedibusDriver::new(config);
+    driver.poll(now);
+    driver.receive(&MedibusCodec::encode_response(command::ICC, b""));
+    driver.poll(now);
+    driver.receive(&MedibusCodec::encode_response(command::DEVICE_ID, b""));
+    driver.poll(now);
+    driver.receive(&MedibusCodec::encode_response(command::MEASURED_DATA, b""));
+
+    assert!(driver.poll(now + Duration::from_secs(4)).is_empty());
+    assert_eq!(
+        driver.poll(now + Duration::from_secs(5)),
+        MedibusCodec::encode_command(command::NOP, b"")
+    );
+}
+
+#[test]
+fn test_driver_timeout_reinitializes() {
+    let start = Instant::now();
+    let mut driver = identified_driver(start);
+
+    let mut now = start;
+    for _ in 0..3 {
+        driver.poll(now);
+        now += Duration::from_secs(3);
+    }
+    // Third timeout detected here, ICC is sent again
+    assert_eq!(
+        driver.poll(now),
+        MedibusCodec::encode_command(command::ICC, b"")
+    );
+    assert_eq!(driver.state(), MedibusState::Initializing);
+
+    let warnings = driver.take_events();
+    assert_eq!(warnings.len(), 4);
+    assert!(matches!(&warnings[3], DriverEvent::Warning(w) if w.contains("re-initializing")));
+}
+
+#[test]
+fn test_driver_reports_bad_frames() {
+    let now = Instant::now();
+    let mut driver = identified_driver(now);
+
+    driver.receive(&MedibusCodec::encode_response(command::ALARMS, b""));
+    let mut corrupted = MedibusCodec::encode_response(command::ICC, b"");
+    corrupted[2] = b'0';
+    driver.receive(&corrupted);
+
+    let events = driver.take_events();
+    assert_eq!(events.len(), 2);
+    assert!(matches!(&events[0], DriverEvent::Warning(w) if w.contains("Unexpected")));
+    assert!(matches!(&events[1], DriverEvent::Warning(w) if w.contains("checksum")));
+}
+
+#[test]
+fn test_driver_from_name() {
+    assert_eq!(driver_from_name("MEDIBUS", None).unwrap().name(), "medibus");
+    assert!(driver_from_name("unknown", None).is_err());
+}
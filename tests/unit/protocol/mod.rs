@@ -0,0 +1,4 @@
+mod datex_tests;
+mod intellivue_tests;
+mod medibus_tests;
+mod preset_tests;
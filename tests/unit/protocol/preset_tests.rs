@@ -0,0 +1,86 @@
+use serialport::{DataBits, Parity, StopBits};
+use std::collections::HashSet;
+use std::time::Duration;
+
+use vital_reader::protocol::{DevicePreset, DEVICE_PRESETS};
+
+#[test]
+fn test_presets_are_consistent() {
+    let mut names = HashSet::new();
+    for preset in DEVICE_PRESETS {
+        assert!(names.insert(preset.name), "duplicate {}", preset.name);
+        preset.serial_config();
+        preset.decoder().unwrap();
+        assert_eq!(
+            preset
+                .create_driver(None)
+                .unwrap()
+                .map(|driver| driver.name()),
+            preset.driver
+        );
+        assert_eq!(preset.csv_columns().len(), preset.parameters.len());
+        // Only MEDIBUS lets the host choose what to poll
+        assert!(preset.requests.is_empty() || preset.driver == Some("medibus"));
+    }
+}
+
+#[test]
+fn test_from_name() {
+    let preset = DevicePreset::from_name("Drager-Evita").unwrap();
+    assert_eq!(preset.name, "drager-evita");
+
+    let config = preset.serial_config();
+    assert_eq!(config.baud, 19200);
+    assert_eq!(config.data_bits, DataBits::Eight);
+    assert_eq!(config.parity, Parity::Even);
+    assert_eq!(config.stop_bits, StopBits::One);
+}
+
+#[test]
+fn test_from_name_invalid() {
+    let error = DevicePreset::from_name("evita-xl").unwrap_err().to_string();
+    assert!(error.contains("Invalid device: evita-xl"));
+    assert!(error.contains("ge-monitor"));
+}
+
+#[test]
+fn test_medibus_preset_polls_its_requests() {
+    let preset = DevicePreset::from_name("drager-fabius").unwrap();
+    let mut driver = preset
+        .create_driver(Some(Duration::from_secs(2)))
+        .unwrap()
+        .unwrap();
+    assert_eq!(driver.name(), "medibus");
+    // The session starts with the ICC initialization request
+    assert!(!driver.poll(std::time::Instant::now()).is_empty());
+}
+
+#[test]
+fn test_preset_csv_columns() {
+    let preset = DevicePreset::from_name("drager-savina").unwrap();
+    let columns = preset.csv_columns();
+    let tv = columns.iter().find(|column| column.header == "TV").unwrap();
+    assert_eq!(tv.code, "20112-9");
+    let peak = columns
+        .iter()
+        .find(|column| column.header == "PPEAK")
+        .unwrap();
+    assert_eq!(peak.code, "76531-8");
+}
+
+#[test]
+fn test_preset_profile() {
+    let preset = DevicePreset::from_name("philips-intellivue").unwrap();
+    let profile = preset.profile();
+    assert_eq!(profile.config.as_deref(), Some("115200,0,8,1"));
+    assert_eq!(profile.protocol.as_deref(), Some("raw"));
+    assert_eq!(profile.driver.as_deref(), Some("intellivue"));
+    assert!(profile.csv_columns.unwrap().starts_with("HR,PR,SpO2"));
+}
+
+#[test]
+fn test_preset_without_driver() {
+    let preset = DevicePreset::from_name("ge-monitor").unwrap();
+    assert!(preset.create_driver(None).unwrap().is_none());
+    assert_eq!(preset.decoder().unwrap().name(), "hl7");
+}
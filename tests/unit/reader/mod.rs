@@ -1,2 +1,7 @@
+mod output_mode_tests;
+mod pipeline_tests;
+mod reconnect_tests;
+mod replay_tests;
 mod stats_tests;
 mod session_tests;
+mod watchdog_tests;
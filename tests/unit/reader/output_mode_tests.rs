@@ -0,0 +1,29 @@
+use vital_reader::reader::OutputMode;
+
+#[test]
+fn test_output_mode_from_name() {
+    assert_eq!(OutputMode::from_name("raw").unwrap(), OutputMode::Raw);
+    assert_eq!(
+        OutputMode::from_name("Observations").unwrap(),
+        OutputMode::Observations
+    );
+    assert_eq!(
+        OutputMode::from_name("obs").unwrap(),
+        OutputMode::Observations
+    );
+    assert!(OutputMode::from_name("csv").is_err());
+}
+
+#[test]
+fn test_output_mode_default_and_names() {
+    assert_eq!(OutputMode::default(), OutputMode::Raw);
+    assert_eq!(OutputMode::Raw.name(), "raw");
+    assert_eq!(OutputMode::Observations.name(), "observations");
+}
+
+#[test]
+fn test_output_mode_jsonl() {
+    assert_eq!(OutputMode::from_name("jsonl").unwrap(), OutputMode::Jsonl);
+    assert_eq!(OutputMode::from_name("JSON").unwrap(), OutputMode::Jsonl);
+    assert_eq!(OutputMode::Jsonl.name(), "jsonl");
+}
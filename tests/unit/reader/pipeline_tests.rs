@@ -0,0 +1,121 @@
+use std::time::Duration;
+
+use vital_reader::data::hl7::AckConfig;
+use vital_reader::data::{Framing, MllpCodec};
+use vital_reader::output::{Event, MemorySink};
+use vital_reader::reader::Pipeline;
+
+const MESSAGE: &str = "MSH|^~\\&|GE_MONITOR|ICU_01|VITAL_REC|HOSPITAL|20250104120000||ORU^R01|MSG000001|P|2.5\rOBX|1|NM|8867-4^Heart Rate^LN||72|bpm|60-100|N|||F\r";
+
+fn pipeline() -> (Pipeline, MemorySink) {
+    let sink = MemorySink::new();
+    let mut pipeline = Pipeline::new();
+    pipeline.set_console(Box::new(sink.clone()));
+    (pipeline, sink)
+}
+
+#[test]
+fn test_pipeline_emits_lines_observations_and_message() {
+    let (mut pipeline, sink) = pipeline();
+
+    assert!(pipeline
+        .receive(MESSAGE.as_bytes(), "t1")
+        .unwrap()
+        .is_empty());
+    assert_eq!(sink.records().len(), 2);
+
+    assert!(pipeline.finish("t2").unwrap().is_empty());
+    let records = sink.take();
+    assert_eq!(records.len(), 4);
+    assert!(matches!(&records[2].event, Event::Observation(o) if o.code == "8867-4"));
+    assert_eq!(records[2].timestamp, "t2");
+    assert!(matches!(records[3].event, Event::Message(_)));
+}
+
+#[test]
+fn test_pipeline_idle_does_not_cut_message_without_acks() {
+    let (mut pipeline, sink) = pipeline();
+    let (msh, segments) = MESSAGE.split_at(MESSAGE.find("OBX").unwrap());
+
+    pipeline.receive(msh.as_bytes(), "t1").unwrap();
+    assert!(pipeline
+        .idle(Duration::from_secs(5), "t2")
+        .unwrap()
+        .is_empty());
+    pipeline
+        .receive(format!("PID|1||12345\r{}", segments).as_bytes(), "t3")
+        .unwrap();
+    pipeline.finish("t4").unwrap();
+
+    let messages: Vec<_> = sink
+        .take()
+        .into_iter()
+        .filter_map(|record| match record.event {
+            Event::Message(message) => Some(message),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(messages.len(), 1);
+    let names: Vec<_> = messages[0]
+        .segments()
+        .iter()
+        .map(|segment| segment.name())
+        .collect();
+    assert_eq!(names, vec!["MSH", "PID", "OBX"]);
+}
+
+#[test]
+fn test_pipeline_idle_acks_after_quiet_period() {
+    let (mut pipeline, sink) = pipeline();
+    pipeline.set_ack(AckConfig::default());
+    pipeline.set_ack_quiet_period(Duration::from_millis(500));
+
+    pipeline.receive(MESSAGE.as_bytes(), "t1").unwrap();
+    assert!(pipeline
+        .idle(Duration::from_millis(100), "t2")
+        .unwrap()
+        .is_empty());
+    assert!(!sink
+        .records()
+        .iter()
+        .any(|record| matches!(record.event, Event::Message(_))));
+
+    let replies = pipeline.idle(Duration::from_millis(500), "t3").unwrap();
+    assert_eq!(replies.len(), 1);
+    assert!(sink
+        .take()
+        .iter()
+        .any(|record| matches!(record.event, Event::Message(_))));
+}
+
+#[test]
+fn test_pipeline_returns_framed_acks() {
+    let (mut pipeline, sink) = pipeline();
+    pipeline.set_framing(Framing::Mllp);
+    pipeline.set_ack(AckConfig::default());
+
+    let replies = pipeline
+        .receive(&MllpCodec::encode(MESSAGE.as_bytes()), "t1")
+        .unwrap();
+
+    assert_eq!(replies.len(), 1);
+    assert_eq!(replies[0].first(), Some(&0x0B));
+    assert!(matches!(
+        sink.records().last().map(|r| &r.event),
+        Some(Event::AckSent { code, .. }) if code == "AA"
+    ));
+    assert!(pipeline.finish("t2").unwrap().is_empty());
+}
+
+#[test]
+fn test_pipeline_sinks_receive_records() {
+    let (mut pipeline, console) = pipeline();
+    let extra = MemorySink::new();
+    pipeline.add_sink(Box::new(extra.clone()));
+
+    pipeline.receive(b"HR=72\r", "t1").unwrap();
+    pipeline.flush().unwrap();
+
+    assert_eq!(console.records(), extra.records());
+    assert_eq!(pipeline.parser().stats().total_bytes, 6);
+}
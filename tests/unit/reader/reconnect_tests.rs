@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+use vital_reader::reader::{ReconnectPolicy, Reconnector};
+use vital_reader::transport::{Endpoint, TcpListenerTransport};
+use vital_reader::SerialConfig;
+
+#[test]
+fn test_policy_backs_off_exponentially() {
+    let policy = ReconnectPolicy::new()
+        .with_initial_delay(Duration::from_millis(100))
+        .with_max_delay(Duration::from_secs(1));
+
+    assert_eq!(policy.delay(1), Duration::from_millis(100));
+    assert_eq!(policy.delay(2), Duration::from_millis(200));
+    assert_eq!(policy.delay(4), Duration::from_millis(800));
+    assert_eq!(policy.delay(5), Duration::from_secs(1));
+    assert_eq!(policy.delay(1000), Duration::from_secs(1));
+}
+
+#[test]
+fn test_policy_attempt_limit() {
+    let policy = ReconnectPolicy::default();
+    assert_eq!(policy.max_attempts, None);
+    assert!(policy.allows(u32::MAX));
+
+    let policy = policy.with_max_attempts(3);
+    assert!(policy.allows(3));
+    assert!(!policy.allows(4));
+}
+
+#[test]
+fn test_reconnector_rejects_inputs_without_a_source() {
+    let config = SerialConfig::default();
+    for uri in ["-", "file://dump.bin", "tcp-listen://0.0.0.0:4001"] {
+        let endpoint = Endpoint::parse(uri, &config).unwrap();
+        assert!(Reconnector::new(endpoint, 10).is_err(), "{}", uri);
+    }
+    for uri in [
+        "/dev/ttyUSB0",
+        "tcp://10.0.0.5:4001",
+        "rfc2217://10.0.0.5:4001",
+    ] {
+        let endpoint = Endpoint::parse(uri, &config).unwrap();
+        assert!(Reconnector::new(endpoint, 10).is_ok(), "{}", uri);
+    }
+}
+
+#[test]
+fn test_reconnector_opens_endpoint_again() {
+    let listener = TcpListenerTransport::bind("127.0.0.1:0", 10).unwrap();
+    let uri = format!("tcp://{}", listener.local_addr().unwrap());
+    let endpoint = Endpoint::parse(&uri, &SerialConfig::default()).unwrap();
+    let reconnector = Reconnector::new(endpoint, 10).unwrap();
+
+    let transport = reconnector.connect().unwrap();
+    assert_eq!(transport.name(), Some(uri));
+    drop(listener);
+    assert!(reconnector.connect().is_err());
+}
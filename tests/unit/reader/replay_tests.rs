@@ -0,0 +1,306 @@
+use std::io::{Cursor, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use vital_reader::capture::{CaptureEntry, CaptureMetadata, CaptureReader, CaptureWriter};
+use vital_reader::data::hl7::AckConfig;
+use vital_reader::data::DataType;
+use vital_reader::output::{Event, MemorySink, Record};
+use vital_reader::reader::{ConnectionState, ReplaySession, ReplaySpeed, Watchdog};
+use vital_reader::SerialConfig;
+
+const MSH: &str =
+    "MSH|^~\\&|GE_MONITOR|ICU_01|VITAL_REC|HOSPITAL|20250104120000||ORU^R01|MSG000001|P|2.5\r";
+const OBX: &str = "OBX|1|NM|8867-4^Heart Rate^LN||72|bpm|60-100|N|||F\r";
+
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Build a capture from (milliseconds, entry) pairs, started at 12:00:00
+fn capture(entries: &[(u64, CaptureEntry)]) -> Vec<u8> {
+    let buffer = SharedBuffer::default();
+    let mut writer = CaptureWriter::with_writer(Box::new(buffer.clone())).unwrap();
+    let mut metadata = CaptureMetadata::new("/dev/ttyUSB0", &SerialConfig::default());
+    metadata.started = "2025-01-04 12:00:00.000".to_string();
+    writer
+        .write_at(Duration::ZERO, &CaptureEntry::Start(metadata))
+        .unwrap();
+    for (millis, entry) in entries {
+        writer
+            .write_at(Duration::from_millis(*millis), entry)
+            .unwrap();
+    }
+    writer.flush().unwrap();
+    let bytes = buffer.0.lock().unwrap().clone();
+    bytes
+}
+
+fn received(text: &str) -> CaptureEntry {
+    CaptureEntry::Received(text.as_bytes().to_vec())
+}
+
+fn replay(bytes: Vec<u8>) -> (ReplaySession, MemorySink) {
+    let reader = CaptureReader::with_reader(Box::new(Cursor::new(bytes))).unwrap();
+    let sink = MemorySink::new();
+    let session = ReplaySession::new(reader, false)
+        .with_speed(ReplaySpeed::Max)
+        .with_console(Box::new(sink.clone()));
+    (session, sink)
+}
+
+fn messages(records: &[Record]) -> Vec<(String, usize)> {
+    records
+        .iter()
+        .filter_map(|record| match &record.event {
+            Event::Message(message) => Some((record.timestamp.clone(), message.segments().len())),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_replay_speed_from_name() {
+    assert_eq!(ReplaySpeed::from_name("max").unwrap(), ReplaySpeed::Max);
+    assert_eq!(
+        ReplaySpeed::from_name("realtime").unwrap(),
+        ReplaySpeed::Scaled(1.0)
+    );
+    assert_eq!(
+        ReplaySpeed::from_name("2X").unwrap(),
+        ReplaySpeed::Scaled(2.0)
+    );
+    assert_eq!(
+        ReplaySpeed::from_name("0.5").unwrap(),
+        ReplaySpeed::Scaled(0.5)
+    );
+    assert_eq!(ReplaySpeed::default(), ReplaySpeed::Scaled(1.0));
+    for invalid in ["0", "-1", "fast", "inf"] {
+        assert!(ReplaySpeed::from_name(invalid).is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn test_replay_rebuilds_live_timestamps() {
+    let (mut session, sink) = replay(capture(&[
+        (5, received("HR=72|SPO2=98\n")),
+        (1250, received("HR=75|SPO2=97\n")),
+        (1300, CaptureEntry::Sent(b"HELLO\r\n".to_vec())),
+        (2000, CaptureEntry::Stop),
+    ]));
+    session.run().unwrap();
+
+    let records = sink.take();
+    let timestamps: Vec<&str> = records.iter().map(|r| r.timestamp.as_str()).collect();
+    assert_eq!(
+        timestamps,
+        vec!["2025-01-04 12:00:00.005", "2025-01-04 12:00:01.250"]
+    );
+    assert_eq!(
+        records[0].event,
+        Event::Data {
+            bytes: b"HR=72|SPO2=98\n".to_vec(),
+            data_type: DataType::Mixed,
+        }
+    );
+}
+
+#[test]
+fn test_replay_splits_lines_across_chunks() {
+    let (mut session, sink) = replay(capture(&[
+        (0, received("HR=7")),
+        (10, received("2\r\nHR=")),
+        (20, received("75")),
+    ]));
+    session.run().unwrap();
+
+    let lines: Vec<Vec<u8>> = sink
+        .take()
+        .into_iter()
+        .filter_map(|record| match record.event {
+            Event::Data { bytes, .. } => Some(bytes),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(lines, vec![b"HR=72\r".to_vec(), b"HR=75".to_vec()]);
+}
+
+#[test]
+fn test_replay_detects_data_type_after_100_bytes() {
+    let line = "HR=72|SPO2=98|BP=120/80|TEMP=36.9\n";
+    let (mut session, sink) = replay(capture(&[
+        (0, received(line)),
+        (10, received(line)),
+        (20, received(line)),
+        (30, received(line)),
+    ]));
+    session.run().unwrap();
+
+    let types: Vec<DataType> = sink
+        .take()
+        .into_iter()
+        .filter_map(|record| match record.event {
+            Event::Data { data_type, .. } => Some(data_type),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        types,
+        vec![
+            DataType::Mixed,
+            DataType::Mixed,
+            DataType::Mixed,
+            DataType::Ascii
+        ]
+    );
+}
+
+#[test]
+fn test_replay_gap_completes_message_like_a_read_timeout() {
+    let bytes = capture(&[(0, received(MSH)), (50, received(OBX))]);
+    let (mut session, sink) = replay(bytes.clone());
+    session.run().unwrap();
+    assert_eq!(
+        messages(&sink.take()),
+        vec![("2025-01-04 12:00:00.050".to_string(), 2)]
+    );
+
+    // Without ACKs a gap longer than a read timeout does not cut the message
+    let (session, sink) = replay(bytes.clone());
+    let mut session = session.with_idle_timeout(Duration::from_millis(20));
+    session.run().unwrap();
+    assert_eq!(
+        messages(&sink.take()),
+        vec![("2025-01-04 12:00:00.050".to_string(), 2)]
+    );
+
+    // With ACKs the message is cut before its OBX, which then has no MSH to join
+    let (session, sink) = replay(bytes);
+    let mut session = session
+        .with_idle_timeout(Duration::from_millis(20))
+        .with_ack(AckConfig::default())
+        .with_ack_quiet_period(Duration::from_millis(20));
+    session.run().unwrap();
+    assert_eq!(
+        messages(&sink.take()),
+        vec![("2025-01-04 12:00:00.020".to_string(), 1)]
+    );
+}
+
+#[test]
+fn test_replay_stops_on_flag() {
+    let bytes = capture(&[(0, received(MSH)), (60_000, received(OBX))]);
+    let stop = Arc::new(AtomicBool::new(false));
+    let (session, sink) = replay(bytes);
+    let mut session = session
+        .with_speed(ReplaySpeed::Scaled(1.0))
+        .with_stop_flag(Arc::clone(&stop));
+
+    let stopper = {
+        let stop = Arc::clone(&stop);
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(200));
+            stop.store(true, Ordering::Relaxed);
+        })
+    };
+    let started = Instant::now();
+    session.run().unwrap();
+    stopper.join().unwrap();
+
+    // The minute long gap is not waited out, the MSH read so far is kept
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(
+        messages(&sink.take()),
+        vec![("2025-01-04 12:00:00.000".to_string(), 1)]
+    );
+}
+
+#[test]
+fn test_replay_reports_connection_state() {
+    let (session, sink) = replay(capture(&[
+        (100, received("HR=72\n")),
+        (40_000, received("HR=75\n")),
+        (40_500, CaptureEntry::Stop),
+    ]));
+    let mut session =
+        session.with_watchdog(Watchdog::new().with_stale_after(Duration::from_secs(10)));
+    session.run().unwrap();
+
+    let states: Vec<(String, ConnectionState, String)> = sink
+        .take()
+        .into_iter()
+        .filter_map(|record| match record.event {
+            Event::ConnectionState { state, detail } => Some((record.timestamp, state, detail)),
+            _ => None,
+        })
+        .collect();
+    let expected = [
+        ("12:00:05.250", ConnectionState::Idle, "no data for 5 s"),
+        ("12:00:10.250", ConnectionState::Stale, "no data for 10 s"),
+        ("12:00:40.250", ConnectionState::Connected, "receiving data"),
+    ];
+    assert_eq!(
+        states,
+        expected
+            .iter()
+            .map(|(time, state, detail)| (
+                format!("2025-01-04 {}", time),
+                *state,
+                detail.to_string()
+            ))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_replay_regenerates_acks() {
+    let (session, sink) = replay(capture(&[(0, received(MSH)), (10, received(OBX))]));
+    let mut session = session.with_ack(AckConfig::default());
+    session.run().unwrap();
+
+    let records = sink.take();
+    assert!(records.iter().any(|record| matches!(
+        &record.event,
+        Event::AckSent { code, control_id } if code == "AA" && control_id == "MSG000001"
+    )));
+}
+
+#[test]
+fn test_replay_processes_truncated_capture() {
+    let mut bytes = capture(&[(0, received("HR=72\n"))]);
+    bytes.extend_from_slice(&[2, 0, 0, 0, 0, 0, 0, 0, 0, 50, 0, 0, 0, b'H']);
+
+    let status = SharedBuffer::default();
+    let (session, sink) = replay(bytes);
+    let mut session = session.with_status_writer(Box::new(status.clone()));
+    session.run().unwrap();
+
+    assert_eq!(sink.take().len(), 1);
+    let status = String::from_utf8(status.0.lock().unwrap().clone()).unwrap();
+    assert!(status.contains("Warning: capture ends with a truncated entry"));
+}
+
+#[test]
+fn test_replay_follows_recorded_timing() {
+    let bytes = capture(&[(100, received("HR=72\n")), (200, CaptureEntry::Stop)]);
+    let (session, _sink) = replay(bytes);
+    let mut session = session.with_speed(ReplaySpeed::Scaled(5.0));
+
+    let start = Instant::now();
+    session.run().unwrap();
+    let elapsed = start.elapsed();
+
+    assert!(elapsed >= Duration::from_millis(40), "{:?}", elapsed);
+    assert!(elapsed < Duration::from_millis(1000), "{:?}", elapsed);
+}
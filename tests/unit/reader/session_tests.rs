@@ -1,5 +1,126 @@
-use vital_reader::reader::ReaderSession;
+use anyhow::Result;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
 use vital_reader::config::SerialConfig;
+use vital_reader::data::DecoderRegistry;
+use vital_reader::output::{Event, MemorySink, Record, Sink};
+use vital_reader::reader::ReaderSession;
+use vital_reader::transport::Transport;
+
+const MESSAGE: &[u8] = b"MSH|^~\\&|GE_MONITOR|ICU_01|VITAL_REC|HOSPITAL|20250104120000||ORU^R01|MSG000001|P|2.5\rOBX|1|NM|8867-4^Heart Rate^LN||72|bpm|60-100|N|||F\r";
+
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    fn text(&self) -> String {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Counts records and notes whether it was flushed
+#[derive(Clone, Default)]
+struct FlushSink {
+    records: Arc<Mutex<usize>>,
+    flushed: Arc<AtomicBool>,
+}
+
+impl Sink for FlushSink {
+    fn write(&mut self, _record: &Record) -> Result<()> {
+        *self.records.lock().unwrap() += 1;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.flushed.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+/// Hands out fixed bytes once, then stays connected without data unless
+/// `ends` is set
+struct QuietTransport {
+    data: Vec<u8>,
+    ends: bool,
+}
+
+impl Transport for QuietTransport {
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        let n = self.data.len().min(buffer.len());
+        buffer[..n].copy_from_slice(&self.data[..n]);
+        self.data.drain(..n);
+        Ok(n)
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<usize> {
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn name(&self) -> Option<String> {
+        Some("quiet".to_string())
+    }
+
+    fn is_connected(&self) -> bool {
+        !self.ends || !self.data.is_empty()
+    }
+}
+
+/// A headless session with its console records, extra sink and status output
+struct Headless {
+    session: ReaderSession,
+    console: MemorySink,
+    sink: FlushSink,
+    status: SharedBuffer,
+}
+
+fn headless_session(ends: bool) -> Headless {
+    let transport = QuietTransport {
+        data: MESSAGE.to_vec(),
+        ends,
+    };
+    let (console, sink, status) = (
+        MemorySink::new(),
+        FlushSink::default(),
+        SharedBuffer::default(),
+    );
+    let session =
+        ReaderSession::from_transport(Box::new(transport), &SerialConfig::default(), false)
+            .with_decoder(DecoderRegistry::new().create("hl7").unwrap())
+            .with_console(Box::new(console.clone()))
+            .with_sink(Box::new(sink.clone()))
+            .with_status_writer(Box::new(status.clone()))
+            .with_headless(true);
+    Headless {
+        session,
+        console,
+        sink,
+        status,
+    }
+}
+
+fn has_message(records: &[Record]) -> bool {
+    records
+        .iter()
+        .any(|record| matches!(record.event, Event::Message(_)))
+}
 
 #[test]
 fn test_session_creation_fails_with_invalid_port() {
@@ -16,3 +137,45 @@ fn test_session_format_timestamp() {
     assert!(result.is_err());
 }
 
+#[test]
+fn test_headless_session_runs_to_end_of_input() {
+    let mut headless = headless_session(true);
+
+    headless.session.run().unwrap();
+
+    // The message in progress is completed when the input ends
+    let records = headless.console.records();
+    assert!(has_message(&records));
+    assert_eq!(*headless.sink.records.lock().unwrap(), records.len());
+    assert!(headless.sink.flushed.load(Ordering::Relaxed));
+    // Headless sessions always end with their statistics
+    let status = headless.status.text();
+    assert!(status.contains("Connected to quiet"));
+    assert!(status.contains("Statistics:"));
+    assert!(status.contains(&format!("Total bytes received: {}", MESSAGE.len())));
+}
+
+#[test]
+fn test_headless_session_stops_on_flag() {
+    let stop = Arc::new(AtomicBool::new(false));
+    let mut headless = headless_session(false);
+    headless.session = headless.session.with_stop_flag(Arc::clone(&stop));
+
+    let stopper = {
+        let stop = Arc::clone(&stop);
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(200));
+            stop.store(true, Ordering::Relaxed);
+        })
+    };
+    let started = Instant::now();
+    headless.session.run().unwrap();
+    stopper.join().unwrap();
+
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert!(has_message(&headless.console.records()));
+    assert!(headless.sink.flushed.load(Ordering::Relaxed));
+    let status = headless.status.text();
+    assert!(status.contains("Disconnecting..."));
+    assert!(status.contains("Statistics:"));
+}
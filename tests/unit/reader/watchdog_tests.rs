@@ -0,0 +1,103 @@
+use std::time::{Duration, Instant};
+
+use vital_reader::reader::{ConnectionState, Watchdog};
+use vital_reader::transport::ModemState;
+
+const DSR: ModemState = ModemState(0x20);
+const NO_LINES: ModemState = ModemState(0);
+
+fn watchdog(start: Instant) -> Watchdog {
+    let mut watchdog = Watchdog::new()
+        .with_idle_after(Duration::from_secs(5))
+        .with_stale_after(Duration::from_secs(30));
+    watchdog.reset(start);
+    watchdog
+}
+
+fn state(change: Option<(ConnectionState, String)>) -> Option<ConnectionState> {
+    change.map(|(state, _)| state)
+}
+
+#[test]
+fn test_quiet_link_goes_idle_then_stale() {
+    let start = Instant::now();
+    let mut watchdog = watchdog(start);
+    assert_eq!(watchdog.state(), ConnectionState::Connected);
+
+    assert_eq!(
+        watchdog.update(start + Duration::from_secs(1), true, None),
+        None
+    );
+    assert_eq!(
+        state(watchdog.update(start + Duration::from_secs(5), true, None)),
+        Some(ConnectionState::Idle)
+    );
+    // Only changes are reported
+    assert_eq!(
+        watchdog.update(start + Duration::from_secs(6), true, None),
+        None
+    );
+
+    let (stale, detail) = watchdog
+        .update(start + Duration::from_secs(31), true, None)
+        .unwrap();
+    assert_eq!(stale, ConnectionState::Stale);
+    assert_eq!(detail, "no data for 31 s");
+
+    watchdog.data(start + Duration::from_secs(40));
+    assert_eq!(
+        state(watchdog.update(start + Duration::from_secs(40), true, None)),
+        Some(ConnectionState::Connected)
+    );
+}
+
+#[test]
+fn test_closed_port_is_lost() {
+    let start = Instant::now();
+    let mut watchdog = watchdog(start);
+
+    let (lost, detail) = watchdog.update(start, false, None).unwrap();
+    assert_eq!(lost, ConnectionState::Lost);
+    assert_eq!(detail, "port closed or removed");
+
+    watchdog.reset(start + Duration::from_secs(2));
+    let (state, detail) = watchdog
+        .update(start + Duration::from_secs(2), true, None)
+        .unwrap();
+    assert_eq!(state, ConnectionState::Connected);
+    assert_eq!(detail, "link restored");
+}
+
+#[test]
+fn test_dropped_carrier_is_lost_once_seen() {
+    let start = Instant::now();
+
+    // A cable without DSR/CD never reports them
+    let mut watchdog = watchdog(start);
+    assert_eq!(watchdog.update(start, true, Some(NO_LINES)), None);
+
+    let mut watchdog = self::watchdog(start);
+    assert_eq!(watchdog.update(start, true, Some(DSR)), None);
+    assert_eq!(
+        watchdog.update(start, true, Some(NO_LINES)),
+        Some((ConnectionState::Lost, "device dropped DSR/CD".to_string()))
+    );
+    assert_eq!(
+        state(watchdog.update(start, true, Some(DSR))),
+        Some(ConnectionState::Connected)
+    );
+}
+
+#[test]
+fn test_state_names() {
+    let names: Vec<String> = [
+        ConnectionState::Connected,
+        ConnectionState::Idle,
+        ConnectionState::Stale,
+        ConnectionState::Lost,
+    ]
+    .iter()
+    .map(|state| state.to_string())
+    .collect();
+    assert_eq!(names, ["connected", "idle", "stale", "lost"]);
+}
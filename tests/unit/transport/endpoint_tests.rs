@@ -0,0 +1,82 @@
+use serialport::{DataBits, Parity, StopBits};
+use vital_reader::transport::Endpoint;
+use vital_reader::SerialConfig;
+
+fn parse(uri: &str) -> Endpoint {
+    Endpoint::parse(uri, &SerialConfig::default()).unwrap()
+}
+
+#[test]
+fn test_bare_name_is_serial_port() {
+    match parse("/dev/ttyUSB0") {
+        Endpoint::Serial { port, config } => {
+            assert_eq!(port, "/dev/ttyUSB0");
+            assert_eq!(config.baud, 115200);
+        }
+        other => panic!("unexpected endpoint {:?}", other),
+    }
+    assert!(matches!(parse("COM3"), Endpoint::Serial { port, .. } if port == "COM3"));
+}
+
+#[test]
+fn test_serial_uri_overrides_config() {
+    match parse("serial:///dev/ttyUSB0?baud=9600&data_bits=7&parity=even&stop_bits=2") {
+        Endpoint::Serial { port, config } => {
+            assert_eq!(port, "/dev/ttyUSB0");
+            assert_eq!(config.baud, 9600);
+            assert_eq!(config.data_bits, DataBits::Seven);
+            assert_eq!(config.parity, Parity::Even);
+            assert_eq!(config.stop_bits, StopBits::Two);
+        }
+        other => panic!("unexpected endpoint {:?}", other),
+    }
+}
+
+#[test]
+fn test_serial_uri_rejects_bad_options() {
+    let config = SerialConfig::default();
+    assert!(Endpoint::parse("serial://", &config).is_err());
+    assert!(Endpoint::parse("serial://COM3?baud=fast", &config).is_err());
+    assert!(Endpoint::parse("serial://COM3?flow=hardware", &config).is_err());
+    assert!(Endpoint::parse("serial://COM3?parity", &config).is_err());
+}
+
+#[test]
+fn test_network_uris() {
+    assert!(matches!(parse("tcp://10.0.0.5:4001"), Endpoint::TcpClient(a) if a == "10.0.0.5:4001"));
+    assert!(
+        matches!(parse("tcp-listen://0.0.0.0:4001"), Endpoint::TcpListener(a) if a == "0.0.0.0:4001")
+    );
+    assert!(parse("tcp://10.0.0.5:4001").serial_config().is_none());
+
+    let config = SerialConfig::default();
+    assert!(Endpoint::parse("tcp://10.0.0.5", &config).is_err());
+    assert!(Endpoint::parse("tcp://:4001", &config).is_err());
+    assert!(Endpoint::parse("tcp://host:99999", &config).is_err());
+}
+
+#[test]
+fn test_stdin_and_file_uris() {
+    assert!(matches!(parse("-"), Endpoint::Stdin));
+    assert!(matches!(parse("stdin://"), Endpoint::Stdin));
+    assert!(matches!(parse("file://capture.bin"), Endpoint::File(p) if p == "capture.bin"));
+    assert!(matches!(parse("file:///tmp/dump.bin"), Endpoint::File(p) if p == "/tmp/dump.bin"));
+
+    let config = SerialConfig::default();
+    assert!(Endpoint::parse("file://", &config).is_err());
+    assert!(Endpoint::parse("ftp://host/file", &config).is_err());
+}
+
+#[test]
+fn test_serial_uri_line_options() {
+    match parse("serial:///dev/ttyS0?stop_bits=1.5&data_bits=5&parity=mark&flow=xonxoff&dtr=off") {
+        Endpoint::Serial { config, .. } => {
+            assert_eq!(config.to_string(), "115200 5M1.5");
+            assert_eq!(config.flow_control, serialport::FlowControl::Software);
+            assert_eq!(config.dtr, Some(false));
+        }
+        other => panic!("unexpected endpoint {:?}", other),
+    }
+    let config = SerialConfig::default();
+    assert!(Endpoint::parse("serial://COM3?flow=rtscts&rts=on", &config).is_err());
+}
@@ -0,0 +1,3 @@
+mod endpoint_tests;
+mod rfc2217_tests;
+mod transport_tests;
@@ -0,0 +1,149 @@
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::time::{Duration, Instant};
+
+use vital_reader::fake::Rfc2217Server;
+use vital_reader::transport::{ComPortSettings, Endpoint, ModemState, Rfc2217Transport, Transport};
+use vital_reader::SerialConfig;
+
+/// Read until `len` bytes arrived or a second passed
+fn read_bytes(transport: &mut dyn Transport, len: usize) -> Vec<u8> {
+    let mut received = Vec::new();
+    let mut buffer = [0u8; 64];
+    let deadline = Instant::now() + Duration::from_secs(1);
+    while received.len() < len && Instant::now() < deadline {
+        let n = transport.read(&mut buffer).unwrap();
+        received.extend_from_slice(&buffer[..n]);
+    }
+    received
+}
+
+/// Wait up to a second for `done`
+fn eventually(mut done: impl FnMut() -> bool) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(1);
+    while !done() {
+        if Instant::now() > deadline {
+            return false;
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    true
+}
+
+#[test]
+fn test_connect_applies_serial_config() {
+    let server = Rfc2217Server::start().unwrap();
+    let config = SerialConfig::new(9600, 7, "even", 2).unwrap();
+
+    let transport = Rfc2217Transport::connect(&server.address(), &config, 10).unwrap();
+
+    let expected = ComPortSettings {
+        baud: 9600,
+        data_size: 7,
+        parity: 3,
+        stop_size: 2,
+    };
+    assert_eq!(server.settings(), expected);
+    assert_eq!(transport.settings(), expected);
+    assert_eq!(expected.to_string(), "9600 7E2");
+    assert_eq!(
+        transport.name(),
+        Some(format!("rfc2217://{}", server.address()))
+    );
+}
+
+#[test]
+fn test_data_is_escaped_both_ways() {
+    let server = Rfc2217Server::start().unwrap();
+    let mut transport =
+        Rfc2217Transport::connect(&server.address(), &SerialConfig::default(), 10).unwrap();
+
+    // 0xFF is the Telnet IAC byte and must survive the round trip
+    let data = [0x02, 0xFF, 0x10, 0xFF, 0xFF, 0x03];
+    transport.write(&data).unwrap();
+    transport.flush().unwrap();
+    assert_eq!(read_bytes(&mut transport, data.len()), data);
+    assert_eq!(server.received(), data);
+
+    server.send(b"HR=72\xFF\r\n");
+    assert_eq!(read_bytes(&mut transport, 8), b"HR=72\xFF\r\n");
+}
+
+#[test]
+fn test_modem_state_is_relayed() {
+    let server = Rfc2217Server::start().unwrap();
+    let mut transport =
+        Rfc2217Transport::connect(&server.address(), &SerialConfig::default(), 10).unwrap();
+    assert_eq!(transport.modem_state(), Some(ModemState(0)));
+
+    // CTS and DCD up
+    server.set_modem_state(0x90);
+    server.send(b"x");
+    assert_eq!(read_bytes(&mut transport, 1), b"x");
+    let state = transport.modem_state().unwrap();
+    assert!(state.cts() && state.dcd());
+    assert!(!state.dsr() && !state.ring());
+
+    transport.set_dtr(true).unwrap();
+    transport.set_rts(false).unwrap();
+    assert!(eventually(
+        || server.dtr() == Some(true) && server.rts() == Some(false)
+    ));
+}
+
+#[test]
+fn test_server_refusing_com_port_option() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut buffer = [0u8; 64];
+        let _ = stream.read(&mut buffer);
+        // IAC DONT COM-PORT-OPTION
+        stream.write_all(&[255, 254, 44]).unwrap();
+        let _ = stream.read(&mut buffer);
+    });
+
+    let result = Rfc2217Transport::connect(&address, &SerialConfig::default(), 10);
+    assert!(result.is_err());
+    assert!(result
+        .err()
+        .unwrap()
+        .to_string()
+        .contains("refused COM-PORT-OPTION"));
+}
+
+#[test]
+fn test_endpoint_uri() {
+    let config = SerialConfig::default();
+    match Endpoint::parse("rfc2217://10.0.0.5:4001?baud=9600&parity=odd", &config).unwrap() {
+        Endpoint::Rfc2217 { address, config } => {
+            assert_eq!(address, "10.0.0.5:4001");
+            assert_eq!(config.baud, 9600);
+            assert_eq!(config.parity, serialport::Parity::Odd);
+        }
+        other => panic!("unexpected endpoint {:?}", other),
+    }
+    assert!(Endpoint::parse("rfc2217://10.0.0.5", &config).is_err());
+
+    let server = Rfc2217Server::start().unwrap();
+    let uri = format!("rfc2217://{}?baud=19200", server.address());
+    let mut transport = Endpoint::parse(&uri, &config).unwrap().open(10).unwrap();
+    assert_eq!(server.settings().baud, 19200);
+    transport.write(b"ping").unwrap();
+    assert_eq!(read_bytes(transport.as_mut(), 4), b"ping");
+}
+
+#[test]
+fn test_connect_applies_flow_control_and_lines() {
+    let server = Rfc2217Server::start().unwrap();
+    let config = SerialConfig::from_string("9600,S,5,1.5,rtscts,dtr=off").unwrap();
+
+    let transport = Rfc2217Transport::connect(&server.address(), &config, 10).unwrap();
+
+    assert_eq!(transport.settings().to_string(), "9600 5S1.5");
+    // Flow and line requests follow the settings, no answer is awaited
+    assert!(eventually(|| server.flow_control() == Some(3)));
+    assert!(eventually(|| server.dtr() == Some(false)));
+    assert_eq!(server.rts(), None);
+}
@@ -0,0 +1,157 @@
+use std::io::Write;
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+
+use vital_reader::transport::{
+    FileTransport, ModemState, TcpClientTransport, TcpListenerTransport, Transport,
+};
+
+/// Read until `len` bytes arrived or a second passed
+fn read_bytes(transport: &mut dyn Transport, len: usize) -> Vec<u8> {
+    let mut received = Vec::new();
+    let mut buffer = [0u8; 64];
+    let deadline = Instant::now() + Duration::from_secs(1);
+    while received.len() < len && Instant::now() < deadline {
+        let n = transport.read(&mut buffer).unwrap();
+        received.extend_from_slice(&buffer[..n]);
+    }
+    received
+}
+
+#[test]
+fn test_file_transport_reads_to_end() {
+    let path = std::env::temp_dir().join(format!("vital-reader-{}.bin", std::process::id()));
+    std::fs::write(&path, b"HR=72|SPO2=98\r\n").unwrap();
+
+    let mut transport = FileTransport::open(path.to_str().unwrap()).unwrap();
+    assert!(transport.is_connected());
+    assert_eq!(read_bytes(&mut transport, 15), b"HR=72|SPO2=98\r\n");
+
+    let mut buffer = [0u8; 16];
+    assert_eq!(transport.read(&mut buffer).unwrap(), 0);
+    assert!(!transport.is_connected());
+    // Replies have nowhere to go and are dropped
+    assert_eq!(transport.write(b"ACK").unwrap(), 3);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_file_transport_missing_file() {
+    let result = FileTransport::open("/nonexistent/vital-reader.bin");
+    assert!(result.is_err());
+    assert!(result.err().unwrap().to_string().contains("/nonexistent"));
+}
+
+#[test]
+fn test_tcp_client_and_listener() {
+    let mut listener = TcpListenerTransport::bind("127.0.0.1:0", 10).unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+
+    // No client yet: reads time out
+    let mut buffer = [0u8; 16];
+    assert_eq!(listener.read(&mut buffer).unwrap(), 0);
+    assert!(listener.is_connected());
+
+    let mut client = TcpClientTransport::connect(&address, 10).unwrap();
+    assert_eq!(client.name(), Some(format!("tcp://{}", address)));
+    client.write(b"MSH|^~\\&|\r").unwrap();
+    client.flush().unwrap();
+    assert_eq!(read_bytes(&mut listener, 10), b"MSH|^~\\&|\r");
+
+    listener.write(b"ACK").unwrap();
+    listener.flush().unwrap();
+    assert_eq!(read_bytes(&mut client, 3), b"ACK");
+
+    // A silent peer is a timeout, not an end of stream
+    assert_eq!(client.read(&mut buffer).unwrap(), 0);
+    assert!(client.is_connected());
+}
+
+#[test]
+fn test_tcp_client_writes_whole_buffer() {
+    let server = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = server.local_addr().unwrap().to_string();
+    let reader = std::thread::spawn(move || {
+        let (mut stream, _) = server.accept().unwrap();
+        let mut received = Vec::new();
+        std::io::Read::read_to_end(&mut stream, &mut received).unwrap();
+        received
+    });
+
+    // Larger than the socket buffers, so the peer has to drain it
+    let data = vec![b'x'; 8 * 1024 * 1024];
+    let mut client = TcpClientTransport::connect(&address, 10).unwrap();
+    assert_eq!(client.write(&data).unwrap(), data.len());
+    drop(client);
+
+    assert_eq!(reader.join().unwrap().len(), data.len());
+}
+
+#[test]
+fn test_tcp_client_detects_close() {
+    let mut listener = TcpListenerTransport::bind("127.0.0.1:0", 10).unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let mut client = TcpClientTransport::connect(&address, 10).unwrap();
+
+    // Accept, then drop the listener and its client socket
+    client.write(b"x").unwrap();
+    assert_eq!(read_bytes(&mut listener, 1), b"x");
+    drop(listener);
+
+    let mut buffer = [0u8; 16];
+    let deadline = Instant::now() + Duration::from_secs(1);
+    while client.is_connected() && Instant::now() < deadline {
+        client.read(&mut buffer).unwrap();
+    }
+    assert!(!client.is_connected());
+}
+
+#[test]
+fn test_tcp_listener_accepts_next_client() {
+    let mut listener = TcpListenerTransport::bind("127.0.0.1:0", 10).unwrap();
+    let address = listener.local_addr().unwrap();
+
+    let mut first = TcpStream::connect(address).unwrap();
+    first.write_all(b"one").unwrap();
+    assert_eq!(read_bytes(&mut listener, 3), b"one");
+    drop(first);
+
+    let mut second = TcpStream::connect(address).unwrap();
+    second.write_all(b"two").unwrap();
+    assert_eq!(read_bytes(&mut listener, 3), b"two");
+}
+
+#[test]
+fn test_tcp_client_connection_refused() {
+    // Bind then drop to find a port nothing listens on
+    let address = TcpListenerTransport::bind("127.0.0.1:0", 10)
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .to_string();
+    let result = TcpClientTransport::connect(&address, 10);
+    assert!(result.is_err());
+    assert!(result.err().unwrap().to_string().contains(&address));
+}
+
+#[test]
+fn test_modem_state_lines() {
+    let state = ModemState::from_lines(true, false, false, true);
+    assert_eq!(state, ModemState(0x90));
+    assert!(state.cts() && state.dcd());
+    assert!(!state.dsr() && !state.ring());
+    assert_eq!(
+        ModemState::from_lines(false, true, true, false),
+        ModemState(0x60)
+    );
+}
+
+#[test]
+fn test_file_transport_has_no_modem_lines() {
+    let path = std::env::temp_dir().join(format!("vital-reader-modem-{}.bin", std::process::id()));
+    std::fs::write(&path, b"x").unwrap();
+    let mut transport = FileTransport::open(path.to_str().unwrap()).unwrap();
+    assert_eq!(transport.modem_state(), None);
+    std::fs::remove_file(path).unwrap();
+}